@@ -0,0 +1,89 @@
+//! Move generation, attack detection and perft timings, run with `cargo bench --features bench`.
+//! Without the bench feature the attack detection group is left out.
+//! The logged group installs SimpleLogger at Info on stderr, as the binary does, so it runs after the others.
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use std::hint::black_box;
+
+use casey_chess::{board::Board, chess_move::Move, utils::performance::perft};
+
+/// Positions move generation is timed on
+const POSITIONS: [(&str, &str); 3] = [
+    ("start", "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"),
+    ("kiwipete", "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1"),
+    ("queens", "2kr3r/pp1q1ppp/2n1bn2/3Q4/3q4/2N1BN2/PP2QPPP/2KR3R w - - 0 1"),
+];
+
+fn board(fen: &str) -> Board {
+    Board::from_fen(fen).expect("benchmark positions are valid")
+}
+
+fn generate_legal_moves(c: &mut Criterion) {
+    let mut group = c.benchmark_group("generate_legal_moves");
+    for (name, fen) in POSITIONS {
+        let board = board(fen);
+        group.bench_function(name, |b| b.iter(|| black_box(&board).generate_legal_moves()));
+    }
+    group.finish();
+}
+
+#[cfg(feature = "bench")]
+fn is_square_attacked(c: &mut Criterion) {
+    use casey_chess::color::Color;
+    let board = board(POSITIONS[1].1);
+    c.bench_function("is_square_attacked/kiwipete all squares", |b| b.iter(|| {
+        let board = black_box(&board);
+        (0..64).filter(|index| board.bench_is_square_attacked(index % 8, index / 8, Color::Black)).count()
+    }));
+}
+
+#[cfg(not(feature = "bench"))]
+fn is_square_attacked(_: &mut Criterion) {}
+
+fn move_piece_undo(c: &mut Criterion) {
+    let mut group = c.benchmark_group("move_piece_undo");
+    for (name, fen) in POSITIONS {
+        let board = board(fen);
+        let moves = board.generate_legal_moves();
+        group.bench_function(name, |b| b.iter_batched_ref(
+            || board.clone(),
+            |board| {
+                for mv in &moves {
+                    board.move_piece(mv.clone()).expect("generated moves are legal");
+                    board.undo_move();
+                }
+            },
+            BatchSize::SmallInput,
+        ));
+    }
+    group.finish();
+}
+
+fn perft_4(c: &mut Criterion) {
+    let board = Board::starting_position();
+    c.bench_function("perft/start depth 4", |b| b.iter(|| perft(4, black_box(board.clone())).expect("perft from a valid position")));
+}
+
+/// Perft and legality probing with a logger installed, so logging on hot paths shows up in the timings
+fn logged(c: &mut Criterion) {
+    simple_logger::SimpleLogger::new().with_level(log::LevelFilter::Info).init().expect("no logger installed yet");
+    let mut group = c.benchmark_group("logged");
+    group.sample_size(10);
+    let start = Board::starting_position();
+    group.bench_function("perft/start depth 5", |b| b.iter(|| perft(5, black_box(start.clone())).expect("perft from a valid position")));
+    let kiwipete = board(POSITIONS[1].1);
+    group.bench_function("is_legal/kiwipete all from to pairs", |b| b.iter(|| {
+        let board = black_box(&kiwipete);
+        let mut legal = 0;
+        for from in 0..64 {
+            let Some(piece) = board.get_piece(from % 8, from / 8) else { continue };
+            for to in 0..64 {
+                legal += board.is_legal(&Move::new(from % 8, from / 8, to % 8, to / 8, piece.get_type(), None)) as usize;
+            }
+        }
+        legal
+    }));
+    group.finish();
+}
+
+criterion_group!(benches, generate_legal_moves, is_square_attacked, move_piece_undo, perft_4, logged);
+criterion_main!(benches);
@@ -0,0 +1,14 @@
+#![no_main]
+
+use casey_chess::board::Board;
+use libfuzzer_sys::fuzz_target;
+
+// Any FEN that parses must write back out to a FEN that parses to the same board
+fuzz_target!(|data: &[u8]| {
+    let Ok(text) = std::str::from_utf8(data) else { return };
+    let Ok(board) = Board::from_fen(text) else { return };
+    let fen = board.to_fen();
+    let again = Board::from_fen(&fen).expect("to_fen output must parse");
+    assert_eq!(again.to_fen(), fen);
+    board.generate_legal_moves();
+});
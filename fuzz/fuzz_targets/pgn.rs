@@ -0,0 +1,16 @@
+#![no_main]
+
+use casey_chess::{board::Board, kibitzer::watcher::{parse_move, pgn_moves}};
+use libfuzzer_sys::fuzz_target;
+
+// Replays the main line of a PGN fragment until the first move that is not legal
+fuzz_target!(|data: &[u8]| {
+    let Ok(text) = std::str::from_utf8(data) else { return };
+    let mut board = Board::starting_position();
+    for token in pgn_moves(text) {
+        match parse_move(&board, &token) {
+            Some(mv) => board.move_piece(mv).unwrap(),
+            None => break,
+        }
+    }
+});
@@ -0,0 +1,20 @@
+#![no_main]
+
+use casey_chess::{board::Board, utils::notation::san_to_move};
+use libfuzzer_sys::fuzz_target;
+
+const POSITIONS: [&str; 4] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 b kq - 0 1",
+    "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+];
+
+// The first byte picks a mid-game position, the rest is the SAN text
+fuzz_target!(|data: &[u8]| {
+    let Some((first, rest)) = data.split_first() else { return };
+    let Ok(text) = std::str::from_utf8(rest) else { return };
+    let mut board = Board::from_fen(POSITIONS[*first as usize % POSITIONS.len()]).unwrap();
+    san_to_move(&board, text);
+    let _ = board.algebraic_move(text);
+});
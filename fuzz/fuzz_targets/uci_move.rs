@@ -0,0 +1,13 @@
+#![no_main]
+
+use casey_chess::{board::Board, utils::notation::{square_to_coords, uci_to_move}};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(text) = std::str::from_utf8(data) else { return };
+    square_to_coords(text);
+    let board = Board::from_fen("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 b kq - 0 1").unwrap();
+    if let Some(mv) = uci_to_move(&board, text) {
+        assert_eq!(uci_to_move(&board, &mv.extended_algebraic()), Some(mv));
+    }
+});
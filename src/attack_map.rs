@@ -0,0 +1,22 @@
+use crate::bitboard::{self, Bitboard};
+
+/// Every square one side attacks, see Board::attack_map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AttackMap {
+    squares: Bitboard,
+}
+
+impl AttackMap {
+    pub(crate) fn new(squares: Bitboard) -> AttackMap {
+        AttackMap { squares }
+    }
+
+    pub fn is_attacked(&self, x: usize, y: usize) -> bool {
+        self.squares & bitboard::square_bit(x, y) != 0
+    }
+
+    /// The attacked squares as a bitboard, bit y * 8 + x for the square (x, y)
+    pub fn bitboard(&self) -> Bitboard {
+        self.squares
+    }
+}
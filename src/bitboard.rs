@@ -0,0 +1,335 @@
+
+/// A set of squares stored as the bits of a u64.
+/// Bit `y * 8 + x` is set when the square (x, y) is in the set, so a1 is bit 0 and h8 is bit 63.
+pub type Bitboard = u64;
+
+const NORTH: usize = 0;
+const SOUTH: usize = 1;
+const EAST: usize = 2;
+const WEST: usize = 3;
+const NORTH_EAST: usize = 4;
+const NORTH_WEST: usize = 5;
+const SOUTH_EAST: usize = 6;
+const SOUTH_WEST: usize = 7;
+
+const DIRECTIONS: [(i8, i8); 8] = [(0, 1), (0, -1), (1, 0), (-1, 0), (1, 1), (-1, 1), (1, -1), (-1, -1)];
+const KNIGHT_DELTAS: [(i8, i8); 8] = [(1, 2), (2, 1), (-1, 2), (-2, 1), (1, -2), (2, -1), (-1, -2), (-2, -1)];
+
+/// Squares a knight attacks from each square
+pub const KNIGHT_ATTACKS: [Bitboard; 64] = leaper_table(&KNIGHT_DELTAS);
+/// Squares a king attacks from each square
+pub const KING_ATTACKS: [Bitboard; 64] = leaper_table(&DIRECTIONS);
+/// Squares a pawn attacks from each square, indexed by the pawn's color index
+pub const PAWN_ATTACKS: [[Bitboard; 64]; 2] = [leaper_table(&[(1, 1), (-1, 1)]), leaper_table(&[(1, -1), (-1, -1)])];
+/// The squares of rank 1
+pub const RANK_1: Bitboard = 0xFF;
+/// The squares of rank 8
+pub const RANK_8: Bitboard = 0xFF << 56;
+/// The squares of the a-file, shift left by x for the other files
+pub const FILE_A: Bitboard = 0x0101_0101_0101_0101;
+/// The light squares, b1, d1 and so on, a1 is dark
+pub const LIGHT_SQUARES: Bitboard = 0x55AA_55AA_55AA_55AA;
+/// Every square along a ray from each square, not including the square itself
+const RAYS: [[Bitboard; 64]; 8] = ray_table();
+
+/// Gets the index of a square in a bitboard
+pub const fn square_index(x: usize, y: usize) -> usize {
+    y * 8 + x
+}
+
+/// Gets the bitboard with only the given square set
+pub const fn square_bit(x: usize, y: usize) -> Bitboard {
+    1 << square_index(x, y)
+}
+
+/// Converts a bitboard index back into (x, y) coordinates
+pub const fn index_to_coords(index: usize) -> (usize, usize) {
+    (index % 8, index / 8)
+}
+
+/// Iterates over the indexes of the set bits of a bitboard, lowest first
+pub fn squares(mut bitboard: Bitboard) -> impl Iterator<Item = usize> {
+    std::iter::from_fn(move || {
+        if bitboard == 0 {
+            return None;
+        }
+        let index = bitboard.trailing_zeros() as usize;
+        bitboard &= bitboard - 1;
+        Some(index)
+    })
+}
+
+/// Squares attacked by a rook on the given square
+/// # Description
+/// Looked up in the magic table, see Magic. The first occupied square along each ray is included
+/// so it can be captured.
+pub fn rook_attacks(index: usize, occupancy: Bitboard) -> Bitboard {
+    SLIDER_ATTACKS[ROOK_MAGICS[index].table_index(occupancy)]
+}
+
+/// Squares attacked by a bishop on the given square
+/// # Description
+/// Looked up in the magic table, see Magic. The first occupied square along each ray is included
+/// so it can be captured.
+pub fn bishop_attacks(index: usize, occupancy: Bitboard) -> Bitboard {
+    SLIDER_ATTACKS[BISHOP_MAGICS[index].table_index(occupancy)]
+}
+
+/// The squares strictly between two squares on a shared rank, file or diagonal, empty for any other pair
+pub fn between(from: usize, to: usize) -> Bitboard {
+    BETWEEN[from][to]
+}
+
+/// The whole rank, file or diagonal through two squares, both included, empty if they share none
+pub fn line(a: usize, b: usize) -> Bitboard {
+    LINES[a][b]
+}
+
+/// Rook attacks found by following each straight ray to its first blocker, the magic table is built from these
+pub(crate) const fn rook_attacks_by_rays(index: usize, occupancy: Bitboard) -> Bitboard {
+    positive_ray(NORTH, index, occupancy)
+        | positive_ray(EAST, index, occupancy)
+        | negative_ray(SOUTH, index, occupancy)
+        | negative_ray(WEST, index, occupancy)
+}
+
+/// Bishop attacks found by following each diagonal ray to its first blocker, the magic table is built from these
+pub(crate) const fn bishop_attacks_by_rays(index: usize, occupancy: Bitboard) -> Bitboard {
+    positive_ray(NORTH_EAST, index, occupancy)
+        | positive_ray(NORTH_WEST, index, occupancy)
+        | negative_ray(SOUTH_EAST, index, occupancy)
+        | negative_ray(SOUTH_WEST, index, occupancy)
+}
+
+/// Ray attacks for directions where the bit index increases, the nearest blocker is the lowest bit
+const fn positive_ray(direction: usize, index: usize, occupancy: Bitboard) -> Bitboard {
+    let ray = RAYS[direction][index];
+    let blockers = ray & occupancy;
+    if blockers == 0 {
+        return ray;
+    }
+    ray ^ RAYS[direction][blockers.trailing_zeros() as usize]
+}
+
+/// Ray attacks for directions where the bit index decreases, the nearest blocker is the highest bit
+const fn negative_ray(direction: usize, index: usize, occupancy: Bitboard) -> Bitboard {
+    let ray = RAYS[direction][index];
+    let blockers = ray & occupancy;
+    if blockers == 0 {
+        return ray;
+    }
+    ray ^ RAYS[direction][63 - blockers.leading_zeros() as usize]
+}
+
+/// Where one square's slider attacks are kept in SLIDER_ATTACKS.
+/// # Description
+/// Only the blockers under mask can change the attacks. The edge square at the end of each ray is left out,
+/// as the ray stops there either way. Multiplying those blockers by the magic number and keeping the top bits
+/// gives an index that differs for any two sets of blockers with different attacks.
+#[derive(Clone, Copy)]
+struct Magic {
+    mask: Bitboard,
+    magic: u64,
+    shift: u32,
+    offset: usize,
+}
+
+impl Magic {
+    const fn table_index(&self, occupancy: Bitboard) -> usize {
+        self.offset + ((occupancy & self.mask).wrapping_mul(self.magic) >> self.shift) as usize
+    }
+}
+
+/// Entries for every rook square, one for each subset of its mask
+const ROOK_TABLE_SIZE: usize = 102_400;
+/// Entries for every bishop square
+const BISHOP_TABLE_SIZE: usize = 5_248;
+/// Found by trying sparse random numbers from a fixed seed until no two blocker sets with different attacks
+/// shared an index
+const ROOK_MAGIC_NUMBERS: [u64; 64] = [
+    0x0980_0080_1140_0020, 0x8340_0044_1000_2000, 0x0880_2000_9000_8268, 0x0080_0800_8010_0004,
+    0x8100_1100_0402_0800, 0x0300_0100_0400_0822, 0x0880_1A00_2900_0080, 0x8100_0500_0120_4882,
+    0x0844_8000_8140_0320, 0x0804_4020_1000_4000, 0x0108_8020_0310_0480, 0x0004_8080_0800_1000,
+    0x0003_0018_0100_1014, 0x0002_0002_0004_1008, 0x0004_0081_0804_2210, 0x0105_0001_0000_9042,
+    0x0400_8080_0040_0021, 0xC100_4040_1000_2000, 0x0060_0080_1000_2088, 0x0400_8080_0800_1000,
+    0x4440_8080_0800_0400, 0x1002_0080_0400_0280, 0x4002_4400_300D_1248, 0x0010_0200_0040_8104,
+    0x0101_0082_0020_4200, 0x8020_0020_4000_5000, 0x4100_1000_8080_2000, 0x4008_006A_8010_0280,
+    0x1020_0800_8004_0080, 0x0004_0100_4002_0040, 0x0018_A124_0008_0290, 0x6140_0042_0000_8104,
+    0x4000_4000_2080_0090, 0x2020_0020_8080_4000, 0x0000_4082_0200_2010, 0x0080_1005_0100_0820,
+    0x0000_8004_0080_0800, 0x000A_2004_0801_4010, 0x0100_8002_0080_0100, 0xA008_0057_0200_008C,
+    0x0080_0040_6000_C010, 0x1040_1000_2800_2000, 0x0048_2001_0011_0040, 0x0068_4902_1003_0020,
+    0x1009_0800_0501_0010, 0x2142_0008_0401_0100, 0x1001_0801_1084_0002, 0x1801_0044_0082_0001,
+    0x0104_4020_8D02_0200, 0x0000_4000_2000_8080, 0x0200_2000_8010_0280, 0x0000_1000_2009_0100,
+    0x0204_0080_0802_0480, 0x8104_0100_4002_0040, 0x7800_0201_B008_0400, 0x0040_8000_5100_2880,
+    0x0050_1080_0100_2041, 0x208A_8011_0061_4003, 0x0006_0020_4208_9082, 0x0011_0900_0420_1001,
+    0x1002_0010_0420_0802, 0x0005_0002_0804_0001, 0x0002_0027_01AC_0822, 0x0000_1025_0184_004A,
+];
+const BISHOP_MAGIC_NUMBERS: [u64; 64] = [
+    0xC0A0_0122_0604_0EA0, 0x8010_2282_0042_0001, 0x0110_0082_2040_0400, 0x0244_5C00_8010_6000,
+    0x0044_0420_0400_8100, 0x0880_9004_2040_8C05, 0x0201_0801_1008_0002, 0x0000_1080_9420_2000,
+    0x0000_0420_0204_0108, 0x0000_6230_2411_0042, 0x0086_1000_9481_1002, 0x0000_0445_0200_2080,
+    0x0100_4602_1140_0040, 0x0008_1090_0420_0004, 0x0202_3200_8484_4000, 0x8040_0424_2104_1009,
+    0x2010_10C0_5102_008C, 0x1020_8882_0802_4080, 0x0108_000C_8029_0200, 0x8048_0004_2042_5203,
+    0x0005_0000_9040_2000, 0x2080_4002_0110_4100, 0x8820_4201_1110_1000, 0x4AC0_3022_0882_1802,
+    0x0004_4000_1002_A840, 0x2002_2000_1004_1080, 0x1012_0802_0100_4400, 0x8440_0400_0241_0120,
+    0x1090_8200_8401_0400, 0x2084_8520_1202_1000, 0x1204_0062_C101_1003, 0x0200_8205_E109_0080,
+    0x088C_1028_0804_2080, 0x0802_1022_0090_4280, 0x8020_2090_0208_0020, 0x2200_0808_0006_0A00,
+    0x20C0_0040_1001_0100, 0x0802_0041_0082_1003, 0x0008_0244_0000_8080, 0x0000_8401_0200_8090,
+    0x0030_A404_2024_4007, 0x0A19_0842_1001_1282, 0x0004_0820_9001_9806, 0x6108_0042_0802_0080,
+    0x0081_2004_1011_0100, 0x1040_8107_0101_0208, 0x0282_0478_3201_2080, 0x0010_0200_9900_0020,
+    0x000E_0104_2240_0840, 0x1020_4208_B008_9090, 0x0810_0444_0C04_8000, 0x88C0_1800_8404_0001,
+    0x3100_0208_0304_0080, 0x8900_70A0_4121_0C00, 0x0020_2001_0101_0A09, 0x0004_1002_4041_0400,
+    0x0006_0044_0208_0200, 0x0801_0624_8404_2000, 0x0001_0002_D744_1004, 0x0810_0800_0020_8800,
+    0x0000_0208_0803_0411, 0x1450_0010_2001_4440, 0x0040_6008_1081_A288, 0x0044_0114_0410_8A00,
+];
+const ROOK_MAGICS: [Magic; 64] = magics(&[NORTH, SOUTH, EAST, WEST], &ROOK_MAGIC_NUMBERS, 0);
+const BISHOP_MAGICS: [Magic; 64] = magics(&[NORTH_EAST, NORTH_WEST, SOUTH_EAST, SOUTH_WEST], &BISHOP_MAGIC_NUMBERS, ROOK_TABLE_SIZE);
+/// The attacks of a rook and then of a bishop on every square for every set of blockers, built at compile time
+static SLIDER_ATTACKS: [Bitboard; ROOK_TABLE_SIZE + BISHOP_TABLE_SIZE] = slider_table();
+/// The squares between each pair of squares, see between
+static BETWEEN: [[Bitboard; 64]; 64] = between_table();
+/// The line through each pair of squares, see line
+static LINES: [[Bitboard; 64]; 64] = line_table();
+
+/// The mask, shift and table offset of each square for a slider moving along the given directions
+const fn magics(directions: &[usize; 4], numbers: &[u64; 64], start: usize) -> [Magic; 64] {
+    let mut magics = [Magic { mask: 0, magic: 0, shift: 0, offset: 0 }; 64];
+    let mut offset = start;
+    let mut index = 0;
+    while index < 64 {
+        let mut mask = 0;
+        let mut i = 0;
+        while i < 4 {
+            let direction = directions[i];
+            let ray = RAYS[direction][index];
+            if ray != 0 {
+                let increasing = matches!(direction, NORTH | EAST | NORTH_EAST | NORTH_WEST);
+                let edge = if increasing { 63 - ray.leading_zeros() } else { ray.trailing_zeros() };
+                mask |= ray & !(1 << edge);
+            }
+            i += 1;
+        }
+        let bits = mask.count_ones();
+        magics[index] = Magic { mask, magic: numbers[index], shift: 64 - bits, offset };
+        offset += 1 << bits;
+        index += 1;
+    }
+    magics
+}
+
+const fn slider_table() -> [Bitboard; ROOK_TABLE_SIZE + BISHOP_TABLE_SIZE] {
+    let mut table = [0; ROOK_TABLE_SIZE + BISHOP_TABLE_SIZE];
+    let mut index = 0;
+    while index < 64 {
+        // Every subset of the mask in turn, by the carry-rippler trick, until it wraps back to the empty set
+        let rook = ROOK_MAGICS[index];
+        let mut blockers: Bitboard = 0;
+        loop {
+            table[rook.table_index(blockers)] = rook_attacks_by_rays(index, blockers);
+            blockers = blockers.wrapping_sub(rook.mask) & rook.mask;
+            if blockers == 0 {
+                break;
+            }
+        }
+        let bishop = BISHOP_MAGICS[index];
+        loop {
+            table[bishop.table_index(blockers)] = bishop_attacks_by_rays(index, blockers);
+            blockers = blockers.wrapping_sub(bishop.mask) & bishop.mask;
+            if blockers == 0 {
+                break;
+            }
+        }
+        index += 1;
+    }
+    table
+}
+
+const fn between_table() -> [[Bitboard; 64]; 64] {
+    let mut table = [[0; 64]; 64];
+    let mut direction = 0;
+    while direction < 8 {
+        let mut from = 0;
+        while from < 64 {
+            let mut targets = RAYS[direction][from];
+            while targets != 0 {
+                let to = targets.trailing_zeros() as usize;
+                // The ray up to `to`, without the part of it beyond `to` or `to` itself
+                table[from][to] = RAYS[direction][from] & !RAYS[direction][to] & !(1 << to);
+                targets &= targets - 1;
+            }
+            from += 1;
+        }
+        direction += 1;
+    }
+    table
+}
+
+const fn line_table() -> [[Bitboard; 64]; 64] {
+    let mut table = [[0; 64]; 64];
+    let mut direction = 0;
+    while direction < 8 {
+        let opposite = match direction {
+            NORTH => SOUTH,
+            SOUTH => NORTH,
+            EAST => WEST,
+            WEST => EAST,
+            NORTH_EAST => SOUTH_WEST,
+            SOUTH_WEST => NORTH_EAST,
+            NORTH_WEST => SOUTH_EAST,
+            _ => NORTH_WEST,
+        };
+        let mut from = 0;
+        while from < 64 {
+            let line = RAYS[direction][from] | RAYS[opposite][from] | (1 << from);
+            let mut targets = RAYS[direction][from];
+            while targets != 0 {
+                table[from][targets.trailing_zeros() as usize] = line;
+                targets &= targets - 1;
+            }
+            from += 1;
+        }
+        direction += 1;
+    }
+    table
+}
+
+const fn leaper_table(deltas: &[(i8, i8)]) -> [Bitboard; 64] {
+    let mut table = [0; 64];
+    let mut index = 0;
+    while index < 64 {
+        let x = (index % 8) as i8;
+        let y = (index / 8) as i8;
+        let mut i = 0;
+        while i < deltas.len() {
+            let nx = x + deltas[i].0;
+            let ny = y + deltas[i].1;
+            if nx >= 0 && nx < 8 && ny >= 0 && ny < 8 {
+                table[index] |= square_bit(nx as usize, ny as usize);
+            }
+            i += 1;
+        }
+        index += 1;
+    }
+    table
+}
+
+const fn ray_table() -> [[Bitboard; 64]; 8] {
+    let mut table = [[0; 64]; 8];
+    let mut direction = 0;
+    while direction < 8 {
+        let (dx, dy) = DIRECTIONS[direction];
+        let mut index = 0;
+        while index < 64 {
+            let mut x = (index % 8) as i8 + dx;
+            let mut y = (index / 8) as i8 + dy;
+            while x >= 0 && x < 8 && y >= 0 && y < 8 {
+                table[direction][index] |= square_bit(x as usize, y as usize);
+                x += dx;
+                y += dy;
+            }
+            index += 1;
+        }
+        direction += 1;
+    }
+    table
+}
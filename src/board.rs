@@ -1,9 +1,17 @@
+use std::{fmt::{self, Display, Formatter}, hash::{Hash, Hasher}};
 
-use crate::{chess_move::Move, color::Color, errors::move_error::MoveError, move_type::MoveType, piece::Piece, piece_type::PieceType, utils::notation::square_to_coords};
+use crate::{attack_map::AttackMap, bitboard::{self, Bitboard, KING_ATTACKS, KNIGHT_ATTACKS, PAWN_ATTACKS}, board_render_options::BoardRenderOptions, castling_rights::CastlingRights, castling_validation::{CastlingRepair, CastlingValidation}, chess_move::Move, color::Color, coords::{self, file_char, rank_char}, draw_reason::DrawReason, en_passant_mode::EnPassantMode, errors::{consistency_error::ConsistencyError, fen_error::FenError, move_error::{MoveError, MoveListError}}, move_kind::MoveKind, move_result::MoveResult, move_type::MoveType, piece::{ray_delta, Piece}, piece_type::PieceType, side::Side, square_name::SquareName, utils::notation::{coords_to_square, san_to_move, square_to_coords}, zobrist};
 
+/// A chess position along with the state needed to continue play from it.
+/// # Thread safety
+/// Board only holds plain data so it is both Send and Sync.
+/// A single board can be shared read-only between threads (e.g. behind an Arc),
+/// and workers that need to make moves should each take their own clone.
+/// # Equality
+/// Boards compare and hash as positions, see the PartialEq impl, so clocks and history are not compared.
 #[derive(Debug, Clone)]
 pub struct Board {
-    squares: Vec<Vec<Option<Piece>>>,
+    squares: [[Option<Piece>; 8]; 8],
     move_number: u32,
     player_turn: Color,
     white_can_castle_king: bool,
@@ -14,12 +22,107 @@ pub struct Board {
     halfmove: u32,
     white_king_position: (usize, usize),
     black_king_position: (usize, usize),
+    // Bitboards kept in sync with squares, indexed by color then piece type
+    bitboards: [[Bitboard; 6]; 2],
+    occupancy: [Bitboard; 2],
+    // Material balance in centipawns, White minus Black, kept in sync with squares like the bitboards
+    material: i32,
+    // Zobrist key of the pieces alone, kept in sync with squares like the material balance, see zobrist_key
+    piece_key: u64,
+    // Whether the side to move is in check, when known. Cleared whenever a square changes,
+    // set by move_piece and null moves and restored by unmake_move
+    in_check: Option<bool>,
+    // Moves played with move_piece and how to take them back, most recent last
+    history: Vec<(Move, UndoInfo)>,
+    // Zobrist keys of the positions moves were made from with make_move, most recent last, see repetition_count
+    keys: Vec<u64>,
+}
+
+/// The state needed to take back a move made with Board::make_move.
+/// # Description
+/// Holds everything a move can change that cannot be worked out from the move itself:
+/// the piece that moved (so a promoted piece goes back as a pawn), the captured piece and the square
+/// it stood on (which differs from the destination for en passant), the castling rights,
+/// the en passant square, the halfmove clock, both king positions and whether the side to move was known to be in check
+/// from before the move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UndoInfo {
+    moved: Piece,
+    captured: Option<Piece>,
+    captured_square: (usize, usize),
+    castling: [bool; 4],
+    en_passant: Option<(usize, usize)>,
+    halfmove: u32,
+    white_king_position: (usize, usize),
+    black_king_position: (usize, usize),
+    in_check: Option<bool>,
+}
+
+/// What the side to move's king is exposed to, worked out once per position so each generated move can be
+/// checked without being played, see Board::legality.
+#[derive(Debug, Clone, Copy)]
+struct Legality {
+    /// None for a position without a king of the side to move, where every move is allowed
+    king: Option<usize>,
+    /// Squares a move other than the king's must land on: the checker and the squares between it and the king
+    /// in single check, every square out of check
+    check_mask: Bitboard,
+    /// The side to move's pieces that shield their king from a slider
+    pinned: Bitboard,
+    /// The squares the opponent attacks, with the side to move's king lifted off
+    attacked: AttackMap,
+}
+
+/// What make_null_move changes that unmake_null_move can not work out, the en passant square, halfmove clock
+/// and whether the side to move was known to be in check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NullUndo {
+    en_passant: Option<(usize, usize)>,
+    halfmove: u32,
+    in_check: Option<bool>,
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Boards are equal when they are the same position for repetition purposes.
+/// # Description
+/// Compares the piece placement, side to move, castling rights and the en passant square when a pawn can capture on it,
+/// the same state repetition_key and zobrist_key are built from.
+/// The halfmove clock, fullmove number and move history are not compared, so two equal boards can have different clocks.
+/// Compare to_fen output when the clocks matter as well.
+impl PartialEq for Board {
+    fn eq(&self, other: &Self) -> bool {
+        self.squares == other.squares
+            && self.player_turn == other.player_turn
+            && self.castling_rights() == other.castling_rights()
+            && self.en_passant_square() == other.en_passant_square()
+    }
+}
+
+impl Eq for Board {}
+
+/// Hashes the Zobrist key, which covers exactly the state PartialEq compares
+impl Hash for Board {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.zobrist_key().hash(state);
+    }
+}
+
+/// Draws the board from White's side, the same as to_ascii(Color::White)
+impl Display for Board {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_ascii(Color::White))
+    }
 }
 
 impl Board {
     /// Creates a new empty board.
     /// # Description
-    /// The board is represented as a 2D array of Option<Piece>.
+    /// The board is represented as a fixed 8x8 array of Option<Piece>.
     /// Each square can either contain a piece or be empty.
     /// # Inputs/Outputs
     /// - Inputs: None
@@ -29,16 +132,9 @@ impl Board {
     /// let board = Board::new();
     /// ```
     pub fn new() -> Self {
-        let mut squares = Vec::new();
-        for _ in 0..8 {
-            let mut row = Vec::new();
-            for _ in 0..8 {
-                row.push(None);
-            }
-            squares.push(row);
-        }
         Board { 
-            squares, move_number: 1, 
+            squares: [[None; 8]; 8],
+            move_number: 1, 
             player_turn: Color::White, 
             white_can_castle_king: true, 
             white_can_castle_queen: true, 
@@ -48,6 +144,13 @@ impl Board {
             halfmove: 0,
             white_king_position: (0, 0),
             black_king_position: (0, 0),
+            bitboards: [[0; 6]; 2],
+            occupancy: [0; 2],
+            material: 0,
+            piece_key: 0,
+            in_check: None,
+            history: Vec::new(),
+            keys: Vec::new(),
         }
     }
 
@@ -58,81 +161,409 @@ impl Board {
     /// 1. Piece placement Each piece is identified by a single letter (uppercase for white, lowercase for black) and empty squares are represented by a number.
     /// 2. Active color. "w" means white moves next, "b" means black moves next.
     /// 3. Castling availability. Each letter indicates whether castling is possible for each side (KQkq) or not (-).
+    ///    A right whose king or rook is not on its starting square is an error, see from_fen_with to drop such rights instead.
     /// 4. En passant target square in algebraic notation. If there is no en passant target square, this is "-".
     /// 5. Halfmove clock: The number of halfmoves since the last capture or pawn advance. This is used to determine if a draw can be claimed under the fifty-move rule.
     /// 6. Fullmove number: The number of the full move. It starts at 1, and is incremented after black moves.
     /// # Inputs/Outputs
     /// - Inputs: A FEN string.
-    /// - Returns: A board if the FEN string is valid, otherwise None.
+    /// - Returns: A board if the FEN string is valid, otherwise the reason it could not be parsed. Malformed input never panics.
     /// # Example
     /// ``` Rust
     /// let board = Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
     /// // this will print the starting position of a chess game to the console
     /// board.print(Color::White);
     /// ```
-    pub fn from_fen(fen: &str) -> Option<Board> {
+    pub fn from_fen(fen: &str) -> Result<Board, FenError> {
+        Board::from_fen_with(fen, CastlingValidation::Strict).map(|(board, _)| board)
+    }
+
+    /// Parse a FEN, choosing what happens to castling rights the position can not have.
+    /// # Description
+    /// Some tools write "KQkq" whatever the position, e.g. for odds games with a rook removed.
+    /// CastlingValidation::Strict rejects such FENs the same as from_fen, CastlingValidation::Repair drops
+    /// each impossible right and lists it so the caller can tell the user.
+    /// # Inputs/Outputs
+    /// - Input: fen - The FEN string
+    /// - Input: validation - Whether to reject or repair inconsistent castling rights
+    /// - Output: The board and the rights dropped, always empty in strict mode, or the reason the FEN could not be parsed
+    /// # Example
+    /// ``` Rust
+    /// let fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/1NBQKBNR w KQkq - 0 1";
+    /// assert_eq!(Board::from_fen(fen).unwrap_err(), FenError::InconsistentCastlingRights('Q'));
+    /// let (board, repairs) = Board::from_fen_with(fen, CastlingValidation::Repair).unwrap();
+    /// assert_eq!(board.castling_rights().to_string(), "Kkq");
+    /// assert_eq!(repairs[0].to_string(), "dropped castling right 'Q', no White rook on a1");
+    /// ```
+    pub fn from_fen_with(fen: &str, validation: CastlingValidation) -> Result<(Board, Vec<CastlingRepair>), FenError> {
         let mut board = Board::new();
         let fields = fen.split_whitespace().collect::<Vec<_>>();
         if fields.len() < 4 {
-            return None;
+            return Err(FenError::MissingFields(fields.len()));
         }
-        // Parse the first field 
-        let mut x = 0;
-        let mut y = 7;
-        for c in fields[0].chars() {
-            if c == '/' {
-                x = 0;
-                y -= 1;
-            } else if c.is_digit(10) {
-                x += c.to_digit(10).unwrap() as usize;
-            } else {
-                if let Some(piece) = Piece::from_fen(c) {
-                    if *piece.get_type() == PieceType::King {
-                        match piece.get_color() {
-                            Color::White => board.white_king_position = (x, y),
-                            Color::Black => board.black_king_position = (x, y),
-                        }
+        // Parse the first field, ranks are listed from 8 down to 1
+        let ranks = fields[0].split('/').collect::<Vec<_>>();
+        if ranks.len() > 8 {
+            return Err(FenError::TooManyRanks);
+        } else if ranks.len() < 8 {
+            return Err(FenError::TooFewRanks);
+        }
+        for (i, rank) in ranks.iter().enumerate() {
+            let y = coords::fen_rank_to_y(i);
+            let mut x = 0;
+            for c in rank.chars() {
+                if let Some(empty) = c.to_digit(10).filter(|digit| (1..=8).contains(digit)) {
+                    x += empty as usize;
+                    if x > 8 {
+                        return Err(FenError::BadRankLength(y + 1));
                     }
-                    board.squares[y][x] = Some(piece);
-                } else {
-                    return None;
+                    continue;
+                }
+                let piece = Piece::from_fen(c).ok_or(FenError::InvalidPieceChar(c))?;
+                if x > 7 {
+                    return Err(FenError::BadRankLength(y + 1));
                 }
+                if piece.get_type() == PieceType::King {
+                    match piece.get_color() {
+                        Color::White => board.white_king_position = (x, y),
+                        Color::Black => board.black_king_position = (x, y),
+                    }
+                }
+                board.set_square(x, y, Some(piece));
                 x += 1;
             }
+            if x != 8 {
+                return Err(FenError::BadRankLength(y + 1));
+            }
         }
         // Parse the second field
-        if fields[1] == "w" {
-            board.player_turn = Color::White;
-        } else if fields[1] == "b" {
-            board.player_turn = Color::Black;
-        } else {
-            return None;
-        }
+        board.player_turn = match fields[1] {
+            "w" => Color::White,
+            "b" => Color::Black,
+            other => return Err(FenError::BadSideToMove(other.to_string())),
+        };
         // Parse the third field
-        board.white_can_castle_king = fields[2].contains("K");
-        board.white_can_castle_queen = fields[2].contains("Q");
-        board.black_can_castle_king = fields[2].contains("k");
-        board.black_can_castle_queen = fields[2].contains("q");
-        // Parse the fourth field
+        if fields[2] != "-" && (fields[2].is_empty() || !fields[2].chars().all(|c| "KQkq".contains(c))) {
+            return Err(FenError::BadCastlingField(fields[2].to_string()));
+        }
+        board.white_can_castle_king = fields[2].contains('K');
+        board.white_can_castle_queen = fields[2].contains('Q');
+        board.black_can_castle_king = fields[2].contains('k');
+        board.black_can_castle_queen = fields[2].contains('q');
+        // Parse the fourth field, the square must be behind a pawn that just moved two squares
         if fields[3] == "-" {
             board.en_passant = None;
         } else {
-            let x = fields[3].chars().nth(0).unwrap() as usize - 'a' as usize;
-            let y = fields[3].chars().nth(1).unwrap() as usize - '1' as usize;
+            let bad_square = || FenError::BadEnPassantSquare(fields[3].to_string());
+            let (x, y) = square_to_coords(fields[3]).ok_or_else(bad_square)?;
+            let expected_y = if board.player_turn == Color::White { 5 } else { 2 };
+            if y != expected_y {
+                return Err(bad_square());
+            }
             board.en_passant = Some((x, y));
         }
         // Parse the fifth field
-        match fields.get(4) {
-            Some(field) => board.halfmove = field.parse().unwrap(),
-            None => board.halfmove = 0,
-        }
+        board.halfmove = match fields.get(4) {
+            Some(field) => field.parse().map_err(|_| FenError::BadClockField(field.to_string()))?,
+            None => 0,
+        };
         // Parse the sixth field
-        match fields.get(5) {
-            Some(field) => board.move_number = field.parse().unwrap(),
-            None => board.move_number = 1,
+        board.move_number = match fields.get(5) {
+            Some(field) => field.parse().map_err(|_| FenError::BadClockField(field.to_string()))?,
+            None => 1,
+        };
+        board.validate_position()?;
+        let repairs = board.castling_repairs();
+        if let Some(repair) = repairs.first() {
+            if validation == CastlingValidation::Strict {
+                return Err(FenError::InconsistentCastlingRights(repair.right));
+            }
+            board.drop_castling_rights(&repairs);
+        }
+
+        Ok((board, repairs))
+    }
+
+    /// Write the position as a FEN string.
+    /// # Description
+    /// Produces all six FEN fields, so the result can be read back with from_fen to get an equal board.
+    /// # Inputs/Outputs
+    /// - Inputs: None
+    /// - Returns: The FEN string of the position.
+    /// # Example
+    /// ``` Rust
+    /// let board = Board::starting_position();
+    /// assert_eq!(board.to_fen(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+    /// ```
+    pub fn to_fen(&self) -> String {
+        self.to_fen_with(EnPassantMode::Capturable)
+    }
+
+    /// Get the FEN of the position, choosing how the en passant square is written.
+    /// # Description
+    /// to_fen only writes the en passant square when a pawn could capture onto it.
+    /// EnPassantMode::Permissive writes it after every double pawn push, for tools that expect that form.
+    /// # Example
+    /// ``` Rust
+    /// let mut board = Board::starting_position();
+    /// board.move_piece(Move::new(4, 1, 4, 3, PieceType::Pawn, None)).unwrap();
+    /// assert_eq!(board.to_fen(), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1");
+    /// assert_eq!(board.to_fen_with(EnPassantMode::Permissive), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+    /// ```
+    pub fn to_fen_with(&self, mode: EnPassantMode) -> String {
+        format!("{} {} {}", self.position_fen(mode), self.halfmove, self.move_number)
+    }
+
+    /// The first four FEN fields, everything but the move clocks
+    fn position_fen(&self, mode: EnPassantMode) -> String {
+        let mut placement = String::new();
+        for fen_rank in 0..8 {
+            let y = coords::fen_rank_to_y(fen_rank);
+            let mut empty = 0;
+            for x in 0..8 {
+                match self.squares[y][x] {
+                    Some(piece) => {
+                        if empty > 0 {
+                            placement.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        placement.push(piece.get_piece_char());
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                placement.push_str(&empty.to_string());
+            }
+            if fen_rank < 7 {
+                placement.push('/');
+            }
+        }
+        let side = if self.player_turn == Color::White { "w" } else { "b" };
+        let en_passant = match self.en_passant_square_with(mode) {
+            Some((x, y)) => format!("{}{}", file_char(x), rank_char(y)),
+            None => "-".to_string(),
+        };
+        format!("{} {} {} {}", placement, side, self.castling_rights(), en_passant)
+    }
+
+    /// The castling rights held whose king or rook is not on its starting square, in "KQkq" order
+    pub(crate) fn castling_repairs(&self) -> Vec<CastlingRepair> {
+        let rights = [
+            (self.white_can_castle_king, 'K', Color::White, 7),
+            (self.white_can_castle_queen, 'Q', Color::White, 0),
+            (self.black_can_castle_king, 'k', Color::Black, 7),
+            (self.black_can_castle_queen, 'q', Color::Black, 0),
+        ];
+        let mut repairs = Vec::new();
+        for (held, right, color, rook_x) in rights {
+            if !held {
+                continue;
+            }
+            let y = if color == Color::White { 0 } else { 7 };
+            let missing = [(4, PieceType::King), (rook_x, PieceType::Rook)]
+                .into_iter()
+                .find(|&(x, ptype)| !self.squares[y][x].is_some_and(|piece| piece.get_type() == ptype && piece.get_color() == color));
+            if let Some((x, piece)) = missing {
+                repairs.push(CastlingRepair { right, color, piece, square: (x, y) });
+            }
+        }
+        repairs
+    }
+
+    /// Clears the rights listed by castling_repairs
+    fn drop_castling_rights(&mut self, repairs: &[CastlingRepair]) {
+        for repair in repairs {
+            match repair.right {
+                'K' => self.white_can_castle_king = false,
+                'Q' => self.white_can_castle_queen = false,
+                'k' => self.black_can_castle_king = false,
+                _ => self.black_can_castle_queen = false,
+            }
+        }
+    }
+
+    /// A key identifying the position for repetition detection.
+    /// # Description
+    /// Two positions with the same key have the same pieces, side to move, castling rights and legal moves,
+    /// so the en passant square is only part of the key when the capture is legal.
+    pub fn repetition_key(&self) -> String {
+        self.position_fen(EnPassantMode::Legal)
+    }
+
+    /// A 64 bit Zobrist key identifying the position for the transposition table.
+    /// # Description
+    /// Covers the pieces, side to move, castling rights and en passant square, the latter only when a capture is possible.
+    /// Different positions can share a key, but it is rare enough for the search to ignore.
+    /// The pieces' part is kept up to date as squares change, so only the rest is worked out on each call.
+    /// # Example
+    /// ``` Rust
+    /// let mut board = Board::starting_position();
+    /// let start = board.zobrist_key();
+    /// board.move_piece(Move::new(6, 0, 5, 2, PieceType::Knight, None)).unwrap();
+    /// assert_ne!(board.zobrist_key(), start);
+    /// ```
+    pub fn zobrist_key(&self) -> u64 {
+        let mut key = self.piece_key;
+        if self.player_turn == Color::Black {
+            key ^= zobrist::BLACK_TO_MOVE_KEY;
+        }
+        let rights = [self.white_can_castle_king, self.white_can_castle_queen, self.black_can_castle_king, self.black_can_castle_queen];
+        for (right, castling_key) in rights.into_iter().zip(zobrist::CASTLING_KEYS) {
+            if right {
+                key ^= castling_key;
+            }
+        }
+        if let Some((x, _)) = self.en_passant_square() {
+            key ^= zobrist::EN_PASSANT_KEYS[x];
+        }
+        key
+    }
+
+    /// Whether the game is drawn in this position, and why.
+    /// # Description
+    /// Checkmate is never a draw, even when the mating move was the hundredth halfmove.
+    /// When more than one reason applies the first in this order is returned, which is also the order of DrawReason:
+    /// stalemate, insufficient material, the fifty-move rule, threefold repetition.
+    /// Repetitions are only seen in the moves played with move_piece or make_move, see repetition_count.
+    /// # Inputs/Outputs
+    /// - Output: The reason the position is drawn, None if play goes on or the side to move is mated
+    /// # Example
+    /// ``` Rust
+    /// let board = Board::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+    /// assert_eq!(board.is_draw(), Some(DrawReason::Stalemate));
+    /// assert_eq!(Board::starting_position().is_draw(), None);
+    /// ```
+    pub fn is_draw(&self) -> Option<DrawReason> {
+        self.draw_reason(self.has_legal_moves(), self.repetition_count())
+    }
+
+    /// is_draw from values the caller has already worked out.
+    /// # Description
+    /// The search and Game::snapshot generate the moves of the position anyway,
+    /// so they pass on whether there are any and the repetition count instead of having them found again.
+    /// Reasons are checked in the same order as is_draw.
+    /// # Inputs/Outputs
+    /// - Input: has_legal_moves - Whether the side to move has a legal move
+    /// - Input: repetitions - The value of repetition_count for the position
+    /// - Output: The reason the position is drawn, None if play goes on or the side to move is mated
+    pub fn draw_reason(&self, has_legal_moves: bool, repetitions: usize) -> Option<DrawReason> {
+        if !has_legal_moves {
+            return (!self.king_in_check()).then_some(DrawReason::Stalemate);
+        }
+        if self.is_insufficient_material() {
+            Some(DrawReason::InsufficientMaterial)
+        } else if self.halfmove >= 100 {
+            Some(DrawReason::FiftyMoveRule)
+        } else if repetitions >= 2 {
+            Some(DrawReason::ThreefoldRepetition)
+        } else {
+            None
+        }
+    }
+
+    /// Whether neither side can ever give mate.
+    /// # Description
+    /// True for king against king, a single knight or bishop against a bare king,
+    /// and any number of bishops when they all stand on squares of one color.
+    /// Positions where mate is possible but can not be forced, such as two knights against a king, are not included.
+    pub fn is_insufficient_material(&self) -> bool {
+        let heavy = [PieceType::Pawn, PieceType::Rook, PieceType::Queen];
+        if [Color::White, Color::Black].iter().any(|&color| heavy.iter().any(|&ptype| self.pieces(ptype, color) != 0)) {
+            return false;
+        }
+        let knights = self.pieces(PieceType::Knight, Color::White) | self.pieces(PieceType::Knight, Color::Black);
+        let bishops = self.pieces(PieceType::Bishop, Color::White) | self.pieces(PieceType::Bishop, Color::Black);
+        if (knights | bishops).count_ones() <= 1 {
+            return true;
+        }
+        knights == 0 && (bishops & bitboard::LIGHT_SQUARES == 0 || bishops & !bitboard::LIGHT_SQUARES == 0)
+    }
+
+    /// How many times the current position has occurred before in the moves played with move_piece or make_move.
+    /// # Description
+    /// Compares Zobrist keys against the keys make_move keeps for the positions it was called from.
+    /// Only positions since the last capture or pawn move with the same side to move are compared, as no others can match.
+    /// A count of 2 means the position has now appeared three times.
+    pub fn repetition_count(&self) -> usize {
+        let key = self.zobrist_key();
+        let reversible = (self.halfmove as usize).min(self.keys.len());
+        self.keys[self.keys.len() - reversible..].iter().rev().skip(1).step_by(2).filter(|&&other| other == key).count()
+    }
+
+    /// Checks the position can be played from.
+    /// # Description
+    /// Each side must have exactly one king, as check detection tracks a single king per color,
+    /// and no pawn may stand on the first or last rank.
+    /// Any amount of other material is allowed, so positions from editors with extra queens still load.
+    /// # Inputs/Outputs
+    /// - Output: Ok if the position is playable, otherwise the problem found
+    /// # Example
+    /// ``` Rust
+    /// let board = Board::starting_position();
+    /// assert!(board.validate_position().is_ok());
+    /// ```
+    pub fn validate_position(&self) -> Result<(), FenError> {
+        for color in [Color::White, Color::Black] {
+            match self.pieces(PieceType::King, color).count_ones() {
+                0 => return Err(FenError::MissingKing(color)),
+                1 => (),
+                _ => return Err(FenError::TooManyKings(color)),
+            }
+        }
+        let pawns = self.pieces(PieceType::Pawn, Color::White) | self.pieces(PieceType::Pawn, Color::Black);
+        if let Some(index) = bitboard::squares(pawns & (bitboard::RANK_1 | bitboard::RANK_8)).next() {
+            return Err(FenError::PawnOnBackRank(coords_to_square(bitboard::index_to_coords(index))));
         }
+        Ok(())
+    }
 
-        Some(board)
+    /// Checks the board's cached state agrees with its pieces.
+    /// # Description
+    /// Check detection reads the cached king squares and move generation reads the bitboards,
+    /// so a board edited behind their back gives wrong answers rather than failing.
+    /// This verifies each side has exactly one king and it is on its cached square, the bitboards match the squares,
+    /// and the en passant square is behind the side that just moved.
+    /// In debug builds move_piece runs it after every move and takes back a move that fails it.
+    /// # Inputs/Outputs
+    /// - Output: Ok if the board is consistent, otherwise the first problem found
+    /// # Example
+    /// ``` Rust
+    /// let mut board = Board::starting_position();
+    /// assert!(board.validate().is_ok());
+    /// ```
+    pub fn validate(&self) -> Result<(), ConsistencyError> {
+        for color in [Color::White, Color::Black] {
+            let count = self.pieces(PieceType::King, color).count_ones();
+            if count != 1 {
+                return Err(ConsistencyError::KingCount { color, count });
+            }
+            let cached = if color == Color::White { self.white_king_position } else { self.black_king_position };
+            if self.squares[cached.1][cached.0] != Some(Piece::new(PieceType::King, color)) {
+                return Err(ConsistencyError::KingCacheMismatch { color, cached });
+            }
+        }
+        for (y, rank) in self.squares.iter().enumerate() {
+            for (x, square) in rank.iter().enumerate() {
+                let bit = bitboard::square_bit(x, y);
+                let expected = square.map(|piece| (piece.get_color().index(), piece.get_type().index()));
+                let matches = (0..2).all(|color| {
+                    let occupied = self.occupancy[color] & bit != 0;
+                    occupied == expected.is_some_and(|(c, _)| c == color)
+                        && (0..6).all(|ptype| (self.bitboards[color][ptype] & bit != 0) == (expected == Some((color, ptype))))
+                });
+                if !matches {
+                    return Err(ConsistencyError::BitboardMismatch((x, y)));
+                }
+            }
+        }
+        if let Some((x, y)) = self.en_passant {
+            let expected_y = if self.player_turn == Color::White { 5 } else { 2 };
+            if y != expected_y {
+                return Err(ConsistencyError::BadEnPassantSquare((x, y)));
+            }
+        }
+        Ok(())
     }
 
     /// Get the starting position of a chess game.
@@ -153,16 +584,192 @@ impl Board {
 
     }
 
-    /// Returns a reference to the squares vectors 
+    /// Creates a board with nothing on it, ready to be filled in with the editing methods.
+    /// # Description
+    /// Unlike new, which keeps every castling right, the empty board has no castling rights.
+    /// White is to move, there is no en passant square, the halfmove clock is 0 and the fullmove number is 1.
+    /// The board is not a legal position until both kings are placed, validate_position reports what is missing.
+    /// While a side has no king the position of its king is meaningless and check detection must not be used.
+    /// # Example
+    /// ``` Rust
+    /// let mut board = Board::empty();
+    /// board.set_piece(4, 0, Piece::new(PieceType::King, Color::White));
+    /// board.set_piece(4, 7, Piece::new(PieceType::King, Color::Black));
+    /// assert!(board.validate_position().is_ok());
+    /// ```
+    pub fn empty() -> Board {
+        Board {
+            white_can_castle_king: false,
+            white_can_castle_queen: false,
+            black_can_castle_king: false,
+            black_can_castle_queen: false,
+            ..Board::new()
+        }
+    }
+
+    /// Put a piece on a square, replacing whatever was there.
+    /// # Description
+    /// The editing methods keep the bitboards and king positions in sync but do not check the result is legal,
+    /// call validate_position once the position is built.
+    /// Editing forgets the move history, so moves made before can no longer be undone.
+    /// # Inputs/Outputs
+    /// - Input: x, y - The square, see the coords module
+    /// - Input: piece - The piece to place
+    /// - Output: The piece that was on the square, if any
+    pub fn set_piece(&mut self, x: usize, y: usize, piece: Piece) -> Option<Piece> {
+        let previous = self.set_square(x, y, Some(piece));
+        self.after_edit();
+        previous
+    }
+
+    /// Take the piece off a square, returning it if there was one
+    pub fn remove_piece(&mut self, x: usize, y: usize) -> Option<Piece> {
+        let previous = self.take_square(x, y);
+        self.after_edit();
+        previous
+    }
+
+    /// Move whatever is on one square to another, ignoring the rules, as when dragging pieces on an analysis board.
+    /// # Description
+    /// Anything on the destination is taken off. The side to move is not changed and nothing is checked for legality,
+    /// but the bitboards and king positions are kept in sync, castling rights whose king or rook has left
+    /// its starting square are dropped and the en passant square is cleared.
+    /// Like the other editing methods it forgets the move history, call validate_position when done.
+    /// # Inputs/Outputs
+    /// - Input: from, to - The squares as (x, y) coordinates, see the coords module
+    /// - Output: The piece that was on the destination, if any. Nothing happens when from is empty.
+    /// # Example
+    /// ``` Rust
+    /// let mut board = Board::starting_position();
+    /// // Drag the h1 rook to h5, White loses the right to castle king side
+    /// board.force_move((7, 0), (7, 4));
+    /// assert_eq!(board.to_fen(), "rnbqkbnr/pppppppp/8/7R/8/8/PPPPPPP1/RNBQKBN1 w Qkq - 0 1");
+    /// ```
+    pub fn force_move(&mut self, from: (usize, usize), to: (usize, usize)) -> Option<Piece> {
+        if from == to || self.squares[from.1][from.0].is_none() {
+            return None;
+        }
+        let captured = self.unchecked_move_piece(from.0, from.1, to.0, to.1);
+        let repairs = self.castling_repairs();
+        self.drop_castling_rights(&repairs);
+        self.en_passant = None;
+        self.after_edit();
+        captured
+    }
+
+    /// Remove every piece and reset the rest of the position to that of Board::empty
+    pub fn clear(&mut self) {
+        *self = Board::empty();
+    }
+
+    /// Set the side to move
+    pub fn set_turn(&mut self, color: Color) {
+        self.player_turn = color;
+        self.in_check = None;
+        self.forget_history();
+    }
+
+    /// Set the castling rights.
+    /// Rights are kept as given even when the king or rook is not on its starting square.
+    pub fn set_castling_rights(&mut self, rights: CastlingRights) {
+        self.white_can_castle_king = rights.white_king;
+        self.white_can_castle_queen = rights.white_queen;
+        self.black_can_castle_king = rights.black_king;
+        self.black_can_castle_queen = rights.black_queen;
+        self.forget_history();
+    }
+
+    /// Set the square behind a pawn that just moved two squares, or None if there is none
+    pub fn set_en_passant(&mut self, square: Option<(usize, usize)>) {
+        self.en_passant = square;
+        self.forget_history();
+    }
+
+    /// The same position with the colors swapped.
+    /// # Description
+    /// Reflects the board between rank 1 and rank 8 and swaps the color of every piece, so White's position
+    /// becomes Black's. The side to move, castling rights and en passant square are swapped to match,
+    /// so the mirrored position has the same legal moves reflected and an evaluation of the opposite sign.
+    /// The clocks are kept and the move history is not carried over.
+    /// # Example
+    /// ``` Rust
+    /// let board = Board::from_fen("4k3/8/8/8/3pP3/8/8/4K2R b K e3 0 1").unwrap();
+    /// assert_eq!(board.mirror().to_fen(), "4k2r/8/8/3Pp3/8/8/8/4K3 w k e6 0 1");
+    /// assert_eq!(board.basic_evaluate(), -board.mirror().basic_evaluate());
+    /// ```
+    pub fn mirror(&self) -> Board {
+        let mut mirrored = self.transformed(|x, y| (x, 7 - y), |piece| Piece::new(piece.get_type(), piece.get_color().opposite()));
+        mirrored.player_turn = self.player_turn.opposite();
+        mirrored.white_can_castle_king = self.black_can_castle_king;
+        mirrored.white_can_castle_queen = self.black_can_castle_queen;
+        mirrored.black_can_castle_king = self.white_can_castle_king;
+        mirrored.black_can_castle_queen = self.white_can_castle_queen;
+        mirrored.en_passant = self.en_passant.map(|(x, y)| (x, 7 - y));
+        mirrored
+    }
+
+    /// The same position reflected between the a-file and the h-file, colors unchanged.
+    /// # Description
+    /// Castling rights are dropped, as the kings and rooks end up on files castling does not start from.
+    /// The en passant square is reflected, the clocks are kept and the move history is not carried over.
+    /// # Example
+    /// ``` Rust
+    /// let board = Board::from_fen("4k3/8/8/8/8/8/1P6/4K2R w K - 0 1").unwrap();
+    /// assert_eq!(board.flip_horizontal().to_fen(), "3k4/8/8/8/8/8/6P1/R2K4 w - - 0 1");
+    /// ```
+    pub fn flip_horizontal(&self) -> Board {
+        let mut flipped = self.transformed(|x, y| (7 - x, y), |piece| piece);
+        flipped.player_turn = self.player_turn;
+        flipped.en_passant = self.en_passant.map(|(x, y)| (7 - x, y));
+        flipped
+    }
+
+    /// An empty board holding every piece moved to a new square and changed, with the clocks copied over
+    fn transformed(&self, square: impl Fn(usize, usize) -> (usize, usize), change: impl Fn(Piece) -> Piece) -> Board {
+        let mut board = Board::empty();
+        for (y, rank) in self.squares.iter().enumerate() {
+            for (x, piece) in rank.iter().enumerate() {
+                if let Some(piece) = piece {
+                    let (to_x, to_y) = square(x, y);
+                    board.set_square(to_x, to_y, Some(change(*piece)));
+                }
+            }
+        }
+        board.after_edit();
+        board.halfmove = self.halfmove;
+        board.move_number = self.move_number;
+        board
+    }
+
+    /// Drops the moves played and the positions they were made from, after an edit makes them unreachable
+    fn forget_history(&mut self) {
+        self.history.clear();
+        self.keys.clear();
+    }
+
+    /// Brings the cached king positions back in line with the bitboards after a square was edited
+    fn after_edit(&mut self) {
+        for color in [Color::White, Color::Black] {
+            let kings = self.pieces(PieceType::King, color);
+            let position = if kings == 0 { (0, 0) } else { bitboard::index_to_coords(kings.trailing_zeros() as usize) };
+            match color {
+                Color::White => self.white_king_position = position,
+                Color::Black => self.black_king_position = position,
+            }
+        }
+        self.forget_history();
+    }
+
+    /// Returns a reference to the squares array 
     /// # Description 
     /// A method private to the crate that is used for testing 
     /// Call once moves have been made to compare to expected postion 
-    pub fn get_squares(&self) -> &Vec<Vec<Option<Piece>>> {
+    pub fn get_squares(&self) -> &[[Option<Piece>; 8]; 8] {
         &self.squares
     }
 
     pub fn get_piece(&self, x: usize, y: usize) -> Option<Piece> {
-        self.squares[y][x].clone()
+        self.squares[y][x]
 
     }
 
@@ -170,10 +777,104 @@ impl Board {
         &self.player_turn
     }
 
+    /// The castling rights of both sides
+    /// # Description
+    /// Rights only record that the king and rook have not moved, castling may still be blocked or pass through check.
+    /// # Example
+    /// ``` Rust
+    /// let board = Board::starting_position();
+    /// assert_eq!(board.castling_rights().to_string(), "KQkq");
+    /// ```
+    pub fn castling_rights(&self) -> CastlingRights {
+        CastlingRights {
+            white_king: self.white_can_castle_king,
+            white_queen: self.white_can_castle_queen,
+            black_king: self.black_can_castle_king,
+            black_queen: self.black_can_castle_queen,
+        }
+    }
+
+    /// The square a pawn can be captured on en passant.
+    /// Only given when a pawn of the side to move stands beside the pawn that just moved two squares.
+    pub fn en_passant_square(&self) -> Option<(usize, usize)> {
+        self.en_passant_square_with(EnPassantMode::Capturable)
+    }
+
+    /// The en passant square as reported by the given mode
+    pub fn en_passant_square_with(&self, mode: EnPassantMode) -> Option<(usize, usize)> {
+        match mode {
+            EnPassantMode::Permissive => self.en_passant,
+            EnPassantMode::Capturable => self.en_passant.filter(|_| self.en_passant_capture_possible()),
+            EnPassantMode::Legal => self.en_passant.filter(|&(x, y)| {
+                let capturers = PAWN_ATTACKS[self.player_turn.opposite().index()][bitboard::square_index(x, y)]
+                    & self.pieces(PieceType::Pawn, self.player_turn);
+                let mut scratch = self.position_copy();
+                bitboard::squares(capturers).any(|index| {
+                    let (from_x, from_y) = bitboard::index_to_coords(index);
+                    scratch.is_legal_move(&Move::new(from_x, from_y, x, y, PieceType::Pawn, None))
+                })
+            }),
+        }
+    }
+
+    /// Halfmoves since the last capture or pawn move, used for the fifty-move rule
+    pub fn halfmove_clock(&self) -> u32 {
+        self.halfmove
+    }
+
+    /// The number of the current full move, starting at 1 and incremented after Black moves
+    pub fn fullmove_number(&self) -> u32 {
+        self.move_number
+    }
+
+    /// Whether a pawn of the side to move attacks the en passant square.
+    /// # Description
+    /// A FEN may record an en passant square after any double pawn push,
+    /// but the capture is only available when an enemy pawn stands beside the pushed pawn.
+    /// Position comparisons should ignore the en passant square when this is false.
+    /// Pins are not considered, the square counts if it is attacked.
+    pub fn en_passant_capture_possible(&self) -> bool {
+        match self.en_passant {
+            Some((x, y)) => {
+                let attackers = PAWN_ATTACKS[self.player_turn.opposite().index()][bitboard::square_index(x, y)];
+                attackers & self.pieces(PieceType::Pawn, self.player_turn) != 0
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the bitboard of all pieces of one type and color
+    pub(crate) fn pieces(&self, piece_type: PieceType, color: Color) -> Bitboard {
+        self.bitboards[color.index()][piece_type.index()]
+    }
+
+    /// Every square with its piece, in the order to_ascii draws them.
+    /// # Description
+    /// Goes along the top row from left to right, then each row below it, as the board is seen from perspective.
+    /// For White that is a8, b8 … h8, a7 … h1 and for Black h1, g1 … a1, h2 … a8.
+    /// Lets a GUI lay out and label its squares without working out the perspective itself.
+    /// # Inputs/Outputs
+    /// - Input: perspective: Color - The side whose pieces are drawn at the bottom
+    /// - Output: The 64 squares, each with the piece standing on it
+    /// # Example
+    /// ``` Rust
+    /// let board = Board::starting_position();
+    /// for (square, piece) in board.squares_in_display_order(Color::Black) {
+    ///     println!("{} {:?}", square, piece);
+    /// }
+    /// ```
+    pub fn squares_in_display_order(&self, perspective: Color) -> impl Iterator<Item = (SquareName, Option<&Piece>)> {
+        (0..64).map(move |position| {
+            let y = coords::y_to_display_row(position / 8, perspective);
+            let x = coords::x_to_display_column(position % 8, perspective);
+            (SquareName::new(x, y), self.squares[y][x].as_ref())
+        })
+    }
+
     /// Print the board to the console.
     /// # Description
-    /// Prints the board to the console with the given perspective.
-    /// Also contains the coordinates of the board.
+    /// Prints the board as drawn by to_ascii with the given perspective.
+    /// Prefer to_ascii or Display where stdout is not free to use, such as under UCI.
     /// # Inputs/Outputs
     /// - Inputs: The perspective of the board.
     /// - Returns: Nothing.
@@ -183,156 +884,675 @@ impl Board {
     /// board.print(Color::White);
     /// ```
     pub fn print(&self, perspective: Color) {
-        let (column_label, rows, columns) = if perspective == Color::White {
-            ("    a   b   c   d   e   f   g   h", (0..8).rev().collect::<Vec<_>>(), (0..8).collect::<Vec<_>>())
-        } else {
-            ("    h   g   f   e   d   c   b   a", (0..8).collect::<Vec<_>>(), (0..8).rev().collect::<Vec<_>>())
+        print!("{}", self.to_ascii(perspective));
+    }
+
+    /// Draw the board as text.
+    /// # Description
+    /// Draws the board from the given perspective with a grid around the squares and the coordinates on every side.
+    /// Pieces use their FEN letters. Every line, including the last, ends with a newline.
+    /// # Inputs/Outputs
+    /// - Input: perspective: Color - The side whose pieces are drawn at the bottom
+    /// - Output: String - The drawing
+    /// # Example
+    /// ``` Rust
+    /// let board = Board::starting_position();
+    /// log::info!("\n{}", board.to_ascii(Color::Black));
+    /// ```
+    pub fn to_ascii(&self, perspective: Color) -> String {
+        self.render(&BoardRenderOptions { perspective, ..BoardRenderOptions::default() })
+    }
+
+    /// Draw the board as text with a choice of piece symbols and highlights.
+    /// # Description
+    /// Uses the same grid as to_ascii. Each square is three characters wide:
+    /// the squares of the highlighted move are drawn as "[P]" and a king in check as "!K!",
+    /// or in inverse video and red with options.ansi.
+    /// Seen from Black's side both the ranks and the files are reversed, labels included.
+    /// With options.status the status_line follows the board.
+    /// # Inputs/Outputs
+    /// - Input: options: &BoardRenderOptions - What to draw
+    /// - Output: String - The drawing, every line ending with a newline
+    /// # Example
+    /// ``` Rust
+    /// let mut board = Board::starting_position();
+    /// let mv = Move::new(4, 1, 4, 3, PieceType::Pawn, None);
+    /// board.move_piece(mv.clone()).unwrap();
+    /// let options = BoardRenderOptions { unicode: true, highlight: Some(mv), mark_check: true, perspective: Color::Black, ..Default::default() };
+    /// println!("{}", board.render(&options));
+    /// ```
+    pub fn render(&self, options: &BoardRenderOptions) -> String {
+        let perspective = options.perspective;
+        let checked_king = match self.player_turn {
+            Color::White => self.white_king_position,
+            Color::Black => self.black_king_position,
         };
-        println!("{}", column_label); 
-
-        for i in &rows {
-            println!("  +---+---+---+---+---+---+---+---+");
-            let row_label = i + 1;
-            print!("{} ", row_label);
-
-            for j in &columns {
-                print!("| ");
-                let symbol = match &self.squares[*i][*j] {
-                    Some(piece) => piece.get_piece_char().to_string(),
-                    None => " ".to_string(),
+        let checked_king = (options.mark_check && self.king_in_check()).then_some(checked_king);
+        let highlighted = |x: usize, y: usize| options.highlight.as_ref()
+            .is_some_and(|mv| (mv.from_x, mv.from_y) == (x, y) || (mv.to_x, mv.to_y) == (x, y));
+        let column_label = (0..8).fold(" ".to_string(), |label, column| {
+            format!("{}   {}", label, file_char(coords::x_to_display_column(column, perspective)))
+        });
+        let mut ascii = format!("{}\n", column_label);
+        for row in 0..8 {
+            let y = coords::y_to_display_row(row, perspective);
+            ascii.push_str("  +---+---+---+---+---+---+---+---+\n");
+            ascii.push_str(&format!("{} ", rank_char(y)));
+            for column in 0..8 {
+                let x = coords::x_to_display_column(column, perspective);
+                let symbol = match &self.squares[y][x] {
+                    Some(piece) if options.unicode => piece.unicode_char(),
+                    Some(piece) => piece.get_piece_char(),
+                    None => ' ',
                 };
-                print!("{} ", symbol);
+                let square = if checked_king == Some((x, y)) {
+                    if options.ansi { format!("\x1b[41m {} \x1b[0m", symbol) } else { format!("!{}!", symbol) }
+                } else if highlighted(x, y) {
+                    if options.ansi { format!("\x1b[7m {} \x1b[0m", symbol) } else { format!("[{}]", symbol) }
+                } else {
+                    format!(" {} ", symbol)
+                };
+                ascii.push_str(&format!("|{}", square));
             }
-            println!("| {}", row_label);
+            ascii.push_str(&format!("| {}\n", rank_char(y)));
+        }
+        ascii.push_str("  +---+---+---+---+---+---+---+---+\n");
+        ascii.push_str(&format!("{}\n", column_label));
+        if options.status {
+            ascii.push_str(&format!("{}\n", self.status_line()));
         }
-        println!("  +---+---+---+---+---+---+---+---+");
-        println!("{}", column_label); 
+        ascii
+    }
+
+    /// A line describing the state of the game for the side to move.
+    /// # Description
+    /// One of "White to move", "Black to move — in check", "Black is checkmated" or "Draw by stalemate".
+    /// # Example
+    /// ``` Rust
+    /// let board = Board::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+    /// assert_eq!(board.status_line(), "Draw by stalemate");
+    /// ```
+    pub fn status_line(&self) -> String {
+        if let Some(reason) = self.is_draw() {
+            return format!("Draw by {}", reason);
+        }
+        if !self.has_legal_moves() {
+            return format!("{} is checkmated", self.player_turn);
+        }
+        if self.king_in_check() {
+            format!("{} to move — in check", self.player_turn)
+        } else {
+            format!("{} to move", self.player_turn)
+        }
+    }
+
+    /// The last move played with move_piece, None at the start or after the position was edited
+    pub fn last_move(&self) -> Option<&Move> {
+        self.history.last().map(|(mv, _)| mv)
+    }
+
+    /// The moves played with move_piece since the position was set up or last edited, oldest first
+    pub fn moves_played(&self) -> impl Iterator<Item = &Move> {
+        self.history.iter().map(|(mv, _)| mv)
     }
 
     /// Move a pice from one square to another.
     /// Returns the piece that was taken, if any.
     /// This function does not check if the move is legal.
     /// It also does not update the player turn or increment the move number.
+    /// A king that is moved has its cached position updated, so check detection stays correct.
     pub fn unchecked_move_piece(&mut self, from_x: usize, from_y: usize, to_x: usize, to_y: usize) -> Option<Piece> {
-        let piece = self.squares[from_y][from_x].take();
-        let taken_piece = self.squares[to_y][to_x].take();
-        self.squares[to_y][to_x] = piece;
-        taken_piece
+        let piece = self.take_square(from_x, from_y);
+        if let Some(king) = piece.filter(|piece| piece.get_type() == PieceType::King) {
+            match king.get_color() {
+                Color::White => self.white_king_position = (to_x, to_y),
+                Color::Black => self.black_king_position = (to_x, to_y),
+            }
+        }
+        self.set_square(to_x, to_y, piece)
+    }
+
+    /// Remove the piece on a square, if any.
+    /// Keeps the bitboards, material balance and piece key in sync with the squares array and forgets whether the side to move is in check.
+    fn take_square(&mut self, x: usize, y: usize) -> Option<Piece> {
+        let piece = self.squares[y][x].take();
+        self.in_check = None;
+        if let Some(piece) = piece {
+            let bit = bitboard::square_bit(x, y);
+            self.bitboards[piece.get_color().index()][piece.get_type().index()] &= !bit;
+            self.occupancy[piece.get_color().index()] &= !bit;
+            self.material -= material_value(piece);
+            self.piece_key ^= zobrist::PIECE_KEYS[piece.get_color().index()][piece.get_type().index()][bitboard::square_index(x, y)];
+        }
+        piece
+    }
+
+    /// Place a piece on a square (or clear it with None) and return what was there before.
+    /// Keeps the bitboards, material balance and piece key in sync with the squares array.
+    fn set_square(&mut self, x: usize, y: usize, piece: Option<Piece>) -> Option<Piece> {
+        let previous = self.take_square(x, y);
+        if let Some(piece) = piece {
+            let bit = bitboard::square_bit(x, y);
+            self.bitboards[piece.get_color().index()][piece.get_type().index()] |= bit;
+            self.occupancy[piece.get_color().index()] |= bit;
+            self.material += material_value(piece);
+            self.piece_key ^= zobrist::PIECE_KEYS[piece.get_color().index()][piece.get_type().index()][bitboard::square_index(x, y)];
+            self.squares[y][x] = Some(piece);
+        }
+        previous
     }
 
-    /// Get the first piece in a given direction.
+    /// Check if a square is attacked by a piece of a given color.
     /// # Description
-    /// Returns the coordinates of the first piece in the given direction.
-    /// If there is no piece in the given direction, it returns None.
-    /// dx and dy are the direction in which to look for a piece.
-    /// These values should be -1, 0, or 1.
+    /// Checks if a square is attacked by a piece of a given color.
+    /// This function is used to check if a king is in check.
+    /// Also used to check if a square is attacked for castling
     /// # Inputs/Outputs
-    /// - Input: The x and y coordinate of the start
-    /// - Input: The direction in which to look for a piece
-    /// - Returns: The coordinates of the first piece in the given direction, if any.
+    /// - Input: The x and y coordinate of the square
+    /// - Input: The color of the attacking pieces
+    /// - Returns: True if the square is attacked, otherwise false.
     /// # Example
     /// ``` Rust
     /// let board = Board::starting_position();
-    /// // Starting from e4 and going north, the first piece is on e7
-    /// let (x, y) = board.first_piece_in_direction(4, 3, 0, 1).unwrap();
-    /// assert_eq!(x, 4);
-    /// assert_eq!(y, 6);
+    /// // The square e3 is attacked by a white pawn
+    /// assert!(board.is_square_attacked(4, 2, Color::White));
+    /// ```
+    pub(crate) fn is_square_attacked(&self, x: usize, y: usize, color: Color) -> bool {
+        self.is_index_attacked(bitboard::square_index(x, y), color, self.occupancy[0] | self.occupancy[1])
+    }
+
+    /// is_square_attacked for the Criterion benchmarks, which can only reach public items
+    #[cfg(feature = "bench")]
+    pub fn bench_is_square_attacked(&self, x: usize, y: usize, color: Color) -> bool {
+        self.is_square_attacked(x, y, color)
+    }
+
+    /// is_square_attacked with the sliders' lines worked out from the given occupancy rather than the board's,
+    /// e.g. with the king lifted off so it can not hide behind itself
+    fn is_index_attacked(&self, index: usize, color: Color, occupancy: Bitboard) -> bool {
+        let pieces = &self.bitboards[color.index()];
+        // A pawn of the attacking color attacks this square from the squares
+        // a pawn of the other color on this square would attack
+        if PAWN_ATTACKS[color.opposite().index()][index] & pieces[PieceType::Pawn.index()] != 0 {
+            return true;
+        }
+        if KNIGHT_ATTACKS[index] & pieces[PieceType::Knight.index()] != 0 {
+            return true;
+        }
+        if KING_ATTACKS[index] & pieces[PieceType::King.index()] != 0 {
+            return true;
+        }
+        let queens = pieces[PieceType::Queen.index()];
+        if bitboard::rook_attacks(index, occupancy) & (pieces[PieceType::Rook.index()] | queens) != 0 {
+            return true;
+        }
+        bitboard::bishop_attacks(index, occupancy) & (pieces[PieceType::Bishop.index()] | queens) != 0
+    }
+
+    /// Every square attacked by one side.
+    /// # Description
+    /// The other side's king is left off the board while the attacks are worked out, so a square behind the king
+    /// on a slider's line counts as attacked. That makes the map the answer to where the king may step.
+    /// Move generation works it out once per position for king moves and castling.
+    /// # Inputs/Outputs
+    /// - Input: by: Color - The attacking side
+    /// - Output: The attacked squares
+    /// # Example
+    /// ``` Rust
+    /// let board = Board::from_fen("R3k3/8/8/8/8/8/8/4K3 b - - 0 1").unwrap();
+    /// // The rook attacks the whole eighth rank through the king
+    /// assert!(board.attack_map(Color::White).is_attacked(5, 7));
+    /// ```
+    pub fn attack_map(&self, by: Color) -> AttackMap {
+        let pieces = &self.bitboards[by.index()];
+        let occupancy = (self.occupancy[0] | self.occupancy[1]) & !self.pieces(PieceType::King, by.opposite());
+        let mut attacked = 0;
+        for index in bitboard::squares(pieces[PieceType::Pawn.index()]) {
+            attacked |= PAWN_ATTACKS[by.index()][index];
+        }
+        for index in bitboard::squares(pieces[PieceType::Knight.index()]) {
+            attacked |= KNIGHT_ATTACKS[index];
+        }
+        for index in bitboard::squares(pieces[PieceType::King.index()]) {
+            attacked |= KING_ATTACKS[index];
+        }
+        let queens = pieces[PieceType::Queen.index()];
+        for index in bitboard::squares(pieces[PieceType::Rook.index()] | queens) {
+            attacked |= bitboard::rook_attacks(index, occupancy);
+        }
+        for index in bitboard::squares(pieces[PieceType::Bishop.index()] | queens) {
+            attacked |= bitboard::bishop_attacks(index, occupancy);
+        }
+        AttackMap::new(attacked)
+    }
+
+    /// Find every piece of a color that attacks a square.
+    /// # Description
+    /// Uses the same pawn, knight, king and sliding attacks as is_square_attacked but collects all attackers.
+    /// Only direct attackers are returned, a slider behind another piece on the same line is not included.
+    /// Pieces of the other color are never included, whatever stands on the square itself.
+    /// # Inputs/Outputs
+    /// - Input: The x and y coordinate of the square
+    /// - Input: The color of the attacking pieces
+    /// - Returns: The coordinates of the attackers, ordered a1 to h8
+    /// # Example
+    /// ``` Rust
+    /// let board = Board::starting_position();
+    /// // f3 is attacked by the g1 knight and the e2 and g2 pawns
+    /// assert_eq!(board.attackers_to(5, 2, Color::White), vec![(6, 0), (4, 1), (6, 1)]);
+    /// ```
+    pub fn attackers_to(&self, x: usize, y: usize, by_color: Color) -> Vec<(usize, usize)> {
+        let occupancy = self.occupancy[0] | self.occupancy[1];
+        bitboard::squares(self.attackers(bitboard::square_index(x, y), by_color, occupancy))
+            .map(bitboard::index_to_coords)
+            .collect()
+    }
+
+    /// The bitboard of pieces of a color attacking a square, given which squares count as occupied
+    pub(crate) fn attackers(&self, index: usize, color: Color, occupancy: Bitboard) -> Bitboard {
+        let pieces = &self.bitboards[color.index()];
+        let queens = pieces[PieceType::Queen.index()];
+        let attackers = (PAWN_ATTACKS[color.opposite().index()][index] & pieces[PieceType::Pawn.index()])
+            | (KNIGHT_ATTACKS[index] & pieces[PieceType::Knight.index()])
+            | (KING_ATTACKS[index] & pieces[PieceType::King.index()])
+            | (bitboard::rook_attacks(index, occupancy) & (pieces[PieceType::Rook.index()] | queens))
+            | (bitboard::bishop_attacks(index, occupancy) & (pieces[PieceType::Bishop.index()] | queens));
+        // Pieces left out of the occupancy are treated as already gone
+        attackers & occupancy
+    }
+
+    /// Sorts moves for searching, captures and promotions first by Move::mvv_lva_score.
+    /// The sort is stable so quiet moves keep their generation order after them.
+    /// # Example
+    /// ``` Rust
+    /// let board = Board::from_fen("4k3/8/8/3q4/4P3/8/8/4K3 w - - 0 1").unwrap();
+    /// let mut moves = board.generate_legal_moves();
+    /// board.order_moves(&mut moves);
+    /// assert_eq!(moves[0].extended_algebraic(), "e4d5");
+    /// ```
+    pub fn order_moves(&self, moves: &mut [Move]) {
+        moves.sort_by_cached_key(|mv| -mv.mvv_lva_score(self));
+    }
+
+    /// Static exchange evaluation of a move.
+    /// # Description
+    /// Plays out the exchange on the destination square, each side recapturing with its least valuable attacker,
+    /// and returns the material balance for the side making the move once either side chooses to stop.
+    /// Sliders behind a piece that has captured join the exchange, pins are not considered.
+    /// En passant captures win a pawn and a pawn reaching the last rank is counted as a queen.
+    /// # Inputs/Outputs
+    /// - Input: mv: &Move - The move to evaluate, normally a capture
+    /// - Output: The expected material gain in centipawns, negative if the move loses material
+    /// # Example
+    /// ``` Rust
+    /// let board = Board::from_fen("4k3/8/3p4/4p3/8/8/8/4QK2 w - - 0 1").unwrap();
+    /// // Qxe5 dxe5 gives up the queen for a pawn
+    /// assert_eq!(board.see(&Move::new(4, 0, 4, 4, PieceType::Queen, None)), -800);
     /// ```
-    fn first_piece_in_direction(&self, x: usize, y: usize, dx: i8, dy: i8) -> Option<(usize, usize)> {
-        let mut x = x as i8 + dx;
-        let mut y = y as i8 + dy;
-        while x >= 0 && x < 8 && y >= 0 && y < 8 {
-            if self.squares[y as usize][x as usize].is_some() {
-                //log::trace!("First piece in direction ({},{}) is ({},{}) - {:?}", dx, dy, x, y, self.get_piece(x as usize, y as usize));
-                return Some((x as usize, y as usize));
+    pub fn see(&self, mv: &Move) -> i32 {
+        let target = bitboard::square_index(mv.to_x, mv.to_y);
+        let promotion_gain = PieceType::Queen.to_centipawns() - PieceType::Pawn.to_centipawns();
+        let last_rank = mv.to_y == 0 || mv.to_y == 7;
+        let mut occupancy = (self.occupancy[0] | self.occupancy[1]) & !bitboard::square_bit(mv.from_x, mv.from_y);
+        let mut color = self.squares[mv.from_y][mv.from_x].map_or(self.player_turn, |piece| piece.get_color());
+
+        let mut gain = [0; 32];
+        gain[0] = match &self.squares[mv.to_y][mv.to_x] {
+            Some(victim) => see_value(victim.get_type()),
+            None if mv.piece_type == PieceType::Pawn && mv.from_x != mv.to_x => {
+                // En passant, the captured pawn is beside the moving pawn
+                occupancy &= !bitboard::square_bit(mv.to_x, mv.from_y);
+                PieceType::Pawn.to_centipawns()
             }
-            x += dx;
-            y += dy;
+            None => 0,
+        };
+        // Value of the piece standing on the target square, the next one to be captured
+        let mut on_square = see_value(mv.piece_type);
+        if let Some(promotion) = mv.promotion {
+            gain[0] += see_value(promotion) - PieceType::Pawn.to_centipawns();
+            on_square = see_value(promotion);
         }
-        None
+
+        let mut depth = 0;
+        while depth + 1 < gain.len() {
+            color = color.opposite();
+            let attackers = self.attackers(target, color, occupancy);
+            let Some((ptype, index)) = SEE_ORDER.iter().find_map(|ptype| {
+                let pieces = attackers & self.bitboards[color.index()][ptype.index()];
+                (pieces != 0).then(|| (*ptype, pieces.trailing_zeros() as usize))
+            }) else {
+                break;
+            };
+            depth += 1;
+            gain[depth] = on_square - gain[depth - 1];
+            on_square = see_value(ptype);
+            if ptype == PieceType::Pawn && last_rank {
+                gain[depth] += promotion_gain;
+                on_square = PieceType::Queen.to_centipawns();
+            }
+            occupancy &= !(1 << index);
+        }
+        // Each side only recaptures when it does better than stopping
+        while depth > 0 {
+            gain[depth - 1] = -(-gain[depth - 1]).max(gain[depth]);
+            depth -= 1;
+        }
+        gain[0]
     }
 
-    /// Check if a square is attacked by a piece of a given color.
+    /// Move a piece from one square to another.
     /// # Description
-    /// Checks if a square is attacked by a piece of a given color.
-    /// This function is used to check if a king is in check.
-    /// Also used to check if a square is attacked for castling
+    /// Validates the move and plays it if it is legal.
+    /// Does not check repetition or validate 50 move rule.
+    /// The move is recorded so it can be taken back with undo_move.
+    /// # Inputs/Outputs
+    /// - Input: mv: Move - The move to play
+    /// - Output: What the move did if it was played, otherwise the reason it was rejected
+    /// # Example
+    /// ``` Rust
+    /// let mut board = Board::from_fen("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    /// let result = board.move_piece(Move::new(1, 6, 1, 7, PieceType::Pawn, Some(PieceType::Queen))).unwrap();
+    /// assert_eq!(result.promotion, Some(PieceType::Queen));
+    /// assert!(result.gives_check);
+    /// ```
+    pub fn move_piece(&mut self, mv: Move) -> Result<MoveResult, MoveError> {
+        let undo = self.make_move(&mv)?;
+        let kind = move_kind(&mv, &undo);
+        let result = MoveResult {
+            captured: undo.captured.map(|piece| piece.get_type()),
+            captured_square: undo.captured.map(|_| undo.captured_square),
+            is_castle: match kind {
+                MoveKind::Castle(side) => Some(side),
+                _ => None,
+            },
+            is_en_passant: kind == MoveKind::EnPassant,
+            promotion: match kind {
+                MoveKind::Promotion(piece_type) => Some(piece_type),
+                _ => None,
+            },
+            gives_check: self.king_in_check(),
+        };
+        self.in_check = Some(result.gives_check);
+        if cfg!(debug_assertions) {
+            if let Err(error) = self.validate() {
+                self.unmake_move(&mv, undo);
+                return Err(MoveError::Inconsistent(error));
+            }
+        }
+        self.history.push((mv, undo));
+        Ok(result)
+    }
+
+    /// Whether a move would be legal and what sort of move it is, without playing it.
+    /// # Description
+    /// Plays the move on a scratch copy of the position with make_move, as move_piece does, so an illegal move
+    /// is rejected with exactly the error move_piece would give, e.g. MoveBlocked or KingInCheck.
+    /// Both read the kind of move from the UndoInfo make_move returns, so the two cannot disagree.
+    /// # Inputs/Outputs
+    /// - Input: mv: &Move - The move to check
+    /// - Output: The kind of move if it is legal, otherwise the reason it would be rejected
+    /// # Example
+    /// ``` Rust
+    /// let board = Board::from_fen("r3k3/8/8/8/8/8/8/R3K2R w KQq - 0 1").unwrap();
+    /// assert_eq!(board.classify_move(&Move::new(4, 0, 6, 0, PieceType::King, None)), Ok(MoveKind::Castle(Side::KingSide)));
+    /// assert_eq!(board.classify_move(&Move::new(0, 0, 0, 7, PieceType::Rook, None)), Ok(MoveKind::Capture(PieceType::Rook)));
+    /// assert_eq!(board.classify_move(&Move::new(7, 0, 7, 0, PieceType::Rook, None)), Err(MoveError::MustMovePiece));
+    /// ```
+    pub fn classify_move(&self, mv: &Move) -> Result<MoveKind, MoveError> {
+        let mut scratch = self.position_copy();
+        let undo = scratch.make_move(mv)?;
+        Ok(move_kind(mv, &undo))
+    }
+
+    /// Play a move given in UCI notation, e.g. "e2e4", "e1g1" or "e7e8q".
+    /// # Description
+    /// The moving piece is read from the board and the promotion letter may be in either case.
+    /// The move is validated and recorded as with move_piece, a pawn reaching the last rank without a letter becomes a queen.
+    /// # Inputs/Outputs
+    /// - Input: text - The move in UCI notation
+    /// - Output: Ok if the move was played, MoveError::MalformedUci for text that is not a move,
+    ///   otherwise the reason move_piece rejected it
+    /// # Example
+    /// ``` Rust
+    /// let mut board = Board::starting_position();
+    /// board.try_move_uci("g1f3").unwrap();
+    /// assert_eq!(board.try_move_uci("e9x9"), Err(MoveError::MalformedUci("e9x9".to_string())));
+    /// ```
+    pub fn try_move_uci(&mut self, text: &str) -> Result<(), MoveError> {
+        let mv = self.parse_uci_move(text)?;
+        self.move_piece(mv).map(|_| ())
+    }
+
+    /// Play a whitespace separated list of UCI moves, such as the moves of a UCI position command.
+    /// # Description
+    /// Stops at the first move that can not be played, leaving the moves before it on the board.
+    /// # Inputs/Outputs
+    /// - Input: moves - e.g. "e2e4 e7e5 g1f3"
+    /// - Output: The number of moves played, or the first move that failed with its index and the reason
+    /// # Example
+    /// ``` Rust
+    /// let mut board = Board::starting_position();
+    /// let error = board.apply_uci_moves("e2e4 e7e5 e4e5 g1f3").unwrap_err();
+    /// assert_eq!((error.index, error.text.as_str()), (2, "e4e5"));
+    /// // e2e4 and e7e5 were played
+    /// assert_eq!(*board.get_player_turn(), Color::White);
+    /// ```
+    pub fn apply_uci_moves(&mut self, moves: &str) -> Result<usize, MoveListError> {
+        let mut played = 0;
+        for (index, text) in moves.split_whitespace().enumerate() {
+            self.try_move_uci(text).map_err(|source| MoveListError { index, text: text.to_string(), source })?;
+            played += 1;
+        }
+        Ok(played)
+    }
+
+    /// The move a UCI string describes, with the moving piece taken from the board.
+    /// Only checks the text and the source square, the move itself may still be illegal.
+    pub(crate) fn parse_uci_move(&self, text: &str) -> Result<Move, MoveError> {
+        let malformed = || MoveError::MalformedUci(text.to_string());
+        let trimmed = text.trim();
+        if !(4..=5).contains(&trimmed.len()) || !trimmed.is_ascii() {
+            return Err(malformed());
+        }
+        let (from_x, from_y) = square_to_coords(&trimmed[0..2]).ok_or_else(malformed)?;
+        let (to_x, to_y) = square_to_coords(&trimmed[2..4]).ok_or_else(malformed)?;
+        let promotion = match trimmed.chars().nth(4) {
+            Some(c) => match PieceType::try_from(c.to_ascii_uppercase()) {
+                Ok(piece_type @ (PieceType::Knight | PieceType::Bishop | PieceType::Rook | PieceType::Queen)) => Some(piece_type),
+                _ => return Err(malformed()),
+            },
+            None => None,
+        };
+        let piece_type = self.squares[from_y][from_x].ok_or(MoveError::NoPieceOnSourceSquare)?.get_type();
+        if promotion.is_some() && (piece_type != PieceType::Pawn || !(to_y == 0 || to_y == 7)) {
+            return Err(MoveError::IllegalMove);
+        }
+        Ok(Move::new(from_x, from_y, to_x, to_y, piece_type, promotion))
+    }
+
+    /// Take back the last move played with move_piece.
+    /// # Description
+    /// Restores the position and all of the FEN state from before the move.
+    /// Can be called repeatedly back to the position the board was created with.
+    /// Moves made with make_move are not recorded and must be taken back with unmake_move.
+    /// # Inputs/Outputs
+    /// - Output: The move that was taken back, or None if there are no moves to undo
+    /// # Example
+    /// ``` Rust
+    /// let mut board = Board::starting_position();
+    /// board.move_piece(Move::new(4, 1, 4, 3, PieceType::Pawn, None)).unwrap();
+    /// assert!(board.undo_move().is_some());
+    /// assert!(board.undo_move().is_none());
+    /// ```
+    pub fn undo_move(&mut self) -> Option<Move> {
+        let (mv, undo) = self.history.pop()?;
+        self.unmake_move(&mv, undo);
+        Some(mv)
+    }
+
+    /// Play a move and return the information needed to take it back.
+    /// # Description
+    /// Performs the same validation as move_piece.
+    /// The returned UndoInfo can be passed to unmake_move with the same move to restore the board,
+    /// which lets search and perft work on a single board instead of cloning it for every move.
+    /// The Zobrist key of the position is kept until then, so repetition_count also sees the positions a search passes through.
+    /// # Inputs/Outputs
+    /// - Input: mv: &Move - The move to play
+    /// - Output: The undo information if the move was played, otherwise the reason it was rejected
+    /// # Example
+    /// ``` Rust
+    /// let mut board = Board::starting_position();
+    /// let mv = Move::new(4, 1, 4, 3, PieceType::Pawn, None);
+    /// let undo = board.make_move(&mv).unwrap();
+    /// board.unmake_move(&mv, undo);
+    /// ```
+    pub fn make_move(&mut self, mv: &Move) -> Result<UndoInfo, MoveError> {
+        let moved = match self.squares[mv.from_y][mv.from_x] {
+            Some(piece) => piece,
+            None => return Err(MoveError::NoPieceOnSourceSquare),
+        };
+        let captured_square = self.captured_square(mv).unwrap_or((mv.to_x, mv.to_y));
+        let undo = UndoInfo {
+            moved,
+            captured: self.squares[captured_square.1][captured_square.0],
+            captured_square,
+            castling: [self.white_can_castle_king, self.white_can_castle_queen, self.black_can_castle_king, self.black_can_castle_queen],
+            en_passant: self.en_passant,
+            halfmove: self.halfmove,
+            white_king_position: self.white_king_position,
+            black_king_position: self.black_king_position,
+            in_check: self.in_check,
+        };
+        let key = self.zobrist_key();
+        self.apply_move(mv)?;
+        self.keys.push(key);
+        Ok(undo)
+    }
+
+    /// The square of the piece a move takes, None if it takes nothing.
+    /// # Description
+    /// The destination for an ordinary capture. For en passant it is the square beside the capturing pawn,
+    /// on the rank it starts from, which is where a UI must remove the pawn from.
+    /// make_move keeps this square so unmake_move and undo_move put the pawn back in the same place.
+    /// # Example
+    /// ``` Rust
+    /// let board = Board::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+    /// assert_eq!(board.captured_square(&Move::new(4, 4, 3, 5, PieceType::Pawn, None)), Some((3, 4)));
+    /// ```
+    pub fn captured_square(&self, mv: &Move) -> Option<(usize, usize)> {
+        let moved = self.squares[mv.from_y][mv.from_x]?;
+        let square = if moved.get_type() == PieceType::Pawn && mv.from_x != mv.to_x && self.squares[mv.to_y][mv.to_x].is_none() {
+            (mv.to_x, mv.from_y)
+        } else {
+            (mv.to_x, mv.to_y)
+        };
+        self.squares[square.1][square.0].map(|_| square)
+    }
+
+    /// The material a move wins, for ordering moves without playing them.
+    /// # Description
+    /// The value of the piece taken, a pawn for en passant, plus what a promotion gains over the pawn,
+    /// so a capture that promotes counts both. Quiet moves are worth 0.
+    /// Only looks at the board, nothing is allocated or changed.
+    /// # Inputs/Outputs
+    /// - Input: mv: &Move - A move in this position
+    /// - Output: The material gained in centipawns
+    /// # Example
+    /// ``` Rust
+    /// let board = Board::from_fen("2r1k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    /// // bxc8=Q takes a rook and turns a pawn into a queen
+    /// assert_eq!(board.capture_value(&Move::new(1, 6, 2, 7, PieceType::Pawn, Some(PieceType::Queen))), 1300);
+    /// ```
+    pub fn capture_value(&self, mv: &Move) -> i32 {
+        let victim = self.captured_square(mv)
+            .and_then(|(x, y)| self.squares[y][x])
+            .map_or(0, |piece| piece.get_type().to_centipawns());
+        let promotion = mv.promotion.map_or(0, |promotion| promotion.to_centipawns() - PieceType::Pawn.to_centipawns());
+        victim + promotion
+    }
+
+    /// Take back a move made with make_move.
+    /// # Description
+    /// Must be called with the move that was made and the UndoInfo it returned,
+    /// on the board as it was left by that move.
+    /// Restores en passant captures to the correct square, moves the rook back after castling
+    /// and turns promoted pieces back into pawns.
+    /// # Inputs/Outputs
+    /// - Input: mv: &Move - The move that was made
+    /// - Input: undo: UndoInfo - The value returned by make_move for that move
+    pub fn unmake_move(&mut self, mv: &Move, undo: UndoInfo) {
+        if self.player_turn == Color::White {
+            self.move_number -= 1;
+        }
+        self.player_turn = self.player_turn.opposite();
+        self.take_square(mv.to_x, mv.to_y);
+        self.set_square(mv.from_x, mv.from_y, Some(undo.moved));
+        if let Some(captured) = undo.captured {
+            self.set_square(undo.captured_square.0, undo.captured_square.1, Some(captured));
+        }
+        if undo.moved.get_type() == PieceType::King && mv.from_x.abs_diff(mv.to_x) == 2 {
+            if mv.to_x == 6 {
+                self.unchecked_move_piece(5, mv.to_y, 7, mv.to_y);
+            } else {
+                self.unchecked_move_piece(3, mv.to_y, 0, mv.to_y);
+            }
+        }
+        [self.white_can_castle_king, self.white_can_castle_queen, self.black_can_castle_king, self.black_can_castle_queen] = undo.castling;
+        self.en_passant = undo.en_passant;
+        self.halfmove = undo.halfmove;
+        self.white_king_position = undo.white_king_position;
+        self.black_king_position = undo.black_king_position;
+        self.in_check = undo.in_check;
+        self.keys.pop();
+    }
+
+    /// Pass the turn to the opponent without moving, for null move pruning and threat analysis.
+    /// # Description
+    /// Flips the side to move, clears the en passant square and counts a halfmove, as a quiet move would.
+    /// Passing while in check would let the opponent take the king, so the caller must check king_in_check first,
+    /// debug builds assert it. The Zobrist key is computed from the board so it changes with the side to move.
+    /// Null moves are not recorded in the history or seen by repetition_count.
     /// # Inputs/Outputs
-    /// - Input: The x and y coordinate of the square
-    /// - Input: The color of the attacking pieces
-    /// - Returns: True if the square is attacked, otherwise false.
+    /// - Output: The information unmake_null_move needs to restore the board
     /// # Example
     /// ``` Rust
-    /// let board = Board::starting_position();
-    /// // The square e3 is attacked by a white pawn
-    /// assert!(board.is_square_attacked(4, 2, Color::White));
+    /// let mut board = Board::starting_position();
+    /// let undo = board.make_null_move();
+    /// // Black's threats, as if Black could move twice
+    /// let threats = board.generate_legal_moves();
+    /// board.unmake_null_move(undo);
     /// ```
-    pub(crate) fn is_square_attacked(&self, x: usize, y: usize, color: Color) -> bool {
-        //log::trace!("Checking if square ({},{}) is being attacked by {} piece", x, y, color);
-        // Define static arrays that get used internally to the function
-        static LINE_PIECES: [PieceType; 2] = [PieceType::Rook, PieceType::Queen];
-        static DIAGONAL_PIECES: [PieceType; 2] = [PieceType::Bishop, PieceType::Queen];
-        static KNIGHT: [PieceType; 1] = [PieceType::Knight];
-        static KING: [PieceType; 1] = [PieceType::King];
-        static STRAIGHT_DIRECTIONS: [(i8, i8); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
-        static DIAGONAL_DIRECTIONS: [(i8, i8); 4] = [(1, 1), (-1, 1), (1, -1), (-1, -1)];
-        static KING_MOVES: [(i8, i8); 8] = [(1, 0), (-1, 0), (0, 1), (0, -1), (1, 1), (-1, 1), (1, -1), (-1, -1)];
-        // Helper function to check if a piece is of the given color and type
-        let is_piece = |piece: Option<&Piece>, check: &[PieceType]| -> bool {
-            piece.map_or(false, |p| *p.get_color() == color && check.contains(p.get_type()))
-        };
-        // Look for pawn attacks
-        let pawn_direction = if color == Color::White { -1 } else { 1 };
-        for &dx in [-1, 1].iter() {
-            let px = x as i8 + dx;
-            let py = y as i8 + pawn_direction;
-            if (0..8).contains(&px) && (0..8).contains(&py) {
-                if is_piece(self.squares[py as usize][px as usize].as_ref(), &[PieceType::Pawn]) {
-                    return true;
-                }
-            }
-        }
-        // look for kings
-        for &(dx, dy) in &KING_MOVES {
-            let nx = x as i8 + dx;
-            let ny = y as i8 + dy;
-            if (0..8).contains(&nx) && (0..8).contains(&ny) && is_piece(self.squares[ny as usize][nx as usize].as_ref(), &KING) {
-                return true;
-            }
-        }
-        // look for rooks and queens
-        for (dx, dy) in &STRAIGHT_DIRECTIONS {
-            if let Some((x, y)) = self.first_piece_in_direction(x, y, *dx, *dy) {
-                if is_piece(self.squares[y][x].as_ref(), &LINE_PIECES) { 
-                    //log::trace!("Square is attacked by a {} straight piece on ({},{})", color, x, y);
-                    return true; 
-                }
-            }
+    pub fn make_null_move(&mut self) -> NullUndo {
+        debug_assert!(!self.king_in_check(), "null move made while in check");
+        let undo = NullUndo { en_passant: self.en_passant, halfmove: self.halfmove, in_check: self.in_check };
+        self.en_passant = None;
+        self.halfmove += 1;
+        if self.player_turn == Color::Black {
+            self.move_number += 1;
         }
-        // look for bishops, queens and pawns
-        for (dx, dy) in &DIAGONAL_DIRECTIONS {
-            if let Some((x, y)) = self.first_piece_in_direction(x, y, *dx, *dy) {
-                //log::trace!("First piece in direction ({},{}) is ({},{})", dx, dy, x, y);
-                if is_piece(self.squares[y][x].as_ref(), &DIAGONAL_PIECES) {
-                    //log::trace!("Square is attacked by a diagonal piece");
-                    return true;
-                }
-            }
+        self.player_turn = self.player_turn.opposite();
+        self.in_check = None;
+        undo
+    }
+
+    /// Take back a null move made with make_null_move
+    pub fn unmake_null_move(&mut self, undo: NullUndo) {
+        self.player_turn = self.player_turn.opposite();
+        if self.player_turn == Color::Black {
+            self.move_number -= 1;
         }
-        // look for knights
-        let knight_moves = [(1, 2), (2, 1), (-1, 2), (-2, 1), (1, -2), (2, -1), (-1, -2), (-2, -1)];
-        knight_moves.iter().any(|&(dx, dy)| {
-            let (nx, ny) = (x as i8 + dx, y as i8 + dy);
-            (0..8).contains(&nx) && (0..8).contains(&ny) && is_piece(self.squares[ny as usize][nx as usize].as_ref(), &KNIGHT)
-        })
+        self.en_passant = undo.en_passant;
+        self.halfmove = undo.halfmove;
+        self.in_check = undo.in_check;
     }
 
-    /// Move a piece from one square to another.
+    /// Validates and plays a move.
     /// This function needs refactoring to be more readable.
     /// Move code for each piece into its own function.
     /// This version of the function is rough but should implement piece movement rules
-    /// Does not check repetition or validate 50 move rule
-    pub fn move_piece(&mut self, mv: Move) -> Result<(), MoveError> {
+    /// Rejected moves are only returned as errors, never logged, as search and move parsing try many on purpose.
+    /// Callers facing a user, such as the UCI engine and the console game, log the error themselves.
+    fn apply_move(&mut self, mv: &Move) -> Result<(), MoveError> {
         let piece_unmoved = match self.squares[mv.from_y][mv.from_x].as_ref() {
             Some(piece) => piece,
             None => return Err(MoveError::NoPieceOnSourceSquare),
@@ -341,31 +1561,28 @@ impl Board {
         if mv.from_y == mv.to_y && mv.from_x == mv.to_x {
             return Err(MoveError::MustMovePiece); 
         }
-        if *piece_unmoved.get_color() != self.player_turn { 
-            log::warn!("Piece {:?} is wrong color, current turn: {}", piece_unmoved, self.player_turn);
+        if piece_unmoved.get_color() != self.player_turn { 
             return Err(MoveError::PieceWrongColor)
         }
         let mut en_passant_target: Option<(usize, usize)> = None;
-        match piece_unmoved.check_move(mv.from_x, mv.from_y, mv.to_x, mv.to_y) {
-            MoveType::Illegal => {
-                log::warn!("Move check failed");
-                return Err(MoveError::IllegalMove);
-            }
+        match piece_unmoved.movement_type((mv.from_x, mv.from_y), (mv.to_x, mv.to_y)) {
+            MoveType::Illegal => return Err(MoveError::IllegalMove),
             MoveType::Pawn1 => {
                 if self.squares[mv.to_y][mv.to_x].is_some() {
                     return Err(MoveError::MoveBlocked);
                 }
                 self.unchecked_move_piece(mv.from_x, mv.from_y, mv.to_x, mv.to_y);
                 if self.king_in_check() {
+                    let attacker = self.first_checker();
                     self.unchecked_move_piece(mv.to_x, mv.to_y, mv.from_x, mv.from_y);
-                    return Err(MoveError::KingInCheck);
+                    return Err(self.king_exposed_error(attacker));
                 }
                 self.halfmove = 0;
                 if mv.to_y == 0 || mv.to_y == 7 {
                     if let Some(promotion) = mv.promotion {
-                        self.squares[mv.to_y][mv.to_x] = Some(Piece::new(promotion, self.player_turn));
+                        self.set_square(mv.to_x, mv.to_y, Some(Piece::new(promotion, self.player_turn)));
                     } else {
-                        self.squares[mv.to_y][mv.to_x] = Some(Piece::new(PieceType::Queen, self.player_turn));
+                        self.set_square(mv.to_x, mv.to_y, Some(Piece::new(PieceType::Queen, self.player_turn)));
                     }
                 }
             },
@@ -378,66 +1595,72 @@ impl Board {
                 }
                 self.unchecked_move_piece(mv.from_x, mv.from_y, mv.to_x, mv.to_y);
                 if self.king_in_check() {
+                    let attacker = self.first_checker();
                     self.unchecked_move_piece(mv.to_x, mv.to_y, mv.from_x, mv.from_y);
-                    return Err(MoveError::KingInCheck);
+                    return Err(self.king_exposed_error(attacker));
                 }
                 en_passant_target = Some((mv.to_x, if self.player_turn == Color::White { 2 } else { 5 }));
                 self.halfmove = 0;
             }, 
             MoveType::PawnCapture => {
+                if let Some(piece) = &self.squares[mv.to_y][mv.to_x] {
+                    if piece.get_color() == piece_unmoved.get_color() {
+                        return Err(MoveError::CannotCaptureOwnPiece)
+                    }
+                }
                 if self.squares[mv.to_y][mv.to_x].is_none() && self.en_passant != Some((mv.to_x, mv.to_y)) {
                     // Check for en passant
                     return Err(MoveError::IllegalMove);
                 }
                 let taken = self.unchecked_move_piece(mv.from_x, mv.from_y, mv.to_x, mv.to_y);
                 if taken.is_none() {
-                    let en_passant = self.en_passant.as_ref().unwrap().clone();
+                    let en_passant = *self.en_passant.as_ref().unwrap();
                     let taken = match self.player_turn {
-                        Color::White => self.squares[en_passant.1 - 1][en_passant.0].take(),
-                        Color::Black => self.squares[en_passant.1 + 1][en_passant.0].take(),
+                        Color::White => self.take_square(en_passant.0, en_passant.1 - 1),
+                        Color::Black => self.take_square(en_passant.0, en_passant.1 + 1),
                     };
                     if self.king_in_check() {
+                        let attacker = self.first_checker();
                         self.unchecked_move_piece(mv.to_x, mv.to_y, mv.from_x, mv.from_y);
                         match self.player_turn {
-                            Color::White => self.squares[en_passant.1 - 1][en_passant.0] = taken,
-                            Color::Black => self.squares[en_passant.1 + 1][en_passant.0] = taken,
-                        }
-                        return Err(MoveError::KingInCheck);
+                            Color::White => self.set_square(en_passant.0, en_passant.1 - 1, taken),
+                            Color::Black => self.set_square(en_passant.0, en_passant.1 + 1, taken),
+                        };
+                        return Err(self.king_exposed_error(attacker));
                     }
                     if mv.to_y == 0 || mv.to_y == 7 {
                         if let Some(promotion) = mv.promotion {
-                            self.squares[mv.to_y][mv.to_x] = Some(Piece::new(promotion, self.player_turn));
+                            self.set_square(mv.to_x, mv.to_y, Some(Piece::new(promotion, self.player_turn)));
                         } else {
-                            self.squares[mv.to_y][mv.to_x] = Some(Piece::new(PieceType::Queen, self.player_turn));
+                            self.set_square(mv.to_x, mv.to_y, Some(Piece::new(PieceType::Queen, self.player_turn)));
                         }
                     }
                 } else {
                     if self.king_in_check() {
+                        let attacker = self.first_checker();
                         self.unchecked_move_piece(mv.to_x, mv.to_y, mv.from_x, mv.from_y);
-                        self.squares[mv.to_y][mv.to_x] = taken;
-                        return Err(MoveError::KingInCheck);
+                        self.set_square(mv.to_x, mv.to_y, taken);
+                        return Err(self.king_exposed_error(attacker));
                     }
                     // handle promotion 
                     if mv.to_y == 0 || mv.to_y == 7 {
                         if let Some(promotion) = mv.promotion {
-                            self.squares[mv.to_y][mv.to_x] = Some(Piece::new(promotion, self.player_turn));
+                            self.set_square(mv.to_x, mv.to_y, Some(Piece::new(promotion, self.player_turn)));
                         } else {
-                            self.squares[mv.to_y][mv.to_x] = Some(Piece::new(PieceType::Queen, self.player_turn));
+                            self.set_square(mv.to_x, mv.to_y, Some(Piece::new(PieceType::Queen, self.player_turn)));
                         }
                     }
                 }
                 self.halfmove = 0;
             },
             MoveType::Rook => {
-                if !self.check_straight_move(mv.from_x as i8, mv.from_y as i8, mv.to_x as i8, mv.to_y as i8) {
-                    log::warn!("Rook from ({},{}) to ({},{}) failed straight move check", mv.from_x, mv.from_y, mv.to_x, mv.to_y);
-                    return Err(MoveError::IllegalMove);
-                }
+                self.check_straight_move(mv.from_x as i8, mv.from_y as i8, mv.to_x as i8, mv.to_y as i8)?;
                 let taken = self.unchecked_move_piece(mv.from_x, mv.from_y, mv.to_x, mv.to_y);
                 if self.king_in_check() {
+                    let attacker = self.first_checker();
                     self.unchecked_move_piece(mv.to_x, mv.to_y, mv.from_x, mv.from_y);
-                    self.squares[mv.to_y][mv.to_x] = taken;
-                    return Err(MoveError::KingInCheck);
+                    self.set_square(mv.to_x, mv.to_y, taken);
+                    return Err(self.king_exposed_error(attacker));
                 }
                 if taken.is_none() {
                     self.halfmove += 1;
@@ -463,16 +1686,16 @@ impl Board {
             },
             MoveType::Knight => {
                 if let Some(piece) = &self.squares[mv.to_y][mv.to_x] {
-                    if *piece.get_color() == *piece_unmoved.get_color() {
-                        log::warn!("Knigt on ({},{}) cannot capture own piece", mv.to_x, mv.to_y);
+                    if piece.get_color() == piece_unmoved.get_color() {
                         return Err(MoveError::CannotCaptureOwnPiece)
                     }
                 }
                 let taken = self.unchecked_move_piece(mv.from_x, mv.from_y, mv.to_x, mv.to_y);
                 if self.king_in_check() {
+                    let attacker = self.first_checker();
                     self.unchecked_move_piece(mv.to_x, mv.to_y, mv.from_x, mv.from_y);
-                    self.squares[mv.to_y][mv.to_x] = taken;
-                    return Err(MoveError::KingInCheck);
+                    self.set_square(mv.to_x, mv.to_y, taken);
+                    return Err(self.king_exposed_error(attacker));
                 }
                 if taken.is_none() {
                     self.halfmove += 1;
@@ -482,15 +1705,13 @@ impl Board {
 
             },
             MoveType::Bishop => {
-                if !self.check_straight_move(mv.from_x as i8, mv.from_y as i8, mv.to_x as i8, mv.to_y as i8) {
-                    log::warn!("Bishop from ({},{}) to ({},{}) failed straight move check", mv.from_x, mv.from_y, mv.to_x, mv.to_y);
-                    return Err(MoveError::IllegalMove);
-                }
+                self.check_straight_move(mv.from_x as i8, mv.from_y as i8, mv.to_x as i8, mv.to_y as i8)?;
                 let taken = self.unchecked_move_piece(mv.from_x, mv.from_y, mv.to_x, mv.to_y);
                 if self.king_in_check() {
+                    let attacker = self.first_checker();
                     self.unchecked_move_piece(mv.to_x, mv.to_y, mv.from_x, mv.from_y);
-                    self.squares[mv.to_y][mv.to_x] = taken;
-                    return Err(MoveError::KingInCheck);
+                    self.set_square(mv.to_x, mv.to_y, taken);
+                    return Err(self.king_exposed_error(attacker));
                 }
                 if taken.is_none() {
                     self.halfmove += 1;
@@ -500,15 +1721,13 @@ impl Board {
 
             },
             MoveType::Queen => {
-                if !self.check_straight_move(mv.from_x as i8, mv.from_y as i8, mv.to_x as i8, mv.to_y as i8) {
-                    log::warn!("Queen from ({},{}) to ({},{}) failed straight move check", mv.from_x, mv.from_y, mv.to_x, mv.to_y);
-                    return Err(MoveError::IllegalMove);
-                }
+                self.check_straight_move(mv.from_x as i8, mv.from_y as i8, mv.to_x as i8, mv.to_y as i8)?;
                 let taken = self.unchecked_move_piece(mv.from_x, mv.from_y, mv.to_x, mv.to_y);
                 if self.king_in_check() {
+                    let attacker = self.first_checker();
                     self.unchecked_move_piece(mv.to_x, mv.to_y, mv.from_x, mv.from_y);
-                    self.squares[mv.to_y][mv.to_x] = taken;
-                    return Err(MoveError::KingInCheck);
+                    self.set_square(mv.to_x, mv.to_y, taken);
+                    return Err(self.king_exposed_error(attacker));
                 }
                 if taken.is_none() {
                     self.halfmove += 1;
@@ -518,22 +1737,34 @@ impl Board {
 
             },
             MoveType::KingNormal => {
-                if self.is_square_attacked(mv.to_x, mv.to_y, piece_unmoved.get_color().opposite()) {
-                    return Err(MoveError::IllegalMove);
+                if let Some(piece) = &self.squares[mv.to_y][mv.to_x] {
+                    if piece.get_color() == self.player_turn {
+                        return Err(MoveError::CannotCaptureOwnPiece)
+                    }
+                }
+                // The king is left out of the occupancy so sliders attacking through its old square are seen
+                let occupancy = (self.occupancy[0] | self.occupancy[1]) & !bitboard::square_bit(mv.from_x, mv.from_y);
+                let attackers = self.attackers(bitboard::square_index(mv.to_x, mv.to_y), self.player_turn.opposite(), occupancy);
+                if let Some(index) = bitboard::squares(attackers).next() {
+                    let attacker = self.piece_on_index(index);
+                    return Err(self.king_exposed_error(attacker));
+                }
+                let taken = self.unchecked_move_piece(mv.from_x, mv.from_y, mv.to_x, mv.to_y);
+                match self.player_turn {
+                    Color::White => self.white_king_position = (mv.to_x, mv.to_y),
+                    Color::Black => self.black_king_position = (mv.to_x, mv.to_y),
                 }
                 match self.player_turn {
                     Color::White => {
                         self.white_can_castle_king = false;
                         self.white_can_castle_queen = false;
-                        self.white_king_position = (mv.to_x, mv.to_y)
                     }
                     Color::Black => {
                         self.black_can_castle_king = false;
                         self.black_can_castle_queen = false;
-                        self.black_king_position = (mv.to_x, mv.to_y)
                     }
                 }
-                if self.unchecked_move_piece(mv.from_x, mv.from_y, mv.to_x, mv.to_y).is_none() {
+                if taken.is_none() {
                     self.halfmove += 1;
                 } else {
                     self.halfmove = 0;
@@ -541,7 +1772,7 @@ impl Board {
 
             },
             MoveType::KingCastleKingSide => {
-                if !self.check_kingside_castle() {
+                if !self.check_kingside_castle(&self.attack_map(self.player_turn.opposite())) {
                     return Err(MoveError::IllegalMove)
                 }
                 self.unchecked_move_piece(mv.from_x, mv.from_y, mv.to_x, mv.to_y);
@@ -562,7 +1793,7 @@ impl Board {
                 self.halfmove += 1;
             },
             MoveType::KingCastleQueenSide => {
-                if !self.check_queenside_castle() {
+                if !self.check_queenside_castle(&self.attack_map(self.player_turn.opposite())) {
                     return Err(MoveError::IllegalMove)
                 }
                 self.unchecked_move_piece(mv.from_x, mv.from_y, mv.to_x, mv.to_y);
@@ -583,6 +1814,14 @@ impl Board {
                 self.halfmove += 1;
             },
         }
+        // A capture on a rook's starting square removes the opponent's right to castle with it
+        match (mv.to_x, mv.to_y) {
+            (0, 0) => self.white_can_castle_queen = false,
+            (7, 0) => self.white_can_castle_king = false,
+            (0, 7) => self.black_can_castle_queen = false,
+            (7, 7) => self.black_can_castle_king = false,
+            _ => (),
+        }
         self.en_passant = en_passant_target;
         if self.player_turn == Color::Black {
             self.move_number += 1;
@@ -596,7 +1835,8 @@ impl Board {
     /// Confirms if the king is in check 
     /// # Description 
     /// A simple function that looks if the king of the current players turn is in check
-    /// This uses the is_square_attacked() function to do so 
+    /// This uses the is_square_attacked() function to do so, unless the answer is already known from the move
+    /// that reached the position, see in_check
     /// # Inputs/Outptus
     /// - Input: None
     /// - Output: True if king in check, false if not 
@@ -606,63 +1846,142 @@ impl Board {
     /// // In this position the white queen is attacking the black king
     /// assert!(board.king_in_check())
     /// ```
-    pub(crate) fn king_in_check(&self) -> bool {
-        match self.player_turn {
-            Color::White => self.is_square_attacked(self.white_king_position.0, self.white_king_position.1, Color::Black),
-            Color::Black => self.is_square_attacked(self.black_king_position.0, self.black_king_position.1, Color::White),
-        }
+    pub fn king_in_check(&self) -> bool {
+        self.in_check(self.player_turn)
     }
 
-    fn check_straight_move(&self, from_x: i8, from_y: i8, to_x: i8, to_y: i8) -> bool {
-        let x_dir = (to_x - from_x).signum();
-        let y_dir = (to_y - from_y).signum();
-        let mut x = from_x + x_dir;
-        let mut y = from_y + y_dir;
-        while x != to_x || y != to_y {
-            if self.squares[y as usize][x as usize].is_some() {
-                return false 
+    /// Whether the king of the given color is attacked, whoever's turn it is.
+    /// # Description
+    /// For the side to move the answer is kept after move_piece, undo_move and unmake_null_move,
+    /// so asking again costs nothing. Otherwise the king's square is checked for attackers.
+    /// # Example
+    /// ``` Rust
+    /// let board = Board::from_fen("4k3/8/8/8/8/8/8/3QK2r w - - 0 1").unwrap();
+    /// assert!(board.in_check(Color::White));
+    /// assert!(!board.in_check(Color::Black));
+    /// ```
+    pub fn in_check(&self, color: Color) -> bool {
+        if color == self.player_turn {
+            if let Some(in_check) = self.in_check {
+                return in_check;
             }
-            x += x_dir;
-            y += y_dir;
+        }
+        let (x, y) = self.king_position(color);
+        self.is_square_attacked(x, y, color.opposite())
+    }
+
+    /// The cached square of the king of the given color, (0, 0) when it has none
+    pub(crate) fn king_position(&self, color: Color) -> (usize, usize) {
+        match color {
+            Color::White => self.white_king_position,
+            Color::Black => self.black_king_position,
+        }
+    }
+
+    /// Squares of the pieces giving check to the side to move.
+    /// # Description
+    /// Empty when the side to move is not in check.
+    /// Two checkers is a double check, where only a king move can get out of check.
+    /// # Inputs/Outputs
+    /// - Output: The (x, y) of each checking piece, ordered by ascending square index
+    /// # Example
+    /// ``` Rust
+    /// let board = Board::from_fen("4k3/8/3N4/8/8/8/8/4RK2 b - - 0 1").unwrap();
+    /// // The rook on e1 and the knight on d6 both give check
+    /// assert_eq!(board.checkers(), vec![(4, 0), (3, 5)]);
+    /// ```
+    pub fn checkers(&self) -> Vec<(usize, usize)> {
+        self.checkers_of(self.player_turn)
+    }
+
+    /// Squares of the pieces giving check to the king of the given color
+    pub fn checkers_of(&self, color: Color) -> Vec<(usize, usize)> {
+        let (x, y) = match color {
+            Color::White => self.white_king_position,
+            Color::Black => self.black_king_position,
+        };
+        self.attackers_to(x, y, color.opposite())
+    }
+
+    /// The lowest square piece giving check to the side to move, which must be in check
+    fn first_checker(&self) -> ((usize, usize), PieceType) {
+        let (x, y) = match self.player_turn {
+            Color::White => self.white_king_position,
+            Color::Black => self.black_king_position,
+        };
+        let occupancy = self.occupancy[0] | self.occupancy[1];
+        let attackers = self.attackers(bitboard::square_index(x, y), self.player_turn.opposite(), occupancy);
+        self.piece_on_index(bitboard::squares(attackers).next().expect("the side to move is in check"))
+    }
+
+    /// The coordinates and type of the piece on a square index, which must be occupied
+    fn piece_on_index(&self, index: usize) -> ((usize, usize), PieceType) {
+        let (x, y) = bitboard::index_to_coords(index);
+        let piece_type = self.squares[y][x].as_ref().expect("attackers stand on occupied squares").get_type();
+        ((x, y), piece_type)
+    }
+
+    /// The error for a move that would leave the mover's king attacked, built once the board has been restored.
+    /// KingInCheck names a piece already giving check, MovePinnedOrExposesKing the attacker the move let through.
+    fn king_exposed_error(&self, attacker: ((usize, usize), PieceType)) -> MoveError {
+        if self.king_in_check() {
+            let (checker, checker_type) = self.first_checker();
+            MoveError::KingInCheck { checker, checker_type }
+        } else {
+            let (attacker, attacker_type) = attacker;
+            MoveError::MovePinnedOrExposesKing { attacker, attacker_type }
+        }
+    }
+
+    /// Checks the squares between a slider and its destination are empty and the destination is not its own piece
+    fn check_straight_move(&self, from_x: i8, from_y: i8, to_x: i8, to_y: i8) -> Result<(), MoveError> {
+        debug_assert!(
+            ray_delta(from_x as usize, from_y as usize, to_x as usize, to_y as usize).is_some(),
+            "({}, {}) to ({}, {}) is not a straight or diagonal move", from_x, from_y, to_x, to_y
+        );
+        let from = bitboard::square_index(from_x as usize, from_y as usize);
+        let to = bitboard::square_index(to_x as usize, to_y as usize);
+        if bitboard::between(from, to) & (self.occupancy[0] | self.occupancy[1]) != 0 {
+            return Err(MoveError::MoveBlocked)
         }
         if let Some(piece) = &self.squares[to_y as usize][to_x as usize] {
-            if *piece.get_color() == self.player_turn {
-                return false
+            if piece.get_color() == self.player_turn {
+                return Err(MoveError::CannotCaptureOwnPiece)
             }
         }
-        true
+        Ok(())
     }
 
-    fn check_kingside_castle(&self) -> bool {
+    fn check_kingside_castle(&self, attacked: &AttackMap) -> bool {
         if self.player_turn == Color::White {
-            if !self.white_can_castle_king {
+            if !self.white_can_castle_king || self.white_king_position != (4, 0) {
                 return false
             }
-            if self.is_square_attacked(4, 0, Color::Black) || self.is_square_attacked(5, 0, Color::Black) || self.is_square_attacked(6, 0, Color::Black) {
+            if attacked.is_attacked(4, 0) || attacked.is_attacked(5, 0) || attacked.is_attacked(6, 0) {
                 return false
             }
             if self.squares[0][5].is_some() || self.squares[0][6].is_some() {
                 return false
             }
             if let Some(piece) = &self.squares[0][7] {
-                if *piece.get_type() != PieceType::Rook || *piece.get_color() != Color::White {
+                if piece.get_type() != PieceType::Rook || piece.get_color() != Color::White {
                     return false
                 }
             } else {
                 return false
             }
         } else {
-            if !self.black_can_castle_king {
+            if !self.black_can_castle_king || self.black_king_position != (4, 7) {
                 return false
             }
-            if self.is_square_attacked(4, 7, Color::White) || self.is_square_attacked(5, 7, Color::White) || self.is_square_attacked(6, 7, Color::White) {
+            if attacked.is_attacked(4, 7) || attacked.is_attacked(5, 7) || attacked.is_attacked(6, 7) {
                 return false
             }
             if self.squares[7][5].is_some() || self.squares[7][6].is_some() {
                 return false
             }
             if let Some(piece) = &self.squares[7][7] {
-                if *piece.get_type() != PieceType::Rook || *piece.get_color() != Color::Black {
+                if piece.get_type() != PieceType::Rook || piece.get_color() != Color::Black {
                     return false
                 }
             } else {
@@ -672,36 +1991,36 @@ impl Board {
         true
     }
 
-    fn check_queenside_castle(&self) -> bool {
+    fn check_queenside_castle(&self, attacked: &AttackMap) -> bool {
         if self.player_turn == Color::White {
-            if !self.white_can_castle_queen {
+            if !self.white_can_castle_queen || self.white_king_position != (4, 0) {
                 return false
             }
-            if self.is_square_attacked(4, 0, Color::Black) || self.is_square_attacked(3, 0, Color::Black) || self.is_square_attacked(2, 0, Color::Black) {
+            if attacked.is_attacked(4, 0) || attacked.is_attacked(3, 0) || attacked.is_attacked(2, 0) {
                 return false
             }
             if self.squares[0][3].is_some() || self.squares[0][2].is_some() || self.squares[0][1].is_some() {
                 return false
             }
             if let Some(piece) = &self.squares[0][0] {
-                if *piece.get_type() != PieceType::Rook || *piece.get_color() != Color::White {
+                if piece.get_type() != PieceType::Rook || piece.get_color() != Color::White {
                     return false
                 }
             } else {
                 return false
             }
         } else {
-            if !self.black_can_castle_queen {
+            if !self.black_can_castle_queen || self.black_king_position != (4, 7) {
                 return false
             }
-            if self.is_square_attacked(4, 7, Color::White) || self.is_square_attacked(3, 7, Color::White) || self.is_square_attacked(2, 7, Color::White) {
+            if attacked.is_attacked(4, 7) || attacked.is_attacked(3, 7) || attacked.is_attacked(2, 7) {
                 return false
             }
             if self.squares[7][3].is_some() || self.squares[7][2].is_some() || self.squares[7][1].is_some() {
                 return false
             }
             if let Some(piece) = &self.squares[7][0] {
-                if *piece.get_type() != PieceType::Rook || *piece.get_color() != Color::Black {
+                if piece.get_type() != PieceType::Rook || piece.get_color() != Color::Black {
                     return false
                 }
             } else {
@@ -711,264 +2030,557 @@ impl Board {
         true
     }
 
-    pub fn algebraic_move(&mut self, move_str: &str) -> Result<(), MoveError> {
-        let move_str = move_str.trim();
-        let chars = move_str.chars().collect::<Vec<_>>();
-        if chars.len() < 2 {
-            return Err(MoveError::IllegalMove);
+    /// Play a move given in Standard Algebraic Notation, e.g. "Nf3", "exd5", "O-O" or "e8=Q".
+    /// Returns an error if the text does not describe a legal move, it never panics on malformed input.
+    pub fn algebraic_move(&mut self, move_str: &str) -> Result<MoveResult, MoveError> {
+        let mv = san_to_move(self, move_str).ok_or(MoveError::IllegalMove)?;
+        self.move_piece(mv)
+    }
+
+    /// Every legal move for the side to move, collected from legal_moves_iter
+    pub fn generate_legal_moves(&self) -> Vec<Move> {
+        self.legal_moves_iter().collect()
+    }
+
+    /// generate_legal_moves into a buffer the caller keeps, which is cleared first.
+    /// # Description
+    /// The buffer's capacity is kept, so perft and search pass one buffer per ply and stop allocating
+    /// once each has grown to the largest move list seen at its ply.
+    /// # Example
+    /// ``` Rust
+    /// let mut moves = Vec::new();
+    /// Board::starting_position().generate_legal_moves_into(&mut moves);
+    /// assert_eq!(moves.len(), 20);
+    /// ```
+    pub fn generate_legal_moves_into(&self, moves: &mut Vec<Move>) {
+        self.generate_legal_moves_counted(moves);
+    }
+
+    /// generate_legal_moves_into, also returning how many pseudo legal moves were generated before the illegal
+    /// ones were removed, for the search's statistics.
+    /// The opponent's attack map is worked out once and used both for castling and for the legality checks.
+    pub(crate) fn generate_legal_moves_counted(&self, moves: &mut Vec<Move>) -> usize {
+        let legality = self.legality();
+        self.push_pseudo_legal_moves(moves, &legality.attacked);
+        let generated = moves.len();
+        moves.retain(|mv| self.is_pseudo_legal_move_legal(mv, &legality));
+        generated
+    }
+
+    /// The legal moves for the side to move, generated one piece at a time as they are asked for.
+    /// # Description
+    /// Yields the same moves in the same order as generate_legal_moves, but each piece's moves are only generated
+    /// and checked for legality when the iterator reaches them, so a caller that stops early skips the rest.
+    /// Checks and pins are found once when the iterator is made, and the iterator only borrows the board.
+    /// # Example
+    /// ``` Rust
+    /// let board = Board::starting_position();
+    /// // Only the first pieces' moves are generated
+    /// let first_two: Vec<Move> = board.legal_moves_iter().take(2).collect();
+    /// ```
+    pub fn legal_moves_iter(&self) -> impl Iterator<Item = Move> + '_ {
+        let legality = self.legality();
+        bitboard::squares(self.occupancy[self.player_turn.index()])
+            .flat_map(move |index| {
+                let (x, y) = bitboard::index_to_coords(index);
+                let piece = self.squares[y][x].expect("occupancy matches the squares");
+                self.generate_piece_moves(x, y, &piece, &legality.attacked)
+            })
+            .filter(move |mv| self.is_pseudo_legal_move_legal(mv, &legality))
+    }
+
+    /// Keeps the moves that do not leave the mover's king in check, for search code that generates in stages.
+    /// # Inputs/Outputs
+    /// - Input: moves - Pseudo legal moves for the side to move, e.g. from generate_pseudo_legal_moves
+    /// - Output: The legal ones, in the same order
+    pub fn filter_legal(&self, mut moves: Vec<Move>) -> Vec<Move> {
+        self.retain_legal(&mut moves);
+        moves
+    }
+
+    /// Whether a move is legal for the side to move, checked on a scratch copy so the board is untouched.
+    /// Use classify_move to find out why a move is not legal.
+    pub fn is_legal(&self, mv: &Move) -> bool {
+        self.position_copy().is_legal_move(mv)
+    }
+
+    /// Whether the side to move has any legal move.
+    /// # Description
+    /// Generates moves one piece at a time and stops at the first legal one, so it is much cheaper than
+    /// generate_legal_moves when only the answer is needed, e.g. to tell mate and stalemate apart from play going on.
+    /// The king is tried first as it is the piece most likely to have a move when in check.
+    /// # Example
+    /// ``` Rust
+    /// let board = Board::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+    /// assert!(!board.has_legal_moves());
+    /// assert!(Board::starting_position().has_legal_moves());
+    /// ```
+    pub fn has_legal_moves(&self) -> bool {
+        let legality = self.legality();
+        let mut moves = Vec::new();
+        let king = self.pieces(PieceType::King, self.player_turn);
+        let others = self.occupancy[self.player_turn.index()] & !king;
+        bitboard::squares(king).chain(bitboard::squares(others)).any(|index| {
+            let (x, y) = bitboard::index_to_coords(index);
+            let piece = self.squares[y][x].expect("occupied squares hold a piece");
+            moves.clear();
+            self.push_piece_moves(x, y, &piece, &mut moves, &legality.attacked);
+            moves.iter().any(|mv| self.is_pseudo_legal_move_legal(mv, &legality))
+        })
+    }
+
+    /// Whether a move puts the opponent in check, worked out without playing it.
+    /// # Description
+    /// Looks at the attacks on the enemy king after the move: direct checks from the moved or promoted piece,
+    /// discovered checks by sliders behind the from-square, the rook after castling and the sliders
+    /// uncovered by the pawn taken en passant. The move must be legal for the side to move.
+    /// # Example
+    /// ``` Rust
+    /// let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w Q - 0 1").unwrap();
+    /// // The rook lands on d1, not the e-file
+    /// assert!(!board.gives_check(&Move::new(4, 0, 2, 0, PieceType::King, None)));
+    /// assert!(board.gives_check(&Move::new(0, 0, 0, 7, PieceType::Rook, None)));
+    /// ```
+    pub fn gives_check(&self, mv: &Move) -> bool {
+        let us = self.player_turn;
+        let (king_x, king_y) = match us {
+            Color::White => self.black_king_position,
+            Color::Black => self.white_king_position,
+        };
+        let king = bitboard::square_index(king_x, king_y);
+        let king_bit = bitboard::square_bit(king_x, king_y);
+        let from = bitboard::square_bit(mv.from_x, mv.from_y);
+        let to = bitboard::square_bit(mv.to_x, mv.to_y);
+        let to_index = bitboard::square_index(mv.to_x, mv.to_y);
+        let mut occupancy = (self.occupancy[0] | self.occupancy[1]) & !from | to;
+        let pieces = &self.bitboards[us.index()];
+        let queens = pieces[PieceType::Queen.index()];
+        let mut straight = (pieces[PieceType::Rook.index()] | queens) & !from;
+        let mut diagonal = (pieces[PieceType::Bishop.index()] | queens) & !from;
+        if mv.piece_type == PieceType::Pawn && mv.from_x != mv.to_x && self.squares[mv.to_y][mv.to_x].is_none() {
+            occupancy &= !bitboard::square_bit(mv.to_x, mv.from_y);
         }
-        // find the piece type
-        let piece_type = match PieceType::try_from(chars[0]) {
-            Ok(piece) => piece,
-            Err(_) => match chars[0] {
-                'O' => PieceType::King,
-                'a'..='h' => PieceType::Pawn,
-                _ => return Err(MoveError::IllegalMove),
+        if mv.piece_type == PieceType::King && mv.from_x.abs_diff(mv.to_x) == 2 {
+            let (rook_from, rook_to) = if mv.to_x == 6 { (7, 5) } else { (0, 3) };
+            let rook_to = bitboard::square_bit(rook_to, mv.to_y);
+            occupancy = occupancy & !bitboard::square_bit(rook_from, mv.to_y) | rook_to;
+            straight = straight & !bitboard::square_bit(rook_from, mv.to_y) | rook_to;
+        }
+        let direct = match mv.promotion.unwrap_or(mv.piece_type) {
+            PieceType::Pawn => PAWN_ATTACKS[us.index()][to_index] & king_bit != 0,
+            PieceType::Knight => KNIGHT_ATTACKS[to_index] & king_bit != 0,
+            PieceType::Rook => {
+                straight |= to;
+                false
             }
-        };
-        //log::trace!("Piece type: {:?}", piece_type);
-        // handle castling
-        if piece_type == PieceType::King && move_str == "O-O" {
-            let mv: Move = match self.player_turn {
-                Color::White => Move::new(4, 0, 6, 0, PieceType::King, None),
-                Color::Black => Move::new(4, 7, 6, 7, PieceType::King, None),
-            };
-            return self.move_piece(mv)
-        } else if piece_type == PieceType::King && move_str == "O-O-O" {
-            let mv: Move = match self.player_turn {
-                Color::White => Move::new(4, 0, 2, 0, PieceType::King, None),
-                Color::Black => Move::new(4, 7, 2, 7, PieceType::King, None),
-            };
-            return self.move_piece(mv)
-        }
-        match piece_type {
-            PieceType::Pawn => {
-                if chars[1] == 'x' {
-                    //log::trace!("Pawn capture");
-                    if chars.len() < 4 {
-                        return Err(MoveError::IllegalMove);
-                    }
-                    let to = square_to_coords(&move_str[2..4]);
-                    if to.is_none() {
-                        return Err(MoveError::IllegalMove);
-                    }
-                    let (to_x, to_y) = to.unwrap();
-                    let from_x = chars[0] as usize - 'a' as usize;
-                    let from_y = match self.player_turn {
-                        Color::White => to_y - 1,
-                        Color::Black => to_y + 1,
-                    };
-                    let mv = match chars.last().unwrap() {
-                        'Q' => Move::new(from_x, from_y, to_x, to_y, PieceType::Pawn, Some(PieceType::Queen)),
-                        'R' => Move::new(from_x, from_y, to_x, to_y, PieceType::Pawn, Some(PieceType::Rook)),
-                        'N' => Move::new(from_x, from_y, to_x, to_y, PieceType::Pawn, Some(PieceType::Knight)),
-                        'B' => Move::new(from_x, from_y, to_x, to_y, PieceType::Pawn, Some(PieceType::Bishop)),
-                        _ => Move::new(from_x, from_y, to_x, to_y, PieceType::Pawn, None),
-                    };
-                    return self.move_piece(mv);
-                }             
-                //log::trace!("Pawn move");
-                let to = square_to_coords(&move_str[0..2]);
-                if to.is_none() {
-                    return Err(MoveError::IllegalMove);
-                }
-                let (to_x, to_y) = to.unwrap();
-                //log::trace!("To: ({},{})", to_x, to_y);
-                let from_y = match self.player_turn {
-                    Color::White => {
-                        if self.squares[to_y - 1][to_x].is_some() {
-                            to_y - 1
-                        } else {
-                            to_y - 2
-                        }
-                    }
-                    Color::Black => {
-                        if self.squares[to_y + 1][to_x].is_some() {
-                            to_y + 1
-                        } else {
-                            to_y + 2
-                        }
-                    }
-                };
-                let mv = match chars.last().unwrap() {
-                    'Q' => Move::new(to_x, from_y, to_x, to_y, PieceType::Pawn, Some(PieceType::Queen)),
-                    'R' => Move::new(to_x, from_y, to_x, to_y, PieceType::Pawn, Some(PieceType::Rook)),
-                    'N' => Move::new(to_x, from_y, to_x, to_y, PieceType::Pawn, Some(PieceType::Knight)),
-                    'B' => Move::new(to_x, from_y, to_x, to_y, PieceType::Pawn, Some(PieceType::Bishop)),
-                    _ => Move::new(to_x, from_y, to_x, to_y, PieceType::Pawn, None),
-                };
-                return self.move_piece(mv);
+            PieceType::Bishop => {
+                diagonal |= to;
+                false
             }
-            PieceType::Rook | PieceType::Knight | PieceType::Bishop | PieceType::Queen | PieceType::King => {
-                let capture = move_str.find('x');
-                let to = match capture {
-                    Some(x) => square_to_coords(&move_str[x+1..=x+2]),
-                    None => square_to_coords(&move_str[1..3]),
-                };
-                if to.is_none() {
-                    log::warn!("Invalid square: {:?}", &move_str[1..3]);
-                    return Err(MoveError::IllegalMove);
-                }
-                let (to_x, to_y) = to.unwrap();
-                let mut from_x = usize::MAX;
-                let mut from_y = usize::MAX;
-                for y in 0..8 {
-                    for x in 0..8 {
-                        if let Some(piece) = &self.squares[y][x] {
-                            if *piece.get_color() == self.player_turn && *piece.get_type() == piece_type {
-                                if piece.check_move(x, y, to_x, to_y) != MoveType::Illegal {
-                                    from_x = x;
-                                    from_y = y;
-                                }
-                            }
-                        }
-                    }
-                }
-                if from_x == usize::MAX || from_y == usize::MAX {
-                    return Err(MoveError::IllegalMove);
-                }
-                let mv = Move::new(from_x, from_y, to_x, to_y, piece_type, None);
-                return self.move_piece(mv);
+            PieceType::Queen => {
+                straight |= to;
+                diagonal |= to;
+                false
             }
+            PieceType::King => false,
+        };
+        direct
+            || bitboard::rook_attacks(king, occupancy) & straight != 0
+            || bitboard::bishop_attacks(king, occupancy) & diagonal != 0
+    }
+
+    /// Whether a legal move checkmates the opponent, false for an illegal move.
+    /// # Description
+    /// Plays the move on a scratch copy without the history and looks for any legal reply,
+    /// stopping at the first, so it is worth calling only for moves known to give check.
+    /// # Example
+    /// ``` Rust
+    /// let board = Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+    /// assert!(board.is_mate_after(&Move::new(0, 0, 0, 7, PieceType::Rook, None)));
+    /// ```
+    pub fn is_mate_after(&self, mv: &Move) -> bool {
+        let mut scratch = self.position_copy();
+        scratch.make_move(mv).is_ok() && scratch.king_in_check() && !scratch.has_legal_moves()
+    }
 
+    /// Legal moves of the piece on one square.
+    /// # Description
+    /// Includes castling for the king, every promotion choice for pawns and en passant captures.
+    /// Empty squares and pieces of the side not to move have no moves.
+    /// # Inputs/Outputs
+    /// - Input: x, y: usize - The square of the piece, (0, 0) is a1
+    /// - Output: The legal moves of that piece
+    /// # Example
+    /// ``` Rust
+    /// let board = Board::starting_position();
+    /// // The g1 knight can go to f3 or h3
+    /// assert_eq!(board.legal_moves_from(6, 0).len(), 2);
+    /// assert!(board.legal_moves_from(6, 7).is_empty());
+    /// ```
+    pub fn legal_moves_from(&self, x: usize, y: usize) -> Vec<Move> {
+        let Some(piece) = self.squares.get(y).and_then(|rank| rank.get(x)).copied().flatten() else {
+            return Vec::new();
+        };
+        if piece.get_color() != self.player_turn {
+            return Vec::new();
         }
+        let legality = self.legality();
+        let mut moves = self.generate_piece_moves(x, y, &piece, &legality.attacked);
+        moves.retain(|mv| self.is_pseudo_legal_move_legal(mv, &legality));
+        moves
     }
 
-    pub fn generate_legal_moves(&self) -> Vec<Move> {
-        let mut legal_moves = Vec::new();
-        
-        for y in 0..8 {
-            for x in 0..8 {
-                if let Some(piece) = &self.squares[y][x] {
-                    if *piece.get_color() == self.player_turn {
-                        let piece_moves = self.generate_piece_moves(x, y, piece);
-                        for mv in piece_moves {
-                            if self.is_legal_move(&mv) {
-                                legal_moves.push(mv);
-                            }
-                        }
-                    }
-                }
-            }
+    /// Every move the side to move's pieces can make, before checking whether it leaves the king in check.
+    /// # Description
+    /// Never includes a move onto the mover's own pieces or a pawn push onto an occupied square, and includes
+    /// en passant captures and castling when the rights allow. Castling is already checked for occupied or
+    /// attacked squares on the king's path; any other move that leaves the king attacked is left for
+    /// filter_legal or is_legal to remove, so a search can try its best candidates before paying for the check.
+    /// # Example
+    /// ``` Rust
+    /// let board = Board::from_fen("4k3/8/8/8/8/8/3r4/4K3 w - - 0 1").unwrap();
+    /// let moves = board.generate_pseudo_legal_moves();
+    /// // Kd1, Ke2, Kf1, Kf2 and Kxd2 are generated but only Kf1 and Kxd2 are legal
+    /// assert_eq!(moves.len(), 5);
+    /// assert_eq!(board.filter_legal(moves).len(), 2);
+    /// ```
+    pub fn generate_pseudo_legal_moves(&self) -> Vec<Move> {
+        let mut moves = Vec::new();
+        self.generate_pseudo_legal_moves_into(&mut moves);
+        moves
+    }
+
+    /// generate_pseudo_legal_moves into a buffer the caller keeps, which is cleared first.
+    /// Reusing one buffer per ply saves allocating a new Vec at every node of a search or perft.
+    pub fn generate_pseudo_legal_moves_into(&self, moves: &mut Vec<Move>) {
+        self.push_pseudo_legal_moves(moves, &self.attack_map(self.player_turn.opposite()));
+    }
+
+    /// generate_pseudo_legal_moves_into with the opponent's attack map already worked out
+    fn push_pseudo_legal_moves(&self, moves: &mut Vec<Move>, attacked: &AttackMap) {
+        moves.clear();
+        for index in bitboard::squares(self.occupancy[self.player_turn.index()]) {
+            let (x, y) = bitboard::index_to_coords(index);
+            let piece = self.squares[y][x].expect("occupancy matches the squares");
+            self.push_piece_moves(x, y, &piece, moves, attacked);
         }
-        legal_moves
     }
 
-    pub(crate) fn is_legal_move(&self, mv: &Move) -> bool {
-        let mut temp_board = self.clone();
-        temp_board.unchecked_move_piece(mv.from_x, mv.from_y, mv.to_x, mv.to_y);
-        if mv.piece_type == PieceType::King {
-            match self.player_turn {
-                Color::White => temp_board.white_king_position = (mv.to_x, mv.to_y),
-                Color::Black => temp_board.black_king_position = (mv.to_x, mv.to_y),
+    /// The legal captures and queen promotions for the side to move, for a quiescence search.
+    /// # Description
+    /// Works from the attack bitboards and only emits moves that land on an enemy piece, take en passant
+    /// or push a pawn to the last rank, so the quiet moves are never generated or filtered.
+    /// Promotions, capturing or not, only produce the queen; under-promotions are left to the full generator.
+    /// # Example
+    /// ``` Rust
+    /// let board = Board::from_fen("3r3k/2P5/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    /// // c8=Q and cxd8=Q
+    /// assert_eq!(board.generate_captures().len(), 2);
+    /// ```
+    pub fn generate_captures(&self) -> Vec<Move> {
+        let color = self.player_turn;
+        let enemies = self.occupancy[color.opposite().index()];
+        let occupancy = self.occupancy[0] | self.occupancy[1];
+        let mut moves = Vec::new();
+        for ptype in [PieceType::Knight, PieceType::Bishop, PieceType::Rook, PieceType::Queen, PieceType::King] {
+            for from in bitboard::squares(self.pieces(ptype, color)) {
+                let attacks = match ptype {
+                    PieceType::Knight => KNIGHT_ATTACKS[from],
+                    PieceType::Bishop => bitboard::bishop_attacks(from, occupancy),
+                    PieceType::Rook => bitboard::rook_attacks(from, occupancy),
+                    PieceType::Queen => bitboard::rook_attacks(from, occupancy) | bitboard::bishop_attacks(from, occupancy),
+                    _ => KING_ATTACKS[from],
+                };
+                let (from_x, from_y) = bitboard::index_to_coords(from);
+                for target in bitboard::squares(attacks & enemies) {
+                    let (to_x, to_y) = bitboard::index_to_coords(target);
+                    moves.push(Move { from_x, from_y, to_x, to_y, piece_type: ptype, promotion: None });
+                }
+            }
+        }
+        let en_passant = self.en_passant.map_or(0, |(x, y)| bitboard::square_bit(x, y));
+        let (direction, last_rank) = if color == Color::White { (1, 7) } else { (-1, 0) };
+        for from in bitboard::squares(self.pieces(PieceType::Pawn, color)) {
+            let (from_x, from_y) = bitboard::index_to_coords(from);
+            let to_y = (from_y as i8 + direction) as usize;
+            let promotion = (to_y == last_rank).then_some(PieceType::Queen);
+            for target in bitboard::squares(PAWN_ATTACKS[color.index()][from] & (enemies | en_passant)) {
+                let (to_x, _) = bitboard::index_to_coords(target);
+                moves.push(Move { from_x, from_y, to_x, to_y, piece_type: PieceType::Pawn, promotion });
             }
-        } else if mv.piece_type == PieceType::Pawn && mv.to_x == self.en_passant.unwrap_or((9, 9)).0 && mv.to_y == self.en_passant.unwrap_or((9, 9)).1 {
-            match self.player_turn {
-                Color::White => temp_board.squares[mv.to_y - 1][mv.to_x] = None,
-                Color::Black => temp_board.squares[mv.to_y + 1][mv.to_x] = None,
+            if promotion.is_some() && self.squares[to_y][from_x].is_none() {
+                moves.push(Move { from_x, from_y, to_x: from_x, to_y, piece_type: PieceType::Pawn, promotion });
             }
         }
-        !temp_board.king_in_check()
+        self.filter_legal(moves)
     }
 
-    fn generate_piece_moves(&self, x: usize, y: usize, piece: &Piece) -> Vec<Move> {
+    /// The legal quiet moves that give check, the companion to generate_captures for a quiescence search.
+    /// # Description
+    /// Captures and queen promotions are left out since generate_captures already returns them,
+    /// under-promotions that give check are included.
+    pub fn generate_checks(&self) -> Vec<Move> {
+        let legality = self.legality();
         let mut moves = Vec::new();
-        let directions: Vec<(i8, i8)> = match piece.get_type() {
-            PieceType::Pawn => self.generate_pawn_moves(x, y, piece),
-            PieceType::Rook => vec![(1, 0), (-1, 0), (0, 1), (0, -1)],
-            PieceType::Knight => vec![(2, 1), (1, 2), (-1, 2), (-2, 1), (-2, -1), (-1, -2), (1, -2), (2, -1)],
-            PieceType::Bishop => vec![(1, 1), (-1, 1), (1, -1), (-1, -1)],
-            PieceType::Queen => vec![(1, 0), (-1, 0), (0, 1), (0, -1), (1, 1), (-1, 1), (1, -1), (-1, -1)],
-            PieceType::King => vec![(1, 0), (-1, 0), (0, 1), (0, -1), (1, 1), (-1, 1), (1, -1), (-1, -1)],
+        self.push_pseudo_legal_moves(&mut moves, &legality.attacked);
+        moves.retain(|mv| {
+            self.squares[mv.to_y][mv.to_x].is_none() && mv.promotion != Some(PieceType::Queen)
+                && (mv.piece_type != PieceType::Pawn || mv.from_x == mv.to_x)
+                && self.is_pseudo_legal_move_legal(mv, &legality)
+                && self.gives_check(mv)
+        });
+        moves
+    }
+
+    /// The checkers and absolutely pinned pieces of the side to move
+    fn legality(&self) -> Legality {
+        let us = self.player_turn;
+        let them = us.opposite();
+        let attacked = self.attack_map(them);
+        let king_bits = self.pieces(PieceType::King, us);
+        if king_bits == 0 {
+            return Legality { king: None, check_mask: !0, pinned: 0, attacked };
+        }
+        let king = king_bits.trailing_zeros() as usize;
+        let occupancy = self.occupancy[0] | self.occupancy[1];
+        let enemy = &self.bitboards[them.index()];
+        let queens = enemy[PieceType::Queen.index()];
+        let straight = enemy[PieceType::Rook.index()] | queens;
+        let diagonal = enemy[PieceType::Bishop.index()] | queens;
+        let checkers = if self.in_check == Some(false) {
+            0
+        } else {
+            PAWN_ATTACKS[us.index()][king] & enemy[PieceType::Pawn.index()]
+                | KNIGHT_ATTACKS[king] & enemy[PieceType::Knight.index()]
+                | bitboard::rook_attacks(king, occupancy) & straight
+                | bitboard::bishop_attacks(king, occupancy) & diagonal
+        };
+        let check_mask = match checkers.count_ones() {
+            0 => !0,
+            1 => checkers | bitboard::between(king, checkers.trailing_zeros() as usize),
+            _ => 0,
         };
+        // Sliders that would see the king through the side to move's pieces pin the one piece in their way
+        let their_pieces = self.occupancy[them.index()];
+        let snipers = bitboard::rook_attacks(king, their_pieces) & straight | bitboard::bishop_attacks(king, their_pieces) & diagonal;
+        let pinned = bitboard::squares(snipers).fold(0, |pinned, sniper| {
+            let blockers = bitboard::between(king, sniper) & occupancy;
+            if blockers.count_ones() == 1 && blockers & self.occupancy[us.index()] != 0 { pinned | blockers } else { pinned }
+        });
+        Legality { king: Some(king), check_mask, pinned, attacked }
+    }
 
-        for &(dx, dy) in &directions {
-            let (mut nx, mut ny) = (x as i8 + dx, y as i8 + dy);
-            while nx >= 0 && nx < 8 && ny >= 0 && ny < 8 {
-                let to_x = nx as usize;
-                let to_y = ny as usize;
+    /// Whether a pseudo legal move leaves the mover's king safe, without playing it.
+    /// # Description
+    /// The king may go to any square the opponent does not attack once the king has left its square.
+    /// Castling was already checked for attacked squares when it was generated.
+    /// Other pieces must land on the check mask, and a pinned piece must stay on the line through its king.
+    /// En passant removes two pieces from one rank, so the king is checked for attacks afresh after it.
+    /// Debug builds confirm every answer by playing the move on a copy of the board.
+    fn is_pseudo_legal_move_legal(&self, mv: &Move, legality: &Legality) -> bool {
+        let legal = self.check_pseudo_legal_move(mv, legality);
+        debug_assert_eq!(legal, self.position_copy().is_legal_move(mv), "{:?} in {}", mv, self.to_fen());
+        legal
+    }
 
-                if let Some(target_piece) = &self.squares[to_y][to_x] {
-                    if target_piece.get_color() != piece.get_color() {
-                        // handle promotion
-                        if *piece.get_type() == PieceType::Pawn && (to_y == 0 || to_y == 7) {
-                            moves.push(Move { from_x: x, from_y: y, to_x, to_y, piece_type: piece.get_type().clone(), promotion: Some(PieceType::Queen) });
-                            moves.push(Move { from_x: x, from_y: y, to_x, to_y, piece_type: piece.get_type().clone(), promotion: Some(PieceType::Rook) });
-                            moves.push(Move { from_x: x, from_y: y, to_x, to_y, piece_type: piece.get_type().clone(), promotion: Some(PieceType::Knight) });
-                            moves.push(Move { from_x: x, from_y: y, to_x, to_y, piece_type: piece.get_type().clone(), promotion: Some(PieceType::Bishop) });
-                        } else {
-                            moves.push(Move { from_x: x, from_y: y, to_x, to_y, piece_type: piece.get_type().clone(), promotion: None });
-                        }
-                    }
-                    break;
-                } else {
-                    if *piece.get_type() == PieceType::Pawn && (to_y == 0 || to_y == 7) {
-                        moves.push(Move { from_x: x, from_y: y, to_x, to_y, piece_type: piece.get_type().clone(), promotion: Some(PieceType::Queen) });
-                        moves.push(Move { from_x: x, from_y: y, to_x, to_y, piece_type: piece.get_type().clone(), promotion: Some(PieceType::Rook) });
-                        moves.push(Move { from_x: x, from_y: y, to_x, to_y, piece_type: piece.get_type().clone(), promotion: Some(PieceType::Knight) });
-                        moves.push(Move { from_x: x, from_y: y, to_x, to_y, piece_type: piece.get_type().clone(), promotion: Some(PieceType::Bishop) });
-                    } else {
-                        moves.push(Move { from_x: x, from_y: y, to_x, to_y, piece_type: piece.get_type().clone(), promotion: None });
-                    }
-                }
+    fn check_pseudo_legal_move(&self, mv: &Move, legality: &Legality) -> bool {
+        let Some(king) = legality.king else {
+            return true;
+        };
+        let from = bitboard::square_index(mv.from_x, mv.from_y);
+        let to = bitboard::square_index(mv.to_x, mv.to_y);
+        let them = self.player_turn.opposite();
+        let occupancy = self.occupancy[0] | self.occupancy[1];
+        if from == king {
+            if mv.from_x.abs_diff(mv.to_x) == 2 {
+                return true;
+            }
+            return !legality.attacked.is_attacked(mv.to_x, mv.to_y);
+        }
+        if mv.piece_type == PieceType::Pawn && mv.from_x != mv.to_x && self.squares[mv.to_y][mv.to_x].is_none() {
+            let captured = bitboard::square_index(mv.to_x, mv.from_y);
+            let after = occupancy & !(1 << from) & !(1 << captured) | 1 << to;
+            let enemy = &self.bitboards[them.index()];
+            let queens = enemy[PieceType::Queen.index()];
+            return PAWN_ATTACKS[self.player_turn.index()][king] & enemy[PieceType::Pawn.index()] & !(1 << captured) == 0
+                && KNIGHT_ATTACKS[king] & enemy[PieceType::Knight.index()] == 0
+                && bitboard::rook_attacks(king, after) & (enemy[PieceType::Rook.index()] | queens) == 0
+                && bitboard::bishop_attacks(king, after) & (enemy[PieceType::Bishop.index()] | queens) == 0;
+        }
+        if legality.check_mask & 1 << to == 0 {
+            return false;
+        }
+        legality.pinned & 1 << from == 0 || bitboard::line(king, from) & 1 << to != 0
+    }
 
-                if *piece.get_type() == PieceType::Knight || *piece.get_type() == PieceType::King || *piece.get_type() == PieceType::Pawn {
-                    break;
-                }
+    /// Keeps the legal moves of a list of pseudo legal moves for the side to move, in order
+    pub(crate) fn retain_legal(&self, moves: &mut Vec<Move>) {
+        let legality = self.legality();
+        moves.retain(|mv| self.is_pseudo_legal_move_legal(mv, &legality));
+    }
 
-                nx += dx;
-                ny += dy;
+    /// Checks a move by playing it and taking it back.
+    /// # Description
+    /// Uses the same make move routine as move_piece, so castling, promotions and en passant
+    /// are judged exactly as they would be played and the two can never disagree.
+    /// The board is left as it was whether or not the move is legal.
+    pub(crate) fn is_legal_move(&mut self, mv: &Move) -> bool {
+        match self.make_move(mv) {
+            Ok(undo) => {
+                self.unmake_move(mv, undo);
+                true
             }
+            Err(_) => false,
+        }
+    }
+
+    /// A copy of the position without the move history, for trying moves on
+    fn position_copy(&self) -> Board {
+        Board {
+            squares: self.squares,
+            move_number: self.move_number,
+            player_turn: self.player_turn,
+            white_can_castle_king: self.white_can_castle_king,
+            white_can_castle_queen: self.white_can_castle_queen,
+            black_can_castle_king: self.black_can_castle_king,
+            black_can_castle_queen: self.black_can_castle_queen,
+            en_passant: self.en_passant,
+            halfmove: self.halfmove,
+            white_king_position: self.white_king_position,
+            black_king_position: self.black_king_position,
+            bitboards: self.bitboards,
+            occupancy: self.occupancy,
+            material: self.material,
+            piece_key: self.piece_key,
+            in_check: self.in_check,
+            history: Vec::new(),
+            keys: Vec::new(),
         }
-        if *piece.get_type() == PieceType::King {
-            if self.check_kingside_castle() {
+    }
+
+    /// The running material balance, read by basic_evaluate
+    pub(crate) fn material(&self) -> i32 {
+        self.material
+    }
+
+    fn generate_piece_moves(&self, x: usize, y: usize, piece: &Piece, attacked: &AttackMap) -> Vec<Move> {
+        let mut moves = Vec::new();
+        self.push_piece_moves(x, y, piece, &mut moves, attacked);
+        moves
+    }
+
+    /// Appends the pseudo legal moves of one piece to moves.
+    /// attacked is the opponent's attack map, which decides whether the king may castle.
+    fn push_piece_moves(&self, x: usize, y: usize, piece: &Piece, moves: &mut Vec<Move>, attacked: &AttackMap) {
+        if piece.get_type() == PieceType::Pawn {
+            self.push_pawn_moves(x, y, piece, moves);
+        } else {
+            self.push_non_pawn_moves(x, y, piece, moves, attacked);
+        }
+    }
+
+    /// Appends the pseudo legal moves of a knight, bishop, rook, queen or king.
+    /// Targets come from the attack bitboards, minus squares holding the mover's own pieces.
+    fn push_non_pawn_moves(&self, x: usize, y: usize, piece: &Piece, moves: &mut Vec<Move>, attacked: &AttackMap) {
+        let index = bitboard::square_index(x, y);
+        let occupancy = self.occupancy[0] | self.occupancy[1];
+        let attacks = match piece.get_type() {
+            PieceType::Knight => KNIGHT_ATTACKS[index],
+            PieceType::Bishop => bitboard::bishop_attacks(index, occupancy),
+            PieceType::Rook => bitboard::rook_attacks(index, occupancy),
+            PieceType::Queen => bitboard::rook_attacks(index, occupancy) | bitboard::bishop_attacks(index, occupancy),
+            PieceType::King => KING_ATTACKS[index],
+            PieceType::Pawn => 0,
+        };
+        let targets = attacks & !self.occupancy[piece.get_color().index()];
+        moves.extend(bitboard::squares(targets).map(|target| {
+            let (to_x, to_y) = bitboard::index_to_coords(target);
+            Move { from_x: x, from_y: y, to_x, to_y, piece_type: piece.get_type(), promotion: None }
+        }));
+        if piece.get_type() == PieceType::King {
+            if self.check_kingside_castle(attacked) {
                 moves.push(Move { from_x: x, from_y: y, to_x: x + 2, to_y: y, piece_type: PieceType::King, promotion: None });
             }
-            if self.check_queenside_castle() {
+            if self.check_queenside_castle(attacked) {
                 moves.push(Move { from_x: x, from_y: y, to_x: x - 2, to_y: y, piece_type: PieceType::King, promotion: None });
             }
         }
-        let dy: i32 = if *piece.get_color() == Color::White { 1 } else { -1 };
-        if *piece.get_type() == PieceType::Pawn {
-            if let Some((ex, ey)) = self.en_passant {
-                if ey as i32 == y as i32 + dy && ex == x {
-                    moves.push(Move { from_x: x, from_y: y, to_x: ex, to_y: ey, piece_type: PieceType::Pawn, promotion: None });
-                }
-            }
-        }
-        moves
     }
 
-    fn generate_pawn_moves(&self, x: usize, y: usize, piece: &Piece) -> Vec<(i8, i8)> {
-        let mut moves = Vec::new();
-        let direction = if *piece.get_color() == Color::White { 1 } else { -1 };
+    /// Appends the pushes and captures of a pawn, with all four promotions for each one reaching the last rank
+    fn push_pawn_moves(&self, x: usize, y: usize, piece: &Piece, moves: &mut Vec<Move>) {
+        let direction = if piece.get_color() == Color::White { 1 } else { -1 };
+        let mut push = |dx: i8, dy: i8| {
+            let to_x = (x as i8 + dx) as usize;
+            let to_y = (y as i8 + dy) as usize;
+            if to_y == 0 || to_y == 7 {
+                for promotion in [PieceType::Queen, PieceType::Rook, PieceType::Knight, PieceType::Bishop] {
+                    moves.push(Move { from_x: x, from_y: y, to_x, to_y, piece_type: PieceType::Pawn, promotion: Some(promotion) });
+                }
+            } else {
+                moves.push(Move { from_x: x, from_y: y, to_x, to_y, piece_type: PieceType::Pawn, promotion: None });
+            }
+        };
 
         let forward_one = y as i8 + direction;
-        if forward_one >= 0 && forward_one < 8 && self.squares[forward_one as usize][x].is_none() {
-            moves.push((0, direction));
+        if (0..8).contains(&forward_one) && self.squares[forward_one as usize][x].is_none() {
+            push(0, direction);
             let forward_two = y as i8 + 2 * direction;
-            if (*piece.get_color() == Color::White && y == 1) || (*piece.get_color() == Color::Black && y == 6) {
-                if self.squares[forward_two as usize][x].is_none() {
-                    moves.push((0, 2 * direction));
-                }
+            if ((piece.get_color() == Color::White && y == 1) || (piece.get_color() == Color::Black && y == 6))
+                && self.squares[forward_two as usize][x].is_none() {
+                push(0, 2 * direction);
             }
         }
 
         for &dx in [-1, 1].iter() {
             let capture_y = forward_one;
             let capture_x = x as i8 + dx;
-            if capture_x >= 0 && capture_x < 8 && capture_y >= 0 && capture_y < 8 {
+            if (0..8).contains(&capture_x) && (0..8).contains(&capture_y) {
                 if let Some(target_piece) = &self.squares[capture_y as usize][capture_x as usize] {
                     if target_piece.get_color() != piece.get_color() {
-                        moves.push((dx, direction));
+                        push(dx, direction);
                     }
                 } else if self.en_passant == Some((capture_x as usize, capture_y as usize)) {
-                    moves.push((dx, direction));
+                    push(dx, direction);
                 }
             }
         }
+    }
+}
 
-        moves
+/// Order in which pieces join an exchange, least valuable first
+const SEE_ORDER: [PieceType; 6] = [PieceType::Pawn, PieceType::Knight, PieceType::Bishop, PieceType::Rook, PieceType::Queen, PieceType::King];
+/// Value of a king in an exchange, high enough that it never recaptures onto a defended square
+const SEE_KING_VALUE: i32 = 20_000;
+
+/// Material value of a piece type for static exchange evaluation
+fn see_value(ptype: PieceType) -> i32 {
+    match ptype {
+        PieceType::King => SEE_KING_VALUE,
+        _ => ptype.to_centipawns(),
+    }
+}
+
+/// What sort of move make_move just played, read from what it recorded to take the move back
+fn move_kind(mv: &Move, undo: &UndoInfo) -> MoveKind {
+    let moved = undo.moved.get_type();
+    if moved == PieceType::King && mv.from_x.abs_diff(mv.to_x) == 2 {
+        MoveKind::Castle(if mv.to_x == 6 { Side::KingSide } else { Side::QueenSide })
+    } else if moved == PieceType::Pawn && (mv.to_y == 0 || mv.to_y == 7) {
+        MoveKind::Promotion(mv.promotion.unwrap_or(PieceType::Queen))
+    } else if undo.captured.is_some() && undo.captured_square != (mv.to_x, mv.to_y) {
+        MoveKind::EnPassant
+    } else if let Some(captured) = undo.captured {
+        MoveKind::Capture(captured.get_type())
+    } else {
+        MoveKind::Quiet
     }
 }
 
+/// A piece's value towards the material balance, negative for Black
+fn material_value(piece: Piece) -> i32 {
+    match piece.get_color() {
+        Color::White => piece.get_type().to_centipawns(),
+        Color::Black => -piece.get_type().to_centipawns(),
+    }
+}
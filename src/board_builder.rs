@@ -0,0 +1,130 @@
+use crate::{board::Board, castling_rights::CastlingRights, color::Color, errors::fen_error::FenError, piece::Piece, utils::notation::{coords_to_square, square_to_coords}};
+
+/// A square given either by name, e.g. "e4", or as (x, y) coordinates, see the coords module
+pub trait IntoSquare {
+    /// The (x, y) coordinates of the square, or the text to report if it is not a square
+    fn into_square(self) -> Result<(usize, usize), String>;
+}
+
+impl IntoSquare for &str {
+    fn into_square(self) -> Result<(usize, usize), String> {
+        square_to_coords(self).ok_or_else(|| self.to_string())
+    }
+}
+
+impl IntoSquare for (usize, usize) {
+    fn into_square(self) -> Result<(usize, usize), String> {
+        let (x, y) = self;
+        if x < 8 && y < 8 {
+            Ok(self)
+        } else {
+            Err(format!("({}, {})", x, y))
+        }
+    }
+}
+
+/// Builds a position piece by piece and checks it the way from_fen does.
+/// # Description
+/// Starts from an empty board with White to move and no castling rights.
+/// Mistakes such as a bad square or piece character are kept until build, so calls can be chained.
+/// build rejects positions without exactly one king each or with pawns on the first or last rank, the same as from_fen.
+/// A castling right whose king or rook is not on its starting square is an error, as it is for from_fen.
+/// # Example
+/// ```rust
+/// use casey_chess::{board_builder::BoardBuilder, color::Color, search::{search_limits::SearchLimits, searcher::analyze}};
+///
+/// // A promotion race, both pawns are three moves from queening and White moves first
+/// let board = BoardBuilder::new()
+///     .piece("h7", 'K')
+///     .piece("a3", 'k')
+///     .piece("b5", 'P')
+///     .piece((6, 3), 'p')
+///     .turn(Color::White)
+///     .build()
+///     .unwrap();
+/// assert_eq!(board.to_fen(), "8/7K/8/1P6/6p1/k7/8/8 w - - 0 1");
+/// let result = analyze(&board, SearchLimits::depth(5));
+/// assert_eq!(result.best_move.unwrap().extended_algebraic(), "b5b6");
+/// ```
+#[derive(Debug, Clone)]
+pub struct BoardBuilder {
+    board: Board,
+    castling: CastlingRights,
+    en_passant: Option<Result<(usize, usize), String>>,
+    error: Option<FenError>,
+}
+
+impl Default for BoardBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BoardBuilder {
+    pub fn new() -> BoardBuilder {
+        BoardBuilder { board: Board::empty(), castling: CastlingRights::none(), en_passant: None, error: None }
+    }
+
+    /// Put a piece on a square, replacing anything there.
+    /// # Inputs/Outputs
+    /// - Input: square - A square name such as "e1" or (x, y) coordinates
+    /// - Input: piece - The piece as a FEN character, uppercase for White
+    pub fn piece(mut self, square: impl IntoSquare, piece: char) -> BoardBuilder {
+        let square = square.into_square();
+        match (square, Piece::from_fen(piece)) {
+            (Ok((x, y)), Some(piece)) => {
+                self.board.set_piece(x, y, piece);
+            }
+            (Err(square), _) => self.fail(FenError::InvalidSquare(square)),
+            (_, None) => self.fail(FenError::InvalidPieceChar(piece)),
+        }
+        self
+    }
+
+    /// Set the side to move, White if not given
+    pub fn turn(mut self, color: Color) -> BoardBuilder {
+        self.board.set_turn(color);
+        self
+    }
+
+    /// Set the castling rights, none if not given
+    pub fn castling(mut self, rights: CastlingRights) -> BoardBuilder {
+        self.castling = rights;
+        self
+    }
+
+    /// Set the square behind a pawn that just moved two squares
+    pub fn en_passant(mut self, square: impl IntoSquare) -> BoardBuilder {
+        self.en_passant = Some(square.into_square());
+        self
+    }
+
+    /// Check the position and return the board.
+    /// # Inputs/Outputs
+    /// - Output: The board, or the first problem found, reported as the matching FEN error
+    pub fn build(self) -> Result<Board, FenError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        let mut board = self.board;
+        board.validate_position()?;
+        if let Some(square) = self.en_passant {
+            let (x, y) = square.map_err(FenError::InvalidSquare)?;
+            let expected_y = if *board.get_player_turn() == Color::White { 5 } else { 2 };
+            if y != expected_y {
+                return Err(FenError::BadEnPassantSquare(coords_to_square((x, y))));
+            }
+            board.set_en_passant(Some((x, y)));
+        }
+        board.set_castling_rights(self.castling);
+        if let Some(repair) = board.castling_repairs().first() {
+            return Err(FenError::InconsistentCastlingRights(repair.right));
+        }
+        Ok(board)
+    }
+
+    /// Keeps the first mistake, which build reports
+    fn fail(&mut self, error: FenError) {
+        self.error.get_or_insert(error);
+    }
+}
@@ -0,0 +1,34 @@
+use crate::{chess_move::Move, color::Color};
+
+
+/// How Board::render draws a board.
+/// # Description
+/// The default draws FEN letters from White's side with nothing highlighted and no status line, the same as Board::print.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoardRenderOptions {
+    /// Draw pieces as chess symbols such as '♔' instead of FEN letters
+    pub unicode: bool,
+    /// A move whose from and to squares are drawn in square brackets, normally the last move played
+    pub highlight: Option<Move>,
+    /// Draw exclamation marks around the king of the side to move when it is in check
+    pub mark_check: bool,
+    /// The side drawn at the bottom of the board
+    pub perspective: Color,
+    /// Draw the highlight and the checked king with ANSI inverse video and red instead of brackets and exclamation marks
+    pub ansi: bool,
+    /// Add a line under the board saying who is to move and whether they are in check, mated or drawn
+    pub status: bool,
+}
+
+impl Default for BoardRenderOptions {
+    fn default() -> Self {
+        BoardRenderOptions {
+            unicode: false,
+            highlight: None,
+            mark_check: false,
+            perspective: Color::White,
+            ansi: false,
+            status: false,
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use std::fmt::{self, Display, Formatter};
+
+use crate::color::Color;
+
+
+/// Which castling moves are still available to each side.
+/// # Description
+/// A right is lost once the king or the matching rook moves, or the rook is captured on its starting square.
+/// Having the right does not mean castling is legal right now, the squares between must also be empty and safe.
+/// Displays in FEN form, e.g. "KQkq", "Kq" or "-".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CastlingRights {
+    pub white_king: bool,
+    pub white_queen: bool,
+    pub black_king: bool,
+    pub black_queen: bool,
+}
+
+impl CastlingRights {
+    /// Rights with no castling available for either side
+    pub fn none() -> CastlingRights {
+        CastlingRights { white_king: false, white_queen: false, black_king: false, black_queen: false }
+    }
+
+    /// Whether the given side may still castle kingside
+    pub fn kingside(&self, color: Color) -> bool {
+        match color {
+            Color::White => self.white_king,
+            Color::Black => self.black_king,
+        }
+    }
+
+    /// Whether the given side may still castle queenside
+    pub fn queenside(&self, color: Color) -> bool {
+        match color {
+            Color::White => self.white_queen,
+            Color::Black => self.black_queen,
+        }
+    }
+}
+
+impl Display for CastlingRights {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let rights = [(self.white_king, 'K'), (self.white_queen, 'Q'), (self.black_king, 'k'), (self.black_queen, 'q')];
+        let field = rights.iter().filter(|(allowed, _)| *allowed).map(|(_, c)| *c).collect::<String>();
+        if field.is_empty() {
+            write!(f, "-")
+        } else {
+            write!(f, "{}", field)
+        }
+    }
+}
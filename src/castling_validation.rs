@@ -0,0 +1,35 @@
+use std::fmt::{self, Display, Formatter};
+
+use crate::{color::Color, coords::{file_char, rank_char}, piece_type::PieceType};
+
+
+/// What from_fen_with does with a castling right whose king or rook is not on its starting square.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CastlingValidation {
+    /// Reject the FEN with FenError::InconsistentCastlingRights
+    #[default]
+    Strict,
+    /// Drop the right and report it as a CastlingRepair, for FENs from tools that copy "KQkq" into odds positions
+    Repair,
+}
+
+/// A castling right dropped while reading a FEN in repair mode, and the piece that was missing.
+/// Displays as e.g. "dropped castling right 'Q', no White rook on a1".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CastlingRepair {
+    /// The right as its FEN character, one of "KQkq"
+    pub right: char,
+    pub color: Color,
+    /// The king or rook that was not on its starting square
+    pub piece: PieceType,
+    /// The starting square, as (x, y) coordinates
+    pub square: (usize, usize),
+}
+
+impl Display for CastlingRepair {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let piece = if self.piece == PieceType::King { "king" } else { "rook" };
+        let (x, y) = self.square;
+        write!(f, "dropped castling right '{}', no {} {} on {}{}", self.right, self.color, piece, file_char(x), rank_char(y))
+    }
+}
@@ -0,0 +1,141 @@
+use std::sync::{atomic::AtomicBool, Arc};
+
+use crate::{board::Board, chess_move::Move, errors::move_error::MoveError, hint::format_score, piece_type::PieceType, search::{search_limits::SearchLimits, searcher::{analyze_with_table, Searcher}, transposition_table::{TranspositionTable, ANALYSIS_HASH_MB}}, utils::notation::{line_to_san, move_to_san}};
+
+/// Centipawns a move may lose against the best move before the coach warns about it
+pub const DEFAULT_COACH_THRESHOLD_CP: i32 = 200;
+/// Milliseconds the coach spends on each of its two searches
+pub const DEFAULT_COACH_MOVETIME_MS: u64 = 300;
+/// Moves of the refuting line written out in a warning
+const REFUTATION_SAN_MOVES: usize = 4;
+
+/// How the console's coach mode checks the player's moves before they are played.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CoachConfig {
+    /// A move losing more than this many centipawns against the best move is a blunder
+    pub threshold_cp: i32,
+    /// Time for each search, in milliseconds
+    pub movetime_ms: u64,
+}
+
+impl Default for CoachConfig {
+    fn default() -> Self {
+        CoachConfig { threshold_cp: DEFAULT_COACH_THRESHOLD_CP, movetime_ms: DEFAULT_COACH_MOVETIME_MS }
+    }
+}
+
+impl CoachConfig {
+    /// The limits of each search, a fixed movetime
+    pub fn limits(&self) -> SearchLimits {
+        SearchLimits::movetime(self.movetime_ms)
+    }
+
+    /// Judges a move with this config's movetime and threshold, see judge_user_move
+    pub fn judge(&self, board: &Board, mv: &Move) -> Result<Judgment, MoveError> {
+        judge_user_move(board, mv, &self.limits(), self.threshold_cp)
+    }
+}
+
+/// The coach's verdict on a move the player has entered but not yet played.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Judgment {
+    /// The move is close enough to the best move to be played without a word
+    Pass { cp_loss: i32 },
+    Blunder(Blunder),
+}
+
+/// A move that loses more than the coach's threshold, with the reply that punishes it.
+/// Scores are in centipawns from the perspective of the player.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Blunder {
+    pub played_san: String,
+    pub cp_loss: i32,
+    /// The score after the move, once the opponent finds the refutation
+    pub played_score: i32,
+    pub best_move: Move,
+    pub best_san: String,
+    /// The opponent's best reply and the expected continuation, empty if the move ends the game
+    pub refutation: Vec<Move>,
+    /// The start of the refutation in SAN, cut short with "…"
+    pub refutation_san: String,
+    /// The piece the first move of the refutation captures, if any
+    pub lost_piece: Option<PieceType>,
+}
+
+impl Blunder {
+    /// What the move gives away, e.g. "Qd2 loses a knight to Nxe5, Nc3 was better" or
+    /// "g4 allows Qh4# (#-1), e4 was better"
+    pub fn warning(&self) -> String {
+        let reply = self.refutation_san.split(' ').next().unwrap_or_default();
+        match self.lost_piece {
+            Some(piece) => format!("{} loses a {} to {}, {} was better", self.played_san, piece.name(), reply, self.best_san),
+            None => format!(
+                "{} allows {} ({}), {} was better",
+                self.played_san, self.refutation_san, format_score(self.played_score), self.best_san,
+            ),
+        }
+    }
+}
+
+/// Compare a move the player wants to make with the engine's best move, for the console's coach mode.
+/// # Description
+/// Searches the position for the best move, then searches just the best move and the player's move again
+/// to the depth the first search completed, so both are scored exactly by the same iteration.
+/// The move is a blunder when it scores more than threshold_cp below the best move,
+/// and the opponent's best reply with its continuation is kept as the refutation.
+/// A move the engine also chose always passes and is only searched once.
+/// # Inputs/Outputs
+/// - Input: board: &Board - The position before the move
+/// - Input: mv: &Move - The move the player entered
+/// - Input: limits: &SearchLimits - The limits of each search, e.g. a fixed movetime, the second also stops at the depth of the first
+/// - Input: threshold_cp: i32 - The most centipawns the move may lose and still pass
+/// - Output: The judgment, or an error if the move is not legal
+/// # Example
+/// ``` Rust
+/// let board = Board::from_fen("4k3/8/8/3p4/8/8/3Q4/4K3 w - - 0 1").unwrap();
+/// let mv = san_to_move(&board, "Qd4").unwrap();
+/// if let Judgment::Blunder(blunder) = judge_user_move(&board, &mv, &SearchLimits::depth(3), 200).unwrap() {
+///     println!("{}", blunder.warning());
+/// }
+/// ```
+pub fn judge_user_move(board: &Board, mv: &Move, limits: &SearchLimits, threshold_cp: i32) -> Result<Judgment, MoveError> {
+    let mut after = board.clone();
+    after.move_piece(mv.clone())?;
+    let mut table = TranspositionTable::new(ANALYSIS_HASH_MB);
+    let best = analyze_with_table(board, limits.clone(), &mut table);
+    let best_move = best.best_move.unwrap_or_else(|| mv.clone());
+    if best_move == *mv {
+        return Ok(Judgment::Pass { cp_loss: 0 });
+    }
+    // If the comparison runs out of time at the first search's depth, both moves keep their scores from the depth before
+    let compare = SearchLimits { depth: Some(best.depth.max(1)), ..limits.clone() };
+    let mut searcher = Searcher::new_with_table(compare, Arc::new(AtomicBool::new(false)), table)
+        .with_search_moves(vec![best_move.clone(), mv.clone()])
+        .with_multi_pv(2);
+    let scores = searcher.search(board).root_scores();
+    let score_of = |wanted: &Move| scores.iter().find(|(mv, _)| mv == wanted).map_or(0, |(_, score)| *score);
+    let played_score = score_of(mv);
+    let cp_loss = (score_of(&best_move) - played_score).max(0);
+    if cp_loss <= threshold_cp {
+        return Ok(Judgment::Pass { cp_loss });
+    }
+    // The line after the player's move, which the comparison searched with a full window
+    let refutation: Vec<Move> = searcher.principal_variation(board, mv, best.depth.max(1) as usize + 1).into_iter().skip(1).collect();
+    let lost_piece = refutation.first().and_then(|first| {
+        match after.get_piece(first.to_x, first.to_y) {
+            Some(piece) => Some(piece.get_type()),
+            None if first.piece_type == PieceType::Pawn && first.from_x != first.to_x => Some(PieceType::Pawn),
+            None => None,
+        }
+    });
+    Ok(Judgment::Blunder(Blunder {
+        played_san: move_to_san(board, mv),
+        cp_loss,
+        played_score,
+        best_san: move_to_san(board, &best_move),
+        best_move,
+        refutation_san: line_to_san(&after, &refutation, REFUTATION_SAN_MOVES),
+        refutation,
+        lost_piece,
+    }))
+}
@@ -2,6 +2,7 @@ use std::fmt::{self, Display, Formatter};
 
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Color {
     White,
     Black,
@@ -18,6 +19,14 @@ impl Color {
     pub fn is_white(&self) -> bool {
         *self == Color::White
     }
+
+    /// Index of the color for tables stored per color
+    pub fn index(&self) -> usize {
+        match self {
+            Color::White => 0,
+            Color::Black => 1,
+        }
+    }
 }
 
 impl Display for Color {
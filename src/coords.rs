@@ -0,0 +1,56 @@
+//! The coordinate convention used everywhere squares are stored.
+//! # Description
+//! A square is (x, y) with x the file, 0 for a to 7 for h, and y the rank index, 0 for rank 1 to 7 for rank 8.
+//! Boards are stored as squares[y][x], so squares[0] is White's back rank and e2 is squares[1][4].
+//! Bitboards use the same numbering with index y * 8 + x.
+//! Anything that reads or writes squares in another order, FEN listing rank 8 first or a board drawn
+//! from either side, converts with the helpers here rather than doing its own arithmetic.
+
+use crate::color::Color;
+
+/// The y of the n-th rank listed in a FEN placement field, which starts with rank 8.
+/// Also converts y back to the position of its rank in the FEN field.
+/// # Example
+/// ``` Rust
+/// assert_eq!(fen_rank_to_y(0), 7);
+/// assert_eq!(fen_rank_to_y(6), 1);
+/// ```
+pub const fn fen_rank_to_y(fen_rank: usize) -> usize {
+    7 - fen_rank
+}
+
+/// The row, counted from the top, that rank y is drawn on when the board is seen from a side.
+/// White sees rank 8 at the top and Black sees rank 1 at the top.
+/// Also converts a row back to y, as the mapping is its own inverse.
+/// # Example
+/// ``` Rust
+/// // e2 is on the second row from the bottom for White
+/// assert_eq!(y_to_display_row(1, Color::White), 6);
+/// assert_eq!(y_to_display_row(1, Color::Black), 1);
+/// ```
+pub const fn y_to_display_row(y: usize, perspective: Color) -> usize {
+    match perspective {
+        Color::White => 7 - y,
+        Color::Black => y,
+    }
+}
+
+/// The column, counted from the left, that file x is drawn in when the board is seen from a side.
+/// White sees the a-file on the left and Black sees the h-file on the left.
+/// Also converts a column back to x, as the mapping is its own inverse.
+pub const fn x_to_display_column(x: usize, perspective: Color) -> usize {
+    match perspective {
+        Color::White => x,
+        Color::Black => 7 - x,
+    }
+}
+
+/// The letter of file x, 'a' to 'h'
+pub const fn file_char(x: usize) -> char {
+    (b'a' + x as u8) as char
+}
+
+/// The digit of rank y, '1' to '8'
+pub const fn rank_char(y: usize) -> char {
+    (b'1' + y as u8) as char
+}
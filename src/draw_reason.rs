@@ -0,0 +1,27 @@
+use std::fmt::{self, Display, Formatter};
+
+
+
+/// Why a position is drawn, as reported by Board::is_draw.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrawReason {
+    /// The side to move has no legal move and is not in check
+    Stalemate,
+    /// Neither side has the material to give mate by any series of legal moves
+    InsufficientMaterial,
+    /// A hundred halfmoves without a capture or pawn move
+    FiftyMoveRule,
+    /// The position has appeared three times
+    ThreefoldRepetition,
+}
+
+impl Display for DrawReason {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", match self {
+            DrawReason::Stalemate => "stalemate",
+            DrawReason::InsufficientMaterial => "insufficient material",
+            DrawReason::FiftyMoveRule => "the fifty-move rule",
+            DrawReason::ThreefoldRepetition => "threefold repetition",
+        })
+    }
+}
@@ -0,0 +1,137 @@
+use crate::{board::Board, chess_move::Move, color::Color, errors::drill_error::DrillError, kibitzer::watcher::{parse_move, pgn_moves}};
+
+/// What Drill::step made of the user's move.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DrillFeedback {
+    /// The move reached the position the line expects and was played.
+    /// reply is the scripted answer the drill played for the other side, None if the line ended with the user's move.
+    Correct { reply: Option<Move> },
+    /// The move, or an illegal one, leaves the line and was not played. expected is the scripted move.
+    Deviation { expected: Move },
+    /// The line has been played to the end, there is nothing left to drill
+    Finished,
+}
+
+/// An opening drill: a scripted line where the user plays one side and the drill answers for the other.
+/// # Description
+/// A move is judged by the position it reaches, not its text, so it does not matter whether it was entered in SAN
+/// or UCI, and a move that reaches a position further along the line skips ahead to it.
+/// Deviations are counted and the board is left as it was so the user can try again or ask for the expected move.
+/// # Example
+/// ``` Rust
+/// let mut drill = Drill::new("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", "1. e4 e5 2. Nf3 Nc6", Color::White).unwrap();
+/// let nf3 = Move::new(6, 0, 5, 2, PieceType::Knight, None);
+/// assert_eq!(drill.step(&nf3), DrillFeedback::Deviation { expected: Move::new(4, 1, 4, 3, PieceType::Pawn, None) });
+/// let feedback = drill.step(&Move::new(4, 1, 4, 3, PieceType::Pawn, None));
+/// assert_eq!(feedback, DrillFeedback::Correct { reply: Some(Move::new(4, 6, 4, 4, PieceType::Pawn, None)) });
+/// ```
+#[derive(Debug, Clone)]
+pub struct Drill {
+    board: Board,
+    user: Color,
+    line: Vec<Move>,
+    /// The position after each move of the line
+    positions: Vec<Board>,
+    /// Moves of the line played so far
+    ply: usize,
+    deviations: usize,
+}
+
+impl Drill {
+    /// A drill of a line from a position.
+    /// # Description
+    /// The line may be in SAN or UCI, with move numbers, comments and variations as in PGN movetext.
+    /// If the user does not have the move in the starting position the drill plays the first scripted move straight away.
+    /// # Inputs/Outputs
+    /// - Input: fen - The starting position
+    /// - Input: line - The moves of both sides
+    /// - Input: user - The side the user plays
+    /// - Output: The drill, or the first move of the line that is not legal
+    pub fn new(fen: &str, line: &str, user: Color) -> Result<Drill, DrillError> {
+        let start = Board::from_fen(fen)?;
+        let mut board = start.clone();
+        let mut moves = Vec::new();
+        let mut positions = Vec::new();
+        for (i, text) in pgn_moves(line).into_iter().enumerate() {
+            let mv = parse_move(&board, &text).ok_or(DrillError::BadMove { ply: i + 1, text })?;
+            board.move_piece(mv.clone()).expect("parsed moves are legal");
+            moves.push(mv);
+            positions.push(board.clone());
+        }
+        let mut drill = Drill { board: start, user, line: moves, positions, ply: 0, deviations: 0 };
+        drill.play_reply();
+        Ok(drill)
+    }
+
+    /// Reads a drill file: the first non-empty line is the starting FEN, or "startpos", and the rest is the line.
+    /// # Example
+    /// ``` Rust
+    /// let drill = Drill::parse("startpos\n1. d4 d5 2. c4 e6 {Queen's Gambit Declined}\n", Color::Black).unwrap();
+    /// ```
+    pub fn parse(text: &str, user: Color) -> Result<Drill, DrillError> {
+        let mut lines = text.lines().skip_while(|line| line.trim().is_empty());
+        let fen = match lines.next().map(str::trim) {
+            Some("startpos") => Board::starting_position().to_fen(),
+            Some(fen) => fen.to_string(),
+            None => return Err(DrillError::MissingFen),
+        };
+        Drill::new(&fen, &lines.collect::<Vec<_>>().join("\n"), user)
+    }
+
+    /// Judge the user's move, playing it and the scripted reply if it stays on the line.
+    pub fn step(&mut self, user_move: &Move) -> DrillFeedback {
+        let Some(expected) = self.expected().cloned() else {
+            return DrillFeedback::Finished;
+        };
+        let mut after = self.board.clone();
+        if after.move_piece(user_move.clone()).is_err() {
+            self.deviations += 1;
+            return DrillFeedback::Deviation { expected };
+        }
+        // Only positions the user's own moves lead to count, the drill plays every move of the other side
+        let reached = (self.ply..self.line.len())
+            .step_by(2)
+            .find(|&ply| self.positions[ply] == after);
+        let Some(ply) = reached else {
+            self.deviations += 1;
+            return DrillFeedback::Deviation { expected };
+        };
+        self.board = after;
+        self.ply = ply + 1;
+        DrillFeedback::Correct { reply: self.play_reply() }
+    }
+
+    /// The scripted move the user should play next, None once the line is finished
+    pub fn expected(&self) -> Option<&Move> {
+        self.line.get(self.ply)
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// The side the user plays
+    pub fn user(&self) -> Color {
+        self.user
+    }
+
+    /// How many moves the user got wrong so far
+    pub fn deviations(&self) -> usize {
+        self.deviations
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.ply == self.line.len()
+    }
+
+    /// Plays the scripted move for the other side if it is its turn
+    fn play_reply(&mut self) -> Option<Move> {
+        if *self.board.get_player_turn() == self.user {
+            return None;
+        }
+        let reply = self.line.get(self.ply)?.clone();
+        self.board.move_piece(reply.clone()).expect("the line was checked when the drill was made");
+        self.ply += 1;
+        Some(reply)
+    }
+}
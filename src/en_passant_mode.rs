@@ -0,0 +1,17 @@
+
+
+/// How strictly an en passant square is reported.
+/// # Description
+/// The board remembers the square behind every double pawn push, as older FEN writers do.
+/// For comparing positions the square only matters when it changes the available moves,
+/// which is what the stricter modes check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EnPassantMode {
+    /// The square after any double pawn push
+    Permissive,
+    /// The square only when a pawn of the side to move stands beside the pushed pawn
+    #[default]
+    Capturable,
+    /// The square only when an en passant capture is legal, so pinned pawns do not count
+    Legal,
+}
@@ -0,0 +1,16 @@
+use thiserror::Error;
+
+use super::{fen_error::FenError, move_error::MoveError};
+
+
+
+/// Any error the engine can report to a caller that does not care which part failed
+#[derive(Debug, Error)]
+pub enum ChessError {
+    #[error(transparent)]
+    Fen(#[from] FenError),
+    #[error(transparent)]
+    Move(#[from] MoveError),
+    #[error("The analysis worker stopped without a result")]
+    WorkerLost,
+}
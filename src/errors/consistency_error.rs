@@ -0,0 +1,20 @@
+use thiserror::Error;
+
+use crate::{color::Color, utils::notation::coords_to_square};
+
+
+
+/// A way the board's cached state can disagree with its pieces, found by Board::validate
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum ConsistencyError {
+    #[error("{color} has {count} kings")]
+    KingCount { color: Color, count: u32 },
+    /// The cached king position used by check detection does not hold that side's king
+    #[error("{color} king is cached on {} but is not there", coords_to_square(*.cached))]
+    KingCacheMismatch { color: Color, cached: (usize, usize) },
+    #[error("En passant square {} is not on the third or sixth rank behind the side that just moved", coords_to_square(*.0))]
+    BadEnPassantSquare((usize, usize)),
+    /// The bitboards and the squares array disagree about a square
+    #[error("Bitboards do not match the piece on {}", coords_to_square(*.0))]
+    BitboardMismatch((usize, usize)),
+}
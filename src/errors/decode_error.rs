@@ -0,0 +1,27 @@
+use thiserror::Error;
+
+use super::fen_error::FenError;
+
+
+
+/// Why a binary game record could not be read back, see GameRecord::decode
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum DecodeError {
+    #[error("Record ends early, at byte {0}")]
+    Truncated(usize),
+    #[error("Unknown starting position flag {0}")]
+    BadStartFlag(u8),
+    #[error("Invalid piece code {0}")]
+    BadPieceCode(u8),
+    #[error("Invalid starting position: {0}")]
+    BadPosition(#[from] FenError),
+    /// Plies are counted from 1 for the first move of the record
+    #[error("Move {ply} of the record is not legal")]
+    IllegalMove { ply: usize },
+    #[error("Unknown result code {0}")]
+    BadResultCode(u8),
+    #[error("Recorded result {recorded} does not match the final position, which is {actual}")]
+    ResultMismatch { recorded: &'static str, actual: &'static str },
+    #[error("{0} bytes left over after the record")]
+    TrailingBytes(usize),
+}
@@ -0,0 +1,16 @@
+use thiserror::Error;
+
+use super::fen_error::FenError;
+
+
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum DrillError {
+    #[error("The drill has no starting position")]
+    MissingFen,
+    #[error(transparent)]
+    Fen(#[from] FenError),
+    /// Plies are counted from 1 for the first move of the line
+    #[error("Move {ply} '{text}' of the line is not legal")]
+    BadMove { ply: usize, text: String },
+}
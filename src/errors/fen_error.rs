@@ -0,0 +1,131 @@
+use thiserror::Error;
+
+use crate::{color::Color, coords, utils::notation::square_to_coords};
+
+
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum FenError {
+    #[error("FEN needs at least 4 fields, found {0}")]
+    MissingFields(usize),
+    #[error("FEN has more than 8 ranks")]
+    TooManyRanks,
+    #[error("FEN has fewer than 8 ranks")]
+    TooFewRanks,
+    #[error("Rank {0} does not describe exactly 8 squares")]
+    BadRankLength(usize),
+    #[error("Invalid piece character '{0}'")]
+    InvalidPieceChar(char),
+    #[error("Invalid side to move '{0}'")]
+    BadSideToMove(String),
+    #[error("Invalid castling field '{0}'")]
+    BadCastlingField(String),
+    #[error("Invalid en passant square '{0}'")]
+    BadEnPassantSquare(String),
+    #[error("Invalid halfmove or fullmove field '{0}'")]
+    BadClockField(String),
+    #[error("{0} has no king")]
+    MissingKing(Color),
+    #[error("{0} has more than one king")]
+    TooManyKings(Color),
+    #[error("Pawn on {0}, pawns can not stand on the first or last rank")]
+    PawnOnBackRank(String),
+    #[error("Castling right '{0}' needs the king and rook on their starting squares")]
+    InconsistentCastlingRights(char),
+    #[error("Invalid square '{0}'")]
+    InvalidSquare(String),
+}
+
+/// Names of the FEN fields, in order
+const FIELD_NAMES: [&str; 6] = ["piece placement", "side to move", "castling", "en passant", "halfmove clock", "fullmove number"];
+
+impl FenError {
+    /// Describe the error for a person reading the FEN they typed.
+    /// # Description
+    /// Names the field at fault and the character offset of the problem, counted from 1,
+    /// followed by a copy of the input with the offending characters underlined with carets.
+    /// # Inputs/Outputs
+    /// - Input: fen: &str - The FEN that failed to parse
+    /// - Output: A multi line message
+    /// # Example
+    /// ``` Rust
+    /// let fen = "8/8/8/8/8/8/8 w - - 0 1";
+    /// let error = Board::from_fen(fen).unwrap_err();
+    /// // FEN has fewer than 8 ranks (piece placement field, character 14)
+    /// // 8/8/8/8/8/8/8 w - - 0 1
+    /// //              ^
+    /// println!("{}", error.annotate(fen));
+    /// ```
+    pub fn annotate(&self, fen: &str) -> String {
+        let chars = fen.chars().collect::<Vec<_>>();
+        let fields = field_spans(&chars);
+        let field = |index: usize| fields.get(index).copied().unwrap_or((chars.len(), chars.len()));
+        let placement = field(0);
+        let (field_index, (start, end)) = match self {
+            FenError::MissingFields(found) => (*found, (chars.len(), chars.len())),
+            FenError::TooManyRanks => {
+                // Everything after the eighth rank is extra
+                let extra = (placement.0..placement.1).filter(|&i| chars[i] == '/').nth(7).map_or(placement.0, |i| i + 1);
+                (0, (extra, placement.1))
+            }
+            FenError::TooFewRanks => (0, (placement.1, placement.1)),
+            FenError::BadRankLength(rank) => (0, rank_span(&chars, placement, coords::fen_rank_to_y(rank.saturating_sub(1).min(7)))),
+            FenError::InvalidPieceChar(c) => {
+                let at = (placement.0..placement.1).find(|&i| chars[i] == *c).unwrap_or(placement.0);
+                (0, (at, at + 1))
+            }
+            FenError::BadSideToMove(_) => (1, field(1)),
+            FenError::BadCastlingField(_) => (2, field(2)),
+            FenError::BadEnPassantSquare(_) => (3, field(3)),
+            FenError::BadClockField(value) => {
+                let index = (4..fields.len()).find(|&i| chars[fields[i].0..fields[i].1].iter().collect::<String>() == *value).unwrap_or(4);
+                (index, field(index))
+            }
+            FenError::MissingKing(_) | FenError::TooManyKings(_) | FenError::InvalidSquare(_) => (0, placement),
+            FenError::PawnOnBackRank(square) => match square_to_coords(square) {
+                Some((_, y)) => (0, rank_span(&chars, placement, coords::fen_rank_to_y(y))),
+                None => (0, placement),
+            },
+            FenError::InconsistentCastlingRights(_) => (2, field(2)),
+        };
+        let field_name = FIELD_NAMES.get(field_index).copied().unwrap_or("extra");
+        let underline = " ".repeat(start) + &"^".repeat((end - start).max(1));
+        format!("{} ({} field, character {})\n{}\n{}", self, field_name, start + 1, fen, underline)
+    }
+}
+
+/// Start and end character offsets of each whitespace separated field
+fn field_spans(chars: &[char]) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let mut start = None;
+    for (i, c) in chars.iter().enumerate() {
+        match (c.is_whitespace(), start) {
+            (true, Some(s)) => {
+                spans.push((s, i));
+                start = None;
+            }
+            (false, None) => start = Some(i),
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        spans.push((s, chars.len()));
+    }
+    spans
+}
+
+/// Start and end character offsets of a rank within the piece placement field, counted from the first rank listed
+fn rank_span(chars: &[char], placement: (usize, usize), rank_index: usize) -> (usize, usize) {
+    let mut start = placement.0;
+    let mut rank = 0;
+    for (i, c) in chars.iter().enumerate().take(placement.1).skip(placement.0) {
+        if *c == '/' {
+            if rank == rank_index {
+                return (start, i);
+            }
+            rank += 1;
+            start = i + 1;
+        }
+    }
+    (start, placement.1)
+}
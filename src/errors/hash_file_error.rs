@@ -0,0 +1,15 @@
+use thiserror::Error;
+
+
+
+#[derive(Debug, Error)]
+pub enum HashFileError {
+    #[error("Could not read or write the hash file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Not a hash file")]
+    BadMagic,
+    #[error("Unsupported hash file version {0}")]
+    UnsupportedVersion(u32),
+    #[error("Hash file entries are {0} bytes, expected {1}")]
+    EntrySizeMismatch(u32, u32),
+}
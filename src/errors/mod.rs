@@ -1,2 +1,10 @@
 
 pub mod move_error;
+pub mod fen_error;
+pub mod hash_file_error;
+pub mod chess_error;
+pub mod consistency_error;
+pub mod uci_option_error;
+pub mod decode_error;
+pub mod drill_error;
+pub mod perft_error;
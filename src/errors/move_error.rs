@@ -1,8 +1,8 @@
 use thiserror::Error;
 
+use crate::{chess_move::Move, errors::consistency_error::ConsistencyError, piece_type::PieceType, utils::notation::coords_to_square};
 
-
-#[derive(Debug, Error)]
+#[derive(Debug, Error, PartialEq, Eq)]
 pub enum MoveError {
     #[error("The source square is empty")]
     NoPieceOnSourceSquare,
@@ -10,12 +10,72 @@ pub enum MoveError {
     MustMovePiece,
     #[error("Illegal move")]
     IllegalMove,
-    #[error("King is in check")]
-    KingInCheck,
+    /// The king was already in check and the move does not get it out of check
+    #[error("King is in check from the {} on {}", .checker_type.name(), coords_to_square(*.checker))]
+    KingInCheck { checker: (usize, usize), checker_type: PieceType },
+    /// The king was not in check but would be after the move, because the moved piece was pinned
+    /// or the king stepped onto an attacked square
+    #[error("Move exposes the king to the {} on {}", .attacker_type.name(), coords_to_square(*.attacker))]
+    MovePinnedOrExposesKing { attacker: (usize, usize), attacker_type: PieceType },
     #[error("Cannot capture own piece")]
     CannotCaptureOwnPiece,
     #[error("Cannot move opponent's piece")]
     PieceWrongColor,
     #[error("Move is blocked")] 
     MoveBlocked,    
+    /// Text that is not a move in UCI notation, such as "e9x9"
+    #[error("Malformed UCI move '{0}'")]
+    MalformedUci(String),
+    /// Only reported in debug builds, where move_piece checks the board with Board::validate after each move,
+    /// e.g. capturing the king in a position loaded with the side not to move in check
+    #[error("Move leaves the board inconsistent: {0}")]
+    Inconsistent(ConsistencyError),
+}
+
+/// A move in a list given to Board::apply_uci_moves that could not be played, and its place in the list
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("Move {} '{text}' could not be played: {source}", .index + 1)]
+pub struct MoveListError {
+    /// Counted from 0, the moves before it were played
+    pub index: usize,
+    pub text: String,
+    pub source: MoveError,
+}
+
+impl MoveError {
+    /// Describe why a move was rejected in terms of the pieces involved, for showing to the player
+    /// # Description
+    /// Errors that leave the king in check name the piece responsible, other errors use their Display text.
+    /// # Inputs/Outputs
+    /// - Input: mv: &Move - The move that was rejected with this error
+    /// - Output: String - The explanation
+    /// # Example
+    /// ``` Rust
+    /// let error = MoveError::MovePinnedOrExposesKing { attacker: (1, 2), attacker_type: PieceType::Bishop };
+    /// let mv = Move::new(3, 4, 5, 3, PieceType::Knight, None);
+    /// assert_eq!(error.explain(&mv), "your knight on d5 is pinned by the bishop on b3");
+    /// ```
+    pub fn explain(&self, mv: &Move) -> String {
+        match self {
+            MoveError::KingInCheck { checker, checker_type } => format!(
+                "your king is in check from the {} on {}",
+                checker_type.name(),
+                coords_to_square(*checker),
+            ),
+            MoveError::MovePinnedOrExposesKing { attacker, attacker_type } if mv.piece_type == PieceType::King => format!(
+                "your king can not move to {}, it is attacked by the {} on {}",
+                coords_to_square((mv.to_x, mv.to_y)),
+                attacker_type.name(),
+                coords_to_square(*attacker),
+            ),
+            MoveError::MovePinnedOrExposesKing { attacker, attacker_type } => format!(
+                "your {} on {} is pinned by the {} on {}",
+                mv.piece_type.name(),
+                coords_to_square((mv.from_x, mv.from_y)),
+                attacker_type.name(),
+                coords_to_square(*attacker),
+            ),
+            _ => self.to_string(),
+        }
+    }
 }
@@ -0,0 +1,16 @@
+use thiserror::Error;
+
+use super::move_error::MoveError;
+
+
+
+/// A generated move that the board refused to play during a perft, which means move generation has a bug
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("Generated move {mv} could not be played in {fen}: {source}")]
+pub struct PerftError {
+    /// The move in UCI notation
+    pub mv: String,
+    /// The position it was generated in
+    pub fen: String,
+    pub source: MoveError,
+}
@@ -0,0 +1,13 @@
+use thiserror::Error;
+
+
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum UciOptionError {
+    #[error("Unknown option {0}")]
+    UnknownOption(String),
+    #[error("Option {name} needs a number, got '{value}'")]
+    NotANumber { name: String, value: String },
+    #[error("Option {name} needs true or false, got '{value}'")]
+    NotABoolean { name: String, value: String },
+}
@@ -0,0 +1,432 @@
+use crate::{bitboard, board::Board, color::Color, pawn_structure::PawnStructure, piece_type::PieceType};
+
+/// Evaluations are clamped to this so positions with extra material never reach the mate scores
+pub const MAX_EVAL: i32 = 30_000;
+/// game_phase of a position with all the starting pieces or more, scored purely by the midgame tables
+pub const MAX_PHASE: u32 = 24;
+
+const PIECE_TYPES: [PieceType; 5] = [PieceType::Pawn, PieceType::Rook, PieceType::Knight, PieceType::Bishop, PieceType::Queen];
+
+/// Penalty for each file beside or in front of the king without a pawn of its own ahead of the king
+const MISSING_SHIELD_PENALTY: i32 = 25;
+/// Penalty for each shield pawn that has moved two or more ranks ahead of the king
+const ADVANCED_SHIELD_PENALTY: i32 = 15;
+/// Extra penalty for each of those files that has no pawns at all, open for the opponent's rooks and queen
+const OPEN_FILE_PENALTY: i32 = 20;
+
+/// Midgame and endgame penalties for each pawn beyond the first on a file
+const DOUBLED_PAWN_PENALTY: (i32, i32) = (10, 20);
+/// Midgame and endgame penalties for each isolated pawn
+const ISOLATED_PAWN_PENALTY: (i32, i32) = (15, 20);
+/// Midgame and endgame penalties for each backward pawn
+const BACKWARD_PAWN_PENALTY: (i32, i32) = (8, 10);
+/// Midgame and endgame bonuses for a passed pawn, indexed by its rank counted from its own side, 0 for rank 1.
+/// On the seventh rank it is worth close to a minor piece in the endgame.
+const PASSED_PAWN_BONUS: [(i32, i32); 8] = [(0, 0), (5, 10), (10, 20), (15, 35), (25, 60), (45, 110), (70, 190), (0, 0)];
+/// Extra midgame and endgame bonus for a passed pawn with another passed pawn beside it, on a neighbouring file
+/// within a rank, as the two can shepherd each other forward
+pub(crate) const CONNECTED_PASSER_BONUS: (i32, i32) = (10, 30);
+
+/// Bonus for holding both bishops
+const BISHOP_PAIR_BONUS: i32 = 40;
+/// Change in a knight's value for each pawn of its own side above five, knights do well in closed positions
+const KNIGHT_PAWN_ADJUSTMENT: i32 = 4;
+/// Change in a rook's value for each pawn of its own side below five, rooks gain as files open up
+const ROOK_PAWN_ADJUSTMENT: i32 = 6;
+
+/// Midgame and endgame bonuses for each knight on an outpost, see Board::knight_outposts
+pub(crate) const KNIGHT_OUTPOST_BONUS: (i32, i32) = (25, 10);
+
+/// The material lead a side needs over an opponent without pawns for mop_up to drive the kings together
+const MOP_UP_MIN_ADVANTAGE: i32 = 400;
+/// Bonus for each step the losing king stands closer to a corner than the centre squares, see CORNER_DISTANCE
+const MOP_UP_CORNER_BONUS: i32 = 10;
+/// Bonus for each step the winning king is closer to the losing king than the 14 steps between opposite corners
+const MOP_UP_PROXIMITY_BONUS: i32 = 10;
+
+/// How far each square is from the nearest corner, as the steps along ranks and files plus the king moves to it.
+/// 0 in the corners and 9 on the centre squares, and lower along the edges beside a corner than in the middle of them,
+/// so mop_up drives a lone king into a corner, where the mates with queen or rook are found fastest.
+#[rustfmt::skip]
+const CORNER_DISTANCE: [i32; 64] = [
+    0, 2, 4, 6, 6, 4, 2, 0,
+    2, 3, 5, 7, 7, 5, 3, 2,
+    4, 5, 6, 8, 8, 6, 5, 4,
+    6, 7, 8, 9, 9, 8, 7, 6,
+    6, 7, 8, 9, 9, 8, 7, 6,
+    4, 5, 6, 8, 8, 6, 5, 4,
+    2, 3, 5, 7, 7, 5, 3, 2,
+    0, 2, 4, 6, 6, 4, 2, 0,
+];
+
+/// How much each piece type counts towards game_phase, indexed by PieceType::index
+const PHASE_WEIGHTS: [u32; 6] = [0, 2, 1, 1, 4, 0];
+
+/// Piece square tables in centipawns, written from White's side with rank 8 on the first line,
+/// indexed by PieceType::index. Black reads them mirrored between rank 1 and rank 8.
+#[rustfmt::skip]
+const MIDGAME_TABLES: [[i32; 64]; 6] = [
+    // Pawn
+    [
+          0,   0,   0,   0,   0,   0,   0,   0,
+         50,  50,  50,  50,  50,  50,  50,  50,
+         10,  10,  20,  30,  30,  20,  10,  10,
+          5,   5,  10,  25,  25,  10,   5,   5,
+          0,   0,   0,  20,  20,   0,   0,   0,
+          5,  -5, -10,   0,   0, -10,  -5,   5,
+          5,  10,  10, -20, -20,  10,  10,   5,
+          0,   0,   0,   0,   0,   0,   0,   0,
+    ],
+    // Rook
+    [
+          0,   0,   0,   0,   0,   0,   0,   0,
+          5,  10,  10,  10,  10,  10,  10,   5,
+         -5,   0,   0,   0,   0,   0,   0,  -5,
+         -5,   0,   0,   0,   0,   0,   0,  -5,
+         -5,   0,   0,   0,   0,   0,   0,  -5,
+         -5,   0,   0,   0,   0,   0,   0,  -5,
+         -5,   0,   0,   0,   0,   0,   0,  -5,
+          0,   0,   0,   5,   5,   0,   0,   0,
+    ],
+    // Knight
+    [
+        -50, -40, -30, -30, -30, -30, -40, -50,
+        -40, -20,   0,   0,   0,   0, -20, -40,
+        -30,   0,  10,  15,  15,  10,   0, -30,
+        -30,   5,  15,  20,  20,  15,   5, -30,
+        -30,   0,  15,  20,  20,  15,   0, -30,
+        -30,   5,  10,  15,  15,  10,   5, -30,
+        -40, -20,   0,   5,   5,   0, -20, -40,
+        -50, -40, -30, -30, -30, -30, -40, -50,
+    ],
+    // Bishop
+    [
+        -20, -10, -10, -10, -10, -10, -10, -20,
+        -10,   0,   0,   0,   0,   0,   0, -10,
+        -10,   0,   5,  10,  10,   5,   0, -10,
+        -10,   5,   5,  10,  10,   5,   5, -10,
+        -10,   0,  10,  10,  10,  10,   0, -10,
+        -10,  10,  10,  10,  10,  10,  10, -10,
+        -10,   5,   0,   0,   0,   0,   5, -10,
+        -20, -10, -10, -10, -10, -10, -10, -20,
+    ],
+    // Queen
+    [
+        -20, -10, -10,  -5,  -5, -10, -10, -20,
+        -10,   0,   0,   0,   0,   0,   0, -10,
+        -10,   0,   5,   5,   5,   5,   0, -10,
+         -5,   0,   5,   5,   5,   5,   0,  -5,
+         -5,   0,   5,   5,   5,   5,   0,  -5,
+        -10,   0,   5,   5,   5,   5,   0, -10,
+        -10,   0,   0,   0,   0,   0,   0, -10,
+        -20, -10, -10,  -5,  -5, -10, -10, -20,
+    ],
+    // King, sheltered behind its pawns
+    [
+        -30, -40, -40, -50, -50, -40, -40, -30,
+        -30, -40, -40, -50, -50, -40, -40, -30,
+        -30, -40, -40, -50, -50, -40, -40, -30,
+        -30, -40, -40, -50, -50, -40, -40, -30,
+        -20, -30, -30, -40, -40, -30, -30, -20,
+        -10, -20, -20, -20, -20, -20, -20, -10,
+         20,  20,   0,   0,   0,   0,  20,  20,
+         20,  30,  10,   0,   0,  10,  30,  20,
+    ],
+];
+
+/// The endgame counterparts of MIDGAME_TABLES, where passed pawns are pushed and the king comes to the centre
+#[rustfmt::skip]
+const ENDGAME_TABLES: [[i32; 64]; 6] = [
+    // Pawn
+    [
+          0,   0,   0,   0,   0,   0,   0,   0,
+         80,  80,  80,  80,  80,  80,  80,  80,
+         50,  50,  50,  50,  50,  50,  50,  50,
+         30,  30,  30,  30,  30,  30,  30,  30,
+         15,  15,  15,  15,  15,  15,  15,  15,
+          5,   5,   5,   5,   5,   5,   5,   5,
+          0,   0,   0,   0,   0,   0,   0,   0,
+          0,   0,   0,   0,   0,   0,   0,   0,
+    ],
+    MIDGAME_TABLES[1],
+    MIDGAME_TABLES[2],
+    MIDGAME_TABLES[3],
+    MIDGAME_TABLES[4],
+    // King
+    [
+        -50, -40, -30, -20, -20, -30, -40, -50,
+        -30, -20, -10,   0,   0, -10, -20, -30,
+        -30, -10,  20,  30,  30,  20, -10, -30,
+        -30, -10,  30,  40,  40,  30, -10, -30,
+        -30, -10,  30,  40,  40,  30, -10, -30,
+        -30, -10,  20,  30,  30,  20, -10, -30,
+        -30, -30,   0,   0,   0,   0, -30, -30,
+        -50, -30, -30, -30, -30, -30, -30, -50,
+    ],
+];
+
+impl Board {
+    /// Evaluates the position by counting material.
+    /// # Description
+    /// Reads the material balance the board keeps up to date as pieces are placed, moved, captured and promoted,
+    /// so it costs nothing however often the search calls it.
+    /// The score is from White's perspective, positive means White is ahead.
+    /// It is clamped to MAX_EVAL either way, which only matters for positions with far more than the standard material.
+    /// # Inputs/Outputs
+    /// - Input: None
+    /// - Output: The material balance in centipawns
+    /// # Example
+    /// ``` Rust
+    /// let board = Board::starting_position();
+    /// assert_eq!(board.basic_evaluate(), 0);
+    /// ```
+    pub fn basic_evaluate(&self) -> i32 {
+        self.material().clamp(-MAX_EVAL, MAX_EVAL)
+    }
+
+    /// basic_evaluate worked out by counting every piece on the board, to check the running balance against
+    pub fn evaluate_slow(&self) -> i32 {
+        PIECE_TYPES.iter().map(|piece_type| {
+            let white = self.pieces(*piece_type, Color::White).count_ones() as i32;
+            let black = self.pieces(*piece_type, Color::Black).count_ones() as i32;
+            (white - black) * piece_type.to_centipawns()
+        }).sum::<i32>().clamp(-MAX_EVAL, MAX_EVAL)
+    }
+
+    /// Evaluates the position by material and piece placement, blended between midgame and endgame.
+    /// # Description
+    /// Adds the piece square table score, piece combinations, knight outposts, king safety, pawn structure,
+    /// passed pawns and mop_up to the material balance.
+    /// Every piece is scored by both the midgame and the endgame tables and the two totals are mixed by game_phase,
+    /// all midgame with the starting pieces and all endgame once only kings and pawns are left,
+    /// so the king shelters early and centralizes late.
+    /// The score is from White's perspective and clamped to MAX_EVAL like basic_evaluate.
+    /// # Inputs/Outputs
+    /// - Input: None
+    /// - Output: The evaluation in centipawns, positive when White is better
+    /// # Example
+    /// ``` Rust
+    /// assert_eq!(Board::starting_position().evaluate(), 0);
+    /// // Without pieces the endgame table draws the kings to the centre
+    /// let cornered = Board::from_fen("8/8/8/8/8/8/4P3/k6K w - - 0 1").unwrap();
+    /// let central = Board::from_fen("8/8/8/8/3K4/8/4P3/k7 w - - 0 1").unwrap();
+    /// assert!(central.evaluate() > cornered.evaluate());
+    /// ```
+    pub fn evaluate(&self) -> i32 {
+        let (mut midgame, mut endgame) = (0, 0);
+        for color in [Color::White, Color::Black] {
+            let sign = if color.is_white() { 1 } else { -1 };
+            for piece_type in PIECE_TYPES.iter().chain([PieceType::King].iter()) {
+                for index in bitboard::squares(self.pieces(*piece_type, color)) {
+                    // Table index of the square as seen by its owner, rank 8 first
+                    let (x, y) = bitboard::index_to_coords(index);
+                    let rank = if color.is_white() { 7 - y } else { y };
+                    midgame += sign * MIDGAME_TABLES[piece_type.index()][rank * 8 + x];
+                    endgame += sign * ENDGAME_TABLES[piece_type.index()][rank * 8 + x];
+                }
+            }
+            let combination = self.material_adjustment(color);
+            let outposts = self.knight_outposts(color).len() as i32;
+            midgame += sign * (self.king_safety(color) + combination + outposts * KNIGHT_OUTPOST_BONUS.0);
+            endgame += sign * (combination + outposts * KNIGHT_OUTPOST_BONUS.1);
+            let (pawns_midgame, pawns_endgame) = pawn_structure_score(&self.pawn_structure(color));
+            let (passed_midgame, passed_endgame) = self.passed_pawn_score(color);
+            midgame += sign * (pawns_midgame + passed_midgame);
+            endgame += sign * (pawns_endgame + passed_endgame);
+        }
+        endgame += self.mop_up();
+        let phase = self.game_phase() as i32;
+        let placement = (midgame * phase + endgame * (MAX_PHASE as i32 - phase)) / MAX_PHASE as i32;
+        (self.material() + placement).clamp(-MAX_EVAL, MAX_EVAL)
+    }
+
+    /// How well the pawns in front of a king shelter it, as a penalty in centipawns for that king's side.
+    /// # Description
+    /// Looks at the king's file and the files either side of it. Each of them costs MISSING_SHIELD_PENALTY
+    /// without a pawn of the king's color ahead of the king, or ADVANCED_SHIELD_PENALTY when the nearest such pawn
+    /// is two or more ranks ahead, and a further OPEN_FILE_PENALTY when no pawn of either color stands on the file.
+    /// The king is only in danger while the opponent has a queen, so the penalty is 0 without one.
+    /// evaluate counts it with the midgame tables, so it fades out as pieces come off.
+    /// # Inputs/Outputs
+    /// - Input: color - The king to look at
+    /// - Output: The penalty, 0 or negative
+    /// # Example
+    /// ``` Rust
+    /// let board = Board::from_fen("r1bq1rk1/pppp1ppp/2n2n2/4p3/4P3/5NP1/PPPP1P1P/RNBQ1RK1 w - - 0 1").unwrap();
+    /// assert!(board.king_safety(Color::White) < board.king_safety(Color::Black));
+    /// ```
+    pub fn king_safety(&self, color: Color) -> i32 {
+        if self.pieces(PieceType::Queen, color.opposite()) == 0 {
+            return 0;
+        }
+        let (king_x, king_y) = self.king_position(color);
+        let own_pawns = self.pieces(PieceType::Pawn, color);
+        let all_pawns = own_pawns | self.pieces(PieceType::Pawn, color.opposite());
+        let mut penalty = 0;
+        for x in king_x.saturating_sub(1)..=(king_x + 1).min(7) {
+            let file = bitboard::FILE_A << x;
+            // Ranks ahead of the king to the nearest pawn of its own on this file
+            let distance = bitboard::squares(own_pawns & file)
+                .map(|index| bitboard::index_to_coords(index).1)
+                .filter_map(|y| if color.is_white() { y.checked_sub(king_y) } else { king_y.checked_sub(y) })
+                .filter(|distance| *distance > 0)
+                .min();
+            penalty += match distance {
+                None => MISSING_SHIELD_PENALTY,
+                Some(1) => 0,
+                Some(_) => ADVANCED_SHIELD_PENALTY,
+            };
+            if all_pawns & file == 0 {
+                penalty += OPEN_FILE_PENALTY;
+            }
+        }
+        -penalty
+    }
+
+    /// The midgame and endgame bonuses for a side's passed pawns, see PASSED_PAWN_BONUS.
+    /// A passer with a piece standing on the square in front of it is blockaded and gets half the bonus.
+    pub(crate) fn passed_pawn_score(&self, color: Color) -> (i32, i32) {
+        let passers = self.passed_pawns(color);
+        let (mut midgame, mut endgame) = (0, 0);
+        for &(x, y) in &passers {
+            let rank = if color.is_white() { y } else { 7 - y };
+            let (mut passed_midgame, mut passed_endgame) = PASSED_PAWN_BONUS[rank];
+            let stop_y = if color.is_white() { Some(y + 1).filter(|y| *y < 8) } else { y.checked_sub(1) };
+            if stop_y.is_some_and(|stop_y| self.get_piece(x, stop_y).is_some()) {
+                passed_midgame /= 2;
+                passed_endgame /= 2;
+            }
+            if passers.iter().any(|&(other_x, other_y)| other_x.abs_diff(x) == 1 && other_y.abs_diff(y) <= 1) {
+                passed_midgame += CONNECTED_PASSER_BONUS.0;
+                passed_endgame += CONNECTED_PASSER_BONUS.1;
+            }
+            midgame += passed_midgame;
+            endgame += passed_endgame;
+        }
+        (midgame, endgame)
+    }
+
+    /// Guides a side that is winning on material towards mate once the opponent has no pawns left.
+    /// # Description
+    /// When one side leads by at least MOP_UP_MIN_ADVANTAGE and the other has no pawns, every position
+    /// on the way to mate has the same material, so the winner is rewarded for driving the losing king towards
+    /// a corner, MOP_UP_CORNER_BONUS a step of CORNER_DISTANCE, and for bringing its own king closer,
+    /// MOP_UP_PROXIMITY_BONUS a step along ranks and files.
+    /// evaluate counts it with the endgame tables, so it has little weight while many pieces are left.
+    /// # Inputs/Outputs
+    /// - Output: The bonus from White's perspective, 0 when neither side qualifies
+    /// # Example
+    /// ``` Rust
+    /// let centre = Board::from_fen("8/8/8/8/3k4/8/8/4QK2 w - - 0 1").unwrap();
+    /// let edge = Board::from_fen("3k4/8/3K4/8/8/8/8/4Q3 w - - 0 1").unwrap();
+    /// assert!(edge.mop_up() > centre.mop_up());
+    /// ```
+    pub fn mop_up(&self) -> i32 {
+        let lead = self.material();
+        let (winner, sign) = match lead {
+            lead if lead >= MOP_UP_MIN_ADVANTAGE => (Color::White, 1),
+            lead if lead <= -MOP_UP_MIN_ADVANTAGE => (Color::Black, -1),
+            _ => return 0,
+        };
+        if self.pieces(PieceType::Pawn, winner.opposite()) != 0 {
+            return 0;
+        }
+        let (winner_x, winner_y) = self.king_position(winner);
+        let (loser_x, loser_y) = self.king_position(winner.opposite());
+        // The table is the same from either side, so it needs no mirroring for Black
+        let corner = 9 - CORNER_DISTANCE[bitboard::square_index(loser_x, loser_y)];
+        let kings_apart = (winner_x.abs_diff(loser_x) + winner_y.abs_diff(loser_y)) as i32;
+        sign * (MOP_UP_CORNER_BONUS * corner + MOP_UP_PROXIMITY_BONUS * (14 - kings_apart))
+    }
+
+    /// How much of the game is left before the endgame, from the pieces other than pawns and kings.
+    /// # Description
+    /// Knights and bishops count 1, rooks 2 and queens 4, so the starting position counts MAX_PHASE.
+    /// More than that, from promotions, is capped at MAX_PHASE. 0 means only kings and pawns are left.
+    /// # Example
+    /// ``` Rust
+    /// assert_eq!(Board::starting_position().game_phase(), MAX_PHASE);
+    /// assert_eq!(Board::from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1").unwrap().game_phase(), 0);
+    /// ```
+    pub fn game_phase(&self) -> u32 {
+        let [white, black] = self.piece_counts();
+        let phase = PHASE_WEIGHTS.iter().enumerate().map(|(index, weight)| (white[index] + black[index]) * weight).sum::<u32>();
+        phase.min(MAX_PHASE)
+    }
+
+    /// How many pieces of each type each side has.
+    /// # Inputs/Outputs
+    /// - Output: The counts indexed by Color::index then PieceType::index
+    /// # Example
+    /// ``` Rust
+    /// let counts = Board::starting_position().piece_counts();
+    /// assert_eq!(counts[Color::White.index()][PieceType::Pawn.index()], 8);
+    /// assert_eq!(counts[Color::Black.index()][PieceType::Queen.index()], 1);
+    /// ```
+    pub fn piece_counts(&self) -> [[u32; 6]; 2] {
+        [Color::White, Color::Black].map(|color| {
+            let mut counts = [0; 6];
+            for piece_type in PIECE_TYPES.iter().chain([PieceType::King].iter()) {
+                counts[piece_type.index()] = self.pieces(*piece_type, color).count_ones();
+            }
+            counts
+        })
+    }
+
+    /// Adjustments to a side's material for how its pieces combine, in centipawns.
+    /// # Description
+    /// BISHOP_PAIR_BONUS when the side has two or more bishops. Each knight gains KNIGHT_PAWN_ADJUSTMENT for every
+    /// pawn of its side above five and loses it for every pawn below, and each rook gains ROOK_PAWN_ADJUSTMENT for
+    /// every pawn of its side below five and loses it for every pawn above.
+    /// # Inputs/Outputs
+    /// - Input: color - The side to score
+    /// - Output: The adjustment from that side's point of view
+    /// # Example
+    /// ``` Rust
+    /// let board = Board::from_fen("4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1").unwrap();
+    /// assert_eq!(board.material_adjustment(Color::White), 40);
+    /// ```
+    pub fn material_adjustment(&self, color: Color) -> i32 {
+        let counts = self.piece_counts()[color.index()];
+        let count = |piece_type: PieceType| counts[piece_type.index()] as i32;
+        let pawns_above_five = count(PieceType::Pawn) - 5;
+        let pair = if count(PieceType::Bishop) >= 2 { BISHOP_PAIR_BONUS } else { 0 };
+        pair + count(PieceType::Knight) * KNIGHT_PAWN_ADJUSTMENT * pawns_above_five - count(PieceType::Rook) * ROOK_PAWN_ADJUSTMENT * pawns_above_five
+    }
+
+    /// Evaluates the position from the given side's perspective.
+    /// # Description
+    /// The one place the sign convention lives, positive is always good for `color`.
+    /// # Inputs/Outputs
+    /// - Input: color: Color - The side to score the position for
+    /// - Output: The evaluation in centipawns
+    /// # Example
+    /// ``` Rust
+    /// let board = Board::from_fen("4k3/8/8/8/8/8/8/3QK3 w - - 0 1").unwrap();
+    /// assert_eq!(board.evaluate_for(Color::White), board.evaluate());
+    /// assert_eq!(board.evaluate_for(Color::Black), -board.evaluate());
+    /// ```
+    pub fn evaluate_for(&self, color: Color) -> i32 {
+        let score = self.evaluate();
+        match color {
+            Color::White => score,
+            Color::Black => -score,
+        }
+    }
+
+    /// Evaluates the position from the perspective of the side to move, as negamax expects
+    pub fn evaluate_to_move(&self) -> i32 {
+        self.evaluate_for(*self.get_player_turn())
+    }
+}
+
+/// The midgame and endgame penalties for the weaknesses in a side's pawns, as negative scores
+fn pawn_structure_score(structure: &PawnStructure) -> (i32, i32) {
+    let doubled = structure.doubled.iter().map(|&x| structure.pawns_per_file[x] as i32 - 1).sum::<i32>();
+    let isolated = structure.isolated.iter().map(|&x| structure.pawns_per_file[x] as i32).sum::<i32>();
+    let backward = structure.backward.len() as i32;
+    let penalty = |(midgame, endgame): (i32, i32), count: i32| (midgame * count, endgame * count);
+    [penalty(DOUBLED_PAWN_PENALTY, doubled), penalty(ISOLATED_PAWN_PENALTY, isolated), penalty(BACKWARD_PAWN_PENALTY, backward)]
+        .iter()
+        .fold((0, 0), |(midgame, endgame), (m, e)| (midgame - m, endgame - e))
+}
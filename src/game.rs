@@ -1,11 +1,32 @@
+use std::sync::{atomic::AtomicBool, Arc};
+
 use rand::Rng;
 
-use crate::{board::Board, chess_move::{self, Move}, color::Color, errors::move_error::MoveError};
+use crate::{board::Board, board_render_options::BoardRenderOptions, castling_validation::{CastlingRepair, CastlingValidation}, chess_move::{self, Move}, color::Color, errors::{fen_error::FenError, move_error::{MoveError, MoveListError}}, game_snapshot::{GameSnapshot, SnapshotMove}, move_result::MoveResult, piece::Piece, piece_type::PieceType, search::{engine_config::EngineConfig, searcher::{SearchResult, Searcher, MATE_SCORE, MAX_DEPTH}, transposition_table::{TranspositionTable, ANALYSIS_HASH_MB}}, utils::notation::move_to_san};
 
+/// A game being played from a position, with the moves made by each side.
+/// # Thread safety
+/// Game is Send so it can be moved onto a worker thread.
+/// It is intended to be owned by one worker at a time, use one Game per worker.
 pub struct Game {
     pub board: Board,
     pub move_history_white: Vec<chess_move::Move>,
     pub move_history_black: Vec<chess_move::Move>,
+    /// The FEN the game was set up from, None when it started from the standard position
+    pub initial_fen: Option<String>,
+    /// The search behind the last move played by engine_move, None if it was a random move
+    pub last_search: Option<SearchResult>,
+    engine_configs: [Option<EngineConfig>; 2],
+    // Set between begin_edit and end_edit, while moves are not recorded
+    editing: bool,
+    // The score of the last hint given with the key of the position it was for, shown by render
+    hint_score: Option<(u64, i32)>,
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Game {
@@ -14,20 +35,73 @@ impl Game {
             board: Board::starting_position(),
             move_history_white: Vec::new(),
             move_history_black: Vec::new(),
+            initial_fen: None,
+            last_search: None,
+            engine_configs: [None, None],
+            editing: false,
+            hint_score: None,
         }
     }
 
-    pub fn make_move(&mut self, mv: chess_move::Move) -> Result<(), MoveError> {
-        self.board.move_piece(mv.clone())?;
-        match self.board.get_player_turn() {
+    /// Play a move for the side to move and record it, returning what the move did.
+    /// In edit mode the move is played but not recorded.
+    pub fn make_move(&mut self, mv: chess_move::Move) -> Result<MoveResult, MoveError> {
+        let color = *self.board.get_player_turn();
+        let result = self.board.move_piece(mv.clone())?;
+        if self.editing {
+            return Ok(result);
+        }
+        match color {
             Color::White => self.move_history_white.push(mv),
             Color::Black => self.move_history_black.push(mv),
         }
-        Ok(())
+        Ok(result)
     }
 
+    /// Play and record a move given in UCI notation, e.g. "e2e4" or "e7e8q", see Board::try_move_uci
+    pub fn try_move_uci(&mut self, text: &str) -> Result<MoveResult, MoveError> {
+        let mv = self.board.parse_uci_move(text)?;
+        self.make_move(mv)
+    }
+
+    /// Play and record a whitespace separated list of UCI moves, such as the moves of a UCI position command.
+    /// # Description
+    /// Stops at the first move that can not be played, keeping the moves before it, see Board::apply_uci_moves.
+    /// # Inputs/Outputs
+    /// - Input: moves - e.g. "e2e4 e7e5 g1f3"
+    /// - Output: The number of moves played, or the first move that failed with its index and the reason
+    pub fn apply_uci_moves(&mut self, moves: &str) -> Result<usize, MoveListError> {
+        let mut played = 0;
+        for (index, text) in moves.split_whitespace().enumerate() {
+            self.try_move_uci(text).map_err(|source| MoveListError { index, text: text.to_string(), source })?;
+            played += 1;
+        }
+        Ok(played)
+    }
+
+    /// The recorded moves of both sides in the order they were played
+    pub fn moves(&self) -> Vec<Move> {
+        let mut moves = Vec::new();
+        self.replay(|_, mv| moves.push(mv.clone()));
+        moves
+    }
+
+    /// Play a move for the side to move.
+    /// # Description
+    /// Searches with the side's engine config if it has one and records the search in last_search,
+    /// otherwise plays a random legal move.
     pub fn engine_move(&mut self) -> Move {
-        let color = self.board.get_player_turn().clone();
+        let color = *self.board.get_player_turn();
+        if self.engine_config_for(color).is_some() {
+            let result = self.hint();
+            if let Some(mv) = result.best_move.clone() {
+                self.make_move(mv.clone()).expect("search returns legal moves");
+                log::trace!("Engine searched to depth {} and made move: {}", result.depth, mv.extended_algebraic());
+                self.last_search = Some(result);
+                return mv;
+            }
+        }
+        self.last_search = None;
         let moves = self.board.generate_legal_moves();
         let mut rng = rand::thread_rng(); 
         let random_move = &moves[rng.gen_range(0..moves.len())];
@@ -37,14 +111,295 @@ impl Game {
             Color::White => self.move_history_white.push(random_move.clone()),
             Color::Black => self.move_history_black.push(random_move.clone()),
         }
-        return random_move.clone();
+        random_move.clone()
     }
 
-    pub fn from_fen(fen: &str) -> Game {
-        Game {
-            board: Board::from_fen(fen).unwrap(),
+    pub fn from_fen(fen: &str) -> Result<Game, FenError> {
+        Game::from_fen_with(fen, CastlingValidation::Strict).map(|(game, _)| game)
+    }
+
+    /// Start a game from a FEN, choosing what happens to castling rights the position can not have.
+    /// # Description
+    /// See Board::from_fen_with. When rights are dropped the repaired FEN is kept as the initial position,
+    /// so PGN export and replay never see the inconsistent rights.
+    pub fn from_fen_with(fen: &str, validation: CastlingValidation) -> Result<(Game, Vec<CastlingRepair>), FenError> {
+        let (board, repairs) = Board::from_fen_with(fen, validation)?;
+        let initial_fen = if repairs.is_empty() { fen.split_whitespace().collect::<Vec<_>>().join(" ") } else { board.to_fen() };
+        let game = Game {
+            board,
             move_history_white: Vec::new(),
             move_history_black: Vec::new(),
+            initial_fen: Some(initial_fen),
+            last_search: None,
+            engine_configs: [None, None],
+            editing: false,
+            hint_score: None,
+        };
+        Ok((game, repairs))
+    }
+
+    /// Start the game again from a FEN.
+    /// # Description
+    /// Clears the move history and remembers the FEN as the initial position for PGN export.
+    /// Engine configs are kept. If the FEN is invalid the game is left as it was.
+    pub fn load_fen(&mut self, fen: &str) -> Result<(), FenError> {
+        let engine_configs = self.engine_configs.clone();
+        *self = Game::from_fen(fen)?;
+        self.engine_configs = engine_configs;
+        Ok(())
+    }
+
+    /// Start editing the position, as on an analysis board.
+    /// # Description
+    /// Until end_edit pieces can be dragged with force_move, the turn passed and moves played without being recorded.
+    /// The board need not be a legal position in between.
+    /// # Example
+    /// ``` Rust
+    /// let mut game = Game::new();
+    /// game.begin_edit();
+    /// // Take White's queen off and let Black start
+    /// game.board.remove_piece(3, 0);
+    /// game.pass();
+    /// game.end_edit().unwrap();
+    /// assert_eq!(game.initial_fen.as_deref(), Some("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNB1KBNR b KQkq - 0 1"));
+    /// ```
+    pub fn begin_edit(&mut self) {
+        self.editing = true;
+    }
+
+    /// Whether the game is between begin_edit and end_edit
+    pub fn is_editing(&self) -> bool {
+        self.editing
+    }
+
+    /// Move a piece ignoring the rules, see Board::force_move. Starts edit mode if the game was not in it.
+    pub fn force_move(&mut self, from: (usize, usize), to: (usize, usize)) -> Option<Piece> {
+        self.begin_edit();
+        self.board.force_move(from, to)
+    }
+
+    /// Give the move to the other side without moving. Starts edit mode if the game was not in it.
+    pub fn pass(&mut self) {
+        self.begin_edit();
+        let turn = self.board.get_player_turn().opposite();
+        self.board.set_turn(turn);
+        self.board.set_en_passant(None);
+    }
+
+    /// Finish editing and start the game again from the edited position.
+    /// # Description
+    /// The position is checked with validate_position. If it is legal it becomes the initial position,
+    /// the move history and last search are cleared and moves are recorded again.
+    /// Otherwise the game stays in edit mode so the mistake can be fixed.
+    /// # Inputs/Outputs
+    /// - Output: Ok once out of edit mode, or the problem with the position
+    pub fn end_edit(&mut self) -> Result<(), FenError> {
+        self.board.validate_position()?;
+        self.editing = false;
+        self.initial_fen = Some(self.board.to_fen());
+        self.move_history_white.clear();
+        self.move_history_black.clear();
+        self.last_search = None;
+        Ok(())
+    }
+
+    /// Set how the engine searches when it plays or gives hints for one side.
+    /// # Description
+    /// Sides without a config get random moves from engine_move and a default depth search from hint.
+    /// # Example
+    /// ``` Rust
+    /// // Queen odds against a quick engine, with full depth hints for White
+    /// let mut game = Game::from_fen("rnb1kbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+    /// game.set_engine_config_for(Color::Black, EngineConfig::movetime(100));
+    /// game.set_engine_config_for(Color::White, EngineConfig::depth(5));
+    /// ```
+    pub fn set_engine_config_for(&mut self, color: Color, config: EngineConfig) {
+        self.engine_configs[color.index()] = Some(config);
+    }
+
+    /// The engine config of one side, if it has been set
+    pub fn engine_config_for(&self, color: Color) -> Option<&EngineConfig> {
+        self.engine_configs[color.index()].as_ref()
+    }
+
+    /// Remember the score of a hint for the current position, so render can say when a mate is available.
+    /// Forgotten as soon as the position changes.
+    pub fn cache_hint(&mut self, score: i32) {
+        self.hint_score = Some((self.board.zobrist_key(), score));
+    }
+
+    /// Draw the board for the console, see Board::render.
+    /// # Description
+    /// Highlights the last move played when options.highlight is None. With options.status the status line
+    /// also says when a cached hint found a mate, e.g. "White to move — mate in 2 available".
+    /// # Example
+    /// ``` Rust
+    /// let options = BoardRenderOptions { mark_check: true, status: true, ..Default::default() };
+    /// println!("{}", game.render(&options));
+    /// ```
+    pub fn render(&self, options: &BoardRenderOptions) -> String {
+        let mut options = options.clone();
+        if options.highlight.is_none() {
+            options.highlight = self.board.last_move().cloned();
+        }
+        let mut text = self.board.render(&options);
+        let mate_moves = self.hint_score
+            .filter(|(key, score)| *key == self.board.zobrist_key() && MATE_SCORE - score <= MAX_DEPTH as i32)
+            .map(|(_, score)| (MATE_SCORE - score + 1) / 2);
+        if let Some(moves) = mate_moves.filter(|_| options.status) {
+            text.pop();
+            text.push_str(&format!(" — mate in {} available\n", moves));
+        }
+        text
+    }
+
+    /// The evaluation of the current position without searching, in centipawns from the side to move's perspective.
+    /// The same figure a search of the position reports as its static_eval.
+    pub fn static_eval(&self) -> i32 {
+        self.board.evaluate_to_move()
+    }
+
+    /// Megabytes for a table shared by searches made for both sides, such as a review of the game.
+    /// The smaller of the two engine configs' hash budgets, so neither side's memory cap is exceeded,
+    /// or ANALYSIS_HASH_MB when neither side has a config.
+    pub fn analysis_hash_mb(&self) -> usize {
+        self.engine_configs.iter().flatten().map(EngineConfig::hash_budget_mb).min().unwrap_or(ANALYSIS_HASH_MB)
+    }
+
+    /// Search the current position for the side to move using that side's engine config
+    pub fn hint(&self) -> SearchResult {
+        let config = self.engine_config_for(*self.board.get_player_turn()).cloned().unwrap_or_default();
+        let table = TranspositionTable::new(config.hash_budget_mb());
+        Searcher::new_with_table(config.limits, Arc::new(AtomicBool::new(false)), table).search(&self.board)
+    }
+
+    /// The moves of the game as PGN.
+    /// # Description
+    /// Games set up from a FEN get the SetUp and FEN tags so other programs replay them from the right position.
+    /// The game ends with its result, "*" while it is still in progress.
+    /// # Example
+    /// ``` Rust
+    /// let mut game = Game::from_fen("4k3/8/8/8/8/8/4P3/4K3 b - - 0 7").unwrap();
+    /// game.make_move(Move::new(4, 7, 3, 7, PieceType::King, None)).unwrap();
+    /// game.make_move(Move::new(4, 1, 4, 3, PieceType::Pawn, None)).unwrap();
+    /// // [SetUp "1"]
+    /// // [FEN "4k3/8/8/8/8/8/4P3/4K3 b - - 0 7"]
+    /// //
+    /// // 7... Kd8 8. e4 *
+    /// println!("{}", game.to_pgn());
+    /// ```
+    pub fn to_pgn(&self) -> String {
+        let mut pgn = String::new();
+        if let Some(fen) = &self.initial_fen {
+            pgn.push_str(&format!("[SetUp \"1\"]\n[FEN \"{}\"]\n\n", fen));
+        }
+        let mut first = true;
+        self.replay(|board, mv| {
+            match board.get_player_turn() {
+                Color::White => pgn.push_str(&format!("{}. ", board.fullmove_number())),
+                Color::Black if first => pgn.push_str(&format!("{}... ", board.fullmove_number())),
+                Color::Black => {}
+            }
+            pgn.push_str(&move_to_san(board, mv));
+            pgn.push(' ');
+            first = false;
+        });
+        pgn.push_str(self.result());
+        pgn
+    }
+
+    /// The result of the game in PGN form.
+    /// # Description
+    /// "1-0" or "0-1" after checkmate, "1/2-1/2" for any reason Board::is_draw gives,
+    /// otherwise "*" as the game is still in progress.
+    pub fn result(&self) -> &'static str {
+        if self.board.is_draw().is_some() {
+            return "1/2-1/2";
+        }
+        if !self.board.has_legal_moves() {
+            return match self.board.get_player_turn() {
+                Color::White => "0-1",
+                Color::Black => "1-0",
+            };
+        }
+        "*"
+    }
+
+    /// Everything a client needs to show the game, gathered in one call.
+    /// # Description
+    /// The moves played are replayed once from the initial position to write them in SAN and find the captured pieces.
+    /// The legal moves, check and repetition count are found once and the result is worked out from them.
+    /// With the serde feature the snapshot can be serialized, e.g. as the JSON body of a web response.
+    /// # Example
+    /// ``` Rust
+    /// let mut game = Game::new();
+    /// game.make_move(Move::new(4, 1, 4, 3, PieceType::Pawn, None)).unwrap();
+    /// let snapshot = game.snapshot();
+    /// assert_eq!(snapshot.history_san, vec!["e4"]);
+    /// assert_eq!(snapshot.last_move.as_deref(), Some("e2e4"));
+    /// ```
+    pub fn snapshot(&self) -> GameSnapshot {
+        let mut history_san = Vec::new();
+        let mut captured = Vec::new();
+        let mut last_move = None;
+        self.replay(|board, mv| {
+            history_san.push(move_to_san(board, mv));
+            // A pawn moving diagonally onto an empty square captures en passant
+            let victim = board.get_piece(mv.to_x, mv.to_y)
+                .or_else(|| (mv.piece_type == PieceType::Pawn && mv.from_x != mv.to_x).then(|| board.get_piece(mv.to_x, mv.from_y)).flatten());
+            if let Some(victim) = victim {
+                captured.push(victim.get_piece_char());
+            }
+            last_move = Some(mv.extended_algebraic());
+        });
+        let legal_moves = self.board.generate_legal_moves()
+            .iter()
+            .map(|mv| SnapshotMove {
+                uci: mv.extended_algebraic(),
+                san: move_to_san(&self.board, mv),
+                captured_square: self.board.captured_square(mv),
+            })
+            .collect::<Vec<_>>();
+        let in_check = self.board.king_in_check();
+        let repetition_count = self.board.repetition_count();
+        // The same answer as result, from what is already in hand
+        let result = match (legal_moves.is_empty(), in_check, self.board.get_player_turn()) {
+            (true, true, Color::White) => "0-1",
+            (true, true, Color::Black) => "1-0",
+            _ if self.board.draw_reason(!legal_moves.is_empty(), repetition_count).is_some() => "1/2-1/2",
+            _ => "*",
+        };
+        GameSnapshot {
+            fen: self.board.to_fen(),
+            side_to_move: *self.board.get_player_turn(),
+            legal_moves,
+            in_check,
+            result: result.to_string(),
+            history_san,
+            captured,
+            repetition_count,
+            last_move,
+        }
+    }
+
+    /// Plays the moves of the game in order from the initial position, calling visit with each move and the board before it
+    pub(crate) fn replay(&self, mut visit: impl FnMut(&Board, &Move)) {
+        let mut board = match &self.initial_fen {
+            Some(fen) => Board::from_fen(fen).expect("initial FEN was validated when the game was created"),
+            None => Board::starting_position(),
+        };
+        let mut white_moves = self.move_history_white.iter();
+        let mut black_moves = self.move_history_black.iter();
+        loop {
+            let mv = match board.get_player_turn() {
+                Color::White => white_moves.next(),
+                Color::Black => black_moves.next(),
+            };
+            let Some(mv) = mv else { break };
+            visit(&board, mv);
+            if board.move_piece(mv.clone()).is_err() {
+                break;
+            }
         }
     }
 }
@@ -0,0 +1,218 @@
+use crate::{board::Board, bitboard, castling_rights::CastlingRights, chess_move::Move, color::Color, errors::{decode_error::DecodeError, fen_error::FenError}, game::Game, piece::Piece, piece_type::PieceType, search::transposition_table::pack_move};
+
+/// Piece codes used by the position encoding, White's pieces first
+const PIECE_CODES: [char; 12] = ['P', 'R', 'N', 'B', 'Q', 'K', 'p', 'r', 'n', 'b', 'q', 'k'];
+/// The results a record can hold, indexed by their code
+const RESULTS: [&str; 4] = ["*", "1-0", "0-1", "1/2-1/2"];
+/// First byte of a record for a game from the standard starting position
+const START_POSITION: u8 = 0;
+/// First byte of a record for a game from a set up position, followed by the position
+const SET_UP_POSITION: u8 = 1;
+/// En passant byte when there is no en passant square
+const NO_EN_PASSANT: u8 = 0xFF;
+
+/// A compact binary form of a game, for storing many games where PGN would be too large.
+/// # Description
+/// A record is laid out as follows, numbers are little endian:
+/// - 1 byte: 0 for the standard starting position, 1 for a set up position
+/// - set up positions only: the occupied squares as a u64 bitboard, then a 4 bit piece code per occupied square
+///   from a1 to h8, two to a byte with the first in the low bits, then a byte holding the side to move in bit 0
+///   and the castling rights KQkq in bits 1 to 4, the en passant square index or 0xFF, and the halfmove clock
+///   and fullmove number as u16s
+/// - u16: the number of moves, followed by each move packed with pack_move
+/// - 1 byte: the result, 0 for "*", 1 for "1-0", 2 for "0-1" and 3 for "1/2-1/2"
+///
+/// A game from the starting position takes 4 bytes and 2 per move. Records can be written one after another
+/// in a file with frame and read back with frames.
+/// # Example
+/// ``` Rust
+/// let mut game = Game::new();
+/// game.make_move(Move::new(4, 1, 4, 3, PieceType::Pawn, None)).unwrap();
+/// let mut file = Vec::new();
+/// file.extend(GameRecord::frame(&GameRecord::encode(&game)));
+/// for record in GameRecord::frames(&file) {
+///     let game = GameRecord::decode(record.unwrap()).unwrap();
+///     assert_eq!(game.move_history_white.len(), 1);
+/// }
+/// ```
+pub struct GameRecord;
+
+impl GameRecord {
+    /// The record of a game, from its initial position to the moves played so far and its result
+    pub fn encode(game: &Game) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        match &game.initial_fen {
+            Some(fen) => {
+                bytes.push(SET_UP_POSITION);
+                encode_position(&Board::from_fen(fen).expect("initial FEN was validated when the game was created"), &mut bytes);
+            }
+            None => bytes.push(START_POSITION),
+        }
+        let mut moves = Vec::new();
+        game.replay(|_, mv| moves.push(pack_move(&promotion_made_explicit(mv))));
+        bytes.extend((moves.len() as u16).to_le_bytes());
+        for mv in moves {
+            bytes.extend(mv.to_le_bytes());
+        }
+        bytes.push(RESULTS.iter().position(|&result| result == game.result()).expect("Game::result is one of RESULTS") as u8);
+        bytes
+    }
+
+    /// Reads a record back into a game, checking it as it goes.
+    /// # Description
+    /// The starting position is checked as from_fen would, every move must be legal when it is replayed
+    /// and the recorded result must be the result of the final position.
+    /// # Inputs/Outputs
+    /// - Input: bytes - One record as made by encode, without its frame
+    /// - Output: The game, or the first problem found, with the ply for a move that is not legal
+    pub fn decode(bytes: &[u8]) -> Result<Game, DecodeError> {
+        let mut reader = Reader { bytes, position: 0 };
+        let mut game = match reader.u8()? {
+            START_POSITION => Game::new(),
+            SET_UP_POSITION => Game::from_fen(&decode_position(&mut reader)?)?,
+            flag => return Err(DecodeError::BadStartFlag(flag)),
+        };
+        let count = reader.u16()? as usize;
+        for ply in 1..=count {
+            let packed = reader.u16()?;
+            let mv = game.board.generate_legal_moves()
+                .into_iter()
+                .find(|mv| pack_move(mv) == packed)
+                .ok_or(DecodeError::IllegalMove { ply })?;
+            game.make_move(mv).expect("generated moves are legal");
+        }
+        let code = reader.u8()?;
+        let recorded = *RESULTS.get(code as usize).ok_or(DecodeError::BadResultCode(code))?;
+        if recorded != game.result() {
+            return Err(DecodeError::ResultMismatch { recorded, actual: game.result() });
+        }
+        if reader.position < bytes.len() {
+            return Err(DecodeError::TrailingBytes(bytes.len() - reader.position));
+        }
+        Ok(game)
+    }
+
+    /// A record prefixed with its length as a u32, so records can be stored one after another
+    pub fn frame(record: &[u8]) -> Vec<u8> {
+        let mut framed = (record.len() as u32).to_le_bytes().to_vec();
+        framed.extend_from_slice(record);
+        framed
+    }
+
+    /// The records in bytes made by joining frames, in order.
+    /// A frame cut short ends the iteration with DecodeError::Truncated.
+    pub fn frames(bytes: &[u8]) -> Frames<'_> {
+        Frames { reader: Reader { bytes, position: 0 } }
+    }
+}
+
+/// Iterator over framed records, see GameRecord::frames
+pub struct Frames<'a> {
+    reader: Reader<'a>,
+}
+
+impl<'a> Iterator for Frames<'a> {
+    type Item = Result<&'a [u8], DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.reader.position == self.reader.bytes.len() {
+            return None;
+        }
+        let frame = self.reader.u32().and_then(|length| self.reader.take(length as usize));
+        if frame.is_err() {
+            self.reader.position = self.reader.bytes.len();
+        }
+        Some(frame)
+    }
+}
+
+/// Reads numbers from the front of a record, reporting where it ran out
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, length: usize) -> Result<&'a [u8], DecodeError> {
+        let end = self.position + length;
+        let taken = self.bytes.get(self.position..end).ok_or(DecodeError::Truncated(self.bytes.len()))?;
+        self.position = end;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, DecodeError> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, DecodeError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, DecodeError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+}
+
+/// A pawn reaching the last rank without a promotion piece becomes a queen, packed moves need it spelled out
+fn promotion_made_explicit(mv: &Move) -> Move {
+    let mut mv = mv.clone();
+    if mv.piece_type == PieceType::Pawn && (mv.to_y == 0 || mv.to_y == 7) && mv.promotion.is_none() {
+        mv.promotion = Some(PieceType::Queen);
+    }
+    mv
+}
+
+fn encode_position(board: &Board, bytes: &mut Vec<u8>) {
+    let mut occupied = 0u64;
+    let mut codes = Vec::new();
+    for index in 0..64 {
+        let (x, y) = bitboard::index_to_coords(index);
+        if let Some(piece) = board.get_piece(x, y) {
+            occupied |= 1 << index;
+            codes.push(PIECE_CODES.iter().position(|&c| c == piece.get_piece_char()).unwrap() as u8);
+        }
+    }
+    bytes.extend(occupied.to_le_bytes());
+    bytes.extend(codes.chunks(2).map(|pair| pair[0] | pair.get(1).map_or(0, |code| code << 4)));
+    let rights = board.castling_rights();
+    let black_to_move = !board.get_player_turn().is_white();
+    bytes.push(black_to_move as u8 | (rights.white_king as u8) << 1 | (rights.white_queen as u8) << 2 | (rights.black_king as u8) << 3 | (rights.black_queen as u8) << 4);
+    bytes.push(board.en_passant_square().map_or(NO_EN_PASSANT, |(x, y)| bitboard::square_index(x, y) as u8));
+    bytes.extend((board.halfmove_clock() as u16).to_le_bytes());
+    bytes.extend((board.fullmove_number() as u16).to_le_bytes());
+}
+
+/// Reads a position written by encode_position back as FEN, which from_fen then checks
+fn decode_position(reader: &mut Reader) -> Result<String, DecodeError> {
+    let occupied = reader.u64()?;
+    let packed = reader.take((occupied.count_ones() as usize).div_ceil(2))?;
+    let mut board = Board::empty();
+    for (i, index) in bitboard::squares(occupied).enumerate() {
+        let code = (packed[i / 2] >> (4 * (i % 2))) & 0xF;
+        let piece = PIECE_CODES.get(code as usize).and_then(|&c| Piece::from_fen(c)).ok_or(DecodeError::BadPieceCode(code))?;
+        let (x, y) = bitboard::index_to_coords(index);
+        board.set_piece(x, y, piece);
+    }
+    let flags = reader.u8()?;
+    board.set_turn(if flags & 1 == 0 { Color::White } else { Color::Black });
+    board.set_castling_rights(CastlingRights {
+        white_king: flags & 2 != 0,
+        white_queen: flags & 4 != 0,
+        black_king: flags & 8 != 0,
+        black_queen: flags & 16 != 0,
+    });
+    match reader.u8()? {
+        NO_EN_PASSANT => {}
+        index if index < 64 => board.set_en_passant(Some(bitboard::index_to_coords(index as usize))),
+        index => return Err(FenError::BadEnPassantSquare(index.to_string()).into()),
+    }
+    let halfmove = reader.u16()?;
+    let fullmove = reader.u16()?;
+    let fen = board.to_fen();
+    let fields = fen.split_whitespace().take(4).collect::<Vec<_>>();
+    Ok(format!("{} {} {}", fields.join(" "), halfmove, fullmove))
+}
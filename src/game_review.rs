@@ -0,0 +1,171 @@
+use std::fmt::{self, Display, Formatter};
+
+use crate::{chess_move::Move, color::Color, game::Game, kibitzer::move_judgment::MoveClass, search::{search_limits::SearchLimits, searcher::analyze_with_table, transposition_table::TranspositionTable}, utils::notation::move_to_san};
+
+/// Evaluations are capped at this many centipawns either way before losses are measured,
+/// so missing a mate in an already won position is not counted as a loss of thousands of centipawns
+pub const REVIEW_CP_CAP: i32 = 1000;
+/// Worst moves listed when a review is displayed
+pub const WORST_MOVES_SHOWN: usize = 3;
+
+/// The engine's verdict on one move of a reviewed game.
+/// Evaluations are in centipawns from the perspective of the side that played the move, capped at REVIEW_CP_CAP.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReviewedMove {
+    pub move_number: u32,
+    pub color: Color,
+    pub played: Move,
+    pub played_san: String,
+    /// The engine's choice in the position before the move
+    pub best_move: Option<Move>,
+    pub best_san: Option<String>,
+    pub eval_before: i32,
+    pub eval_after: i32,
+    /// How much worse the position became, never negative
+    pub cp_loss: i32,
+    /// From 0 to 100, see move_accuracy
+    pub accuracy: f64,
+    pub class: MoveClass,
+}
+
+impl ReviewedMove {
+    /// The move with its number, e.g. "12. Nf3" or "12... Qxb2"
+    pub fn label(&self) -> String {
+        let dots = if self.color == Color::White { "." } else { "..." };
+        format!("{}{} {}", self.move_number, dots, self.played_san)
+    }
+}
+
+/// A finished game analysed move by move, as made by Game::review.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameReview {
+    /// Every move of the game in order
+    pub moves: Vec<ReviewedMove>,
+}
+
+impl GameReview {
+    /// The mean accuracy of one side's moves, None if it made no moves
+    pub fn accuracy(&self, color: Color) -> Option<f64> {
+        side_accuracy(&self.side_moves(color).map(|reviewed| reviewed.accuracy).collect::<Vec<_>>())
+    }
+
+    /// The mean centipawn loss of one side's moves, None if it made no moves
+    pub fn average_cp_loss(&self, color: Color) -> Option<f64> {
+        let losses = self.side_moves(color).map(|reviewed| reviewed.cp_loss as f64).collect::<Vec<_>>();
+        (!losses.is_empty()).then(|| losses.iter().sum::<f64>() / losses.len() as f64)
+    }
+
+    /// The moves that lost the most, worst first, leaving out moves that lost nothing.
+    /// Moves that lost the same amount are kept in game order.
+    pub fn worst_moves(&self, n: usize) -> Vec<&ReviewedMove> {
+        let mut losing = self.moves.iter().filter(|reviewed| reviewed.cp_loss > 0).collect::<Vec<_>>();
+        losing.sort_by_key(|reviewed| -reviewed.cp_loss);
+        losing.truncate(n);
+        losing
+    }
+
+    fn side_moves(&self, color: Color) -> impl Iterator<Item = &ReviewedMove> {
+        self.moves.iter().filter(move |reviewed| reviewed.color == color)
+    }
+}
+
+/// Each side's accuracy and average loss, then the worst moves with the engine's alternative, e.g.
+/// "White: accuracy 91.2%, average loss 18 cp" and "  12... Qxb2 lost 350 cp, a blunder, Nf6 was better"
+impl Display for GameReview {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for color in [Color::White, Color::Black] {
+            if let (Some(accuracy), Some(loss)) = (self.accuracy(color), self.average_cp_loss(color)) {
+                writeln!(f, "{}: accuracy {:.1}%, average loss {:.0} cp", color, accuracy, loss)?;
+            }
+        }
+        let worst = self.worst_moves(WORST_MOVES_SHOWN);
+        if worst.is_empty() {
+            return writeln!(f, "No moves lost anything");
+        }
+        writeln!(f, "Worst moves:")?;
+        for reviewed in worst {
+            write!(f, "  {} lost {} cp, {}", reviewed.label(), reviewed.cp_loss, reviewed.class)?;
+            match &reviewed.best_san {
+                Some(best) => writeln!(f, ", {} was better", best)?,
+                None => writeln!(f)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The chance of winning, from 0 to 100 percent, for an evaluation in centipawns.
+/// # Description
+/// Uses the logistic curve lichess fitted to its games: win% = 50 + 50 * (2 / (1 + e^(-0.00368208 * cp)) - 1).
+/// An equal position is 50%, a pawn up is about 59% and a rook up about 86%.
+pub fn win_percent(cp: i32) -> f64 {
+    50.0 + 50.0 * (2.0 / (1.0 + (-0.00368208 * cp as f64).exp()) - 1.0)
+}
+
+/// How accurate a move was, from 0 to 100, given the mover's winning chances before and after it.
+/// # Description
+/// accuracy = 103.1668 * e^(-0.04354 * (win_before - win_after)) - 3.1669, clamped to 0..=100.
+/// A move that keeps or improves the winning chances scores 100, and the score falls off quickly,
+/// losing 10% of the winning chances scores about 64 and losing 30% about 25.
+pub fn move_accuracy(win_before: f64, win_after: f64) -> f64 {
+    let lost = win_before - win_after;
+    if lost <= 0.0 {
+        return 100.0;
+    }
+    (103.1668 * (-0.04354 * lost).exp() - 3.1669).clamp(0.0, 100.0)
+}
+
+/// A side's accuracy, the mean of its move accuracies, None for no moves
+pub fn side_accuracy(move_accuracies: &[f64]) -> Option<f64> {
+    (!move_accuracies.is_empty()).then(|| move_accuracies.iter().sum::<f64>() / move_accuracies.len() as f64)
+}
+
+impl Game {
+    /// Analyse every move of the game, for a summary once it is over.
+    /// # Description
+    /// Searches each position of the game once with the given limits, including the final one.
+    /// All the searches share one transposition table, sized by analysis_hash_mb.
+    /// A move's loss is the evaluation before it less the evaluation after it, both from the mover's side and capped
+    /// at REVIEW_CP_CAP. Playing the engine's own choice never counts as a loss.
+    /// Accuracy is worked out from the change in winning chances, see win_percent and move_accuracy.
+    /// # Inputs/Outputs
+    /// - Input: limits - The search for each position, e.g. a fixed movetime
+    /// - Output: Every move with its loss and accuracy
+    /// # Example
+    /// ``` Rust
+    /// let review = game.review(SearchLimits::movetime(200));
+    /// print!("{}", review);
+    /// // White: accuracy 91.2%, average loss 18 cp
+    /// // Black: accuracy 64.0%, average loss 95 cp
+    /// // Worst moves:
+    /// //   12... Qxb2 lost 350 cp, a blunder, Nf6 was better
+    /// ```
+    pub fn review(&self, limits: SearchLimits) -> GameReview {
+        let mut positions = Vec::new();
+        self.replay(|board, mv| positions.push((board.clone(), mv.clone())));
+        let mut table = TranspositionTable::new(self.analysis_hash_mb());
+        let mut searches = positions.iter().map(|(board, _)| analyze_with_table(board, limits.clone(), &mut table)).collect::<Vec<_>>();
+        searches.push(analyze_with_table(&self.board, limits, &mut table));
+        let capped = |score: i32| score.clamp(-REVIEW_CP_CAP, REVIEW_CP_CAP);
+        let moves = positions.into_iter().enumerate().map(|(i, (board, played))| {
+            let before = &searches[i];
+            let eval_before = capped(before.score);
+            let eval_after = capped(-searches[i + 1].score);
+            let cp_loss = if before.best_move.as_ref() == Some(&played) { 0 } else { (eval_before - eval_after).max(0) };
+            ReviewedMove {
+                move_number: board.fullmove_number(),
+                color: *board.get_player_turn(),
+                played_san: move_to_san(&board, &played),
+                best_san: before.best_move.as_ref().map(|best| move_to_san(&board, best)),
+                best_move: before.best_move.clone(),
+                played,
+                eval_before,
+                eval_after,
+                cp_loss,
+                accuracy: move_accuracy(win_percent(eval_before), win_percent(eval_before - cp_loss)),
+                class: MoveClass::from_cp_loss(cp_loss),
+            }
+        }).collect();
+        GameReview { moves }
+    }
+}
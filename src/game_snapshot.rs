@@ -0,0 +1,34 @@
+use crate::color::Color;
+
+/// A legal move in both notations a client may want.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SnapshotMove {
+    /// The move in UCI notation, e.g. "e7e8q"
+    pub uci: String,
+    /// The move in Standard Algebraic Notation, e.g. "e8=Q+"
+    pub san: String,
+    /// Where the piece the move takes stands as (x, y), beside the destination for en passant, None for no capture
+    pub captured_square: Option<(usize, usize)>,
+}
+
+/// The state of a game at one moment, built by Game::snapshot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameSnapshot {
+    pub fen: String,
+    pub side_to_move: Color,
+    pub legal_moves: Vec<SnapshotMove>,
+    /// Whether the side to move is in check
+    pub in_check: bool,
+    /// The result in PGN form, "*" while the game is in progress
+    pub result: String,
+    /// Every move played so far in SAN
+    pub history_san: Vec<String>,
+    /// FEN characters of the captured pieces in the order they were taken, uppercase for White's pieces
+    pub captured: Vec<char>,
+    /// How many times the current position occurred before
+    pub repetition_count: usize,
+    /// The last move played in UCI notation
+    pub last_move: Option<String>,
+}
@@ -0,0 +1,74 @@
+use std::{fmt::{self, Display, Formatter}, sync::{atomic::AtomicBool, Arc}};
+
+use crate::{chess_move::Move, game::Game, search::{search_limits::SearchLimits, searcher::{Searcher, MATE_SCORE, MAX_DEPTH}, transposition_table::TranspositionTable}, utils::notation::{line_to_san, move_to_san}};
+
+/// Moves of each line written out in full before it is cut short with "…"
+const HINT_PV_MOVES: usize = 4;
+
+/// One candidate move suggested by hint.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HintLine {
+    pub mv: Move,
+    /// The move in SAN
+    pub san: String,
+    /// Score in centipawns from the side to move's perspective
+    pub score: i32,
+    /// The expected continuation in SAN starting with the move, cut short after a few moves
+    pub pv: String,
+    /// Whether this is the move the engine would play
+    pub chosen: bool,
+}
+
+/// e.g. "* Nxe5   +1.25  Nxe5 d6 Nf3 Qe7 …", the engine's choice is marked with "*"
+impl Display for HintLine {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let marker = if self.chosen { '*' } else { ' ' };
+        write!(f, "{} {:<7} {:>6}  {}", marker, self.san, format_score(self.score), self.pv)
+    }
+}
+
+/// A score for people, in pawns such as "+1.25", or moves to mate such as "#3" and "#-2"
+pub fn format_score(score: i32) -> String {
+    let plies_to_mate = MATE_SCORE - score.abs();
+    if plies_to_mate <= MAX_DEPTH as i32 {
+        let moves = (plies_to_mate + 1) / 2;
+        format!("#{}", if score > 0 { moves } else { -moves })
+    } else {
+        format!("{:+.2}", score as f64 / 100.0)
+    }
+}
+
+/// The best few moves for the side to move, each with its score and expected continuation.
+/// # Description
+/// Runs a search that scores the best n moves exactly and reads each move's continuation from the
+/// transposition table. The search uses the hash size of the side to move's engine config.
+/// The game is only read, never changed.
+/// # Inputs/Outputs
+/// - Input: game - The game to give hints for
+/// - Input: limits - How long to search
+/// - Input: n - The most lines to return
+/// - Output: Up to n lines, best first, empty if the side to move has no legal moves
+/// # Example
+/// ``` Rust
+/// let game = Game::new();
+/// for line in hint(&game, SearchLimits::depth(4), 3) {
+///     println!("{}", line);
+/// }
+/// ```
+pub fn hint(game: &Game, limits: SearchLimits, n: usize) -> Vec<HintLine> {
+    let config = game.engine_config_for(*game.board.get_player_turn()).cloned().unwrap_or_default();
+    let table = TranspositionTable::new(config.hash_budget_mb());
+    let mut searcher = Searcher::new_with_table(limits, Arc::new(AtomicBool::new(false)), table).with_multi_pv(n);
+    let result = searcher.search(&game.board);
+    let max_len = (result.depth as usize).max(1);
+    result.root_scores().into_iter().take(n).map(|(mv, score)| {
+        let line = searcher.principal_variation(&game.board, &mv, max_len);
+        HintLine {
+            san: move_to_san(&game.board, &mv),
+            score,
+            pv: line_to_san(&game.board, &line, HINT_PV_MOVES),
+            chosen: result.best_move.as_ref() == Some(&mv),
+            mv,
+        }
+    }).collect()
+}
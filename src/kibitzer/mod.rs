@@ -0,0 +1,3 @@
+
+pub mod move_judgment;
+pub mod watcher;
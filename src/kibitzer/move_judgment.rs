@@ -0,0 +1,113 @@
+use std::fmt::Display;
+
+use crate::{board::Board, chess_move::Move, errors::move_error::MoveError, search::{search_limits::SearchLimits, searcher::analyze_with_table, transposition_table::{TranspositionTable, ANALYSIS_HASH_MB}}, utils::notation::move_to_san};
+
+/// Largest centipawn loss still counted as a good move
+pub const GOOD_THRESHOLD: i32 = 50;
+/// Largest centipawn loss counted as an inaccuracy
+pub const INACCURACY_THRESHOLD: i32 = 100;
+/// Largest centipawn loss counted as a mistake, anything more is a blunder
+pub const MISTAKE_THRESHOLD: i32 = 300;
+
+/// How good a played move was compared to the engine's choice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveClass {
+    Best,
+    Good,
+    Inaccuracy,
+    Mistake,
+    Blunder,
+}
+
+impl MoveClass {
+    /// Classifies a move from the centipawns it lost against the best move
+    pub fn from_cp_loss(cp_loss: i32) -> MoveClass {
+        if cp_loss <= 0 {
+            MoveClass::Best
+        } else if cp_loss <= GOOD_THRESHOLD {
+            MoveClass::Good
+        } else if cp_loss <= INACCURACY_THRESHOLD {
+            MoveClass::Inaccuracy
+        } else if cp_loss <= MISTAKE_THRESHOLD {
+            MoveClass::Mistake
+        } else {
+            MoveClass::Blunder
+        }
+    }
+}
+
+impl Display for MoveClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", match self {
+            MoveClass::Best => "best",
+            MoveClass::Good => "good",
+            MoveClass::Inaccuracy => "an inaccuracy",
+            MoveClass::Mistake => "a mistake",
+            MoveClass::Blunder => "a blunder",
+        })
+    }
+}
+
+/// The engine's verdict on a played move.
+/// Scores are in centipawns from the perspective of the side that played the move.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MoveJudgment {
+    pub played: Move,
+    pub played_san: String,
+    pub played_score: i32,
+    pub best_move: Move,
+    pub best_san: String,
+    pub best_score: i32,
+    pub cp_loss: i32,
+    pub class: MoveClass,
+}
+
+impl MoveJudgment {
+    /// A one line comment on the move, suggesting the engine's choice when the move was not the best
+    pub fn commentary(&self) -> String {
+        if self.class == MoveClass::Best {
+            return format!("{} is the best move ({:+})", self.played_san, self.best_score);
+        }
+        format!("{} is {} (-{} cp), {} was better ({:+})", self.played_san, self.class, self.cp_loss, self.best_san, self.best_score)
+    }
+}
+
+/// Judge a move by comparing it to the engine's best move.
+/// # Description
+/// Searches the position before the move with the given limits, then searches the position after the played move
+/// with the same limits to score it. The difference is the centipawn loss, which is never negative.
+/// A move the engine also chose is always classed as best.
+/// # Inputs/Outputs
+/// - Input: board: &Board - The position before the move
+/// - Input: played: &Move - The move that was played
+/// - Input: limits: &SearchLimits - The limits of each search, e.g. a fixed movetime
+/// - Output: The judgment, or an error if the move is not legal
+/// # Example
+/// ``` Rust
+/// let board = Board::starting_position();
+/// let judgment = classify_move(&board, &Move::new(4, 1, 4, 3, PieceType::Pawn, None), &SearchLimits::movetime(500)).unwrap();
+/// println!("{}", judgment.commentary());
+/// ```
+pub fn classify_move(board: &Board, played: &Move, limits: &SearchLimits) -> Result<MoveJudgment, MoveError> {
+    let mut after = board.clone();
+    after.move_piece(played.clone())?;
+    let mut table = TranspositionTable::new(ANALYSIS_HASH_MB);
+    let best = analyze_with_table(board, limits.clone(), &mut table);
+    let best_move = best.best_move.unwrap_or_else(|| played.clone());
+    let (played_score, cp_loss) = if best_move == *played {
+        (best.score, 0)
+    } else {
+        let played_score = -analyze_with_table(&after, limits.clone(), &mut table).score;
+        (played_score, (best.score - played_score).max(0))
+    };
+    Ok(MoveJudgment {
+        played_san: move_to_san(board, played),
+        played: played.clone(),
+        played_score,
+        best_san: move_to_san(board, &best_move),
+        best_move,
+        best_score: best.score,
+        cp_loss,
+        class: MoveClass::from_cp_loss(cp_loss),
+    })
+}
@@ -0,0 +1,166 @@
+use std::{io::{self, BufRead, Write}, path::Path, thread, time::Duration};
+
+use crate::{board::Board, chess_move::Move, color::Color, errors::move_error::MoveError, search::search_limits::SearchLimits, utils::notation::{san_to_move, uci_to_move}};
+
+use super::move_judgment::{classify_move, MoveJudgment};
+
+/// How often a followed PGN file is re-read
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Follows a game move by move and judges each move as it arrives.
+pub struct Kibitzer {
+    board: Board,
+    limits: SearchLimits,
+    moves_seen: usize,
+}
+
+impl Kibitzer {
+    pub fn new(limits: SearchLimits) -> Kibitzer {
+        Kibitzer { board: Board::starting_position(), limits, moves_seen: 0 }
+    }
+
+    /// Number of moves played so far
+    pub fn moves_seen(&self) -> usize {
+        self.moves_seen
+    }
+
+    /// Judge a move given in SAN or long algebraic notation, then play it
+    /// # Description
+    /// Returns the commentary line prefixed with the move number, e.g. "12... Nf6 is the best move (+35)".
+    pub fn observe(&mut self, token: &str) -> Result<(MoveJudgment, String), MoveError> {
+        let mv = parse_move(&self.board, token).ok_or(MoveError::IllegalMove)?;
+        let judgment = classify_move(&self.board, &mv, &self.limits)?;
+        let number = self.moves_seen / 2 + 1;
+        let prefix = if *self.board.get_player_turn() == Color::White { format!("{}.", number) } else { format!("{}...", number) };
+        self.board.move_piece(mv)?;
+        self.moves_seen += 1;
+        let line = format!("{} {}", prefix, judgment.commentary());
+        Ok((judgment, line))
+    }
+}
+
+/// Finds the legal move for a token in SAN ("Nf3") or long algebraic notation ("g1f3")
+pub fn parse_move(board: &Board, token: &str) -> Option<Move> {
+    uci_to_move(board, token).or_else(|| san_to_move(board, token))
+}
+
+/// Extracts the moves from PGN text
+/// # Description
+/// Skips tag pairs, brace and ";" comments, variations, move numbers, NAGs and the result,
+/// returning the SAN tokens of the main line.
+/// # Example
+/// ``` Rust
+/// let moves = pgn_moves("[Event \"?\"]\n\n1. e4 {best by test} e5 2. Nf3 (2. f4) Nc6 ; Two Knights next\n1-0");
+/// assert_eq!(moves, vec!["e4", "e5", "Nf3", "Nc6"]);
+/// ```
+pub fn pgn_moves(text: &str) -> Vec<String> {
+    let mut movetext = String::new();
+    let mut comment = false;
+    let mut variation = 0;
+    for line in text.lines() {
+        if !comment && variation == 0 && line.trim_start().starts_with('[') {
+            continue;
+        }
+        for c in line.chars() {
+            match c {
+                '{' if variation == 0 => comment = true,
+                '}' => comment = false,
+                // The rest of the line is a comment
+                ';' if !comment => break,
+                '(' if !comment => variation += 1,
+                ')' if !comment => variation -= 1,
+                _ if comment || variation > 0 => (),
+                _ => movetext.push(c),
+            }
+        }
+        movetext.push(' ');
+    }
+    movetext.split_whitespace()
+        .filter(|token| !["1-0", "0-1", "1/2-1/2", "*"].contains(token))
+        .map(|token| token.trim_start_matches(|c: char| c.is_ascii_digit() || c == '.'))
+        .filter(|token| !token.is_empty() && !token.starts_with('$'))
+        .map(|token| token.to_string())
+        .collect()
+}
+
+/// Re-reads a growing PGN file and writes a comment on every new move until the process is stopped
+/// or the output can no longer be written to
+pub fn follow_pgn(path: &Path, limits: SearchLimits, output: &mut impl Write) {
+    let mut follower = PgnFollower::new(limits);
+    loop {
+        if let Ok(text) = std::fs::read_to_string(path) {
+            if let Err(e) = follower.update(&text, output) {
+                log::error!("Could not write commentary: {}", e);
+                return;
+            }
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// How far follow_pgn has got through the game in the file it is following
+pub(crate) struct PgnFollower {
+    kibitzer: Kibitzer,
+    limits: SearchLimits,
+    /// The ply and token of a move that could not be played, only tried again once the file changes it
+    pub(crate) unplayable: Option<(usize, String)>,
+}
+
+impl PgnFollower {
+    pub(crate) fn new(limits: SearchLimits) -> PgnFollower {
+        PgnFollower { kibitzer: Kibitzer::new(limits.clone()), limits, unplayable: None }
+    }
+
+    /// Comments on the moves of the file's text that have not been seen yet.
+    /// # Description
+    /// A game shorter than the one followed so far is a new game and is followed from the start.
+    /// A move that can not be played is reported once and the moves after it wait until the file changes it.
+    /// # Inputs/Outputs
+    /// - Input: text - Everything the file holds now
+    /// - Input: output - Where the comments are written
+    /// - Output: The error writing a comment
+    pub(crate) fn update(&mut self, text: &str, output: &mut impl Write) -> io::Result<()> {
+        let moves = pgn_moves(text);
+        if moves.len() < self.kibitzer.moves_seen() {
+            log::info!("PGN file restarted, following the new game");
+            self.kibitzer = Kibitzer::new(self.limits.clone());
+            self.unplayable = None;
+        }
+        for token in &moves[self.kibitzer.moves_seen()..] {
+            let ply = self.kibitzer.moves_seen();
+            if self.unplayable.as_ref().is_some_and(|(bad_ply, bad_token)| *bad_ply == ply && bad_token == token) {
+                break;
+            }
+            match self.kibitzer.observe(token) {
+                Ok((_, line)) => {
+                    self.unplayable = None;
+                    writeln!(output, "{}", line)?;
+                }
+                Err(e) => {
+                    log::warn!("Could not follow move {}: {}", token, e);
+                    self.unplayable = Some((ply, token.clone()));
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Writes a comment on moves read from stdin, one or more per line, until stdin closes
+pub fn follow_stdin(limits: SearchLimits, output: &mut impl Write) {
+    let mut kibitzer = Kibitzer::new(limits);
+    for line in std::io::stdin().lock().lines().map_while(Result::ok) {
+        for token in pgn_moves(&line) {
+            match kibitzer.observe(&token) {
+                Ok((_, line)) => {
+                    if let Err(e) = writeln!(output, "{}", line) {
+                        log::error!("Could not write commentary: {}", e);
+                        return;
+                    }
+                }
+                Err(e) => log::warn!("Could not follow move {}: {}", token, e),
+            }
+        }
+    }
+}
@@ -1,13 +1,39 @@
 
 pub mod errors;
 pub mod board;
+pub mod board_builder;
+pub mod board_render_options;
+pub mod bitboard;
+pub mod attack_map;
+pub mod coords;
+pub mod zobrist;
 pub mod piece;
 pub mod piece_type;
 pub mod color;
+pub mod castling_rights;
+pub mod castling_validation;
+pub mod en_passant_mode;
 pub mod move_type;
 pub mod utils;
 pub mod chess_move;
+pub mod move_result;
+pub mod move_kind;
+pub mod side;
+pub mod square_name;
+pub mod draw_reason;
 pub mod game;
+pub mod game_snapshot;
+pub mod game_review;
+pub mod game_record;
+pub mod drill;
+pub mod hint;
+pub mod coach;
+pub mod evaluation;
+pub mod pawn_structure;
+pub mod mate_solver;
+pub mod search;
+pub mod selfplay;
+pub mod kibitzer;
 pub mod uci;
 
 #[cfg(test)]
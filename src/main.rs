@@ -1,18 +1,274 @@
-use casey_chess::uci::uci_interface::UciHandler;
+use casey_chess::{board::Board, kibitzer::watcher::{follow_pgn, follow_stdin}, search::{bench::{bench, BENCH_POSITIONS, DEFAULT_BENCH_DEPTH}, engine_config::EngineConfig, search_limits::SearchLimits}, selfplay::match_runner::MatchRunner, uci::uci_interface::UciHandler, utils::{batch::evaluate_fens, file_logger::FileLogger, performance::{perft_divide, perft_timed}}};
 
 //const LOG_LEVEL: log::LevelFilter = log::LevelFilter::Trace;
 const LOG_LEVEL: log::LevelFilter = log::LevelFilter::Info;
+/// Time spent analysing each move in watch mode
+const WATCH_MOVETIME_MS: u64 = 1000;
 
 
+/// casey_chess [--log-file <path>] [--max-memory-mb <MB>] [watch ... | bench ... | perft ... | score ... | selfplay ...]
+/// Speaks UCI on stdin and stdout unless a subcommand is given. Logs go to stderr, or to the file given with --log-file.
+/// --max-memory-mb caps the memory of the search tables, the Hash option can not go above it.
 fn main() {
-    // setup simple logger 
-    simple_logger::SimpleLogger::new()
-        .with_colors(true)
-        .with_level(LOG_LEVEL)
-        .init()
-        .unwrap();
-    UciHandler::new("Casey".to_string(), "JKDow".to_string()).run();
+    let mut args = std::env::args().skip(1).collect::<Vec<_>>();
+    let log_file = args.iter().position(|arg| arg == "--log-file").map(|index| {
+        let path = args.get(index + 1).cloned();
+        args.drain(index..(index + 2).min(args.len()));
+        path
+    });
+    match log_file {
+        Some(Some(path)) => {
+            let logger = FileLogger::new(std::path::Path::new(&path), LOG_LEVEL).unwrap_or_else(|e| {
+                eprintln!("Could not open log file {}: {}", path, e);
+                std::process::exit(1);
+            });
+            logger.init().unwrap();
+        }
+        Some(None) => {
+            eprintln!("--log-file needs a path");
+            std::process::exit(1);
+        }
+        None => {
+            // setup simple logger 
+            simple_logger::SimpleLogger::new()
+                .with_colors(true)
+                .with_level(LOG_LEVEL)
+                .init()
+                .unwrap();
+        }
+    }
+    let mut config = EngineConfig::default();
+    if let Some(index) = args.iter().position(|arg| arg == "--max-memory-mb") {
+        let Some(max) = args.get(index + 1).and_then(|value| value.parse().ok()) else {
+            eprintln!("--max-memory-mb needs a size in megabytes");
+            std::process::exit(1);
+        };
+        config.max_memory_mb = Some(max);
+        args.drain(index..index + 2);
+    }
+    match args.first().map(String::as_str) {
+        Some("watch") => {
+            watch(&args[1..]);
+            return;
+        }
+        Some("bench") => {
+            run_bench(&args[1..]);
+            return;
+        }
+        Some("perft") => {
+            run_perft(&args[1..]);
+            return;
+        }
+        Some("score") => {
+            run_score(&args[1..]);
+            return;
+        }
+        Some("selfplay") => {
+            run_selfplay(&args[1..]);
+            return;
+        }
+        _ => {}
+    }
+    let input = std::io::BufReader::new(std::io::stdin());
+    UciHandler::with_config("Casey".to_string(), "JKDow".to_string(), input, std::io::stdout(), config).run();
     //
     // console_game_loop();
 }
 
+/// casey_chess watch [--pgn-follow <path>] [--movetime <ms>]
+/// Follows a PGN file as it grows, or reads moves from stdin when no file is given
+fn watch(args: &[String]) {
+    let mut path = None;
+    let mut movetime = WATCH_MOVETIME_MS;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--pgn-follow" => path = args.next().cloned(),
+            "--movetime" => match args.next().and_then(|value| value.parse().ok()) {
+                Some(value) => movetime = value,
+                None => log::warn!("--movetime needs a number of milliseconds"),
+            },
+            other => log::warn!("Ignoring unknown watch argument: {}", other),
+        }
+    }
+    let limits = SearchLimits::movetime(movetime);
+    match path {
+        Some(path) => follow_pgn(std::path::Path::new(&path), limits, &mut std::io::stdout()),
+        None => follow_stdin(limits, &mut std::io::stdout()),
+    }
+}
+
+/// casey_chess bench [depth]
+/// Searches the bench positions to a fixed depth and prints the time to each depth, branching factors and search counters
+fn run_bench(args: &[String]) {
+    let depth = match args.first() {
+        Some(depth) => match depth.parse() {
+            Ok(depth) => depth,
+            Err(_) => {
+                log::warn!("Ignoring invalid bench depth: {}", depth);
+                DEFAULT_BENCH_DEPTH
+            }
+        },
+        None => DEFAULT_BENCH_DEPTH,
+    };
+    for (index, fen) in BENCH_POSITIONS.iter().enumerate() {
+        println!("position {:>2} {}", index + 1, fen);
+    }
+    println!("{}", bench(depth));
+}
+
+/// casey_chess score [--in <path>] [--out <path>] [--depth <depth>] [--threads <n>]
+/// Writes "fen,score" for each FEN line of the input, see evaluate_fens. Reads stdin and writes stdout unless files
+/// are given. Scores are static evaluations unless a search depth is given.
+fn run_score(args: &[String]) {
+    let mut input = None;
+    let mut output = None;
+    let mut limits = None;
+    let mut threads = 1;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--in" => input = args.next().cloned(),
+            "--out" => output = args.next().cloned(),
+            "--depth" => match args.next().and_then(|value| value.parse().ok()) {
+                Some(depth) => limits = Some(SearchLimits::depth(depth)),
+                None => log::warn!("--depth needs a number of plies"),
+            },
+            "--threads" => match args.next().and_then(|value| value.parse().ok()) {
+                Some(value) => threads = value,
+                None => log::warn!("--threads needs a number"),
+            },
+            other => log::warn!("Ignoring unknown score argument: {}", other),
+        }
+    }
+    let reader: Box<dyn std::io::BufRead> = match &input {
+        Some(path) => match std::fs::File::open(path) {
+            Ok(file) => Box::new(std::io::BufReader::new(file)),
+            Err(e) => {
+                eprintln!("Could not open {}: {}", path, e);
+                std::process::exit(1);
+            }
+        },
+        None => Box::new(std::io::stdin().lock()),
+    };
+    let writer: Box<dyn std::io::Write> = match &output {
+        Some(path) => match std::fs::File::create(path) {
+            Ok(file) => Box::new(std::io::BufWriter::new(file)),
+            Err(e) => {
+                eprintln!("Could not create {}: {}", path, e);
+                std::process::exit(1);
+            }
+        },
+        None => Box::new(std::io::BufWriter::new(std::io::stdout().lock())),
+    };
+    match evaluate_fens(reader, writer, limits, threads) {
+        Ok(report) => log::info!("{}", report),
+        Err(e) => {
+            eprintln!("Scoring stopped: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// casey_chess selfplay [--games <n>] [--depth-a <depth>] [--depth-b <depth>] [--random-openings <plies>] [--pgn <path>]
+/// Plays two search depths against each other and prints the result from the first one's perspective.
+/// --random-openings starts each pair of games from a random balanced opening of that many plies,
+/// --pgn appends every game to a file, opening moves included.
+fn run_selfplay(args: &[String]) {
+    let mut games = 2;
+    let mut depths = [3, 3];
+    let mut random_openings = None;
+    let mut pgn = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--games" => match args.next().and_then(|value| value.parse().ok()) {
+                Some(value) => games = value,
+                None => log::warn!("--games needs a number"),
+            },
+            "--depth-a" | "--depth-b" => match args.next().and_then(|value| value.parse().ok()) {
+                Some(depth) => depths[usize::from(arg == "--depth-b")] = depth,
+                None => log::warn!("{} needs a number of plies", arg),
+            },
+            "--random-openings" => match args.next().and_then(|value| value.parse().ok()) {
+                Some(plies) => random_openings = Some(plies),
+                None => log::warn!("--random-openings needs a number of plies"),
+            },
+            "--pgn" => pgn = args.next().cloned(),
+            other => log::warn!("Ignoring unknown selfplay argument: {}", other),
+        }
+    }
+    let mut runner = MatchRunner::new(SearchLimits::depth(depths[0]), SearchLimits::depth(depths[1]), games);
+    if let Some(plies) = random_openings {
+        runner = runner.with_random_openings(plies);
+    }
+    if let Some(path) = pgn {
+        runner = runner.with_pgn(path);
+    }
+    let (result, _) = runner.run();
+    println!("+{} ={} -{}", result.wins, result.draws, result.losses);
+}
+
+/// casey_chess perft <depth> [--fen <fen>] [--divide] [--format text|json]
+/// Counts positions at every depth from 1 to the one given, from the start position unless a FEN is given.
+/// --divide adds the count below each root move and --format json prints the results as a JSON array,
+/// which needs the serde feature.
+fn run_perft(args: &[String]) {
+    let mut depth = None;
+    let mut fen = None;
+    let mut divide = false;
+    let mut json = false;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--fen" => fen = args.next().cloned(),
+            "--divide" => divide = true,
+            "--format" => match args.next().map(String::as_str) {
+                Some("json") => json = true,
+                Some("text") => json = false,
+                other => log::warn!("Ignoring unknown perft format: {}", other.unwrap_or("")),
+            },
+            other => match other.parse::<u32>() {
+                Ok(value) => depth = Some(value),
+                Err(_) => log::warn!("Ignoring unknown perft argument: {}", other),
+            },
+        }
+    }
+    let Some(depth) = depth else {
+        eprintln!("perft needs a depth");
+        std::process::exit(1);
+    };
+    let board = match fen.as_deref().map(Board::from_fen) {
+        Some(Ok(board)) => board,
+        Some(Err(e)) => {
+            eprintln!("{}", e.annotate(fen.as_deref().unwrap_or_default()));
+            std::process::exit(1);
+        }
+        None => Board::starting_position(),
+    };
+    let results = (1..=depth)
+        .map(|depth| if divide { perft_divide(depth, &board) } else { perft_timed(depth, &board) })
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        });
+    if json {
+        print_json(&results);
+    } else {
+        for result in &results {
+            println!("{}", result);
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+fn print_json(results: &[casey_chess::utils::performance::PerftResult]) {
+    println!("{}", casey_chess::utils::performance::perft_json(results));
+}
+
+#[cfg(not(feature = "serde"))]
+fn print_json(_results: &[casey_chess::utils::performance::PerftResult]) {
+    eprintln!("--format json needs casey_chess built with the serde feature");
+    std::process::exit(1);
+}
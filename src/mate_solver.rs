@@ -0,0 +1,192 @@
+use crate::{board::Board, chess_move::Move, utils::notation::move_to_san};
+
+/// The longest mate solve_mate_in will look for, longer requests are searched to this depth.
+/// The search is full width so its cost grows with the number of legal moves to the power 2n.
+pub const MAX_MATE_DEPTH: u32 = 5;
+
+/// A forced mate for the side to move, as found by Board::solve_mate_in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MateProof {
+    /// The number of moves the attacker needs against the best defence
+    pub mate_in: u32,
+    /// The key move and the mate that follows every defence
+    pub solution: MateTree,
+    /// Other first moves that also force mate within the requested number of moves.
+    /// A sound problem has none.
+    pub cooks: Vec<Move>,
+}
+
+/// An attacking move and the mate that follows every reply to it.
+/// The attack gives mate when there are no defences.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MateTree {
+    pub attack: Move,
+    /// Every legal reply, in move generation order
+    pub defences: Vec<MateDefence>,
+}
+
+/// A defence and the quickest mate against it
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MateDefence {
+    pub defence: Move,
+    pub continuation: MateTree,
+}
+
+impl MateProof {
+    /// The first move of the solution
+    pub fn key_move(&self) -> &Move {
+        &self.solution.attack
+    }
+
+    /// Whether the key move is the only first move that forces mate
+    pub fn is_sound(&self) -> bool {
+        self.cooks.is_empty()
+    }
+
+    /// Writes the solution out in the layout used for published problems.
+    /// # Description
+    /// Moves are numbered from 1 whatever the move number of the position. The key is marked with "!",
+    /// each defence starts a line indented below the move it answers and cooks are listed last.
+    /// # Inputs/Outputs
+    /// - Input: board - The position the proof was found in
+    /// - Output: The solution in SAN, one line per defence
+    /// # Example
+    /// ``` Rust
+    /// let proof = board.solve_mate_in(2).unwrap();
+    /// print!("{}", proof.to_text(&board));
+    /// // 1. Ra6!
+    /// //     1... bxa6 2. b7#
+    /// //     1... Bh2 2. Rxa7#
+    /// // ...
+    /// ```
+    pub fn to_text(&self, board: &Board) -> String {
+        let mut text = format!("1. {}!\n", move_to_san(board, &self.solution.attack));
+        let mut board = board.clone();
+        write_defences(&mut board, &self.solution, 1, &mut text);
+        if !self.cooks.is_empty() {
+            let cooks = self.cooks.iter().map(|mv| move_to_san(&board, mv)).collect::<Vec<_>>();
+            text.push_str(&format!("Cooks: {}\n", cooks.join(", ")));
+        }
+        text
+    }
+}
+
+fn write_defences(board: &mut Board, tree: &MateTree, move_number: u32, text: &mut String) {
+    let attack = board.make_move(&tree.attack).unwrap();
+    let indent = "    ".repeat(move_number as usize);
+    for line in &tree.defences {
+        let defence = move_to_san(board, &line.defence);
+        let undo = board.make_move(&line.defence).unwrap();
+        let reply = move_to_san(board, &line.continuation.attack);
+        text.push_str(&format!("{}{}... {} {}. {}\n", indent, move_number, defence, move_number + 1, reply));
+        write_defences(board, &line.continuation, move_number + 1, text);
+        board.unmake_move(&line.defence, undo);
+    }
+    board.unmake_move(&tree.attack, attack);
+}
+
+impl Board {
+    /// Proves a forced mate for the side to move, or that there is none.
+    /// # Description
+    /// Every legal move of both sides is searched with no pruning, so a mate is never missed and
+    /// every first move that forces mate is found. The key is the move with the quickest forced mate,
+    /// any other move that mates within n moves is reported as a cook.
+    /// The fifty-move rule and repetitions are ignored, as they are in problems.
+    /// # Inputs/Outputs
+    /// - Input: n - The number of moves the side to move has to give mate, at most MAX_MATE_DEPTH
+    /// - Output: The key move and how every defence is mated, None if no move forces mate within n moves
+    /// # Example
+    /// ``` Rust
+    /// // Morphy's mate in two, solved by 1. Ra6!
+    /// let board = Board::from_fen("kbK5/pp6/1P6/8/8/8/8/R7 w - - 0 1").unwrap();
+    /// let proof = board.solve_mate_in(2).unwrap();
+    /// assert_eq!(proof.key_move().extended_algebraic(), "a1a6");
+    /// ```
+    pub fn solve_mate_in(&self, n: u32) -> Option<MateProof> {
+        let n = n.min(MAX_MATE_DEPTH);
+        let mut board = self.clone();
+        let mut keys = Vec::new();
+        for mv in board.generate_legal_moves() {
+            let undo = board.make_move(&mv).unwrap();
+            let mate_in = (1..=n).find(|&depth| defender_loses(&mut board, depth));
+            board.unmake_move(&mv, undo);
+            if let Some(mate_in) = mate_in {
+                keys.push((mv, mate_in));
+            }
+        }
+        let quickest = keys.iter().enumerate().min_by_key(|(_, (_, mate_in))| *mate_in)?.0;
+        let (key, mate_in) = keys.remove(quickest);
+        let solution = mate_tree(&mut board, key, mate_in);
+        Some(MateProof { mate_in, solution, cooks: keys.into_iter().map(|(mv, _)| mv).collect() })
+    }
+}
+
+/// Whether the side to move has a move that mates within n moves
+fn attacker_mates(board: &mut Board, n: u32) -> bool {
+    for mv in board.generate_pseudo_legal_moves() {
+        let Ok(undo) = board.make_move(&mv) else {
+            continue;
+        };
+        let mates = defender_loses(board, n);
+        board.unmake_move(&mv, undo);
+        if mates {
+            return true;
+        }
+    }
+    false
+}
+
+/// Whether the side to move, who was just attacked, is mated within the n attacking moves that include the last one
+fn defender_loses(board: &mut Board, n: u32) -> bool {
+    let in_check = board.king_in_check();
+    if n <= 1 && !in_check {
+        return false;
+    }
+    let mut has_defence = false;
+    for defence in board.generate_pseudo_legal_moves() {
+        let Ok(undo) = board.make_move(&defence) else {
+            continue;
+        };
+        has_defence = true;
+        let mated = n > 1 && attacker_mates(board, n - 1);
+        board.unmake_move(&defence, undo);
+        if !mated {
+            return false;
+        }
+    }
+    // With no legal reply the attack either mated or stalemated
+    has_defence || in_check
+}
+
+/// The quickest mating move for the side to move within n moves, and how many moves it takes
+fn quickest_mate(board: &mut Board, n: u32) -> Option<(Move, u32)> {
+    for depth in 1..=n {
+        for mv in board.generate_legal_moves() {
+            let undo = board.make_move(&mv).unwrap();
+            let mates = defender_loses(board, depth);
+            board.unmake_move(&mv, undo);
+            if mates {
+                return Some((mv, depth));
+            }
+        }
+    }
+    None
+}
+
+/// Builds the proof for an attack already known to mate in n
+fn mate_tree(board: &mut Board, attack: Move, n: u32) -> MateTree {
+    let undo = board.make_move(&attack).unwrap();
+    let mut defences = Vec::new();
+    for defence in board.generate_legal_moves() {
+        let defence_undo = board.make_move(&defence).unwrap();
+        let (reply, depth) = quickest_mate(board, n - 1).unwrap();
+        let continuation = mate_tree(board, reply, depth);
+        board.unmake_move(&defence, defence_undo);
+        defences.push(MateDefence { defence, continuation });
+    }
+    board.unmake_move(&attack, undo);
+    MateTree { attack, defences }
+}
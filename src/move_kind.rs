@@ -0,0 +1,17 @@
+use crate::{piece_type::PieceType, side::Side};
+
+
+/// What sort of move a legal move is, as found by Board::classify_move without playing it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveKind {
+    /// A move to an empty square that is not castling, en passant or a promotion
+    Quiet,
+    /// Takes the piece on the destination square
+    Capture(PieceType),
+    /// A pawn taking a pawn that just moved two squares past it
+    EnPassant,
+    /// The king castling towards a side
+    Castle(Side),
+    /// A pawn reaching the last rank, with or without a capture, and the piece it becomes
+    Promotion(PieceType),
+}
@@ -0,0 +1,21 @@
+use crate::{piece_type::PieceType, side::Side};
+
+
+/// What happened when a move was played with Board::move_piece.
+/// # Description
+/// Saves callers from comparing the board before and after the move to describe it,
+/// e.g. for SAN, move sounds in a UI or UCI output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MoveResult {
+    /// The type of the piece taken, a pawn for en passant
+    pub captured: Option<PieceType>,
+    /// Where the taken piece stood as (x, y), beside the destination for en passant, see Board::captured_square
+    pub captured_square: Option<(usize, usize)>,
+    /// The side castled towards, None for any other move
+    pub is_castle: Option<Side>,
+    pub is_en_passant: bool,
+    /// The piece a pawn became on reaching the last rank
+    pub promotion: Option<PieceType>,
+    /// Whether the opponent is in check after the move
+    pub gives_check: bool,
+}
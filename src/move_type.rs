@@ -1,15 +1,22 @@
-
-#[derive(Debug, Clone, Copy, PartialEq)]
+/// How a piece moves between two squares, as found by Piece::movement_type from the geometry alone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MoveType {
+    /// The piece can never move between the squares
     Illegal,
+    /// A pawn one square forward
     Pawn1,
+    /// A pawn two squares forward from its starting rank
     Pawn2,
+    /// A pawn one square diagonally forward, a capture or en passant
     PawnCapture,
     Rook,
     Knight,
     Bishop,
     Queen,
+    /// A king one square in any direction
     KingNormal,
+    /// A king two files towards the h-file from its home square
     KingCastleKingSide,
+    /// A king two files towards the a-file from its home square
     KingCastleQueenSide,
 }
@@ -0,0 +1,137 @@
+use crate::{bitboard::{self, Bitboard, FILE_A, PAWN_ATTACKS}, board::Board, color::Color, piece_type::PieceType};
+
+/// The weaknesses in one side's pawns, see Board::pawn_structure.
+/// Files are numbered from 0 for the a-file, squares are (x, y) as in the coords module.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PawnStructure {
+    /// The number of pawns on each file
+    pub pawns_per_file: [u8; 8],
+    /// Files holding two or more pawns
+    pub doubled: Vec<usize>,
+    /// Files whose pawns have no pawn of their own on either neighbouring file
+    pub isolated: Vec<usize>,
+    /// Pawns left behind by the pawns on the neighbouring files, whose square ahead is guarded by an enemy pawn
+    pub backward: Vec<(usize, usize)>,
+}
+
+impl Board {
+    /// Finds the doubled, isolated and backward pawns of one side.
+    /// # Description
+    /// Counts the pawns on each file once and reads the doubled and isolated files from the counts.
+    /// A pawn is backward when it is not isolated, every pawn of its own on the neighbouring files is further up
+    /// the board, so none can protect it as it advances, and the square in front of it is attacked by an enemy pawn.
+    /// # Inputs/Outputs
+    /// - Input: color - The side whose pawns to look at
+    /// - Output: The pawn counts per file, the weak files from the a-file and the backward pawns in square order from a1
+    /// # Example
+    /// ``` Rust
+    /// let board = Board::from_fen("4k3/8/8/8/8/2P5/P1P5/4K3 w - - 0 1").unwrap();
+    /// let structure = board.pawn_structure(Color::White);
+    /// assert_eq!(structure.doubled, vec![2]);
+    /// assert_eq!(structure.isolated, vec![0, 2]);
+    /// ```
+    pub fn pawn_structure(&self, color: Color) -> PawnStructure {
+        let pawns = self.pieces(PieceType::Pawn, color);
+        let enemy_pawns = self.pieces(PieceType::Pawn, color.opposite());
+        let mut structure = PawnStructure::default();
+        for (x, count) in structure.pawns_per_file.iter_mut().enumerate() {
+            *count = (pawns & FILE_A << x).count_ones() as u8;
+        }
+        let counts = structure.pawns_per_file;
+        let neighbours = |x: usize| (x > 0 && counts[x - 1] > 0) || (x < 7 && counts[x + 1] > 0);
+        structure.doubled = (0..8).filter(|&x| counts[x] > 1).collect();
+        structure.isolated = (0..8).filter(|&x| counts[x] > 0 && !neighbours(x)).collect();
+        structure.backward = bitboard::squares(pawns)
+            .map(bitboard::index_to_coords)
+            .filter(|&(x, y)| neighbours(x) && is_backward(color, x, y, pawns, enemy_pawns))
+            .collect();
+        structure
+    }
+
+    /// The pawns of one side that no enemy pawn can stop or capture on their way to promotion.
+    /// # Description
+    /// A pawn is passed when no enemy pawn stands ahead of it on its own file or either neighbouring file.
+    /// # Inputs/Outputs
+    /// - Input: color - The side whose pawns to look at
+    /// - Output: The squares of the passed pawns in square order from a1
+    /// # Example
+    /// ``` Rust
+    /// let board = Board::from_fen("4k3/8/1p6/8/P6P/8/8/4K3 w - - 0 1").unwrap();
+    /// // The b6 pawn watches a5, nothing stands in front of h4
+    /// assert_eq!(board.passed_pawns(Color::White), vec![(7, 3)]);
+    /// ```
+    pub fn passed_pawns(&self, color: Color) -> Vec<(usize, usize)> {
+        let enemy_pawns = self.pieces(PieceType::Pawn, color.opposite());
+        bitboard::squares(self.pieces(PieceType::Pawn, color))
+            .map(bitboard::index_to_coords)
+            .filter(|&(x, y)| enemy_pawns & front_span(color, x, y) == 0)
+            .collect()
+    }
+
+    /// Whether a pawn of the given color attacks the square now or could after advancing.
+    /// # Description
+    /// Looks for such pawns on the files either side of the square, on the ranks they still have to come down
+    /// (or up, for White) to reach it. Pawns only move forward, so a square none of them can reach is safe from pawns
+    /// for the rest of the game, unless a capture brings a pawn onto one of those files.
+    /// # Example
+    /// ``` Rust
+    /// let board = Board::from_fen("4k3/2p5/8/3N4/8/8/8/4K3 w - - 0 1").unwrap();
+    /// // c7-c6 would attack d5, nothing can reach e5
+    /// assert!(board.pawn_can_attack(3, 4, Color::Black));
+    /// assert!(!board.pawn_can_attack(4, 4, Color::Black));
+    /// ```
+    pub fn pawn_can_attack(&self, x: usize, y: usize, pawn_color: Color) -> bool {
+        let adjacent_files = front_span(pawn_color.opposite(), x, y) & !(FILE_A << x);
+        self.pieces(PieceType::Pawn, pawn_color) & adjacent_files != 0
+    }
+
+    /// The knights of one side on outposts: in the opponent's half, protected by a pawn and out of reach of enemy pawns.
+    /// # Description
+    /// For White the knight must be on rank 4, 5 or 6 and for Black on rank 5, 4 or 3,
+    /// defended by a pawn of its own side, with pawn_can_attack false for the opponent's pawns.
+    /// # Inputs/Outputs
+    /// - Input: color - The side whose knights to look at
+    /// - Output: The squares of the knights on outposts in square order from a1
+    /// # Example
+    /// ``` Rust
+    /// let board = Board::from_fen("4k3/pp3ppp/3p4/3N4/4P3/8/8/4K3 w - - 0 1").unwrap();
+    /// assert_eq!(board.knight_outposts(Color::White), vec![(3, 4)]);
+    /// ```
+    pub fn knight_outposts(&self, color: Color) -> Vec<(usize, usize)> {
+        let pawns = self.pieces(PieceType::Pawn, color);
+        let ranks = if color.is_white() { 3..=5 } else { 2..=4 };
+        bitboard::squares(self.pieces(PieceType::Knight, color))
+            .map(bitboard::index_to_coords)
+            .filter(|&(x, y)| {
+                let index = bitboard::square_index(x, y);
+                // A pawn of ours defends the knight from where an enemy pawn on the knight's square would attack
+                ranks.contains(&y) && PAWN_ATTACKS[color.opposite().index()][index] & pawns != 0 && !self.pawn_can_attack(x, y, color.opposite())
+            })
+            .collect()
+    }
+}
+
+/// The squares ahead of a pawn on (x, y) on its own file and the neighbouring files
+fn front_span(color: Color, x: usize, y: usize) -> Bitboard {
+    let files = (x.saturating_sub(1)..=(x + 1).min(7)).fold(0, |files, x| files | FILE_A << x);
+    let ahead = match color {
+        Color::White if y == 7 => 0,
+        Color::White => !0 << ((y + 1) * 8),
+        Color::Black => (1 << (y * 8)) - 1,
+    };
+    files & ahead
+}
+
+/// Whether the pawn on (x, y) has no pawn of its own beside or behind it on the neighbouring files
+/// and an enemy pawn guards the square in front of it
+fn is_backward(color: Color, x: usize, y: usize, pawns: Bitboard, enemy_pawns: Bitboard) -> bool {
+    let Some(stop_y) = (if color.is_white() { Some(y + 1).filter(|y| *y < 8) } else { y.checked_sub(1) }) else {
+        return false;
+    };
+    let supported = bitboard::squares(pawns).map(bitboard::index_to_coords).any(|(px, py)| {
+        px.abs_diff(x) == 1 && if color.is_white() { py <= y } else { py >= y }
+    });
+    let stop = bitboard::square_index(x, stop_y);
+    !supported && PAWN_ATTACKS[color.index()][stop] & enemy_pawns != 0
+}
@@ -1,6 +1,6 @@
 use crate::{color::Color, move_type::MoveType, piece_type::PieceType};
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Piece {
     piece: PieceType,
     color: Color,
@@ -11,12 +11,12 @@ impl Piece {
         Piece { piece, color }
     }
 
-    pub fn get_type(&self) -> &PieceType {
-        &self.piece
+    pub fn get_type(&self) -> PieceType {
+        self.piece
     }
 
-    pub fn get_color(&self) -> &Color {
-        &self.color
+    pub fn get_color(&self) -> Color {
+        self.color
     }
 
     pub fn is_white(&self) -> bool {
@@ -41,6 +41,24 @@ impl Piece {
         }
     }
 
+    /// The chess symbol of the piece, outlined for White and filled for Black, e.g. '♔' or '♟'
+    pub fn unicode_char(&self) -> char {
+        match (self.color, self.piece) {
+            (Color::White, PieceType::King) => '♔',
+            (Color::White, PieceType::Queen) => '♕',
+            (Color::White, PieceType::Rook) => '♖',
+            (Color::White, PieceType::Bishop) => '♗',
+            (Color::White, PieceType::Knight) => '♘',
+            (Color::White, PieceType::Pawn) => '♙',
+            (Color::Black, PieceType::King) => '♚',
+            (Color::Black, PieceType::Queen) => '♛',
+            (Color::Black, PieceType::Rook) => '♜',
+            (Color::Black, PieceType::Bishop) => '♝',
+            (Color::Black, PieceType::Knight) => '♞',
+            (Color::Black, PieceType::Pawn) => '♟',
+        }
+    }
+
     pub fn from_fen(fen: char) -> Option<Piece> {
         let color = if fen.is_uppercase() {
             Color::White
@@ -59,12 +77,27 @@ impl Piece {
         Some(Piece::new(piece, color))
     }
 
-    /// Checks if the move is possible at all
-    /// Simply checks if the given piece could theoretically move to the target square
-    /// Does not check for check, pinned pieces, pieces in the way, etc.
-    pub fn check_move(&self, from_x: usize, from_y:usize, to_x: usize, to_y: usize) -> MoveType {
-        // check if the move is on the board
-        // required to ensire as i8 cast is safe
+    /// How the piece would move from one square to another on an empty board.
+    /// # Description
+    /// Squares are (x, y) as described in coords. Only the geometry is checked: sliders must move along a true
+    /// rank, file or diagonal, knights jump, kings step one square, and pawns step forward for their color,
+    /// two squares from their starting rank, or one square diagonally forward to capture.
+    /// A king on its standard home square moving two files along the back rank is castling, Chess960 is not supported.
+    /// Blocking pieces, captures, checks and castling rights are left to the board.
+    /// Squares off the board and staying on the same square are Illegal.
+    /// # Inputs/Outputs
+    /// - Input: from: (usize, usize) - The square the piece stands on
+    /// - Input: to: (usize, usize) - The square it would move to
+    /// - Output: The kind of movement, Illegal if the piece can never make it
+    /// # Example
+    /// ``` Rust
+    /// let pawn = Piece::new(PieceType::Pawn, Color::Black);
+    /// assert_eq!(pawn.movement_type((4, 6), (4, 4)), MoveType::Pawn2);
+    /// assert_eq!(pawn.movement_type((4, 6), (4, 7)), MoveType::Illegal);
+    /// ```
+    pub fn movement_type(&self, from: (usize, usize), to: (usize, usize)) -> MoveType {
+        let ((from_x, from_y), (to_x, to_y)) = (from, to);
+        // Keeps the i8 casts below in range
         if from_x > 7 || from_y > 7 || to_x > 7 || to_y > 7 {
             return MoveType::Illegal;
         }
@@ -105,10 +138,10 @@ impl Piece {
     }
 
     fn is_legal_rook_move(&self, from_x: usize, from_y:usize, to_x: usize, to_y: usize) -> MoveType {
-        if from_x != to_x && from_y != to_y {
-            return MoveType::Illegal;
+        match ray_delta(from_x, from_y, to_x, to_y) {
+            Some((dx, dy)) if dx == 0 || dy == 0 => MoveType::Rook,
+            _ => MoveType::Illegal,
         }
-        MoveType::Rook
     }
 
     fn is_legal_knight_move(&self, from_x: usize, from_y:usize, to_x: usize, to_y: usize) -> MoveType {
@@ -121,25 +154,23 @@ impl Piece {
     }
 
     fn is_legal_bishop_move(&self, from_x: usize, from_y:usize, to_x: usize, to_y: usize) -> MoveType {
-        if (to_x as i32 - from_x as i32).abs() != (to_y as i32 - from_y as i32).abs() {
-            return MoveType::Illegal;
+        match ray_delta(from_x, from_y, to_x, to_y) {
+            Some((dx, dy)) if dx != 0 && dy != 0 => MoveType::Bishop,
+            _ => MoveType::Illegal,
         }
-        MoveType::Bishop
     }
 
     fn is_legal_queen_move(&self, from_x: usize, from_y:usize, to_x: usize, to_y: usize) -> MoveType {
-        if from_x != to_x && from_y != to_y {
-            if (to_x as i32 - from_x as i32).abs() != (to_y as i32 - from_y as i32).abs() {
-                return MoveType::Illegal;
-            }
+        match ray_delta(from_x, from_y, to_x, to_y) {
+            Some(_) => MoveType::Queen,
+            None => MoveType::Illegal,
         }
-        MoveType::Queen
     }
 
     fn is_legal_king_move(&self, from_x: usize, from_y:usize, to_x: usize, to_y: usize) -> MoveType {
         let x_diff = (to_x as i32 - from_x as i32).abs();
         let y_diff = (to_y as i32 - from_y as i32).abs();
-        if x_diff <= 1 && y_diff <= 1 {
+        if x_diff <= 1 && y_diff <= 1 && (x_diff, y_diff) != (0, 0) {
             return MoveType::KingNormal;
         }
         // check for castling
@@ -154,3 +185,13 @@ impl Piece {
         MoveType::Illegal
     }
 }
+
+/// The (x, y) distance from one square to another if they are on the same rank, file or diagonal.
+/// None for any other move, including knight jumps and moving to the same square,
+/// so a sliding move is only walked square by square along a true ray.
+pub(crate) fn ray_delta(from_x: usize, from_y: usize, to_x: usize, to_y: usize) -> Option<(i8, i8)> {
+    let dx = to_x as i8 - from_x as i8;
+    let dy = to_y as i8 - from_y as i8;
+    let is_ray = (dx == 0) != (dy == 0) || (dx != 0 && dx.abs() == dy.abs());
+    is_ray.then_some((dx, dy))
+}
@@ -1,7 +1,8 @@
 use std::fmt::{self, Display, Formatter};
 
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PieceType {
     Pawn,
     Rook,
@@ -11,6 +12,45 @@ pub enum PieceType {
     King,
 }
 
+impl PieceType {
+    /// Index of the piece type for tables stored per piece type
+    pub fn index(&self) -> usize {
+        match self {
+            PieceType::Pawn => 0,
+            PieceType::Rook => 1,
+            PieceType::Knight => 2,
+            PieceType::Bishop => 3,
+            PieceType::Queen => 4,
+            PieceType::King => 5,
+        }
+    }
+
+    /// Lower case English name of the piece type, for messages shown to the player
+    pub fn name(&self) -> &'static str {
+        match self {
+            PieceType::Pawn => "pawn",
+            PieceType::Rook => "rook",
+            PieceType::Knight => "knight",
+            PieceType::Bishop => "bishop",
+            PieceType::Queen => "queen",
+            PieceType::King => "king",
+        }
+    }
+
+    /// Material value of the piece type in centipawns
+    /// The king is given no value as it can never be traded
+    pub fn to_centipawns(&self) -> i32 {
+        match self {
+            PieceType::Pawn => 100,
+            PieceType::Rook => 500,
+            PieceType::Knight => 300,
+            PieceType::Bishop => 300,
+            PieceType::Queen => 900,
+            PieceType::King => 0,
+        }
+    }
+}
+
 impl Display for PieceType {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         write!(f, "{}", match self {
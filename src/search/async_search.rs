@@ -0,0 +1,151 @@
+//! Searches that can be awaited from async code.
+//! # Description
+//! The search is CPU bound and would block an async executor, so each analysis runs on its own worker
+//! thread and the caller awaits a future that completes when the worker does.
+//! The future is woken by the worker itself, so it works under any executor and no runtime is needed.
+//! Dropping the future before it completes raises the search's stop flag and the worker ends at its next check.
+
+use std::{future::Future, pin::Pin, sync::{atomic::{AtomicBool, Ordering}, Arc, Mutex}, task::{Context, Poll, Waker}, thread};
+
+use crate::{board::Board, errors::chess_error::ChessError};
+
+use super::{search_limits::SearchLimits, searcher::{SearchResult, Searcher}};
+
+/// The outcome of an analysis, the same as a blocking search
+pub type Analysis = SearchResult;
+
+/// Analyse a position without blocking the executor.
+/// # Description
+/// Runs the search on a worker thread. Dropping the returned future stops the search.
+/// # Inputs/Outputs
+/// - Input: fen - The position to analyse
+/// - Input: limits - When to stop searching, unlimited searches only end when the future is dropped
+/// - Output: The search result, or the error if the FEN could not be parsed
+/// # Example
+/// ``` Rust
+/// let analysis = analyze_async(fen, SearchLimits::depth(6)).await?;
+/// println!("{:?} {}", analysis.best_move, analysis.score);
+/// ```
+pub async fn analyze_async(fen: String, limits: SearchLimits) -> Result<Analysis, ChessError> {
+    AnalysisTask::spawn(&fen, limits)?.await
+}
+
+/// What the worker and the future share
+#[derive(Default)]
+struct State {
+    result: Option<SearchResult>,
+    /// Nodes the worker searched, kept even when the result is never collected
+    nodes: Option<u64>,
+    finished: bool,
+    waker: Option<Waker>,
+}
+
+/// A search running on a worker thread, completed by awaiting it.
+/// # Description
+/// Dropping the task raises the search's stop flag.
+/// Use status to watch the worker after the task has been dropped.
+pub struct AnalysisTask {
+    state: Arc<Mutex<State>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl AnalysisTask {
+    /// Parse the position and start searching it on a new worker thread
+    pub fn spawn(fen: &str, limits: SearchLimits) -> Result<AnalysisTask, ChessError> {
+        let board = Board::from_fen(fen)?;
+        let state = Arc::new(Mutex::new(State::default()));
+        let stop = Arc::new(AtomicBool::new(false));
+        let completion = Completion(state.clone());
+        let worker_stop = stop.clone();
+        thread::Builder::new()
+            .name("analysis".to_string())
+            .spawn(move || {
+                let result = Searcher::new(limits, worker_stop).search(&board);
+                completion.finish(result);
+            })
+            .map_err(|_| ChessError::WorkerLost)?;
+        Ok(AnalysisTask { state, stop })
+    }
+
+    /// Ask the search to finish early, the task still completes with the best move found so far
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+
+    /// A handle for watching the worker, which outlives the task
+    pub fn status(&self) -> AnalysisStatus {
+        AnalysisStatus { state: self.state.clone(), stop: self.stop.clone() }
+    }
+}
+
+impl Future for AnalysisTask {
+    type Output = Result<Analysis, ChessError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(result) = state.result.take() {
+            return Poll::Ready(Ok(result));
+        }
+        if state.finished {
+            return Poll::Ready(Err(ChessError::WorkerLost));
+        }
+        state.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+impl Drop for AnalysisTask {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Whether an analysis worker has been stopped and has finished
+#[derive(Clone)]
+pub struct AnalysisStatus {
+    state: Arc<Mutex<State>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl AnalysisStatus {
+    /// True once the search has been asked to stop
+    pub fn is_stopped(&self) -> bool {
+        self.stop.load(Ordering::Relaxed)
+    }
+
+    /// True once the worker thread is done with the search
+    pub fn is_finished(&self) -> bool {
+        self.state.lock().unwrap().finished
+    }
+
+    /// The nodes the worker searched, None until it has finished or if it panicked
+    pub fn nodes(&self) -> Option<u64> {
+        self.state.lock().unwrap().nodes
+    }
+}
+
+/// Owned by the worker, marks the analysis finished and wakes the future when dropped.
+/// Because it is dropped while unwinding too, a panicking search completes the future with WorkerLost
+/// rather than leaving it pending forever.
+struct Completion(Arc<Mutex<State>>);
+
+impl Completion {
+    fn finish(self, result: SearchResult) {
+        let mut state = self.0.lock().unwrap();
+        state.nodes = Some(result.nodes);
+        state.result = Some(result);
+    }
+}
+
+impl Drop for Completion {
+    fn drop(&mut self) {
+        let waker = {
+            let mut state = self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            state.finished = true;
+            state.waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
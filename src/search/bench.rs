@@ -0,0 +1,145 @@
+use std::{fmt, time::Duration};
+
+use crate::board::Board;
+
+use super::{search_limits::SearchLimits, search_stats::SearchStats, searcher::{analyze, SearchResult}};
+
+/// Depth searched by bench when none is given
+pub const DEFAULT_BENCH_DEPTH: u32 = 4;
+
+/// Positions searched by bench, the perft test suite covers openings, castling, en passant and promotions
+pub const BENCH_POSITIONS: [&str; 5] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+];
+
+/// The combined outcome of searching every bench position.
+#[derive(Debug, Clone)]
+pub struct BenchResult {
+    /// The search result of each position, in the order of BENCH_POSITIONS
+    pub results: Vec<SearchResult>,
+    /// Nodes visited across all positions
+    pub nodes: u64,
+    /// Time spent searching across all positions
+    pub elapsed: Duration,
+    /// Search counters summed across all positions
+    pub stats: SearchStats,
+}
+
+impl BenchResult {
+    /// Geometric mean across the positions of the time taken to complete an iteration of the given depth
+    /// # Description
+    /// None if any position did not complete that depth.
+    pub fn time_to_depth(&self, depth: u32) -> Option<Duration> {
+        let times = self.results.iter()
+            .map(|result| result.iterations.iter().find(|report| report.depth == depth).map(|report| report.elapsed.as_secs_f64()))
+            .collect::<Option<Vec<_>>>()?;
+        geometric_mean(&times).map(Duration::from_secs_f64)
+    }
+
+    /// Geometric mean of the effective branching factor of each position
+    pub fn effective_branching_factor(&self) -> Option<f64> {
+        let factors = self.results.iter()
+            .filter_map(|result| effective_branching_factor(&result.iterations.iter().map(|report| report.iteration_nodes).collect::<Vec<_>>()))
+            .collect::<Vec<_>>();
+        geometric_mean(&factors)
+    }
+
+    /// Nodes searched per second, 0 if no time was measured
+    pub fn nps(&self) -> u64 {
+        let micros = self.elapsed.as_micros();
+        if micros == 0 {
+            return 0;
+        }
+        (self.nodes as u128 * 1_000_000 / micros) as u64
+    }
+}
+
+/// The average growth in nodes from one iteration to the next.
+/// # Description
+/// The geometric mean of the ratios between the node counts of successive depths,
+/// which reduces to (last / first) ^ (1 / (iterations - 1)).
+/// None with fewer than two iterations or when an iteration visited no nodes.
+/// # Example
+/// ``` Rust
+/// assert_eq!(effective_branching_factor(&[10, 40, 160]), Some(4.0));
+/// ```
+pub fn effective_branching_factor(iteration_nodes: &[u64]) -> Option<f64> {
+    if iteration_nodes.len() < 2 || iteration_nodes.contains(&0) {
+        return None;
+    }
+    let ratios = iteration_nodes.windows(2).map(|pair| pair[1] as f64 / pair[0] as f64).collect::<Vec<_>>();
+    geometric_mean(&ratios)
+}
+
+/// The geometric mean of positive values, None for an empty slice
+pub fn geometric_mean(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    Some((values.iter().map(|value| value.ln()).sum::<f64>() / values.len() as f64).exp())
+}
+
+/// Search each bench position to a fixed depth.
+/// # Description
+/// The node count only depends on the search, so it doubles as a signature that changes
+/// whenever a change alters the shape of the tree.
+/// # Inputs/Outputs
+/// - Input: depth: u32 - The depth to search each position to
+/// - Output: The per position results and their totals
+/// # Example
+/// ``` Rust
+/// let result = bench(2);
+/// println!("{} nodes {} nps", result.nodes, result.nps());
+/// ```
+pub fn bench(depth: u32) -> BenchResult {
+    let mut bench = BenchResult {
+        results: Vec::new(),
+        nodes: 0,
+        elapsed: Duration::ZERO,
+        stats: SearchStats::default(),
+    };
+    for fen in BENCH_POSITIONS {
+        let board = Board::from_fen(fen).expect("bench positions are valid");
+        let result = analyze(&board, SearchLimits::depth(depth));
+        bench.nodes += result.nodes;
+        bench.elapsed += result.elapsed;
+        bench.stats.add(&result.stats);
+        bench.results.push(result);
+    }
+    bench
+}
+
+/// Prints a line per position with the time to reach each depth and its branching factor,
+/// followed by the geometric means across the suite and the totals
+impl fmt::Display for BenchResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let depths = self.results.iter().map(|result| result.depth).max().unwrap_or(0);
+        for (index, result) in self.results.iter().enumerate() {
+            write!(f, "position {:>2} {:>10} nodes", index + 1, result.nodes)?;
+            for report in &result.iterations {
+                write!(f, "  d{} {:>8.1}ms", report.depth, report.elapsed.as_secs_f64() * 1000.0)?;
+            }
+            let nodes = result.iterations.iter().map(|report| report.iteration_nodes).collect::<Vec<_>>();
+            match effective_branching_factor(&nodes) {
+                Some(ebf) => writeln!(f, "  ebf {:.2}", ebf)?,
+                None => writeln!(f, "  ebf -")?,
+            }
+        }
+        write!(f, "geometric mean        ")?;
+        for depth in 1..=depths {
+            if let Some(time) = self.time_to_depth(depth) {
+                write!(f, "  d{} {:>8.1}ms", depth, time.as_secs_f64() * 1000.0)?;
+            }
+        }
+        match self.effective_branching_factor() {
+            Some(ebf) => writeln!(f, "  ebf {:.2}", ebf)?,
+            None => writeln!(f, "  ebf -")?,
+        }
+        writeln!(f, "{}", self.stats)?;
+        write!(f, "{} nodes {} ms {} nps", self.nodes, self.elapsed.as_millis(), self.nps())
+    }
+}
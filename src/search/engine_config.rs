@@ -0,0 +1,48 @@
+use super::{search_limits::SearchLimits, transposition_table::DEFAULT_HASH_MB};
+
+/// How an engine playing one side of a Game searches.
+/// # Description
+/// Each side of a game can have its own config, so a handicapped computer opponent
+/// and a full strength hint engine can be set up for the same game.
+/// The memory cap bounds every table the search allocates, which today is only the transposition table,
+/// so a hash size above the cap is cut down to fit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EngineConfig {
+    /// The limits every search for this side uses
+    pub limits: SearchLimits,
+    /// Size of the transposition table in megabytes
+    pub hash_mb: usize,
+    /// The most memory in megabytes the search tables may use together, None for no cap
+    pub max_memory_mb: Option<usize>,
+}
+
+impl Default for EngineConfig {
+    fn default() -> Self {
+        EngineConfig { limits: SearchLimits::default(), hash_mb: DEFAULT_HASH_MB, max_memory_mb: None }
+    }
+}
+
+impl EngineConfig {
+    /// A config that searches every move to a fixed depth
+    pub fn depth(depth: u32) -> EngineConfig {
+        EngineConfig { limits: SearchLimits::depth(depth), ..Default::default() }
+    }
+
+    /// A config that thinks for a fixed time per move, in milliseconds
+    pub fn movetime(movetime: u64) -> EngineConfig {
+        EngineConfig { limits: SearchLimits::movetime(movetime), ..Default::default() }
+    }
+
+    /// The transposition table size to allocate, hash_mb cut down to fit the memory cap
+    pub fn hash_budget_mb(&self) -> usize {
+        self.clamp_hash_mb(self.hash_mb)
+    }
+
+    /// A requested transposition table size cut down to fit the memory cap
+    pub fn clamp_hash_mb(&self, megabytes: usize) -> usize {
+        match self.max_memory_mb {
+            Some(max) => megabytes.min(max),
+            None => megabytes,
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use std::fmt::{self, Display, Formatter};
+
+/// Memory held by the search tables, in bytes.
+/// # Description
+/// Counts what the tables have actually allocated, not what was asked for.
+/// The transposition table is the only structure whose size is configured,
+/// the rest of the search state such as move lists and root moves is a few kilobytes and is not counted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryReport {
+    pub transposition_table: usize,
+}
+
+impl MemoryReport {
+    /// Bytes allocated across all tables
+    pub fn total(&self) -> usize {
+        self.transposition_table
+    }
+}
+
+/// e.g. "transposition table 16.0 MB, total 16.0 MB"
+impl Display for MemoryReport {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let mb = |bytes: usize| bytes as f64 / (1024.0 * 1024.0);
+        write!(f, "transposition table {:.1} MB, total {:.1} MB", mb(self.transposition_table), mb(self.total()))
+    }
+}
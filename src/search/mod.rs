@@ -0,0 +1,11 @@
+
+pub mod search_limits;
+pub mod searcher;
+pub mod search_stats;
+pub mod bench;
+pub mod root_moves;
+pub mod engine_config;
+pub mod transposition_table;
+pub mod memory_report;
+#[cfg(feature = "async")]
+pub mod async_search;
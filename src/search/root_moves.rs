@@ -0,0 +1,75 @@
+use crate::{board::Board, chess_move::Move};
+
+/// A legal move at the root of the search and what the search has learned about it.
+#[derive(Debug, Clone)]
+pub struct RootMove {
+    pub mv: Move,
+    /// Score from the last completed iteration, from the side to move's perspective
+    pub score: i32,
+    /// Score found so far in the iteration being searched
+    pub(crate) pending_score: i32,
+    /// Nodes spent below this move across all iterations
+    pub nodes: u64,
+}
+
+/// The legal moves at the root, kept across iterations.
+/// # Description
+/// Moves start ordered captures first and are re-sorted by score after every completed iteration,
+/// so the best move of one iteration is searched first in the next.
+/// The best move's score is exact, or the best n with Searcher::with_multi_pv(n),
+/// the others are upper bounds from alpha-beta and may tie with them.
+#[derive(Debug, Clone, Default)]
+pub struct RootMoves {
+    moves: Vec<RootMove>,
+}
+
+impl RootMoves {
+    /// The legal moves of a position, captures and promotions first as sorted by Board::order_moves
+    pub fn new(board: &Board) -> RootMoves {
+        let mut moves = board.generate_legal_moves();
+        board.order_moves(&mut moves);
+        RootMoves {
+            moves: moves.into_iter().map(|mv| RootMove { mv, score: 0, pending_score: 0, nodes: 0 }).collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.moves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+
+    /// The move searched first, the best move once an iteration has completed
+    pub fn first(&self) -> Option<&RootMove> {
+        self.moves.first()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &RootMove> {
+        self.moves.iter()
+    }
+
+    pub(crate) fn get_mut(&mut self, index: usize) -> &mut RootMove {
+        &mut self.moves[index]
+    }
+
+    /// Drop the moves not in the given list, e.g. to compare a few moves at the same depth
+    pub(crate) fn retain(&mut self, keep: &[Move]) {
+        self.moves.retain(|root_move| keep.contains(&root_move.mv));
+    }
+
+    /// Keep the scores of a fully searched iteration and sort the best move first.
+    /// The sort is stable so the move that first reached the best score stays ahead of later moves that tie with it.
+    pub(crate) fn complete_iteration(&mut self) {
+        for root_move in &mut self.moves {
+            root_move.score = root_move.pending_score;
+        }
+        self.moves.sort_by_key(|root_move| -root_move.score);
+    }
+
+    /// Every root move with its score, best first
+    pub fn scores(&self) -> Vec<(Move, i32)> {
+        self.moves.iter().map(|root_move| (root_move.mv.clone(), root_move.score)).collect()
+    }
+}
@@ -0,0 +1,151 @@
+use std::time::Duration;
+
+use crate::color::Color;
+
+/// Depth searched when a go command gives no limits at all
+pub const DEFAULT_DEPTH: u32 = 4;
+/// Time kept in reserve when thinking on the clock, to cover I/O latency
+const MOVE_OVERHEAD_MS: u64 = 50;
+/// Number of moves the remaining clock time is assumed to be shared across
+const DEFAULT_MOVES_TO_GO: u64 = 30;
+
+/// The limits a search must respect.
+/// # Description
+/// Every provided limit is a stop condition and the search stops as soon as any of them is hit.
+/// For example `go depth 20 movetime 5000` stops at depth 20 or after 5 seconds, whichever comes first.
+/// `infinite` only disables the clock based time allocation, explicit depth, node and time caps still apply.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchLimits {
+    pub depth: Option<u32>,
+    pub nodes: Option<u64>,
+    pub movetime: Option<u64>,
+    pub mate: Option<u32>,
+    pub wtime: Option<u64>,
+    pub btime: Option<u64>,
+    pub winc: Option<u64>,
+    pub binc: Option<u64>,
+    pub movestogo: Option<u64>,
+    pub infinite: bool,
+}
+
+impl SearchLimits {
+    /// Parse the parameters of a UCI go command.
+    /// # Description
+    /// Parameters may be given in any order.
+    /// If a parameter is repeated the first value is kept and a warning is logged.
+    /// Unknown or malformed parameters are logged and skipped.
+    /// # Inputs/Outputs
+    /// - Input: options: &str - Everything after "go", e.g. "depth 20 movetime 5000"
+    /// - Output: The parsed limits
+    /// # Example
+    /// ``` Rust
+    /// let limits = SearchLimits::from_go("movetime 5000 depth 20");
+    /// assert_eq!(limits.depth, Some(20));
+    /// assert_eq!(limits.movetime, Some(5000));
+    /// ```
+    pub fn from_go(options: &str) -> SearchLimits {
+        let mut limits = SearchLimits::default();
+        let mut tokens = options.split_whitespace();
+        while let Some(token) = tokens.next() {
+            let field = match token {
+                "infinite" => {
+                    if limits.infinite {
+                        log::warn!("Ignoring duplicate go parameter: infinite");
+                    }
+                    limits.infinite = true;
+                    continue;
+                }
+                "depth" | "nodes" | "movetime" | "mate" | "wtime" | "btime" | "winc" | "binc" | "movestogo" => token,
+                _ => {
+                    log::warn!("Ignoring unsupported go parameter: {}", token);
+                    continue;
+                }
+            };
+            let value = match tokens.next().map(|value| value.parse::<u64>()) {
+                Some(Ok(value)) => value,
+                _ => {
+                    log::warn!("Missing or invalid value for go parameter: {}", field);
+                    continue;
+                }
+            };
+            let first = match field {
+                "depth" => Self::set_once(&mut limits.depth, value as u32),
+                "nodes" => Self::set_once(&mut limits.nodes, value),
+                "movetime" => Self::set_once(&mut limits.movetime, value),
+                "mate" => Self::set_once(&mut limits.mate, value as u32),
+                "wtime" => Self::set_once(&mut limits.wtime, value),
+                "btime" => Self::set_once(&mut limits.btime, value),
+                "winc" => Self::set_once(&mut limits.winc, value),
+                "binc" => Self::set_once(&mut limits.binc, value),
+                _ => Self::set_once(&mut limits.movestogo, value),
+            };
+            if !first {
+                log::warn!("Ignoring duplicate go parameter: {}", field);
+            }
+        }
+        limits
+    }
+
+    /// Sets the value if it has not been set yet, returns false if it was already set
+    fn set_once<T>(slot: &mut Option<T>, value: T) -> bool {
+        if slot.is_some() {
+            return false;
+        }
+        *slot = Some(value);
+        true
+    }
+
+    /// Limits that only cap the search depth
+    pub fn depth(depth: u32) -> SearchLimits {
+        SearchLimits { depth: Some(depth), ..Default::default() }
+    }
+
+    /// Limits that only cap the time spent searching, in milliseconds
+    pub fn movetime(movetime: u64) -> SearchLimits {
+        SearchLimits { movetime: Some(movetime), ..Default::default() }
+    }
+
+    /// The deepest iteration the search may start
+    /// # Description
+    /// Uses the depth limit if given, otherwise a mate target bounds the depth at 2N - 1 plies.
+    /// When nothing at all limits the search the default depth is used so a bare "go" still returns.
+    pub fn max_depth(&self, max: u32) -> u32 {
+        let mut depth = max;
+        if let Some(limit) = self.depth {
+            depth = depth.min(limit.max(1));
+        }
+        if let Some(mate) = self.mate {
+            depth = depth.min((2 * mate).saturating_sub(1).max(1));
+        }
+        if self.is_unbounded() {
+            depth = depth.min(DEFAULT_DEPTH);
+        }
+        depth
+    }
+
+    /// The time the search may use, if it is limited by time at all
+    /// # Description
+    /// movetime is used as given. With a clock the budget is an even share of the remaining time
+    /// plus most of the increment, kept under the time left minus a safety margin.
+    /// When both are present the smaller budget wins.
+    pub fn time_budget(&self, color: Color) -> Option<Duration> {
+        let mut budget = self.movetime;
+        let (time, inc) = match color {
+            Color::White => (self.wtime, self.winc.unwrap_or(0)),
+            Color::Black => (self.btime, self.binc.unwrap_or(0)),
+        };
+        if let (Some(time), false) = (time, self.infinite) {
+            let moves_to_go = self.movestogo.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+            let usable = time.saturating_sub(MOVE_OVERHEAD_MS);
+            let clock_budget = (time / moves_to_go + inc * 3 / 4).min(usable).max(1);
+            budget = Some(budget.map_or(clock_budget, |movetime| movetime.min(clock_budget)));
+        }
+        budget.map(Duration::from_millis)
+    }
+
+    /// True when no parameter limits the search
+    fn is_unbounded(&self) -> bool {
+        !self.infinite && self.depth.is_none() && self.nodes.is_none() && self.movetime.is_none()
+            && self.mate.is_none() && self.wtime.is_none() && self.btime.is_none()
+    }
+}
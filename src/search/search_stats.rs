@@ -0,0 +1,61 @@
+use std::fmt;
+
+/// Counters collected during a single search, for tuning and debugging.
+/// # Description
+/// Every counter is a plain increment on the searcher, they are zeroed at the start of each search.
+/// Counters for features the search does not use yet stay at zero.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SearchStats {
+    /// Nodes visited below the root
+    pub nodes: u64,
+    /// Calls to the move generator
+    pub movegen_calls: u64,
+    /// Pseudo legal moves produced by the move generator
+    pub moves_generated: u64,
+    /// Generated moves thrown away because they left the king in check
+    pub legality_rejections: u64,
+    /// Transposition table lookups
+    pub tt_probes: u64,
+    /// Transposition table lookups that found the position
+    pub tt_hits: u64,
+    /// Nodes cut off by a null move search
+    pub null_move_cutoffs: u64,
+    /// Late move reductions that had to be searched again at full depth
+    pub lmr_researches: u64,
+    /// Quiescence nodes that returned the static evaluation
+    pub qsearch_stand_pats: u64,
+}
+
+impl SearchStats {
+    /// Adds the counters of another search to these ones
+    pub fn add(&mut self, other: &SearchStats) {
+        self.nodes += other.nodes;
+        self.movegen_calls += other.movegen_calls;
+        self.moves_generated += other.moves_generated;
+        self.legality_rejections += other.legality_rejections;
+        self.tt_probes += other.tt_probes;
+        self.tt_hits += other.tt_hits;
+        self.null_move_cutoffs += other.null_move_cutoffs;
+        self.lmr_researches += other.lmr_researches;
+        self.qsearch_stand_pats += other.qsearch_stand_pats;
+    }
+}
+
+/// Prints the counters as space separated name value pairs, suitable for a UCI "info string"
+impl fmt::Display for SearchStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "nodes {} movegen {} generated {} rejected {} ttprobes {} tthits {} nullcuts {} lmrresearches {} standpats {}",
+            self.nodes,
+            self.movegen_calls,
+            self.moves_generated,
+            self.legality_rejections,
+            self.tt_probes,
+            self.tt_hits,
+            self.null_move_cutoffs,
+            self.lmr_researches,
+            self.qsearch_stand_pats,
+        )
+    }
+}
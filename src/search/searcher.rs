@@ -0,0 +1,467 @@
+use std::{sync::{atomic::{AtomicBool, Ordering}, Arc}, time::{Duration, Instant}};
+
+use crate::{board::Board, chess_move::Move};
+
+use super::{memory_report::MemoryReport, root_moves::RootMoves, search_limits::SearchLimits, search_stats::SearchStats, transposition_table::{pack_move, Bound, TranspositionTable, TtEntry, ANALYSIS_HASH_MB}};
+
+/// Score given to being checkmated, adjusted by ply so shorter mates score higher
+pub const MATE_SCORE: i32 = 100_000;
+/// The deepest iteration the search will attempt
+pub const MAX_DEPTH: u32 = 64;
+/// How often (in nodes) the clock is checked
+const TIME_CHECK_INTERVAL: u64 = 256;
+/// Budgets below this many milliseconds use a single depth 1 search
+pub const PANIC_BUDGET_MS: u64 = 25;
+
+/// The outcome of a search.
+#[derive(Debug, Clone)]
+pub struct SearchResult {
+    /// Best move found, None if the side to move has no legal moves
+    pub best_move: Option<Move>,
+    /// Score of the best move in centipawns from the side to move's perspective
+    pub score: i32,
+    /// Evaluation of the root position itself, from the side to move's perspective and without searching,
+    /// made by the same evaluation the search uses at its leaves
+    pub static_eval: i32,
+    /// Deepest fully completed iteration
+    pub depth: u32,
+    /// Nodes visited across all iterations
+    pub nodes: u64,
+    /// Wall clock time spent searching
+    pub elapsed: Duration,
+    /// Counters collected across all iterations
+    pub stats: SearchStats,
+    /// The root moves as ordered after the last completed iteration
+    pub root_moves: RootMoves,
+    /// A report for every completed iteration, shallowest first
+    pub iterations: Vec<DepthReport>,
+}
+
+/// What the search knows when an iteration of iterative deepening completes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DepthReport {
+    pub depth: u32,
+    /// Score of the best move in centipawns from the side to move's perspective
+    pub score: i32,
+    pub best_move: Move,
+    /// Nodes visited by this iteration alone
+    pub iteration_nodes: u64,
+    /// Nodes visited since the search started
+    pub nodes: u64,
+    /// Time from the start of the search until this iteration completed
+    pub elapsed: Duration,
+}
+
+impl SearchResult {
+    /// Every legal root move with its score from the last completed iteration, best first
+    /// # Description
+    /// Only the best move's score is exact, or the best n with with_multi_pv(n), the others are upper bounds from alpha-beta.
+    /// If no iteration completed every score is 0 and the moves are in their initial order.
+    pub fn root_scores(&self) -> Vec<(Move, i32)> {
+        self.root_moves.scores()
+    }
+}
+
+/// Callback run after every completed iteration
+pub type DepthObserver = Box<dyn FnMut(&DepthReport) + Send>;
+
+/// Iterative deepening alpha-beta search.
+/// # Description
+/// Searches one depth at a time, keeping the result of the last completed iteration.
+/// The search stops when any of the limits is reached or when the shared stop flag is raised.
+pub struct Searcher {
+    limits: SearchLimits,
+    stop: Arc<AtomicBool>,
+    start: Instant,
+    budget: Option<Duration>,
+    stats: SearchStats,
+    aborted: bool,
+    panic_mode: bool,
+    root_moves: RootMoves,
+    iterations: Vec<DepthReport>,
+    observer: Option<DepthObserver>,
+    table: TranspositionTable,
+    // How many of the best root moves get exact scores
+    multi_pv: usize,
+    // The root moves to search, all legal moves when None
+    search_moves: Option<Vec<Move>>,
+    // A move list per ply, kept between nodes so their capacity is reused
+    move_buffers: Vec<Vec<Move>>,
+}
+
+impl Searcher {
+    /// A searcher with a table of ANALYSIS_HASH_MB, for one-off searches.
+    /// Engines with a hash size or memory cap from an EngineConfig use new_with_table instead.
+    pub fn new(limits: SearchLimits, stop: Arc<AtomicBool>) -> Searcher {
+        Searcher::new_with_table(limits, stop, TranspositionTable::new(ANALYSIS_HASH_MB))
+    }
+
+    /// A searcher using the given transposition table, so no other table is allocated first
+    pub fn new_with_table(limits: SearchLimits, stop: Arc<AtomicBool>, table: TranspositionTable) -> Searcher {
+        Searcher {
+            limits,
+            stop,
+            start: Instant::now(),
+            budget: None,
+            stats: SearchStats::default(),
+            aborted: false,
+            panic_mode: false,
+            root_moves: RootMoves::default(),
+            iterations: Vec::new(),
+            observer: None,
+            table,
+            multi_pv: 1,
+            search_moves: None,
+            move_buffers: Vec::new(),
+        }
+    }
+
+    /// Calls the observer every time an iteration completes, e.g. to print UCI info lines while searching
+    pub fn with_observer(mut self, observer: impl FnMut(&DepthReport) + Send + 'static) -> Searcher {
+        self.observer = Some(Box::new(observer));
+        self
+    }
+
+    /// Score the best n root moves exactly rather than only the best one.
+    /// # Description
+    /// Each root move is searched with alpha at the n-th best score found so far in the iteration,
+    /// so the first n moves of the root moves after the search have exact scores and the rest are upper bounds.
+    /// This costs more nodes the larger n is, 1 is a normal search.
+    pub fn with_multi_pv(mut self, n: usize) -> Searcher {
+        self.multi_pv = n.max(1);
+        self
+    }
+
+    /// Search only the given root moves, like the searchmoves of a UCI go command.
+    /// Moves that are not legal in the searched position are ignored.
+    pub fn with_search_moves(mut self, moves: Vec<Move>) -> Searcher {
+        self.search_moves = Some(moves);
+        self
+    }
+
+    /// Searches with the given transposition table instead of an empty one, e.g. one loaded from a file
+    /// or kept from an earlier search
+    pub fn with_transposition_table(mut self, table: TranspositionTable) -> Searcher {
+        self.table = table;
+        self
+    }
+
+    /// The transposition table, holding what every search so far has learned
+    pub fn transposition_table(&self) -> &TranspositionTable {
+        &self.table
+    }
+
+    /// The memory held by the search tables
+    pub fn memory_usage(&self) -> MemoryReport {
+        MemoryReport { transposition_table: self.table.allocated_bytes() }
+    }
+
+    /// Takes the transposition table out of the searcher, leaving one that stores nothing
+    pub fn take_transposition_table(&mut self) -> TranspositionTable {
+        std::mem::take(&mut self.table)
+    }
+
+    /// Search the position and return the best move found.
+    /// # Description
+    /// Runs iterations of increasing depth until a limit is hit.
+    /// Root moves are ordered captures first, and if the first iteration is interrupted the first of them is returned
+    /// so there is always a move to play.
+    /// When the time budget is under PANIC_BUDGET_MS (bullet clocks, tiny movetimes) iterative deepening is skipped,
+    /// a single depth 1 search is run and the clock is checked at every node.
+    /// # Inputs/Outputs
+    /// - Input: board: &Board - The position to search
+    /// - Output: The result of the deepest completed iteration
+    /// # Example
+    /// ``` Rust
+    /// let mut searcher = Searcher::new(SearchLimits::depth(3), Arc::new(AtomicBool::new(false)));
+    /// let result = searcher.search(&Board::starting_position());
+    /// assert_eq!(result.depth, 3);
+    /// ```
+    pub fn search(&mut self, board: &Board) -> SearchResult {
+        self.start = Instant::now();
+        self.budget = self.limits.time_budget(*board.get_player_turn());
+        self.stats = SearchStats::default();
+        self.aborted = false;
+        self.panic_mode = self.budget.is_some_and(|budget| budget < Duration::from_millis(PANIC_BUDGET_MS));
+        self.root_moves = RootMoves::new(board);
+        if let Some(moves) = &self.search_moves {
+            self.root_moves.retain(moves);
+        }
+        self.iterations = Vec::new();
+
+        let mut result = SearchResult {
+            best_move: self.root_moves.first().map(|root_move| root_move.mv.clone()),
+            score: 0,
+            static_eval: board.evaluate_to_move(),
+            depth: 0,
+            nodes: 0,
+            elapsed: Duration::ZERO,
+            stats: SearchStats::default(),
+            root_moves: RootMoves::default(),
+            iterations: Vec::new(),
+        };
+        if self.root_moves.is_empty() {
+            result.score = if board.king_in_check() { -MATE_SCORE } else { 0 };
+            return result;
+        }
+        let mut board = board.clone();
+        let max_depth = if self.panic_mode { 1 } else { self.limits.max_depth(MAX_DEPTH) };
+        for depth in 1..=max_depth {
+            let nodes_before = self.stats.nodes;
+            let (best_move, score) = self.search_root(&mut board, depth);
+            if self.aborted {
+                if self.panic_mode {
+                    // Better than nothing when the clock ran out part way through the only iteration
+                    result.best_move = Some(best_move);
+                    result.score = score;
+                }
+                break;
+            }
+            self.root_moves.complete_iteration();
+            result.best_move = Some(best_move.clone());
+            result.score = score;
+            result.depth = depth;
+            log::debug!("Completed depth {} with score {} best move {}", depth, score, best_move.extended_algebraic());
+            let report = DepthReport {
+                depth,
+                score,
+                best_move,
+                iteration_nodes: self.stats.nodes - nodes_before,
+                nodes: self.stats.nodes,
+                elapsed: self.start.elapsed(),
+            };
+            if let Some(observer) = self.observer.as_mut() {
+                observer(&report);
+            }
+            self.iterations.push(report);
+            if self.mate_target_reached(score) || self.should_stop() {
+                break;
+            }
+        }
+        result.nodes = self.stats.nodes;
+        result.elapsed = self.start.elapsed();
+        result.stats = self.stats;
+        result.root_moves = self.root_moves.clone();
+        result.iterations = std::mem::take(&mut self.iterations);
+        result
+    }
+
+    /// Search every root move to the given depth, recording each move's score and nodes
+    fn search_root(&mut self, board: &mut Board, depth: u32) -> (Move, i32) {
+        let beta = MATE_SCORE + 1;
+        let mut best_move = self.root_moves.get_mut(0).mv.clone();
+        let mut best_score = -MATE_SCORE - 1;
+        // Scores of the moves searched so far this iteration, best first
+        let mut scores = Vec::with_capacity(self.root_moves.len());
+        for index in 0..self.root_moves.len() {
+            let mv = self.root_moves.get_mut(index).mv.clone();
+            let undo = match board.make_move(&mv) {
+                Ok(undo) => undo,
+                Err(_) => continue,
+            };
+            let alpha = scores.get(self.multi_pv - 1).copied().unwrap_or(-MATE_SCORE - 1);
+            let nodes_before = self.stats.nodes;
+            let score = -self.negamax(board, depth - 1, -beta, -alpha, 1);
+            board.unmake_move(&mv, undo);
+            if self.aborted {
+                break;
+            }
+            let root_move = self.root_moves.get_mut(index);
+            root_move.pending_score = score;
+            root_move.nodes += self.stats.nodes - nodes_before;
+            scores.insert(scores.partition_point(|&other| other >= score), score);
+            if score > best_score {
+                best_score = score;
+                best_move = mv;
+            }
+        }
+        (best_move, best_score)
+    }
+
+    /// The line the transposition table expects after a move, starting with the move itself.
+    /// # Description
+    /// Follows the best move stored for each position until the table has none, it is not legal
+    /// or max_len moves have been collected. Entries can be overwritten, so the line may be cut short.
+    /// # Inputs/Outputs
+    /// - Input: board - The position the move is played in, usually the one just searched
+    /// - Input: first - A legal move in that position
+    /// - Input: max_len - The most moves to return
+    /// - Output: The line, empty if first is not legal
+    pub fn principal_variation(&self, board: &Board, first: &Move, max_len: usize) -> Vec<Move> {
+        let mut board = board.clone();
+        let mut line = Vec::new();
+        let mut next = Some(first.clone());
+        while let Some(mv) = next.take() {
+            if line.len() >= max_len || board.make_move(&mv).is_err() {
+                break;
+            }
+            line.push(mv);
+            let Some(entry) = self.table.probe(board.zobrist_key()) else {
+                break;
+            };
+            next = board.generate_legal_moves().into_iter().find(|mv| entry.best_move != 0 && pack_move(mv) == entry.best_move);
+        }
+        line
+    }
+
+    fn negamax(&mut self, board: &mut Board, depth: u32, alpha: i32, beta: i32, ply: i32) -> i32 {
+        let index = ply as usize;
+        if self.move_buffers.len() <= index {
+            self.move_buffers.resize_with(index + 1, Vec::new);
+        }
+        let mut moves = std::mem::take(&mut self.move_buffers[index]);
+        let score = self.negamax_with_buffer(board, depth, alpha, beta, ply, &mut moves);
+        self.move_buffers[index] = moves;
+        score
+    }
+
+    /// negamax generating into the move list of its ply
+    fn negamax_with_buffer(&mut self, board: &mut Board, depth: u32, mut alpha: i32, beta: i32, ply: i32, moves: &mut Vec<Move>) -> i32 {
+        self.stats.nodes += 1;
+        if (self.panic_mode || self.stats.nodes.is_multiple_of(TIME_CHECK_INTERVAL)) && self.should_stop() {
+            self.aborted = true;
+        }
+        if self.aborted || self.limits.nodes.is_some_and(|nodes| self.stats.nodes >= nodes) {
+            self.aborted = true;
+            return 0;
+        }
+        let key = board.zobrist_key();
+        self.stats.tt_probes += 1;
+        let entry = self.table.probe(key);
+        if let Some(entry) = entry {
+            self.stats.tt_hits += 1;
+            if entry.depth as u32 >= depth {
+                let score = score_from_table(entry.score, ply);
+                match entry.bound {
+                    Bound::Exact => return score,
+                    Bound::Lower if score >= beta => return beta,
+                    Bound::Upper if score <= alpha => return alpha,
+                    _ => {}
+                }
+            }
+        }
+        // A leaf only needs to know whether any move exists, to tell mate and stalemate from a position to evaluate
+        if depth > 0 {
+            self.generate_moves(board, moves);
+        }
+        let has_moves = if depth == 0 { board.has_legal_moves() } else { !moves.is_empty() };
+        if !has_moves {
+            return if board.king_in_check() { -MATE_SCORE + ply } else { 0 };
+        }
+        // Stalemate was scored above, this finds the draws by rule including repetitions of the game and the search line
+        if board.draw_reason(true, board.repetition_count()).is_some() {
+            return 0;
+        }
+        if depth == 0 {
+            return board.evaluate_to_move();
+        }
+        // Captures are tried first, behind the best move found by an earlier search of the position
+        board.order_moves(moves);
+        if let Some(index) = entry.and_then(|entry| moves.iter().position(|mv| pack_move(mv) == entry.best_move)) {
+            moves[..=index].rotate_right(1);
+        }
+        let original_alpha = alpha;
+        let mut best_move = 0;
+        for mv in moves.iter() {
+            let undo = match board.make_move(mv) {
+                Ok(undo) => undo,
+                Err(_) => continue,
+            };
+            let score = -self.negamax(board, depth - 1, -beta, -alpha, ply + 1);
+            board.unmake_move(mv, undo);
+            if self.aborted {
+                return 0;
+            }
+            if score >= beta {
+                self.store(key, beta, depth, Bound::Lower, pack_move(mv), ply);
+                return beta;
+            }
+            if score > alpha {
+                alpha = score;
+                best_move = pack_move(mv);
+            }
+        }
+        let bound = if alpha > original_alpha { Bound::Exact } else { Bound::Upper };
+        self.store(key, alpha, depth, bound, best_move, ply);
+        alpha
+    }
+
+    fn store(&mut self, key: u64, score: i32, depth: u32, bound: Bound, best_move: u16, ply: i32) {
+        self.table.store(TtEntry { key, score: score_to_table(score, ply), depth: depth as u8, bound, best_move });
+    }
+
+    /// Fills moves with the legal moves of the position, counting generated and rejected moves
+    fn generate_moves(&mut self, board: &mut Board, moves: &mut Vec<Move>) {
+        let generated = board.generate_legal_moves_counted(moves) as u64;
+        self.stats.movegen_calls += 1;
+        self.stats.moves_generated += generated;
+        self.stats.legality_rejections += generated - moves.len() as u64;
+    }
+
+    /// True when the stop flag is raised, the time budget is used up or the node limit is reached
+    fn should_stop(&self) -> bool {
+        if self.stop.load(Ordering::Relaxed) {
+            return true;
+        }
+        if self.budget.is_some_and(|budget| self.start.elapsed() >= budget) {
+            return true;
+        }
+        self.limits.nodes.is_some_and(|nodes| self.stats.nodes >= nodes)
+    }
+
+    /// True when a mate within the requested number of moves has been found
+    fn mate_target_reached(&self, score: i32) -> bool {
+        match self.limits.mate {
+            Some(mate) => score >= MATE_SCORE - (2 * mate as i32 - 1),
+            None => false,
+        }
+    }
+}
+
+/// Mate scores are stored counted from the position rather than the root, so they stay correct when
+/// the position is reached at a different ply
+fn score_to_table(score: i32, ply: i32) -> i32 {
+    if score > MATE_SCORE - MAX_DEPTH as i32 {
+        score + ply
+    } else if score < -MATE_SCORE + MAX_DEPTH as i32 {
+        score - ply
+    } else {
+        score
+    }
+}
+
+/// Converts a stored mate score back to one counted from the root
+fn score_from_table(score: i32, ply: i32) -> i32 {
+    if score > MATE_SCORE - MAX_DEPTH as i32 {
+        score - ply
+    } else if score < -MATE_SCORE + MAX_DEPTH as i32 {
+        score + ply
+    } else {
+        score
+    }
+}
+
+/// Search a position with its own stop flag.
+/// # Description
+/// Convenience wrapper for callers that do not need to stop the search from another thread.
+/// Searches with a table of ANALYSIS_HASH_MB, use analyze_with_table to share one table between many searches.
+pub fn analyze(board: &Board, limits: SearchLimits) -> SearchResult {
+    Searcher::new(limits, Arc::new(AtomicBool::new(false))).search(board)
+}
+
+/// Search a position with its own stop flag and the given transposition table.
+/// # Description
+/// The table keeps what the search learned, so a caller searching many related positions allocates it once
+/// and each search starts from what the ones before found.
+/// # Example
+/// ``` Rust
+/// let mut table = TranspositionTable::new(ANALYSIS_HASH_MB);
+/// let first = analyze_with_table(&board, SearchLimits::depth(5), &mut table);
+/// let second = analyze_with_table(&after, SearchLimits::depth(5), &mut table);
+/// ```
+pub fn analyze_with_table(board: &Board, limits: SearchLimits, table: &mut TranspositionTable) -> SearchResult {
+    let mut searcher = Searcher::new_with_table(limits, Arc::new(AtomicBool::new(false)), std::mem::take(table));
+    let result = searcher.search(board);
+    *table = searcher.take_transposition_table();
+    result
+}
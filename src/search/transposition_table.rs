@@ -0,0 +1,228 @@
+use std::path::Path;
+
+use crate::{chess_move::Move, errors::hash_file_error::HashFileError};
+
+use super::searcher::{MATE_SCORE, MAX_DEPTH};
+
+/// Size of the transposition table in megabytes when none is given
+pub const DEFAULT_HASH_MB: usize = 16;
+/// Size of the table for searches made outside a configured engine, such as analyze,
+/// small enough to allocate for every position analysed
+pub const ANALYSIS_HASH_MB: usize = 1;
+/// Bytes at the start of every saved hash file
+const MAGIC: [u8; 4] = *b"CCTT";
+/// Bumped whenever the layout of a saved entry changes
+const VERSION: u32 = 1;
+/// Bytes used by an entry in memory and in a saved file
+const ENTRY_BYTES: usize = 16;
+/// Magic, version, entry size and entry count
+const HEADER_BYTES: usize = 20;
+
+/// How a stored score relates to the true score of the position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound {
+    /// The score is exact
+    Exact,
+    /// The search failed high, the true score is at least this
+    Lower,
+    /// The search failed low, the true score is at most this
+    Upper,
+}
+
+/// What an earlier search learned about a position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TtEntry {
+    /// Zobrist key of the position
+    pub key: u64,
+    /// Score from the side to move's perspective, mate scores counted from this position
+    pub score: i32,
+    /// Depth the position was searched to
+    pub depth: u8,
+    pub bound: Bound,
+    /// The best or refuting move packed with pack_move, 0 if there was none
+    pub best_move: u16,
+}
+
+/// A fixed size hash table of search results, indexed by Zobrist key.
+/// # Description
+/// Each key maps to a single slot. A new entry replaces the old one unless the old one is for the same
+/// position searched deeper.
+/// The table can be saved to a file and loaded again so long analysis can be resumed in a later session.
+#[derive(Debug, Clone, Default)]
+pub struct TranspositionTable {
+    entries: Vec<Option<TtEntry>>,
+}
+
+impl TranspositionTable {
+    /// A table using at most the given number of megabytes, rounded down to a power of two entries.
+    /// A size of 0 gives a table that stores nothing.
+    pub fn new(megabytes: usize) -> TranspositionTable {
+        let slots = megabytes * 1024 * 1024 / ENTRY_BYTES;
+        let capacity = if slots == 0 { 0 } else { 1 << slots.ilog2() };
+        TranspositionTable { entries: vec![None; capacity] }
+    }
+
+    /// The number of slots in the table
+    pub fn capacity(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// The number of slots holding an entry
+    pub fn occupied(&self) -> usize {
+        self.entries.iter().filter(|entry| entry.is_some()).count()
+    }
+
+    /// Bytes allocated for the slots, whether they hold an entry or not
+    pub fn allocated_bytes(&self) -> usize {
+        self.entries.capacity() * std::mem::size_of::<Option<TtEntry>>()
+    }
+
+    /// Replace the table with an empty one of the given size.
+    /// The old slots are freed before the new ones are allocated so the two are never held at once.
+    pub fn resize(&mut self, megabytes: usize) {
+        self.entries = Vec::new();
+        *self = TranspositionTable::new(megabytes);
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.fill(None);
+    }
+
+    /// The entry for a position, if the table holds one for its key
+    pub fn probe(&self, key: u64) -> Option<TtEntry> {
+        let index = self.index(key)?;
+        self.entries[index].filter(|entry| entry.key == key)
+    }
+
+    /// Store an entry, unless its slot holds a deeper search of the same position
+    pub fn store(&mut self, entry: TtEntry) {
+        let Some(index) = self.index(entry.key) else {
+            return;
+        };
+        if self.entries[index].is_some_and(|old| old.key == entry.key && old.depth > entry.depth) {
+            return;
+        }
+        self.entries[index] = Some(entry);
+    }
+
+    fn index(&self, key: u64) -> Option<usize> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        Some(key as usize & (self.entries.len() - 1))
+    }
+
+    /// Write the occupied entries to a file.
+    /// # Description
+    /// The file holds the magic bytes "CCTT", the format version, the size of an entry and the entry count,
+    /// all little endian, followed by the raw entries.
+    /// # Inputs/Outputs
+    /// - Input: path - Where to write the file, replacing any existing file
+    /// - Output: The number of entries written
+    /// # Example
+    /// ``` Rust
+    /// let mut searcher = Searcher::new(SearchLimits::depth(6), Arc::new(AtomicBool::new(false)));
+    /// searcher.search(&Board::starting_position());
+    /// searcher.transposition_table().save("analysis.hash").unwrap();
+    /// ```
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<usize, HashFileError> {
+        let entries = self.entries.iter().flatten().collect::<Vec<_>>();
+        let mut bytes = Vec::with_capacity(HEADER_BYTES + entries.len() * ENTRY_BYTES);
+        bytes.extend_from_slice(&MAGIC);
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        bytes.extend_from_slice(&(ENTRY_BYTES as u32).to_le_bytes());
+        bytes.extend_from_slice(&(entries.len() as u64).to_le_bytes());
+        for entry in &entries {
+            bytes.extend_from_slice(&encode_entry(entry));
+        }
+        std::fs::write(path, bytes)?;
+        Ok(entries.len())
+    }
+
+    /// Add the entries of a saved file to the table.
+    /// # Description
+    /// Files with the wrong magic bytes, version or entry size are rejected.
+    /// A file cut short is read up to its last whole entry and entries with impossible values are skipped,
+    /// so a damaged file loads what it can and never panics.
+    /// Entries are stored as if the search had found them, so they may replace what the table already holds.
+    /// # Inputs/Outputs
+    /// - Input: path - The file written by save
+    /// - Output: The number of entries read from the file
+    /// # Example
+    /// ``` Rust
+    /// let mut table = TranspositionTable::new(DEFAULT_HASH_MB);
+    /// let loaded = table.load("analysis.hash").unwrap();
+    /// let searcher = Searcher::new(SearchLimits::depth(6), Arc::new(AtomicBool::new(false))).with_transposition_table(table);
+    /// ```
+    pub fn load(&mut self, path: impl AsRef<Path>) -> Result<usize, HashFileError> {
+        let bytes = std::fs::read(path)?;
+        if bytes.len() < HEADER_BYTES || bytes[0..4] != MAGIC {
+            return Err(HashFileError::BadMagic);
+        }
+        let version = u32::from_le_bytes(bytes[4..8].try_into().unwrap());
+        if version != VERSION {
+            return Err(HashFileError::UnsupportedVersion(version));
+        }
+        let entry_bytes = u32::from_le_bytes(bytes[8..12].try_into().unwrap());
+        if entry_bytes as usize != ENTRY_BYTES {
+            return Err(HashFileError::EntrySizeMismatch(entry_bytes, ENTRY_BYTES as u32));
+        }
+        let count = u64::from_le_bytes(bytes[12..20].try_into().unwrap());
+        let mut loaded = 0;
+        for chunk in bytes[HEADER_BYTES..].chunks_exact(ENTRY_BYTES).take(count.min(usize::MAX as u64) as usize) {
+            if let Some(entry) = decode_entry(chunk) {
+                self.store(entry);
+                loaded += 1;
+            }
+        }
+        Ok(loaded)
+    }
+}
+
+fn encode_entry(entry: &TtEntry) -> [u8; ENTRY_BYTES] {
+    let mut bytes = [0; ENTRY_BYTES];
+    bytes[0..8].copy_from_slice(&entry.key.to_le_bytes());
+    bytes[8..12].copy_from_slice(&entry.score.to_le_bytes());
+    bytes[12] = entry.depth;
+    bytes[13] = match entry.bound {
+        Bound::Exact => 0,
+        Bound::Lower => 1,
+        Bound::Upper => 2,
+    };
+    bytes[14..16].copy_from_slice(&entry.best_move.to_le_bytes());
+    bytes
+}
+
+/// Reads an entry back, None if any field holds a value the search could not have written
+fn decode_entry(bytes: &[u8]) -> Option<TtEntry> {
+    let score = i32::from_le_bytes(bytes[8..12].try_into().ok()?);
+    let depth = bytes[12];
+    let bound = match bytes[13] {
+        0 => Bound::Exact,
+        1 => Bound::Lower,
+        2 => Bound::Upper,
+        _ => return None,
+    };
+    if score.abs() > MATE_SCORE + 1 || depth as u32 > MAX_DEPTH {
+        return None;
+    }
+    Some(TtEntry {
+        key: u64::from_le_bytes(bytes[0..8].try_into().ok()?),
+        score,
+        depth,
+        bound,
+        best_move: u16::from_le_bytes(bytes[14..16].try_into().ok()?),
+    })
+}
+
+/// Packs a move into 16 bits: the from square, the to square and the promotion piece.
+/// The null move a1a1 packs to 0, which is used for no move.
+pub fn pack_move(mv: &Move) -> u16 {
+    let from = (mv.from_y * 8 + mv.from_x) as u16;
+    let to = (mv.to_y * 8 + mv.to_x) as u16;
+    let promotion = match mv.promotion {
+        Some(promotion) => promotion.index() as u16,
+        None => 0,
+    };
+    from | (to << 6) | (promotion << 12)
+}
@@ -0,0 +1,103 @@
+
+/// z value for a two sided 95% confidence interval
+const Z_95: f64 = 1.959964;
+
+/// The result of a single game from the first engine's perspective.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameOutcome {
+    Win,
+    Draw,
+    Loss,
+}
+
+/// An Elo difference with its 95% confidence interval.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EloEstimate {
+    pub elo: f64,
+    pub lower: f64,
+    pub upper: f64,
+}
+
+/// Running totals of a match between two engine configurations.
+/// # Description
+/// Counts are from the first engine's perspective.
+/// The statistics use the trinomial model, each game is scored 1, 0.5 or 0.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MatchResult {
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+impl MatchResult {
+    pub fn new(wins: u32, draws: u32, losses: u32) -> MatchResult {
+        MatchResult { wins, draws, losses }
+    }
+
+    /// Records the outcome of one game
+    pub fn add(&mut self, outcome: GameOutcome) {
+        match outcome {
+            GameOutcome::Win => self.wins += 1,
+            GameOutcome::Draw => self.draws += 1,
+            GameOutcome::Loss => self.losses += 1,
+        }
+    }
+
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    /// The mean score per game between 0 and 1, None before any game is played
+    pub fn score(&self) -> Option<f64> {
+        if self.games() == 0 {
+            return None;
+        }
+        Some((self.wins as f64 + self.draws as f64 / 2.0) / self.games() as f64)
+    }
+
+    /// The variance of a single game's score around the mean score
+    pub fn score_variance(&self) -> Option<f64> {
+        let score = self.score()?;
+        let games = self.games() as f64;
+        let variance = self.wins as f64 * (1.0 - score).powi(2)
+            + self.draws as f64 * (0.5 - score).powi(2)
+            + self.losses as f64 * score.powi(2);
+        Some(variance / games)
+    }
+
+    /// Estimate the Elo difference with a 95% confidence interval.
+    /// # Description
+    /// The interval is taken around the mean score using the normal approximation and then converted to Elo.
+    /// Returns None when no games were played or one side scored every point, as the Elo is then unbounded.
+    /// # Inputs/Outputs
+    /// - Output: The estimate, or None if it cannot be computed
+    /// # Example
+    /// ``` Rust
+    /// let estimate = MatchResult::new(60, 0, 40).elo_estimate().unwrap();
+    /// // elo 70.4, interval 2.8 to 143.9
+    /// ```
+    pub fn elo_estimate(&self) -> Option<EloEstimate> {
+        let score = self.score()?;
+        if score <= 0.0 || score >= 1.0 {
+            return None;
+        }
+        let error = (self.score_variance()? / self.games() as f64).sqrt();
+        let lower = (score - Z_95 * error).max(f64::MIN_POSITIVE);
+        let upper = (score + Z_95 * error).min(1.0 - f64::EPSILON);
+        Some(EloEstimate {
+            elo: score_to_elo(score),
+            lower: score_to_elo(lower),
+            upper: score_to_elo(upper),
+        })
+    }
+}
+
+/// Converts an expected score between 0 and 1 into an Elo difference
+pub fn score_to_elo(score: f64) -> f64 {
+    -400.0 * (1.0 / score - 1.0).log10()
+}
+
+/// Converts an Elo difference into the expected score of the stronger side
+pub fn elo_to_score(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
@@ -0,0 +1,167 @@
+use std::{io::Write, path::{Path, PathBuf}};
+
+use crate::{board::Board, color::Color, game::Game, search::{search_limits::SearchLimits, searcher::analyze}, utils::openings::random_balanced_position};
+
+use super::{match_result::{GameOutcome, MatchResult}, sprt::{Sprt, SprtDecision}};
+
+/// Games longer than this many plies are scored as a draw
+const MAX_GAME_PLIES: u32 = 400;
+/// The most material a random opening may leave either side ahead by
+const OPENING_IMBALANCE_CP: i32 = 100;
+
+/// Plays games between two search configurations.
+/// # Description
+/// The engines alternate colors every game starting with engine_a as White.
+/// After every game the SPRT, if one is set, is consulted and the match stops early once a hypothesis is accepted.
+/// Games end on checkmate, stalemate, the fifty-move rule or the ply cap.
+/// With random openings each pair of games starts from the same random line, one game with each engine as White.
+#[derive(Debug, Clone)]
+pub struct MatchRunner {
+    pub engine_a: SearchLimits,
+    pub engine_b: SearchLimits,
+    pub max_games: u32,
+    pub sprt: Option<Sprt>,
+    pub start_position: Board,
+    /// Plies of the random opening played from the start position before each game, see random_balanced_position
+    pub random_openings: Option<u32>,
+    /// File every game is appended to as PGN, opening moves included
+    pub pgn_path: Option<PathBuf>,
+}
+
+impl MatchRunner {
+    pub fn new(engine_a: SearchLimits, engine_b: SearchLimits, max_games: u32) -> MatchRunner {
+        MatchRunner {
+            engine_a,
+            engine_b,
+            max_games,
+            sprt: None,
+            start_position: Board::starting_position(),
+            random_openings: None,
+            pgn_path: None,
+        }
+    }
+
+    /// Start each pair of games from a random balanced opening of this many plies instead of start_position
+    pub fn with_random_openings(mut self, plies: u32) -> MatchRunner {
+        self.random_openings = Some(plies);
+        self
+    }
+
+    /// Append every game to a PGN file
+    pub fn with_pgn(mut self, path: impl Into<PathBuf>) -> MatchRunner {
+        self.pgn_path = Some(path.into());
+        self
+    }
+
+    /// Set the SPRT consulted after every game
+    pub fn with_sprt(mut self, sprt: Sprt) -> MatchRunner {
+        self.sprt = Some(sprt);
+        self
+    }
+
+    /// Play the match.
+    /// # Inputs/Outputs
+    /// - Output: The result from engine_a's perspective and the SPRT decision, Continue if no test was set or it did not finish
+    /// # Example
+    /// ``` Rust
+    /// let runner = MatchRunner::new(SearchLimits::depth(3), SearchLimits::depth(1), 1000).with_sprt(Sprt::default());
+    /// let (result, decision) = runner.run();
+    /// ```
+    pub fn run(&self) -> (MatchResult, SprtDecision) {
+        let mut result = MatchResult::default();
+        let mut decision = SprtDecision::Continue;
+        for game in 0..self.max_games {
+            let opening = self.opening(game);
+            let (white_outcome, record) = if game % 2 == 0 {
+                self.play_game(&self.engine_a, &self.engine_b, opening)
+            } else {
+                self.play_game(&self.engine_b, &self.engine_a, opening)
+            };
+            if let Some(path) = &self.pgn_path {
+                if let Err(e) = append_pgn(path, game + 1, white_outcome, &record) {
+                    log::error!("Could not write game {} to {}: {}", game + 1, path.display(), e);
+                }
+            }
+            let outcome = if game % 2 == 0 { white_outcome } else { invert(white_outcome) };
+            result.add(outcome);
+            log::info!("Game {} finished {:?}, running result {:?}", game + 1, outcome, result);
+            if let Some(sprt) = &self.sprt {
+                decision = sprt.test(&result);
+                if decision != SprtDecision::Continue {
+                    log::info!("SPRT finished with {:?} after {} games", decision, result.games());
+                    break;
+                }
+            }
+        }
+        (result, decision)
+    }
+
+    /// The game the engines play on, with the random opening's moves already recorded when one is set
+    fn opening(&self, game: u32) -> Game {
+        let Some(plies) = self.random_openings else {
+            return Game::from_fen(&self.start_position.to_fen()).unwrap_or_default();
+        };
+        let board = random_balanced_position((game / 2) as u64, plies, OPENING_IMBALANCE_CP);
+        let mut record = Game::new();
+        for mv in board.moves_played() {
+            record.make_move(mv.clone()).expect("opening moves are legal");
+        }
+        record
+    }
+
+    /// Plays one game on from record and returns the outcome from White's perspective with the finished game
+    fn play_game(&self, white: &SearchLimits, black: &SearchLimits, mut record: Game) -> (GameOutcome, Game) {
+        for _ in 0..MAX_GAME_PLIES {
+            if record.board.is_draw().is_some() {
+                return (GameOutcome::Draw, record);
+            }
+            let turn = *record.board.get_player_turn();
+            let limits = if turn == Color::White { white } else { black };
+            let Some(mv) = analyze(&record.board, limits.clone()).best_move else {
+                if !record.board.king_in_check() {
+                    return (GameOutcome::Draw, record);
+                }
+                return (if turn == Color::White { GameOutcome::Loss } else { GameOutcome::Win }, record);
+            };
+            if let Err(e) = record.make_move(mv) {
+                log::error!("Engine played an illegal move in self-play: {}", e);
+                return (if turn == Color::White { GameOutcome::Loss } else { GameOutcome::Win }, record);
+            }
+        }
+        (GameOutcome::Draw, record)
+    }
+}
+
+/// Add a game to the end of a PGN file, with White and Black named after the engine playing them
+fn append_pgn(path: &Path, round: u32, white_outcome: GameOutcome, record: &Game) -> std::io::Result<()> {
+    let result = match white_outcome {
+        GameOutcome::Win => "1-0",
+        GameOutcome::Draw => "1/2-1/2",
+        GameOutcome::Loss => "0-1",
+    };
+    let (white, black) = if round % 2 == 1 { ("engine_a", "engine_b") } else { ("engine_b", "engine_a") };
+    let mut pgn = format!(
+        "[Event \"Self-play\"]\n[Round \"{}\"]\n[White \"{}\"]\n[Black \"{}\"]\n[Result \"{}\"]\n",
+        round, white, black, result,
+    );
+    if let Some(fen) = &record.initial_fen {
+        pgn.push_str(&format!("[SetUp \"1\"]\n[FEN \"{}\"]\n", fen));
+    }
+    // to_pgn ends with "*" for a game stopped at the ply cap, the result tag is used instead
+    let text = record.to_pgn();
+    let movetext = text.rsplit("\n\n").next().unwrap_or_default();
+    match movetext.rsplit_once(' ') {
+        Some((moves, _)) => pgn.push_str(&format!("\n{} {}\n\n", moves, result)),
+        None => pgn.push_str(&format!("\n{}\n\n", result)),
+    }
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(pgn.as_bytes())
+}
+
+fn invert(outcome: GameOutcome) -> GameOutcome {
+    match outcome {
+        GameOutcome::Win => GameOutcome::Loss,
+        GameOutcome::Draw => GameOutcome::Draw,
+        GameOutcome::Loss => GameOutcome::Win,
+    }
+}
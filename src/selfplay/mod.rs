@@ -0,0 +1,4 @@
+
+pub mod match_result;
+pub mod sprt;
+pub mod match_runner;
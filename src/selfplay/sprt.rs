@@ -0,0 +1,78 @@
+use super::match_result::{elo_to_score, MatchResult};
+
+/// The decision of a sequential probability ratio test.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SprtDecision {
+    /// Not enough evidence yet, keep playing
+    Continue,
+    /// The first engine is not stronger than elo0, stop
+    AcceptH0,
+    /// The first engine is stronger by at least elo1, stop
+    AcceptH1,
+}
+
+/// Sequential probability ratio test between two Elo hypotheses.
+/// # Description
+/// H0 is that the first engine is elo0 stronger, H1 that it is elo1 stronger.
+/// alpha is the chance of accepting H1 when H0 is true and beta the chance of accepting H0 when H1 is true.
+/// The log-likelihood ratio uses the normal approximation of the trinomial model.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sprt {
+    pub elo0: f64,
+    pub elo1: f64,
+    pub alpha: f64,
+    pub beta: f64,
+}
+
+impl Default for Sprt {
+    fn default() -> Self {
+        Sprt { elo0: 0.0, elo1: 5.0, alpha: 0.05, beta: 0.05 }
+    }
+}
+
+impl Sprt {
+    pub fn new(elo0: f64, elo1: f64, alpha: f64, beta: f64) -> Sprt {
+        Sprt { elo0, elo1, alpha, beta }
+    }
+
+    /// The LLR below which H0 is accepted and above which H1 is accepted
+    pub fn bounds(&self) -> (f64, f64) {
+        ((self.beta / (1.0 - self.alpha)).ln(), ((1.0 - self.beta) / self.alpha).ln())
+    }
+
+    /// The log-likelihood ratio of H1 against H0 for the games played so far.
+    /// # Description
+    /// Returns 0 while the result carries no information, before any game or when every game scored the same.
+    pub fn llr(&self, result: &MatchResult) -> f64 {
+        let (Some(score), Some(variance)) = (result.score(), result.score_variance()) else {
+            return 0.0;
+        };
+        if variance <= 0.0 {
+            return 0.0;
+        }
+        let score0 = elo_to_score(self.elo0);
+        let score1 = elo_to_score(self.elo1);
+        result.games() as f64 * (score1 - score0) * (2.0 * score - score0 - score1) / (2.0 * variance)
+    }
+
+    /// Decide whether the match can stop.
+    /// # Inputs/Outputs
+    /// - Input: result: &MatchResult - The games played so far
+    /// - Output: Which hypothesis was accepted, or Continue
+    /// # Example
+    /// ``` Rust
+    /// let sprt = Sprt::new(0.0, 10.0, 0.05, 0.05);
+    /// assert_eq!(sprt.test(&MatchResult::new(600, 0, 400)), SprtDecision::AcceptH1);
+    /// ```
+    pub fn test(&self, result: &MatchResult) -> SprtDecision {
+        let llr = self.llr(result);
+        let (lower, upper) = self.bounds();
+        if llr >= upper {
+            SprtDecision::AcceptH1
+        } else if llr <= lower {
+            SprtDecision::AcceptH0
+        } else {
+            SprtDecision::Continue
+        }
+    }
+}
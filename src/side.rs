@@ -0,0 +1,10 @@
+
+
+/// A side of the board the king can castle towards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    /// Towards the h-file, written O-O
+    KingSide,
+    /// Towards the a-file, written O-O-O
+    QueenSide,
+}
@@ -0,0 +1,36 @@
+use std::fmt::{self, Display, Formatter};
+
+use crate::{bitboard, color::Color, utils::notation::coords_to_square};
+
+/// A square with everything a board UI binds to it, see Board::squares_in_display_order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SquareName {
+    /// The square in algebraic notation, e.g. "e4"
+    pub name: String,
+    /// The file, 0 for a to 7 for h
+    pub x: usize,
+    /// The rank index, 0 for rank 1 to 7 for rank 8
+    pub y: usize,
+    /// White for a light square and Black for a dark one, a1 is dark
+    pub color: Color,
+}
+
+impl SquareName {
+    pub fn new(x: usize, y: usize) -> SquareName {
+        let light = bitboard::square_bit(x, y) & bitboard::LIGHT_SQUARES != 0;
+        SquareName {
+            name: coords_to_square((x, y)),
+            x,
+            y,
+            color: if light { Color::White } else { Color::Black },
+        }
+    }
+}
+
+/// The algebraic name, e.g. "e4"
+impl Display for SquareName {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
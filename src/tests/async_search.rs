@@ -0,0 +1,48 @@
+use std::time::{Duration, Instant};
+
+use crate::{errors::chess_error::ChessError, search::{async_search::{analyze_async, AnalysisTask}, search_limits::SearchLimits}};
+
+
+const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+#[tokio::test(flavor = "current_thread")]
+async fn analyze_async_1() {
+    let analysis = analyze_async(START_FEN.to_string(), SearchLimits::depth(3)).await.unwrap();
+    assert_eq!(analysis.depth, 3);
+    assert!(analysis.best_move.is_some());
+    assert!(analysis.nodes > 0);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn analyze_async_bad_fen_1() {
+    let result = analyze_async("not a fen".to_string(), SearchLimits::depth(3)).await;
+    assert!(matches!(result, Err(ChessError::Fen(_))));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn analyze_async_stop_1() {
+    // Stopping early still completes with the best move found so far
+    let task = AnalysisTask::spawn(START_FEN, SearchLimits::default()).unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    task.stop();
+    let analysis = task.await.unwrap();
+    assert!(analysis.best_move.is_some());
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn analyze_async_cancel_1() {
+    // The node limit is far more than can be searched before the future is dropped,
+    // so the worker only ends early if dropping the future stopped it
+    const NODE_CEILING: u64 = 100_000_000;
+    let limits = SearchLimits { nodes: Some(NODE_CEILING), ..Default::default() };
+    let task = AnalysisTask::spawn(START_FEN, limits).unwrap();
+    let status = task.status();
+    assert!(tokio::time::timeout(Duration::from_millis(100), task).await.is_err());
+    assert!(status.is_stopped());
+    let dropped = Instant::now();
+    while !status.is_finished() {
+        assert!(dropped.elapsed() < Duration::from_secs(2), "worker kept searching after the future was dropped");
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    assert!(status.nodes().unwrap() < NODE_CEILING);
+}
@@ -0,0 +1,56 @@
+use crate::bitboard::{self, square_bit, square_index, KING_ATTACKS, KNIGHT_ATTACKS, PAWN_ATTACKS};
+
+
+#[test]
+fn leaper_tables_1() {
+    // Knight in the corner has two moves, in the centre it has eight
+    assert_eq!(KNIGHT_ATTACKS[square_index(0, 0)], square_bit(1, 2) | square_bit(2, 1));
+    assert_eq!(KNIGHT_ATTACKS[square_index(3, 3)].count_ones(), 8);
+    assert_eq!(KING_ATTACKS[square_index(7, 7)].count_ones(), 3);
+    // White pawn on e4 attacks d5 and f5, black pawn on e4 attacks d3 and f3
+    assert_eq!(PAWN_ATTACKS[0][square_index(4, 3)], square_bit(3, 4) | square_bit(5, 4));
+    assert_eq!(PAWN_ATTACKS[1][square_index(4, 3)], square_bit(3, 2) | square_bit(5, 2));
+}
+
+#[test]
+fn sliding_attacks_1() {
+    // Rook on a1 with blockers on a4 and d1 sees a2-a4 and b1-d1
+    let occupancy = square_bit(0, 3) | square_bit(3, 0);
+    let expected = square_bit(0, 1) | square_bit(0, 2) | square_bit(0, 3) | square_bit(1, 0) | square_bit(2, 0) | square_bit(3, 0);
+    assert_eq!(bitboard::rook_attacks(square_index(0, 0), occupancy), expected);
+    // Bishop on d4 on an empty board sees 13 squares, a blocker on f6 cuts off g7 and h8
+    assert_eq!(bitboard::bishop_attacks(square_index(3, 3), 0).count_ones(), 13);
+    assert_eq!(bitboard::bishop_attacks(square_index(3, 3), square_bit(5, 5)).count_ones(), 11);
+}
+
+#[test]
+fn magic_attacks_1() {
+    // The magic lookups agree with following the rays for random blockers on every square
+    let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+    for index in 0..64 {
+        for _ in 0..200 {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let occupancy = state & state.rotate_left(17);
+            assert_eq!(bitboard::rook_attacks(index, occupancy), bitboard::rook_attacks_by_rays(index, occupancy));
+            assert_eq!(bitboard::bishop_attacks(index, occupancy), bitboard::bishop_attacks_by_rays(index, occupancy));
+        }
+    }
+}
+
+#[test]
+fn between_1() {
+    // a1 to h8 passes b2 to g7, a1 to a4 passes a2 and a3, a knight's jump and neighbours have nothing between
+    assert_eq!(bitboard::between(square_index(0, 0), square_index(7, 7)).count_ones(), 6);
+    assert_eq!(bitboard::between(square_index(0, 3), square_index(0, 0)), square_bit(0, 1) | square_bit(0, 2));
+    assert_eq!(bitboard::between(square_index(0, 0), square_index(1, 2)), 0);
+    assert_eq!(bitboard::between(square_index(4, 4), square_index(5, 5)), 0);
+}
+
+#[test]
+fn squares_iter_1() {
+    let bits = square_bit(0, 0) | square_bit(4, 3) | square_bit(7, 7);
+    let squares = bitboard::squares(bits).map(bitboard::index_to_coords).collect::<Vec<_>>();
+    assert_eq!(squares, vec![(0, 0), (4, 3), (7, 7)]);
+}
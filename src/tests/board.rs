@@ -1,34 +1,1836 @@
-use crate::{board::Board, utils::move_depth::depth_check};
+use std::collections::HashSet;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{board::Board, board_render_options::BoardRenderOptions, castling_rights::CastlingRights, castling_validation::{CastlingRepair, CastlingValidation}, chess_move::Move, color::Color, draw_reason::DrawReason, en_passant_mode::EnPassantMode, errors::{consistency_error::ConsistencyError, fen_error::FenError, move_error::MoveError}, move_kind::MoveKind, move_result::MoveResult, piece::Piece, piece_type::PieceType, side::Side, square_name::SquareName, utils::{notation::{square_to_coords, uci_to_move}, performance::{perft, perft_slow}}};
 
 
 #[test]
 fn move_piece_basic_1() {
     let mut board = Board::starting_position();
-    board.move_piece(4, 1, 4, 3).unwrap();
-    board.move_piece(4, 6, 4, 4).unwrap();
-    board.move_piece(1, 0, 2, 2).unwrap();
-    board.move_piece(1, 7, 2, 5).unwrap();
+    board.move_piece(Move::new(4, 1, 4, 3, PieceType::Pawn, None)).unwrap();
+    board.move_piece(Move::new(4, 6, 4, 4, PieceType::Pawn, None)).unwrap();
+    board.move_piece(Move::new(1, 0, 2, 2, PieceType::Knight, None)).unwrap();
+    board.move_piece(Move::new(1, 7, 2, 5, PieceType::Knight, None)).unwrap();
 
     let comp = Board::from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/2N5/PPPP1PPP/R1BQKBNR w KQkq - 2 3").unwrap();
-    let board_ref = board.get_squares();
-    let comp_ref = comp.get_squares();
-    for i in 0..8 {
-        for j in 0..8 {
-            assert_eq!(board_ref[i][j], comp_ref[i][j]);
-        }
-    }
+    assert_eq!(board.get_squares(), comp.get_squares());
 }
 
 #[test]
 fn moves_from_start_1() {
     let board = Board::starting_position();
-    let count = depth_check(1, board);
+    let count = perft(1, board).unwrap();
     assert_eq!(count, 20);
 }
 
 #[test]
 fn moves_from_start_2() {
     let board = Board::starting_position();
-    let count = depth_check(2, board);
+    let count = perft(2, board).unwrap();
     assert_eq!(count, 400);
 }
+
+#[test]
+fn moves_from_start_3() {
+    let board = Board::starting_position();
+    let count = perft(3, board).unwrap();
+    assert_eq!(count, 8902);
+}
+
+#[test]
+#[ignore = "slow in debug builds, run with --release -- --ignored"]
+fn moves_from_start_5() {
+    let board = Board::starting_position();
+    let count = perft(5, board).unwrap();
+    assert_eq!(count, 4865609);
+}
+
+#[test]
+fn kiwipete_3() {
+    let board = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+    let count = perft(3, board).unwrap();
+    assert_eq!(count, 97862);
+}
+
+#[test]
+#[ignore = "slow in debug builds, run with --release -- --ignored"]
+fn kiwipete_5() {
+    let board = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+    let count = perft(5, board).unwrap();
+    assert_eq!(count, 193690690);
+}
+
+/// Checks every field matches, including the clocks and history that Board's PartialEq ignores
+fn assert_identical(board: &Board, expected: &Board) {
+    assert_eq!(format!("{:?}", board), format!("{:?}", expected));
+}
+
+/// Makes and unmakes the move, checking the board is restored exactly
+fn assert_make_unmake(fen: &str, mv: Move) {
+    let original = Board::from_fen(fen).unwrap();
+    let mut board = original.clone();
+    let undo = board.make_move(&mv).unwrap();
+    assert_ne!(board, original);
+    board.unmake_move(&mv, undo);
+    assert_identical(&board, &original);
+}
+
+#[test]
+fn make_unmake_capture_1() {
+    assert_make_unmake("rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2", Move::new(4, 3, 3, 4, PieceType::Pawn, None));
+}
+
+#[test]
+fn make_unmake_en_passant_1() {
+    assert_make_unmake("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3", Move::new(4, 4, 5, 5, PieceType::Pawn, None));
+}
+
+#[test]
+fn make_unmake_castle_1() {
+    let fen = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    assert_make_unmake(fen, Move::new(4, 0, 6, 0, PieceType::King, None));
+    assert_make_unmake(fen, Move::new(4, 0, 2, 0, PieceType::King, None));
+}
+
+#[test]
+fn make_unmake_promotion_1() {
+    let fen = "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 b kq - 0 1";
+    assert_make_unmake(fen, Move::new(1, 1, 1, 0, PieceType::Pawn, Some(PieceType::Queen)));
+    assert_make_unmake(fen, Move::new(1, 1, 0, 0, PieceType::Pawn, Some(PieceType::Knight)));
+}
+
+#[test]
+fn make_unmake_perft_1() {
+    let board = Board::from_fen("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1").unwrap();
+    let count = perft(3, board).unwrap();
+    assert_eq!(count, 9467);
+}
+
+#[test]
+fn perft_bulk_counting_1() {
+    // Counting the last ply without making the moves gives the same totals on the standard perft positions
+    let fens = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+    ];
+    for fen in fens {
+        let board = Board::from_fen(fen).unwrap();
+        for depth in 0..=3 {
+            assert_eq!(perft(depth, board.clone()).unwrap(), perft_slow(depth, board.clone()).unwrap(), "depth {} of {}", depth, fen);
+        }
+    }
+}
+
+/// Plays random legal moves then undoes them all, checking the original board comes back
+fn assert_random_undo(fen: &str, seed: u64, count: usize) {
+    let original = Board::from_fen(fen).unwrap();
+    let mut board = original.clone();
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut played = Vec::new();
+    for _ in 0..count {
+        let moves = board.generate_legal_moves();
+        if moves.is_empty() {
+            break;
+        }
+        let mv = moves[rng.gen_range(0..moves.len())].clone();
+        board.move_piece(mv.clone()).unwrap();
+        played.push(mv);
+    }
+    while let Some(mv) = played.pop() {
+        assert_eq!(board.undo_move(), Some(mv));
+    }
+    assert_eq!(board.undo_move(), None);
+    assert_eq!(board.get_squares(), original.get_squares());
+    assert_identical(&board, &original);
+}
+
+#[test]
+fn undo_move_1() {
+    for seed in 0..20 {
+        assert_random_undo("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", seed, 10);
+        assert_random_undo("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1", seed, 10);
+    }
+}
+
+#[test]
+fn undo_move_empty_1() {
+    let mut board = Board::starting_position();
+    assert_eq!(board.undo_move(), None);
+}
+
+#[test]
+fn en_passant_legal_1() {
+    // the en passant capture is legal and gives check
+    let board = Board::from_fen("8/8/1k6/2b5/2pP4/8/5K2/8 b - d3 0 1").unwrap();
+    assert!(board.en_passant_capture_possible());
+    assert!(board.generate_legal_moves().contains(&Move::new(2, 3, 3, 2, PieceType::Pawn, None)));
+}
+
+#[test]
+#[ignore = "slow in debug builds, run with --release -- --ignored"]
+fn en_passant_legal_2() {
+    let board = Board::from_fen("8/8/1k6/2b5/2pP4/8/5K2/8 b - d3 0 1").unwrap();
+    assert_eq!(perft(6, board).unwrap(), 1440467);
+}
+
+#[test]
+fn en_passant_pinned_1() {
+    // after d7d5 the capture c5xd6 would expose the king on the fifth rank
+    let board = Board::from_fen("3k4/8/8/K1Pp3r/8/8/8/8 w - d6 0 2").unwrap();
+    assert!(board.en_passant_capture_possible());
+    assert!(!board.generate_legal_moves().contains(&Move::new(2, 4, 3, 5, PieceType::Pawn, None)));
+}
+
+#[test]
+#[ignore = "slow in debug builds, run with --release -- --ignored"]
+fn en_passant_pinned_2() {
+    let board = Board::from_fen("3k4/3p4/8/K1P4r/8/8/8/8 b - - 0 1").unwrap();
+    assert_eq!(perft(6, board).unwrap(), 1134888);
+}
+
+#[test]
+fn en_passant_unavailable_1() {
+    // the en passant square is set but no pawn can capture onto it
+    let board = Board::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1").unwrap();
+    let without = Board::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1").unwrap();
+    assert!(!board.en_passant_capture_possible());
+    assert_eq!(perft(4, board).unwrap(), perft(4, without).unwrap());
+    let board = Board::from_fen("8/8/4k3/8/2p5/8/B2P2K1/8 w - - 0 1").unwrap();
+    assert_eq!(perft(6, board).unwrap(), 1015133);
+}
+
+#[test]
+fn position_3_4() {
+    let board = Board::from_fen("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1").unwrap();
+    assert_eq!(perft(4, board).unwrap(), 43238);
+}
+
+#[test]
+fn king_along_check_ray_1() {
+    // the king cannot step away along the rank the rook is checking
+    let mut board = Board::from_fen("4k3/8/8/8/8/8/8/r2K4 w - - 0 1").unwrap();
+    assert!(!board.generate_legal_moves().contains(&Move::new(3, 0, 4, 0, PieceType::King, None)));
+    assert!(board.move_piece(Move::new(3, 0, 4, 0, PieceType::King, None)).is_err());
+    assert!(board.move_piece(Move::new(3, 0, 3, 1, PieceType::King, None)).is_ok());
+}
+
+#[test]
+fn king_along_check_ray_2() {
+    // Black's king on d8 is checked along the eighth rank, c8 is directly behind it on the ray
+    let board = Board::from_fen("3k3R/8/8/8/8/8/8/4K3 b - - 0 1").unwrap();
+    let moves = board.generate_legal_moves();
+    assert!(!moves.contains(&Move::new(3, 7, 2, 7, PieceType::King, None)));
+    assert!(!moves.contains(&Move::new(3, 7, 4, 7, PieceType::King, None)));
+    assert_eq!(moves.len(), 3);
+    // The same along a diagonal, e5 is behind the king as seen from the a1 bishop
+    let board = Board::from_fen("4k3/8/8/8/3K4/8/8/b7 w - - 0 1").unwrap();
+    let moves = board.generate_legal_moves();
+    assert!(!moves.contains(&Move::new(3, 3, 4, 4, PieceType::King, None)));
+    assert_eq!(moves.len(), 6);
+}
+
+#[test]
+fn rook_captured_castling_1() {
+    // capturing the h8 rook removes black's kingside castling even if another rook later stands there
+    let mut board = Board::from_fen("r3k2r/8/8/8/8/8/1B5r/4K3 w kq - 0 1").unwrap();
+    board.move_piece(Move::new(1, 1, 7, 7, PieceType::Bishop, None)).unwrap();
+    board.move_piece(Move::new(7, 1, 7, 7, PieceType::Rook, None)).unwrap();
+    board.move_piece(Move::new(4, 0, 3, 0, PieceType::King, None)).unwrap();
+    assert!(!board.generate_legal_moves().contains(&Move::new(4, 7, 6, 7, PieceType::King, None)));
+}
+
+#[test]
+fn position_5_3() {
+    let board = Board::from_fen("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8").unwrap();
+    assert_eq!(perft(3, board).unwrap(), 62379);
+}
+
+#[test]
+#[ignore = "slow in debug builds, run with --release -- --ignored"]
+fn position_5_4() {
+    let board = Board::from_fen("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8").unwrap();
+    assert_eq!(perft(4, board).unwrap(), 2103487);
+}
+
+#[test]
+#[ignore = "slow in debug builds, run with --release -- --ignored"]
+fn kiwipete_4() {
+    let board = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+    assert_eq!(perft(4, board).unwrap(), 4085603);
+}
+
+#[test]
+fn from_fen_malformed_1() {
+    let cases = [
+        ("", FenError::MissingFields(0)),
+        ("8/8/8", FenError::MissingFields(1)),
+        ("8/8/8 w - -", FenError::TooFewRanks),
+        ("8/8/8/8/8/8/8/8/8 w - -", FenError::TooManyRanks),
+        ("rnbqkbnr/pppppppp/9/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", FenError::InvalidPieceChar('9')),
+        ("rnbqkbnr/pppppppp/0/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", FenError::InvalidPieceChar('0')),
+        ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNX w KQkq - 0 1", FenError::InvalidPieceChar('X')),
+        ("rnbqkbnr/ppppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", FenError::BadRankLength(7)),
+        ("rnbqkbnr/pppppppp/45/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", FenError::BadRankLength(6)),
+        ("rnbqkbnr/pppppppp/7/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", FenError::BadRankLength(6)),
+        ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBN7 w KQkq - 0 1", FenError::BadRankLength(1)),
+        ("rnbqkbnr/pppppppp//8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", FenError::BadRankLength(6)),
+        ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1", FenError::BadSideToMove("x".to_string())),
+        ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkz - 0 1", FenError::BadCastlingField("KQkz".to_string())),
+        ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e9 0 1", FenError::BadEnPassantSquare("e9".to_string())),
+        ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq z6 0 1", FenError::BadEnPassantSquare("z6".to_string())),
+        ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e3 0 1", FenError::BadEnPassantSquare("e3".to_string())),
+        ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e 0 1", FenError::BadEnPassantSquare("e".to_string())),
+        ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - x 1", FenError::BadClockField("x".to_string())),
+        ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 -1", FenError::BadClockField("-1".to_string())),
+        ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - moves e2e4", FenError::BadClockField("moves".to_string())),
+    ];
+    for (fen, expected) in cases {
+        assert_eq!(Board::from_fen(fen).err(), Some(expected), "{}", fen);
+    }
+}
+
+#[test]
+fn from_fen_optional_fields_1() {
+    let board = Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -").unwrap();
+    assert_eq!(board, Board::starting_position());
+}
+
+#[test]
+fn extra_material_1() {
+    // ten white queens from a position editor
+    let board = Board::from_fen("7k/8/8/8/8/8/QQQQQ3/QQQQQ1K1 w - - 0 1").unwrap();
+    assert!(perft(2, board).unwrap() > 0);
+}
+
+#[test]
+fn king_count_1() {
+    assert_eq!(Board::from_fen("k6k/8/8/8/8/8/8/4K3 w - - 0 1").err(), Some(FenError::TooManyKings(Color::Black)));
+    assert_eq!(Board::from_fen("k7/8/8/8/8/8/8/K1K1K3 w - - 0 1").err(), Some(FenError::TooManyKings(Color::White)));
+    assert_eq!(Board::from_fen("8/8/8/8/8/8/8/4K3 w - - 0 1").err(), Some(FenError::MissingKing(Color::Black)));
+}
+
+#[test]
+fn state_accessors_1() {
+    let mut board = Board::starting_position();
+    assert_eq!(board.castling_rights(), CastlingRights { white_king: true, white_queen: true, black_king: true, black_queen: true });
+    assert_eq!(board.en_passant_square(), None);
+    assert_eq!(board.halfmove_clock(), 0);
+    assert_eq!(board.fullmove_number(), 1);
+
+    board.move_piece(Move::new(4, 1, 4, 3, PieceType::Pawn, None)).unwrap();
+    // No black pawn can take on e3 so only the permissive square is set
+    assert_eq!(board.en_passant_square(), None);
+    assert_eq!(board.en_passant_square_with(EnPassantMode::Permissive), Some((4, 2)));
+    assert_eq!(board.fullmove_number(), 1);
+    board.move_piece(Move::new(6, 7, 5, 5, PieceType::Knight, None)).unwrap();
+    assert_eq!(board.en_passant_square(), None);
+    assert_eq!(board.halfmove_clock(), 1);
+    assert_eq!(board.fullmove_number(), 2);
+    board.move_piece(Move::new(4, 0, 4, 1, PieceType::King, None)).unwrap();
+    assert!(!board.castling_rights().kingside(Color::White));
+    assert!(!board.castling_rights().queenside(Color::White));
+    assert!(board.castling_rights().kingside(Color::Black));
+    assert_eq!(board.halfmove_clock(), 2);
+    board.move_piece(Move::new(7, 7, 6, 7, PieceType::Rook, None)).unwrap();
+    assert_eq!(board.castling_rights().to_string(), "q");
+    assert_eq!(board.halfmove_clock(), 3);
+    assert_eq!(board.fullmove_number(), 3);
+}
+
+#[test]
+fn castling_rights_display_1() {
+    let board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w Kq - 0 1").unwrap();
+    assert_eq!(board.castling_rights().to_string(), "Kq");
+    assert_eq!(CastlingRights::none().to_string(), "-");
+}
+
+#[test]
+fn algebraic_move_malformed_1() {
+    // inputs that used to index out of bounds, underflow or slice inside a character
+    let mut board = Board::starting_position();
+    for text in ["e1", "e8", "a9", "Nx", "Nxé", "Né3", "xe4", "O-O", "Ke2", "e5", "", "h", "exd5", "Qh9"] {
+        assert!(board.algebraic_move(text).is_err(), "{}", text);
+    }
+    assert_eq!(board, Board::starting_position());
+    board.algebraic_move("e4").unwrap();
+    board.algebraic_move("d5").unwrap();
+    board.algebraic_move("exd5").unwrap();
+    board.algebraic_move("Qxd5").unwrap();
+    board.algebraic_move("Nc3").unwrap();
+    assert_eq!(board.to_fen(), "rnb1kbnr/ppp1pppp/8/3q4/8/2N5/PPPP1PPP/R1BQKBNR b KQkq - 1 3");
+}
+
+#[test]
+fn to_fen_round_trip_1() {
+    let fens = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        "8/8/1k6/2b5/2pP4/8/5K2/8 b - d3 0 1",
+    ];
+    for fen in fens {
+        assert_eq!(Board::from_fen(fen).unwrap().to_fen(), fen);
+    }
+}
+
+#[test]
+fn attackers_to_1() {
+    // d5 is attacked by the b3 queen, the f4 knight and the e4 pawn, the d1 rook is blocked by the d3 bishop
+    let board = Board::from_fen("4k3/8/8/3p4/4PN2/1Q1B4/8/3RK3 w - - 0 1").unwrap();
+    assert_eq!(board.attackers_to(3, 4, Color::White), vec![(1, 2), (4, 3), (5, 3)]);
+    assert_eq!(board.attackers_to(3, 4, Color::Black), vec![]);
+}
+
+#[test]
+fn attackers_to_2() {
+    // both pawn diagonals, and the square's own piece is never counted
+    let board = Board::from_fen("4k3/8/8/8/3n4/2P1P3/8/4K3 b - - 0 1").unwrap();
+    assert_eq!(board.attackers_to(3, 3, Color::White), vec![(2, 2), (4, 2)]);
+    assert_eq!(board.attackers_to(3, 3, Color::Black), vec![]);
+    assert_eq!(board.attackers_to(5, 2, Color::Black), vec![(3, 3)]);
+    assert_eq!(board.attackers_to(4, 2, Color::Black), vec![]);
+}
+
+#[test]
+fn see_1() {
+    // Qxe5 is answered by dxe5
+    let board = Board::from_fen("4k3/8/3p4/4p3/8/8/8/4QK2 w - - 0 1").unwrap();
+    assert_eq!(board.see(&Move::new(4, 0, 4, 4, PieceType::Queen, None)), -800);
+    // exf5 wins an undefended knight
+    let board = Board::from_fen("4k3/8/8/5n2/4P3/8/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(board.see(&Move::new(4, 3, 5, 4, PieceType::Pawn, None)), 300);
+}
+
+#[test]
+fn see_2() {
+    // Rxd8 Bxd8 trades rooks
+    let board = Board::from_fen("3r2k1/4b3/8/8/8/8/8/3R2K1 w - - 0 1").unwrap();
+    assert_eq!(board.see(&Move::new(3, 0, 3, 7, PieceType::Rook, None)), 0);
+    // With a second rook behind the first, Rxd8 Bxd8 Rxd8 wins a bishop
+    let board = Board::from_fen("3r2k1/4b3/8/8/8/8/3R4/3R2K1 w - - 0 1").unwrap();
+    assert_eq!(board.see(&Move::new(3, 1, 3, 7, PieceType::Rook, None)), 300);
+    // A defended pawn is not worth the rook
+    let board = Board::from_fen("6k1/8/8/8/8/2p5/1p6/1R1K4 w - - 0 1").unwrap();
+    assert_eq!(board.see(&Move::new(1, 0, 1, 1, PieceType::Rook, None)), -400);
+    // The king joins in last, Rxb2 cxb2 Kxb2
+    let board = Board::from_fen("6k1/8/8/8/8/K1p5/1p6/1R6 w - - 0 1").unwrap();
+    assert_eq!(board.see(&Move::new(1, 0, 1, 1, PieceType::Rook, None)), -300);
+}
+
+#[test]
+fn see_3() {
+    // En passant wins the pawn, bxc6 then wins the capturing pawn back
+    let board = Board::from_fen("4k3/1p6/8/2pP4/8/8/8/4K3 w - c6 0 1").unwrap();
+    assert_eq!(board.see(&Move::new(3, 4, 2, 5, PieceType::Pawn, None)), 0);
+    // An undefended promotion capture gains the rook and the promotion
+    let board = Board::from_fen("3r2k1/4P3/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(board.see(&Move::new(4, 6, 3, 7, PieceType::Pawn, Some(PieceType::Queen))), 1300);
+}
+
+#[test]
+fn checkers_1() {
+    assert!(Board::starting_position().checkers().is_empty());
+    // Double check from the e1 rook and the d6 knight
+    let board = Board::from_fen("4k3/8/3N4/8/8/8/8/4RK2 b - - 0 1").unwrap();
+    assert_eq!(board.checkers(), vec![(4, 0), (3, 5)]);
+    // Only king moves answer a double check
+    assert!(board.generate_legal_moves().iter().all(|mv| mv.piece_type == PieceType::King));
+    // The side not to move can be asked about too
+    assert!(board.checkers_of(Color::White).is_empty());
+}
+
+#[test]
+fn checkers_2() {
+    // Nd5 uncovers the e1 rook, a discovered check with the knight not checking
+    let mut board = Board::from_fen("4k3/8/8/8/8/8/4N3/4RK2 w - - 0 1").unwrap();
+    board.move_piece(Move::new(4, 1, 3, 3, PieceType::Knight, None)).unwrap();
+    assert_eq!(board.checkers(), vec![(4, 0)]);
+    // Nd6 instead uncovers the rook and checks with the knight, a double check
+    let mut board = Board::from_fen("4k3/8/8/8/4N3/8/8/4RK2 w - - 0 1").unwrap();
+    board.move_piece(Move::new(4, 3, 3, 5, PieceType::Knight, None)).unwrap();
+    assert_eq!(board.checkers().len(), 2);
+}
+
+#[test]
+fn en_passant_modes_1() {
+    // A black pawn on d4 can take e3, the permissive form is kept for compatibility
+    let board = Board::from_fen("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1").unwrap();
+    assert_eq!(board.en_passant_square(), Some((4, 2)));
+    assert_eq!(board.en_passant_square_with(EnPassantMode::Legal), Some((4, 2)));
+    // The capture exists but would expose the king, so only the legal mode drops it
+    let board = Board::from_fen("3k4/8/8/K1Pp3r/8/8/8/8 w - d6 0 2").unwrap();
+    assert_eq!(board.en_passant_square(), Some((3, 5)));
+    assert_eq!(board.en_passant_square_with(EnPassantMode::Legal), None);
+    assert_eq!(board.to_fen(), "3k4/8/8/K1Pp3r/8/8/8/8 w - d6 0 2");
+    assert_eq!(board.repetition_key(), "3k4/8/8/K1Pp3r/8/8/8/8 w - -");
+    // No pawn can capture at all
+    let board = Board::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1").unwrap();
+    assert_eq!(board.to_fen(), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1");
+    assert_eq!(board.to_fen_with(EnPassantMode::Permissive), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+}
+
+#[test]
+fn repetition_count_1() {
+    // 1. e4 leaves an en passant square no pawn can use, the position still repeats once the knights return
+    let mut board = Board::starting_position();
+    for san in ["e4", "Nf6", "Nf3", "Ng8", "Ng1"] {
+        board.algebraic_move(san).unwrap();
+    }
+    assert_eq!(board.repetition_count(), 1);
+    for san in ["Nf6", "Nf3", "Ng8", "Ng1"] {
+        board.algebraic_move(san).unwrap();
+    }
+    assert_eq!(board.repetition_count(), 2);
+    // Moving a pawn makes the earlier positions unreachable
+    board.algebraic_move("e5").unwrap();
+    assert_eq!(board.repetition_count(), 0);
+}
+
+#[test]
+fn repetition_count_2() {
+    // Moves made with make_move count, as a search makes them, and unmake_move takes them back out
+    let mut board = Board::starting_position();
+    let mut made = Vec::new();
+    for text in ["g1f3", "g8f6", "f3g1", "f6g8", "g1f3", "g8f6", "f3g1", "f6g8"] {
+        let mv = uci_to_move(&board, text).unwrap();
+        made.push((mv.clone(), board.make_move(&mv).unwrap()));
+    }
+    assert_eq!(board.repetition_count(), 2);
+    assert_eq!(board.is_draw(), Some(DrawReason::ThreefoldRepetition));
+    for _ in 0..4 {
+        let (mv, undo) = made.pop().unwrap();
+        board.unmake_move(&mv, undo);
+    }
+    assert_eq!(board.repetition_count(), 1);
+    assert_eq!(board.is_draw(), None);
+}
+
+#[test]
+fn legal_moves_from_1() {
+    let fens = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b KQkq - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        "3k4/8/8/K1Pp3r/8/8/8/8 w - d6 0 2",
+    ];
+    for fen in fens {
+        let board = Board::from_fen(fen).unwrap();
+        let mut union = Vec::new();
+        for y in 0..8 {
+            for x in 0..8 {
+                union.extend(board.legal_moves_from(x, y));
+            }
+        }
+        assert_eq!(union, board.generate_legal_moves(), "{}", fen);
+    }
+}
+
+#[test]
+fn legal_moves_from_2() {
+    // Castling for the king, all four promotions and en passant
+    let board = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+    let king = board.legal_moves_from(4, 0);
+    assert!(king.contains(&Move::new(4, 0, 6, 0, PieceType::King, None)));
+    assert!(king.contains(&Move::new(4, 0, 2, 0, PieceType::King, None)));
+    let board = Board::from_fen("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8").unwrap();
+    let promotions = board.legal_moves_from(3, 6);
+    for piece in [PieceType::Queen, PieceType::Rook, PieceType::Bishop, PieceType::Knight] {
+        assert!(promotions.contains(&Move::new(3, 6, 2, 7, PieceType::Pawn, Some(piece))));
+    }
+    let board = Board::from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3").unwrap();
+    assert!(board.legal_moves_from(4, 4).contains(&Move::new(4, 4, 5, 5, PieceType::Pawn, None)));
+    // Empty squares, opponent pieces and squares off the board have no moves
+    assert!(board.legal_moves_from(4, 3).is_empty());
+    assert!(board.legal_moves_from(3, 4).is_empty());
+    assert!(board.legal_moves_from(8, 0).is_empty());
+}
+
+#[test]
+fn capture_own_piece_1() {
+    // The d4 pawn may not take the knight on e5, and the board is left untouched
+    let mut board = Board::from_fen("4k3/8/8/4N3/3P4/8/8/4K3 w - - 0 1").unwrap();
+    let before = board.clone();
+    let result = board.move_piece(Move::new(3, 3, 4, 4, PieceType::Pawn, None));
+    assert!(matches!(result, Err(MoveError::CannotCaptureOwnPiece)));
+    assert_identical(&board, &before);
+    assert!(!board.generate_legal_moves().contains(&Move::new(3, 3, 4, 4, PieceType::Pawn, None)));
+}
+
+#[test]
+fn capture_own_piece_2() {
+    // Sliders report their own piece on the destination, and a piece in the way as blocked
+    let mut board = Board::from_fen("4k3/8/8/8/8/8/1P6/RB1QK3 w - - 0 1").unwrap();
+    let before = board.clone();
+    assert!(matches!(board.move_piece(Move::new(0, 0, 1, 0, PieceType::Rook, None)), Err(MoveError::CannotCaptureOwnPiece)));
+    assert!(matches!(board.move_piece(Move::new(3, 0, 1, 0, PieceType::Queen, None)), Err(MoveError::CannotCaptureOwnPiece)));
+    assert!(matches!(board.move_piece(Move::new(3, 0, 0, 0, PieceType::Queen, None)), Err(MoveError::MoveBlocked)));
+    assert!(board.move_piece(Move::new(1, 0, 0, 1, PieceType::Bishop, None)).is_ok());
+    board.undo_move();
+    assert!(board.move_piece(Move::new(0, 0, 0, 2, PieceType::Rook, None)).is_ok());
+    board.undo_move();
+    assert_identical(&board, &before);
+}
+
+#[test]
+fn king_move_rejected_1() {
+    // The king may not take its own pawn, and the rejected moves leave the castling rights alone
+    let mut board = Board::from_fen("r3k2r/8/8/8/8/8/3P4/R3K2R w KQkq - 0 1").unwrap();
+    let before = board.clone();
+    assert!(matches!(board.move_piece(Move::new(4, 0, 3, 1, PieceType::King, None)), Err(MoveError::CannotCaptureOwnPiece)));
+    assert_identical(&board, &before);
+    // After Rf8 the f-file is covered, so the king may not step onto it
+    board.move_piece(Move::new(7, 0, 7, 1, PieceType::Rook, None)).unwrap();
+    board.move_piece(Move::new(7, 7, 5, 7, PieceType::Rook, None)).unwrap();
+    let before = board.clone();
+    assert!(matches!(board.move_piece(Move::new(4, 0, 5, 1, PieceType::King, None)),
+        Err(MoveError::MovePinnedOrExposesKing { attacker: (5, 7), attacker_type: PieceType::Rook })));
+    assert!(matches!(board.move_piece(Move::new(4, 0, 5, 0, PieceType::King, None)),
+        Err(MoveError::MovePinnedOrExposesKing { attacker: (5, 7), attacker_type: PieceType::Rook })));
+    assert_identical(&board, &before);
+    assert_eq!(board.castling_rights().to_string(), "Qq");
+}
+
+#[test]
+fn pinned_piece_1() {
+    // The knight on d5 is pinned to the king on g8 by the bishop on b3
+    let mut board = Board::from_fen("6k1/8/8/3n4/8/1B6/8/4K3 b - - 0 1").unwrap();
+    let before = board.clone();
+    assert!(matches!(board.move_piece(Move::new(3, 4, 5, 3, PieceType::Knight, None)),
+        Err(MoveError::MovePinnedOrExposesKing { attacker: (1, 2), attacker_type: PieceType::Bishop })));
+    assert_identical(&board, &before);
+}
+
+#[test]
+fn pinned_piece_2() {
+    // Taking en passant would remove both pawns between the king on a5 and the rook on h5
+    let mut board = Board::from_fen("8/8/8/KPp4r/8/8/8/7k w - c6 0 1").unwrap();
+    assert!(matches!(board.move_piece(Move::new(1, 4, 2, 5, PieceType::Pawn, None)),
+        Err(MoveError::MovePinnedOrExposesKing { attacker: (7, 4), attacker_type: PieceType::Rook })));
+}
+
+#[test]
+fn king_in_check_error_1() {
+    // The bishop on h4 checks the king on e1 and moving the knight to b3 does nothing about it
+    let mut board = Board::from_fen("4k3/8/8/8/7b/8/3N4/4K3 w - - 0 1").unwrap();
+    let before = board.clone();
+    assert!(matches!(board.move_piece(Move::new(3, 1, 1, 2, PieceType::Knight, None)),
+        Err(MoveError::KingInCheck { checker: (7, 3), checker_type: PieceType::Bishop })));
+    assert_identical(&board, &before);
+}
+
+#[test]
+fn king_in_check_error_2() {
+    // In check from the rook on e8, blocking on e3 with the bishop pinned by b4 still reports the rook
+    let mut board = Board::from_fen("4r1k1/8/8/8/1b6/8/3B4/4K3 w - - 0 1").unwrap();
+    assert!(matches!(board.move_piece(Move::new(3, 1, 4, 2, PieceType::Bishop, None)),
+        Err(MoveError::KingInCheck { checker: (4, 7), checker_type: PieceType::Rook })));
+}
+
+/// Logger keeping the records made on threads that asked for them, so parallel tests do not see each other's logs
+struct CapturingLogger;
+
+thread_local! {
+    static CAPTURED: std::cell::RefCell<Option<Vec<String>>> = const { std::cell::RefCell::new(None) };
+}
+
+impl log::Log for CapturingLogger {
+    fn enabled(&self, _: &log::Metadata) -> bool {
+        CAPTURED.with(|captured| captured.borrow().is_some())
+    }
+
+    fn log(&self, record: &log::Record) {
+        CAPTURED.with(|captured| if let Some(records) = captured.borrow_mut().as_mut() {
+            records.push(format!("{} {}", record.level(), record.args()));
+        });
+    }
+
+    fn flush(&self) {}
+}
+
+static CAPTURING_LOGGER: CapturingLogger = CapturingLogger;
+
+/// Runs `f` with records from this thread captured at Info and above, the level the binary logs at
+fn capture_logs(f: impl FnOnce()) -> Vec<String> {
+    static INSTALL: std::sync::Once = std::sync::Once::new();
+    INSTALL.call_once(|| {
+        log::set_logger(&CAPTURING_LOGGER).expect("no other test installs a logger");
+        log::set_max_level(log::LevelFilter::Info);
+    });
+    CAPTURED.with(|captured| *captured.borrow_mut() = Some(Vec::new()));
+    f();
+    CAPTURED.with(|captured| captured.borrow_mut().take().unwrap_or_default())
+}
+
+#[test]
+fn rejected_moves_not_logged_1() {
+    // Trying every from/to pair of kiwipete rejects thousands of moves without logging any of them
+    let mut board = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+    let before = board.clone();
+    let mut rejected = 0;
+    let logs = capture_logs(|| {
+        for from in 0..64 {
+            let Some(piece) = board.get_piece(from % 8, from / 8) else { continue };
+            for to in 0..64 {
+                let mv = Move::new(from % 8, from / 8, to % 8, to / 8, piece.get_type(), None);
+                assert_eq!(board.is_legal(&mv), board.clone().move_piece(mv.clone()).is_ok());
+                match board.move_piece(mv) {
+                    Ok(_) => { board.undo_move(); }
+                    Err(_) => rejected += 1,
+                }
+            }
+        }
+        assert!(board.try_move_uci("e1e3").is_err());
+        assert!(board.try_move_uci("z9a1").is_err());
+    });
+    assert!(rejected > 1000);
+    assert!(logs.is_empty(), "rejected moves logged {:?}", logs);
+    assert_identical(&board, &before);
+}
+
+#[test]
+fn zobrist_key_1() {
+    let mut board = Board::starting_position();
+    let start = board.zobrist_key();
+    let moves = [
+        Move::new(6, 0, 5, 2, PieceType::Knight, None),
+        Move::new(6, 7, 5, 5, PieceType::Knight, None),
+        Move::new(5, 2, 6, 0, PieceType::Knight, None),
+    ];
+    for mv in &moves {
+        board.move_piece(mv.clone()).unwrap();
+        assert_ne!(board.zobrist_key(), start);
+    }
+    board.move_piece(Move::new(5, 5, 6, 7, PieceType::Knight, None)).unwrap();
+    assert_eq!(board.zobrist_key(), start);
+    for _ in 0..4 {
+        board.undo_move();
+    }
+    assert_eq!(board.zobrist_key(), start);
+    // Same pieces with the other side to move or fewer castling rights are different positions
+    let key = |fen| Board::from_fen(fen).unwrap().zobrist_key();
+    assert_ne!(key("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1"), start);
+    assert_ne!(key("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w Kkq - 0 1"), start);
+}
+
+#[test]
+fn zobrist_key_2() {
+    // The en passant square only changes the key when a pawn can capture on it
+    let key = |fen| Board::from_fen(fen).unwrap().zobrist_key();
+    assert_eq!(key("4k3/8/8/8/4P3/8/8/4K3 b - e3 0 1"), key("4k3/8/8/8/4P3/8/8/4K3 b - - 0 1"));
+    assert_ne!(key("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1"), key("4k3/8/8/8/3pP3/8/8/4K3 b - - 0 1"));
+}
+
+#[test]
+fn move_result_1() {
+    let mut board = Board::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+    let result = board.move_piece(Move::new(4, 4, 3, 5, PieceType::Pawn, None)).unwrap();
+    assert_eq!(result, MoveResult { captured: Some(PieceType::Pawn), captured_square: Some((3, 4)), is_castle: None, is_en_passant: true, promotion: None, gives_check: false });
+    // A quiet move reports nothing
+    let result = board.move_piece(Move::new(4, 7, 3, 7, PieceType::King, None)).unwrap();
+    assert_eq!(result, MoveResult::default());
+}
+
+#[test]
+fn move_result_2() {
+    let mut board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+    let result = board.move_piece(Move::new(4, 0, 6, 0, PieceType::King, None)).unwrap();
+    assert_eq!(result, MoveResult { is_castle: Some(Side::KingSide), ..MoveResult::default() });
+    // The rook lands on d8 and checks the king on d1
+    let mut board = Board::from_fen("r3k3/8/8/8/8/8/8/3K4 b q - 0 1").unwrap();
+    let result = board.move_piece(Move::new(4, 7, 2, 7, PieceType::King, None)).unwrap();
+    assert_eq!(result, MoveResult { is_castle: Some(Side::QueenSide), gives_check: true, ..MoveResult::default() });
+}
+
+#[test]
+fn move_result_3() {
+    // Taking the rook on b8 and promoting checks the king along the back rank
+    let mut board = Board::from_fen("1r5k/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    let result = board.move_piece(Move::new(0, 6, 1, 7, PieceType::Pawn, Some(PieceType::Queen))).unwrap();
+    assert_eq!(result, MoveResult { captured: Some(PieceType::Rook), captured_square: Some((1, 7)), is_castle: None, is_en_passant: false, promotion: Some(PieceType::Queen), gives_check: true });
+    // An underpromotion to a knight does not give check
+    board.undo_move();
+    let result = board.move_piece(Move::new(0, 6, 1, 7, PieceType::Pawn, Some(PieceType::Knight))).unwrap();
+    assert_eq!(result.promotion, Some(PieceType::Knight));
+    assert!(!result.gives_check);
+}
+
+#[test]
+fn to_ascii_1() {
+    let board = Board::starting_position();
+    let expected = "    a   b   c   d   e   f   g   h
+  +---+---+---+---+---+---+---+---+
+8 | r | n | b | q | k | b | n | r | 8
+  +---+---+---+---+---+---+---+---+
+7 | p | p | p | p | p | p | p | p | 7
+  +---+---+---+---+---+---+---+---+
+6 |   |   |   |   |   |   |   |   | 6
+  +---+---+---+---+---+---+---+---+
+5 |   |   |   |   |   |   |   |   | 5
+  +---+---+---+---+---+---+---+---+
+4 |   |   |   |   |   |   |   |   | 4
+  +---+---+---+---+---+---+---+---+
+3 |   |   |   |   |   |   |   |   | 3
+  +---+---+---+---+---+---+---+---+
+2 | P | P | P | P | P | P | P | P | 2
+  +---+---+---+---+---+---+---+---+
+1 | R | N | B | Q | K | B | N | R | 1
+  +---+---+---+---+---+---+---+---+
+    a   b   c   d   e   f   g   h
+";
+    assert_eq!(board.to_ascii(Color::White), expected);
+    assert_eq!(board.to_string(), expected);
+}
+
+#[test]
+fn to_ascii_2() {
+    // From Black's side the ranks and files are reversed
+    let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w Q - 0 1").unwrap();
+    let ascii = board.to_ascii(Color::Black);
+    let lines = ascii.lines().collect::<Vec<_>>();
+    assert_eq!(lines[0], "    h   g   f   e   d   c   b   a");
+    assert_eq!(lines[2], "1 |   |   |   | K |   |   |   | R | 1");
+    assert_eq!(lines[16], "8 |   |   |   | k |   |   |   |   | 8");
+}
+
+/// The position after Ra8+ drawn with every render option on
+fn render_after_rook_check(perspective: Color) -> String {
+    let mut board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+    let mv = Move::new(0, 0, 0, 7, PieceType::Rook, None);
+    board.move_piece(mv.clone()).unwrap();
+    board.render(&BoardRenderOptions { unicode: true, highlight: Some(mv), mark_check: true, perspective, ..BoardRenderOptions::default() })
+}
+
+#[test]
+fn render_1() {
+    let expected = "    a   b   c   d   e   f   g   h
+  +---+---+---+---+---+---+---+---+
+8 |[♖]|   |   |   |!♚!|   |   |   | 8
+  +---+---+---+---+---+---+---+---+
+7 |   |   |   |   |   |   |   |   | 7
+  +---+---+---+---+---+---+---+---+
+6 |   |   |   |   |   |   |   |   | 6
+  +---+---+---+---+---+---+---+---+
+5 |   |   |   |   |   |   |   |   | 5
+  +---+---+---+---+---+---+---+---+
+4 |   |   |   |   |   |   |   |   | 4
+  +---+---+---+---+---+---+---+---+
+3 |   |   |   |   |   |   |   |   | 3
+  +---+---+---+---+---+---+---+---+
+2 |   |   |   |   |   |   |   |   | 2
+  +---+---+---+---+---+---+---+---+
+1 |[ ]|   |   |   | ♔ |   |   |   | 1
+  +---+---+---+---+---+---+---+---+
+    a   b   c   d   e   f   g   h
+";
+    assert_eq!(render_after_rook_check(Color::White), expected);
+}
+
+#[test]
+fn render_2() {
+    let expected = "    h   g   f   e   d   c   b   a
+  +---+---+---+---+---+---+---+---+
+1 |   |   |   | ♔ |   |   |   |[ ]| 1
+  +---+---+---+---+---+---+---+---+
+2 |   |   |   |   |   |   |   |   | 2
+  +---+---+---+---+---+---+---+---+
+3 |   |   |   |   |   |   |   |   | 3
+  +---+---+---+---+---+---+---+---+
+4 |   |   |   |   |   |   |   |   | 4
+  +---+---+---+---+---+---+---+---+
+5 |   |   |   |   |   |   |   |   | 5
+  +---+---+---+---+---+---+---+---+
+6 |   |   |   |   |   |   |   |   | 6
+  +---+---+---+---+---+---+---+---+
+7 |   |   |   |   |   |   |   |   | 7
+  +---+---+---+---+---+---+---+---+
+8 |   |   |   |!♚!|   |   |   |[♖]| 8
+  +---+---+---+---+---+---+---+---+
+    h   g   f   e   d   c   b   a
+";
+    assert_eq!(render_after_rook_check(Color::Black), expected);
+}
+
+#[test]
+fn render_3() {
+    // The default options draw the same board as print
+    let board = Board::starting_position();
+    assert_eq!(board.render(&BoardRenderOptions::default()), board.to_ascii(Color::White));
+    let options = BoardRenderOptions { perspective: Color::Black, ..BoardRenderOptions::default() };
+    assert_eq!(board.render(&options), board.to_ascii(Color::Black));
+}
+
+#[test]
+fn edit_position_1() {
+    // Kiwipete built one square at a time matches the parsed FEN exactly
+    let mut board = Board::empty();
+    let pieces = [
+        ("a8", 'r'), ("e8", 'k'), ("h8", 'r'),
+        ("a7", 'p'), ("c7", 'p'), ("d7", 'p'), ("e7", 'q'), ("f7", 'p'), ("g7", 'b'),
+        ("a6", 'b'), ("b6", 'n'), ("e6", 'p'), ("f6", 'n'), ("g6", 'p'),
+        ("d5", 'P'), ("e5", 'N'),
+        ("b4", 'p'), ("e4", 'P'),
+        ("c3", 'N'), ("f3", 'Q'), ("h3", 'p'),
+        ("a2", 'P'), ("b2", 'P'), ("c2", 'P'), ("d2", 'B'), ("e2", 'B'), ("f2", 'P'), ("g2", 'P'), ("h2", 'P'),
+        ("a1", 'R'), ("e1", 'K'), ("h1", 'R'),
+    ];
+    for (square, c) in pieces {
+        let (x, y) = square_to_coords(square).unwrap();
+        assert_eq!(board.set_piece(x, y, Piece::from_fen(c).unwrap()), None);
+    }
+    board.set_castling_rights(CastlingRights { white_king: true, white_queen: true, black_king: true, black_queen: true });
+    board.set_turn(Color::White);
+    let expected = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+    assert_identical(&board, &expected);
+    assert_eq!(perft(2, board).unwrap(), 2039);
+}
+
+#[test]
+fn edit_position_2() {
+    // Moving a king by hand moves its cached position with it
+    let mut board = Board::empty();
+    board.set_piece(4, 0, Piece::new(PieceType::King, Color::White));
+    board.set_piece(4, 7, Piece::new(PieceType::King, Color::Black));
+    board.set_piece(4, 3, Piece::new(PieceType::Rook, Color::White));
+    board.set_turn(Color::Black);
+    assert_eq!(board.checkers(), vec![(4, 3)]);
+    assert_eq!(board.remove_piece(4, 7), Some(Piece::new(PieceType::King, Color::Black)));
+    assert_eq!(board.validate_position(), Err(FenError::MissingKing(Color::Black)));
+    board.set_piece(3, 7, Piece::new(PieceType::King, Color::Black));
+    assert!(board.checkers().is_empty());
+    assert_eq!(board.to_fen(), "3k4/8/8/8/4R3/8/8/4K3 b - - 0 1");
+    board.set_en_passant(Some((4, 2)));
+    assert_eq!(board.to_fen_with(EnPassantMode::Permissive), "3k4/8/8/8/4R3/8/8/4K3 b - e3 0 1");
+    board.clear();
+    assert_eq!(board, Board::empty());
+}
+
+/// Positions covering castling rights, en passant, promotions and both sides to move
+const TRANSFORM_CORPUS: [&str; 8] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+    "4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1",
+    "r1b2rk1/2q1bppp/p2p1n2/np2p3/3PP3/5N1P/PPBN1PP1/R1BQR1K1 b - - 3 14",
+];
+
+#[test]
+fn mirror_1() {
+    // Mirroring swaps the sides, so the evaluation changes sign and the move count is unchanged
+    for fen in TRANSFORM_CORPUS {
+        let board = Board::from_fen(fen).unwrap();
+        let mirrored = board.mirror();
+        assert_eq!(board.basic_evaluate(), -mirrored.basic_evaluate(), "{}", fen);
+        assert_eq!(board.evaluate_to_move(), mirrored.evaluate_to_move(), "{}", fen);
+        assert_eq!(perft(2, board.clone()).unwrap(), perft(2, mirrored.clone()).unwrap(), "{}", fen);
+        assert_eq!(board.checkers().len(), mirrored.checkers().len(), "{}", fen);
+        assert_eq!(mirrored.mirror(), board, "{}", fen);
+        assert_eq!(Board::from_fen(&mirrored.to_fen()).unwrap(), mirrored, "{}", fen);
+    }
+}
+
+#[test]
+fn mirror_2() {
+    let board = Board::from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3").unwrap();
+    assert_eq!(board.mirror().to_fen(), "rnbqkbnr/pppp1ppp/8/8/3PpP2/8/PPP1P1PP/RNBQKBNR b KQkq f3 0 3");
+}
+
+#[test]
+fn flip_horizontal_1() {
+    // Colors are kept so the evaluation is unchanged, castling rights are dropped
+    for fen in TRANSFORM_CORPUS {
+        let board = Board::from_fen(fen).unwrap();
+        let flipped = board.flip_horizontal();
+        assert_eq!(board.basic_evaluate(), flipped.basic_evaluate(), "{}", fen);
+        assert_eq!(flipped.castling_rights(), CastlingRights::none(), "{}", fen);
+        assert_eq!(flipped.flip_horizontal().to_fen(), board.to_fen().replacen(&format!(" {} ", board.castling_rights()), " - ", 1), "{}", fen);
+        assert_eq!(Board::from_fen(&flipped.to_fen()).unwrap(), flipped, "{}", fen);
+        if board.castling_rights() == CastlingRights::none() {
+            assert_eq!(perft(2, board.clone()).unwrap(), perft(2, flipped).unwrap(), "{}", fen);
+        }
+    }
+}
+
+/// Plays moves given in UCI notation from the starting position
+fn play(moves: &[&str]) -> Board {
+    let mut board = Board::starting_position();
+    for text in moves {
+        board.move_piece(uci_to_move(&board, text).unwrap()).unwrap();
+    }
+    board
+}
+
+#[test]
+fn position_equality_1() {
+    // The same position reached in a different order is equal and hashes the same
+    let first = play(&["g1f3", "g8f6", "b1c3", "b8c6"]);
+    let second = play(&["b1c3", "b8c6", "g1f3", "g8f6"]);
+    assert_eq!(first, second);
+    assert_eq!(HashSet::from([first, second]).len(), 1);
+}
+
+#[test]
+fn position_equality_2() {
+    // Clocks are not compared
+    let back_home = play(&["g1f3", "g8f6", "f3g1", "f6g8"]);
+    assert_eq!(back_home.halfmove_clock(), 4);
+    assert_eq!(back_home, Board::starting_position());
+    let mut seen = HashSet::new();
+    seen.insert(Board::starting_position());
+    assert!(seen.contains(&back_home));
+}
+
+#[test]
+fn position_equality_3() {
+    // Losing castling rights makes an otherwise identical position different
+    let rooks = play(&["g1f3", "g8f6", "h1g1", "h8g8", "g1h1", "g8h8"]);
+    let knights = play(&["g1f3", "g8f6", "b1c3", "b8c6", "c3b1", "c6b8"]);
+    assert_eq!(rooks.get_squares(), knights.get_squares());
+    assert_ne!(rooks, knights);
+    assert_eq!(HashSet::from([rooks, knights]).len(), 2);
+}
+
+#[test]
+fn castling_consistency_1() {
+    // Each right on its own needs the king and its rook at home
+    let cases = [
+        ("r3k2r/8/8/8/8/8/8/R3K1R1 w K - 0 1", 'K'),
+        ("r3k2r/8/8/8/8/8/8/1R2K2R w Q - 0 1", 'Q'),
+        ("r3k1r1/8/8/8/8/8/8/R3K2R w k - 0 1", 'k'),
+        ("1r2k2r/8/8/8/8/8/8/R3K2R w q - 0 1", 'q'),
+        ("r3k2r/8/8/8/8/8/4K3/R6R w K - 0 1", 'K'),
+        ("r6r/3k4/8/8/8/8/8/R3K2R w q - 0 1", 'q'),
+    ];
+    for (fen, right) in cases {
+        assert_eq!(Board::from_fen(fen).unwrap_err(), FenError::InconsistentCastlingRights(right), "{}", fen);
+        let (board, repairs) = Board::from_fen_with(fen, CastlingValidation::Repair).unwrap();
+        assert_eq!(board.castling_rights(), CastlingRights::none(), "{}", fen);
+        assert_eq!(repairs.iter().map(|repair| repair.right).collect::<Vec<_>>(), vec![right], "{}", fen);
+    }
+    // Consistent rights are the same in both modes
+    let fen = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+    let (board, repairs) = Board::from_fen_with(fen, CastlingValidation::Repair).unwrap();
+    assert!(repairs.is_empty());
+    assert_eq!(board, Board::from_fen(fen).unwrap());
+}
+
+#[test]
+fn castling_repair_1() {
+    // The king on e2 loses both white rights, the missing a8 rook loses black's queenside
+    let fen = "4k2r/8/8/8/8/8/4K3/R6R w KQkq - 0 1";
+    let (board, repairs) = Board::from_fen_with(fen, CastlingValidation::Repair).unwrap();
+    assert_eq!(board.to_fen(), "4k2r/8/8/8/8/8/4K3/R6R w k - 0 1");
+    assert_eq!(repairs, vec![
+        CastlingRepair { right: 'K', color: Color::White, piece: PieceType::King, square: (4, 0) },
+        CastlingRepair { right: 'Q', color: Color::White, piece: PieceType::King, square: (4, 0) },
+        CastlingRepair { right: 'q', color: Color::Black, piece: PieceType::Rook, square: (0, 7) },
+    ]);
+    let text = repairs.iter().map(|repair| repair.to_string()).collect::<Vec<_>>();
+    assert_eq!(text, [
+        "dropped castling right 'K', no White king on e1",
+        "dropped castling right 'Q', no White king on e1",
+        "dropped castling right 'q', no Black rook on a8",
+    ]);
+}
+
+#[test]
+fn castling_king_home_1() {
+    // Rights set by hand are not enough to castle with the king away from e1
+    let mut board = Board::from_fen("4k3/8/8/8/8/8/8/R3K2R w - - 0 1").unwrap();
+    board.remove_piece(4, 0);
+    board.set_piece(4, 1, Piece::new(PieceType::King, Color::White));
+    board.set_castling_rights(CastlingRights { white_king: true, white_queen: true, black_king: false, black_queen: false });
+    assert!(board.generate_legal_moves().iter().all(|mv| mv.piece_type != PieceType::King || mv.from_x.abs_diff(mv.to_x) < 2));
+}
+
+#[test]
+fn null_move_1() {
+    // Unmaking restores the FEN, including the en passant square and clocks
+    for fen in ["rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3", "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b KQkq - 4 9"] {
+        let original = Board::from_fen(fen).unwrap();
+        let mut board = original.clone();
+        let undo = board.make_null_move();
+        assert_ne!(board.get_player_turn(), original.get_player_turn());
+        assert_eq!(board.en_passant_square_with(EnPassantMode::Permissive), None);
+        assert_eq!(board.halfmove_clock(), original.halfmove_clock() + 1);
+        assert_ne!(board.zobrist_key(), original.zobrist_key());
+        board.unmake_null_move(undo);
+        assert_eq!(board.to_fen(), fen);
+        assert_identical(&board, &original);
+    }
+}
+
+#[test]
+fn null_move_2() {
+    // After White passes the legal moves are Black's
+    let mut board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w Q - 0 1").unwrap();
+    board.make_null_move();
+    let moves = board.generate_legal_moves();
+    assert_eq!(moves.len(), 5);
+    assert!(moves.iter().all(|mv| board.get_squares()[mv.from_y][mv.from_x].unwrap().get_color() == Color::Black));
+    let expected = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 b Q - 1 1").unwrap();
+    assert_eq!(board, expected);
+}
+
+#[test]
+fn validate_1() {
+    // Boards from FENs and after moves are consistent
+    let mut board = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+    assert_eq!(board.validate(), Ok(()));
+    for mv in ["e1g1", "a8d8", "a2a4", "b4a3"] {
+        board.move_piece(uci_to_move(&board, mv).unwrap()).unwrap();
+        assert_eq!(board.validate(), Ok(()), "{}", mv);
+    }
+}
+
+#[test]
+fn validate_2() {
+    // Capturing a king with unchecked_move_piece leaves a side without one
+    let mut board = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    board.unchecked_move_piece(4, 0, 4, 7);
+    assert_eq!(board.validate(), Err(ConsistencyError::KingCount { color: Color::Black, count: 0 }));
+    // Moving a king by hand keeps the cache in step
+    let mut board = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    board.unchecked_move_piece(4, 0, 0, 3);
+    assert_eq!(board.validate(), Ok(()));
+    assert!(board.is_square_attacked(0, 4, Color::White));
+}
+
+#[test]
+fn validate_3() {
+    // Unmaking with the wrong move restores the cached king square but puts the king on another one
+    let mut board = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    let undo = board.make_move(&Move::new(4, 0, 3, 0, PieceType::King, None)).unwrap();
+    board.unmake_move(&Move::new(3, 1, 3, 0, PieceType::King, None), undo);
+    let error = board.validate().unwrap_err();
+    assert_eq!(error, ConsistencyError::KingCacheMismatch { color: Color::White, cached: (4, 0) });
+    assert_eq!(error.to_string(), "White king is cached on e1 but is not there");
+}
+
+#[test]
+fn validate_4() {
+    // An en passant square on the wrong side of the board
+    let mut board = Board::from_fen("4k3/8/8/8/4P3/8/8/4K3 b - - 0 1").unwrap();
+    board.set_en_passant(Some((4, 5)));
+    assert_eq!(board.validate(), Err(ConsistencyError::BadEnPassantSquare((4, 5))));
+}
+
+#[test]
+#[cfg(debug_assertions)]
+fn validate_5() {
+    // With White left in check and Black to move, taking the king is turned away in debug builds rather than panicking
+    let mut board = Board::from_fen("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 b kq - 0 1").unwrap();
+    let before = board.clone();
+    let result = board.move_piece(uci_to_move(&board, "b6g1").unwrap());
+    assert_eq!(result, Err(MoveError::Inconsistent(ConsistencyError::KingCount { color: Color::White, count: 0 })));
+    assert_identical(&board, &before);
+}
+
+#[test]
+fn is_draw_1() {
+    // One position for each reason
+    let stalemate = Board::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+    assert_eq!(stalemate.is_draw(), Some(DrawReason::Stalemate));
+    let bare_kings = Board::from_fen("8/8/4k3/8/8/3K4/8/8 w - - 0 1").unwrap();
+    assert_eq!(bare_kings.is_draw(), Some(DrawReason::InsufficientMaterial));
+    let fifty = Board::from_fen("8/8/4k3/8/8/3K4/8/R7 b - - 100 80").unwrap();
+    assert_eq!(fifty.is_draw(), Some(DrawReason::FiftyMoveRule));
+    let mut repeated = Board::starting_position();
+    for mv in ["g1f3", "g8f6", "f3g1", "f6g8", "g1f3", "g8f6", "f3g1"] {
+        repeated.move_piece(uci_to_move(&repeated, mv).unwrap()).unwrap();
+        assert_eq!(repeated.is_draw(), None, "{}", mv);
+    }
+    repeated.move_piece(uci_to_move(&repeated, "f6g8").unwrap()).unwrap();
+    assert_eq!(repeated.is_draw(), Some(DrawReason::ThreefoldRepetition));
+    let middlegame = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+    assert_eq!(middlegame.is_draw(), None);
+}
+
+#[test]
+fn is_draw_2() {
+    // Stalemate comes first, then insufficient material, then the fifty-move rule
+    let stalemate_bishop = Board::from_fen("7k/5K2/6B1/8/8/8/8/8 b - - 100 90").unwrap();
+    assert_eq!(stalemate_bishop.is_draw(), Some(DrawReason::Stalemate));
+    let bishop_fifty = Board::from_fen("8/8/4k3/8/8/3K4/8/B7 b - - 100 80").unwrap();
+    assert_eq!(bishop_fifty.is_draw(), Some(DrawReason::InsufficientMaterial));
+    // Mate on the hundredth halfmove stands
+    let mated = Board::from_fen("R6k/8/7K/8/8/8/8/8 b - - 100 80").unwrap();
+    assert_eq!(mated.is_draw(), None);
+}
+
+#[test]
+fn insufficient_material_1() {
+    let cases = [
+        ("8/8/4k3/8/8/3K4/8/8 w - - 0 1", true),
+        ("8/8/4k3/8/8/3K4/8/1N6 w - - 0 1", true),
+        ("8/8/4kb2/8/8/3K4/8/8 w - - 0 1", true),
+        // Bishops all on dark squares, whoever owns them
+        ("8/8/4k3/2b5/8/3K4/8/B7 w - - 0 1", true),
+        ("8/8/4k3/2b5/8/3K4/8/1B6 w - - 0 1", false),
+        ("8/8/4k3/8/8/3K4/8/1N4N1 w - - 0 1", false),
+        ("8/8/4kn2/8/8/3K4/8/B7 w - - 0 1", false),
+        ("8/8/4k3/8/8/3K4/7P/8 w - - 0 1", false),
+        ("8/8/4k3/8/8/3K4/8/7R w - - 0 1", false),
+    ];
+    for (fen, insufficient) in cases {
+        assert_eq!(Board::from_fen(fen).unwrap().is_insufficient_material(), insufficient, "{}", fen);
+    }
+}
+
+/// Checks gives_check against playing each legal move on a copy, for every position down to the given depth
+fn assert_gives_check_walk(board: &mut Board, depth: u32) -> usize {
+    let mut checks = 0;
+    for mv in board.generate_legal_moves() {
+        let undo = board.make_move(&mv).unwrap();
+        let in_check = board.king_in_check();
+        if depth > 1 {
+            checks += assert_gives_check_walk(board, depth - 1);
+        }
+        board.unmake_move(&mv, undo);
+        assert_eq!(board.gives_check(&mv), in_check, "{} {}", board.to_fen(), mv.extended_algebraic());
+        checks += in_check as usize;
+    }
+    checks
+}
+
+#[test]
+fn gives_check_1() {
+    // Every move of a perft 3 walk from Kiwipete, which has castling, en passant and promotions
+    let mut board = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+    // No checks at depth 1, 3 at depth 2 and 993 at depth 3
+    assert_eq!(assert_gives_check_walk(&mut board, 3), 3 + 993);
+}
+
+#[test]
+fn gives_check_2() {
+    // Discovered checks from castling, en passant and promotion
+    let cases = [
+        ("5k2/8/8/8/8/8/8/4K2R w K - 0 1", "e1g1", true),
+        ("2k5/8/8/8/8/8/8/R3K3 w Q - 0 1", "e1c1", false),
+        ("8/8/8/RPp4k/8/8/8/4K3 w - c6 0 1", "b5c6", true),
+        ("6k1/8/8/8/8/8/1p6/R1K5 b - - 0 1", "b2a1n", false),
+        ("6k1/8/8/8/8/8/1p6/R1K5 b - - 0 1", "b2a1q", true),
+        ("4k3/8/8/8/8/8/1p6/R3K3 b - - 0 1", "b2b1q", true),
+        ("4k3/8/8/8/8/8/4N3/4K3 w - - 0 1", "e2c3", false),
+        ("4k3/8/8/8/8/8/4N3/4R1K1 w - - 0 1", "e2c3", true),
+    ];
+    for (fen, mv, check) in cases {
+        let board = Board::from_fen(fen).unwrap();
+        let mv = uci_to_move(&board, mv).unwrap();
+        assert_eq!(board.gives_check(&mv), check, "{} {}", fen, mv.extended_algebraic());
+    }
+}
+
+#[test]
+fn classify_move_1() {
+    // Every legal move two plies deep from positions with castling, en passant and promotions is accepted,
+    // and its kind matches what the board shows about the squares it moves between
+    let fens = [
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    ];
+    for fen in fens {
+        let mut board = Board::from_fen(fen).unwrap();
+        for first in board.generate_legal_moves() {
+            board.move_piece(first.clone()).unwrap();
+            for mv in board.generate_legal_moves() {
+                let kind = board.classify_move(&mv).unwrap();
+                let target = board.get_piece(mv.to_x, mv.to_y);
+                let expected = if mv.piece_type == PieceType::King && mv.from_x.abs_diff(mv.to_x) == 2 {
+                    MoveKind::Castle(if mv.to_x == 6 { Side::KingSide } else { Side::QueenSide })
+                } else if mv.piece_type == PieceType::Pawn && (mv.to_y == 0 || mv.to_y == 7) {
+                    MoveKind::Promotion(mv.promotion.unwrap())
+                } else if mv.piece_type == PieceType::Pawn && mv.from_x != mv.to_x && target.is_none() {
+                    MoveKind::EnPassant
+                } else if let Some(piece) = target {
+                    MoveKind::Capture(piece.get_type())
+                } else {
+                    MoveKind::Quiet
+                };
+                assert_eq!(kind, expected, "{} {}", board.to_fen(), mv.extended_algebraic());
+            }
+            board.undo_move();
+        }
+    }
+}
+
+#[test]
+fn classify_move_2() {
+    // Each kind of move and each reason for turning one away, written out by hand
+    let kiwipete = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    let cases = [
+        (kiwipete, "a2a3", Ok(MoveKind::Quiet)),
+        (kiwipete, "e5f7", Ok(MoveKind::Capture(PieceType::Pawn))),
+        (kiwipete, "d5e6", Ok(MoveKind::Capture(PieceType::Pawn))),
+        (kiwipete, "e1g1", Ok(MoveKind::Castle(Side::KingSide))),
+        (kiwipete, "e1c1", Ok(MoveKind::Castle(Side::QueenSide))),
+        ("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6", Ok(MoveKind::EnPassant)),
+        ("n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1", "g2g1q", Ok(MoveKind::Promotion(PieceType::Queen))),
+        ("n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1", "g2h1n", Ok(MoveKind::Promotion(PieceType::Knight))),
+        (kiwipete, "e5e5", Err(MoveError::MustMovePiece)),
+        (kiwipete, "a8a7", Err(MoveError::PieceWrongColor)),
+        (kiwipete, "f3e2", Err(MoveError::CannotCaptureOwnPiece)),
+        (kiwipete, "f3f8", Err(MoveError::MoveBlocked)),
+        (kiwipete, "e5e7", Err(MoveError::IllegalMove)),
+        ("4k3/8/8/8/8/8/4r3/R3K3 w - - 0 1", "a1a2", Err(MoveError::KingInCheck { checker: (4, 1), checker_type: PieceType::Rook })),
+        ("4k3/4r3/8/8/8/8/4B3/4K3 w - - 0 1", "e2d3", Err(MoveError::MovePinnedOrExposesKing { attacker: (4, 6), attacker_type: PieceType::Rook })),
+    ];
+    for (fen, text, expected) in cases {
+        let board = Board::from_fen(fen).unwrap();
+        let mv = board.parse_uci_move(text).unwrap();
+        assert_eq!(board.classify_move(&mv), expected, "{} {}", fen, text);
+    }
+    let empty_square = Move::new(0, 3, 0, 4, PieceType::Pawn, None);
+    assert_eq!(Board::from_fen(kiwipete).unwrap().classify_move(&empty_square), Err(MoveError::NoPieceOnSourceSquare));
+}
+
+#[test]
+fn force_move_1() {
+    // Dragging pieces to an Italian game gives the same board as its FEN, down to the legal moves
+    let mut board = Board::starting_position();
+    for (from, to) in [("e2", "e4"), ("e7", "e5"), ("g1", "f3"), ("b8", "c6"), ("f1", "c4"), ("f8", "c5")] {
+        board.force_move(square_to_coords(from).unwrap(), square_to_coords(to).unwrap());
+    }
+    assert!(board.validate_position().is_ok());
+    assert!(board.validate().is_ok());
+    let italian = Board::from_fen("r1bqk1nr/pppp1ppp/2n5/2b1p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 0 1").unwrap();
+    assert_eq!(board, italian);
+    assert_eq!(perft(3, board.clone()).unwrap(), perft(3, italian).unwrap());
+    // Castling by hand drops both White rights, capturing a rook drops that side's right
+    board.force_move((4, 0), (6, 0));
+    board.force_move((7, 0), (5, 0));
+    assert_eq!(board.force_move((2, 3), (0, 7)), Some(Piece::new(PieceType::Rook, Color::Black)));
+    assert_eq!(board.to_fen(), "B1bqk1nr/pppp1ppp/2n5/2b1p3/4P3/5N2/PPPP1PPP/RNBQ1RK1 w k - 0 1");
+    assert!(board.validate().is_ok());
+    assert_eq!(board.generate_legal_moves().len(), Board::from_fen(&board.to_fen()).unwrap().generate_legal_moves().len());
+}
+
+#[test]
+fn force_move_2() {
+    // Nothing happens from an empty square, en passant is cleared by any drag
+    let mut board = Board::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+    assert_eq!(board.force_move((0, 0), (1, 1)), None);
+    assert_eq!(board.en_passant_square(), Some((3, 5)));
+    board.force_move((4, 0), (3, 0));
+    assert_eq!(board.en_passant_square(), None);
+    // Dragging onto a king takes it off, and a second king can only come from set_piece
+    board.force_move((3, 0), (4, 7));
+    assert_eq!(board.validate_position(), Err(FenError::MissingKing(Color::Black)));
+    board.set_piece(0, 7, Piece::new(PieceType::King, Color::Black));
+    board.set_piece(0, 0, Piece::new(PieceType::King, Color::White));
+    assert_eq!(board.validate_position(), Err(FenError::TooManyKings(Color::White)));
+}
+
+#[test]
+fn try_move_uci_1() {
+    // Castling, en passant and an under-promotion in either case
+    let mut board = Board::from_fen("4k3/P7/8/8/3p4/8/4P3/4K2R w K - 0 1").unwrap();
+    board.try_move_uci("e1g1").unwrap();
+    assert_eq!(board.get_piece(5, 0), Some(Piece::new(PieceType::Rook, Color::White)));
+    board.try_move_uci("e8d8").unwrap();
+    board.try_move_uci("e2e4").unwrap();
+    board.try_move_uci("d4e3").unwrap();
+    assert_eq!(board.get_piece(4, 3), None);
+    board.try_move_uci("a7a8N").unwrap();
+    assert_eq!(board.to_fen(), "N2k4/8/8/8/8/4p3/8/5RK1 b - - 0 3");
+}
+
+#[test]
+fn try_move_uci_2() {
+    // Bad text is an error rather than a panic, and nothing is played
+    let mut board = Board::starting_position();
+    for garbage in ["e9x9", "", "e2", "e2e4e", "e2e4qq", "ä2e4", "e7e8k"] {
+        assert_eq!(board.try_move_uci(garbage), Err(MoveError::MalformedUci(garbage.to_string())), "{:?}", garbage);
+    }
+    assert_eq!(board.try_move_uci("e4e5"), Err(MoveError::NoPieceOnSourceSquare));
+    assert_eq!(board.try_move_uci("e2e5"), Err(MoveError::IllegalMove));
+    assert_eq!(board.try_move_uci("g1g3q"), Err(MoveError::IllegalMove));
+    assert_eq!(board, Board::starting_position());
+}
+
+#[test]
+fn apply_uci_moves_1() {
+    let mut board = Board::starting_position();
+    assert_eq!(board.apply_uci_moves(" e2e4  e7e5\tg1f3 "), Ok(3));
+    let error = board.apply_uci_moves("b8c6 f1c4 c6c6 g8f6").unwrap_err();
+    assert_eq!((error.index, error.text.as_str(), &error.source), (2, "c6c6", &MoveError::MustMovePiece));
+    assert_eq!(error.to_string(), "Move 3 'c6c6' could not be played: Must move piece");
+    assert_eq!(board.to_fen(), "r1bqkbnr/pppp1ppp/2n5/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R b KQkq - 3 3");
+}
+
+#[test]
+fn has_legal_moves_1() {
+    // Mates, stalemates, a pinned-piece-only escape and ordinary positions, then every position of a short random walk
+    let fens = [
+        "rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3",
+        "7k/5Q2/6K1/8/8/8/8/8 b - - 0 1",
+        "k7/8/1Q6/8/8/8/8/7K b - - 0 1",
+        "k7/1R6/1K6/8/8/8/8/8 b - - 0 1",
+        "6rk/5Npp/8/8/8/8/8/6K1 b - - 0 1",
+        "8/8/8/8/8/5k2/5p2/5K2 w - - 0 1",
+        "4k3/4r3/8/8/8/8/4B3/4K3 w - - 0 1",
+        "8/8/8/8/8/8/p7/K1k5 w - - 0 1",
+        "r1bq1rk1/pp2bppp/2n1pn2/3p4/2PP4/2N1PN2/PP2BPPP/R2QKB1R w KQ - 0 8",
+    ];
+    for fen in fens {
+        let board = Board::from_fen(fen).unwrap();
+        assert_eq!(board.has_legal_moves(), !board.generate_legal_moves().is_empty(), "{}", fen);
+    }
+    let mut rng = StdRng::seed_from_u64(60);
+    for _ in 0..20 {
+        let mut board = Board::starting_position();
+        for _ in 0..200 {
+            let moves = board.generate_legal_moves();
+            assert_eq!(board.has_legal_moves(), !moves.is_empty(), "{}", board.to_fen());
+            if moves.is_empty() {
+                break;
+            }
+            board.move_piece(moves[rng.gen_range(0..moves.len())].clone()).unwrap();
+        }
+    }
+}
+
+#[test]
+#[ignore = "timing, run with --release -- --ignored --nocapture"]
+fn has_legal_moves_timing_1() {
+    // A late middlegame with many pieces left, where only the answer is needed
+    let board = Board::from_fen("2r2rk1/1b3ppp/p3pn2/1p1q4/3P4/P1NQ1N2/1P3PPP/2R2RK1 w - - 0 22").unwrap();
+    let runs = 20000;
+    let start = std::time::Instant::now();
+    let found = (0..runs).filter(|_| board.has_legal_moves()).count();
+    let first_move = start.elapsed();
+    let start = std::time::Instant::now();
+    let generated = (0..runs).filter(|_| !board.generate_legal_moves().is_empty()).count();
+    let all_moves = start.elapsed();
+    assert_eq!(found, generated);
+    println!("has_legal_moves {:?}, generate_legal_moves {:?} for {} calls", first_move, all_moves, runs);
+    assert!(first_move < all_moves);
+}
+
+#[test]
+fn pseudo_legal_moves_1() {
+    // Filtering the pseudo legal moves gives the legal moves, none land on the mover's own pieces,
+    // and is_legal agrees with the filter move by move
+    let fens = [
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    ];
+    for fen in fens {
+        let board = Board::from_fen(fen).unwrap();
+        let pseudo = board.generate_pseudo_legal_moves();
+        for mv in &pseudo {
+            assert!(board.get_piece(mv.to_x, mv.to_y).is_none_or(|piece| piece.get_color() != *board.get_player_turn()), "{} {}", fen, mv.extended_algebraic());
+        }
+        let legal = board.filter_legal(pseudo.clone());
+        assert_eq!(legal, board.generate_legal_moves(), "{}", fen);
+        let mut counted = Vec::new();
+        assert_eq!(board.generate_legal_moves_counted(&mut counted), pseudo.len(), "{}", fen);
+        assert_eq!(counted, legal, "{}", fen);
+        let checked: Vec<Move> = pseudo.into_iter().filter(|mv| board.is_legal(mv)).collect();
+        assert_eq!(checked, legal, "{}", fen);
+    }
+}
+
+#[test]
+fn pseudo_legal_moves_2() {
+    // Castling is a candidate when its path is clear, and an en passant capture that exposes the king
+    // is generated, then removed by the filter
+    let board = Board::from_fen("4k3/8/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+    let castle = uci_to_move(&board, "e1g1").unwrap();
+    assert!(board.generate_pseudo_legal_moves().contains(&castle));
+    assert!(board.is_legal(&castle));
+
+    let board = Board::from_fen("4k1r1/8/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+    assert!(!board.generate_pseudo_legal_moves().contains(&castle));
+    assert!(!board.is_legal(&castle));
+
+    let board = Board::from_fen("8/8/8/K1pP3r/8/8/8/4k3 w - c6 0 1").unwrap();
+    let en_passant = board.generate_pseudo_legal_moves().into_iter().find(|mv| (mv.from_x, mv.from_y, mv.to_x, mv.to_y) == (3, 4, 2, 5));
+    let en_passant = en_passant.expect("en passant is a pseudo legal candidate");
+    assert!(!board.is_legal(&en_passant));
+
+    // Perft over the split generator is unchanged
+    let board = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+    assert_eq!(perft(3, board).unwrap(), 97862);
+}
+
+/// Checks generate_captures and generate_checks against the legal moves, for every position down to the given depth
+fn assert_tactical_walk(board: &mut Board, depth: u32) -> usize {
+    let legal = board.generate_legal_moves();
+    let mut captures = Vec::new();
+    let mut checks = Vec::new();
+    for mv in &legal {
+        let is_capture = board.get_piece(mv.to_x, mv.to_y).is_some() || (mv.piece_type == PieceType::Pawn && mv.from_x != mv.to_x);
+        if is_capture && mv.promotion.is_none_or(|piece_type| piece_type == PieceType::Queen) || mv.promotion == Some(PieceType::Queen) {
+            captures.push(mv.clone());
+        } else if !is_capture {
+            let undo = board.make_move(mv).unwrap();
+            let in_check = board.king_in_check();
+            board.unmake_move(mv, undo);
+            if in_check {
+                checks.push(mv.clone());
+            }
+        }
+    }
+    let found: HashSet<String> = board.generate_captures().iter().map(|mv| mv.extended_algebraic()).collect();
+    let expected: HashSet<String> = captures.iter().map(|mv| mv.extended_algebraic()).collect();
+    assert_eq!(found, expected, "{}", board.to_fen());
+    let found: HashSet<String> = board.generate_checks().iter().map(|mv| mv.extended_algebraic()).collect();
+    let expected: HashSet<String> = checks.iter().map(|mv| mv.extended_algebraic()).collect();
+    assert_eq!(found, expected, "{}", board.to_fen());
+    let mut nodes = captures.len();
+    if depth > 1 {
+        for mv in legal {
+            let undo = board.make_move(&mv).unwrap();
+            nodes += assert_tactical_walk(board, depth - 1);
+            board.unmake_move(&mv, undo);
+        }
+    }
+    nodes
+}
+
+#[test]
+fn generate_captures_1() {
+    // A perft 3 walk over positions with castling, en passant, promotions and pins
+    let fens = [
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+    ];
+    for fen in fens {
+        let mut board = Board::from_fen(fen).unwrap();
+        assert!(assert_tactical_walk(&mut board, 3) > 0, "{}", fen);
+    }
+}
+
+#[test]
+fn generate_captures_2() {
+    // Promotions by push and capture give only the queen, and a pinned capturer is left out
+    let board = Board::from_fen("3r3k/2P5/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    let moves: Vec<String> = board.generate_captures().iter().map(|mv| mv.extended_algebraic()).collect();
+    assert_eq!(moves.len(), 2, "{:?}", moves);
+    let board = Board::from_fen("4k3/4r3/8/3p4/4N3/8/8/4K3 w - - 0 1").unwrap();
+    assert!(board.generate_captures().is_empty());
+    // En passant is a capture, the pawn push beside it is not
+    let board = Board::from_fen("4k3/8/8/3Pp3/8/8/8/4K3 w - e6 0 1").unwrap();
+    let moves = board.generate_captures();
+    assert_eq!(moves.len(), 1);
+    assert_eq!((moves[0].to_x, moves[0].to_y), (4, 5));
+}
+
+#[test]
+fn render_4() {
+    // The status line and the ANSI mode, which draws the same squares in inverse video and red
+    let mut board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+    let mv = Move::new(0, 0, 0, 7, PieceType::Rook, None);
+    board.move_piece(mv.clone()).unwrap();
+    let options = BoardRenderOptions { highlight: Some(mv), mark_check: true, status: true, ..BoardRenderOptions::default() };
+    let plain = board.render(&options);
+    assert!(plain.ends_with("    a   b   c   d   e   f   g   h\nBlack to move — in check\n"), "{}", plain);
+    let ansi = board.render(&BoardRenderOptions { ansi: true, ..options });
+    assert!(ansi.contains("8 |\x1b[7m R \x1b[0m|   |   |   |\x1b[41m k \x1b[0m|"), "{}", ansi);
+    assert!(ansi.contains("1 |\x1b[7m   \x1b[0m|"), "{}", ansi);
+    assert_eq!(ansi.replace("\x1b[7m ", "[").replace("\x1b[41m ", "!").replace(" \x1b[0m|", "]|").replace("!k]", "!k!"), plain);
+}
+
+#[test]
+fn squares_in_display_order_1() {
+    let board = Board::starting_position();
+    for (perspective, first, last) in [(Color::White, "a8", "h1"), (Color::Black, "h1", "a8")] {
+        let squares = board.squares_in_display_order(perspective).collect::<Vec<_>>();
+        assert_eq!(squares.len(), 64);
+        assert_eq!(squares[0].0.name, first);
+        assert_eq!(squares[63].0.name, last);
+        let (e1, king) = squares.iter().find(|(square, _)| square.name == "e1").unwrap();
+        assert_eq!((e1.x, e1.y, e1.color), (4, 0, Color::Black));
+        assert_eq!(*king, Some(&Piece::new(PieceType::King, Color::White)));
+        // The pieces come in the order to_ascii draws them
+        let drawn = board.to_ascii(perspective).lines()
+            .filter(|line| line.starts_with(|c: char| c.is_ascii_digit()))
+            .flat_map(|line| line.split('|').skip(1).take(8).map(|cell| cell.trim().to_string()).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let listed = squares.iter().map(|(_, piece)| piece.map_or(String::new(), |piece| piece.get_piece_char().to_string())).collect::<Vec<_>>();
+        assert_eq!(listed, drawn);
+    }
+    assert_eq!(SquareName::new(7, 0).color, Color::White);
+    assert_eq!(SquareName::new(4, 3).to_string(), "e4");
+}
+
+#[test]
+fn status_line_1() {
+    let cases = [
+        ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", "White to move"),
+        ("4k3/8/8/8/8/8/8/R3K2R b - - 0 1", "Black to move"),
+        ("R3k3/8/8/8/8/8/8/4K3 b - - 0 1", "Black to move — in check"),
+        ("R3k3/R7/8/8/8/8/8/4K3 b - - 0 1", "Black is checkmated"),
+        ("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1", "Draw by stalemate"),
+        ("8/8/4k3/8/8/3K4/8/8 w - - 0 1", "Draw by insufficient material"),
+    ];
+    for (fen, status) in cases {
+        assert_eq!(Board::from_fen(fen).unwrap().status_line(), status, "{}", fen);
+    }
+}
+
+#[test]
+fn order_moves_1() {
+    // PxQ, NxR and QxP are all available: the queen falls first, then the rook, then the pawn, then the quiet moves
+    let board = Board::from_fen("4k3/8/8/2rq3p/4P3/1N1P4/8/3QK3 w - - 0 1").unwrap();
+    let mut moves = board.generate_legal_moves();
+    board.order_moves(&mut moves);
+    let order: Vec<String> = moves.iter().map(|mv| mv.extended_algebraic()).collect();
+    assert_eq!(order[..3], ["e4d5", "b3c5", "d1h5"].map(String::from), "{:?}", order);
+    assert!(moves[3..].iter().all(|mv| mv.mvv_lva_score(&board) <= moves[2].mvv_lva_score(&board)));
+    // Promotions and en passant count as gaining material, quiet moves score 0
+    let board = Board::from_fen("4k3/1P6/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+    let mut moves = board.generate_legal_moves();
+    board.order_moves(&mut moves);
+    assert_eq!(moves[0].extended_algebraic(), "b7b8q");
+    assert_eq!(moves[4].extended_algebraic(), "e5d6");
+    assert!(moves[5..].iter().all(|mv| mv.mvv_lva_score(&board) == 0));
+}
+
+#[test]
+fn capture_value_1() {
+    let board = Board::from_fen("2r1k3/1P6/8/3pP3/8/2n5/3B4/4K3 w - d6 0 1").unwrap();
+    // Bishop takes knight
+    assert_eq!(board.capture_value(&Move::new(3, 1, 2, 2, PieceType::Bishop, None)), 300);
+    // En passant takes the pawn beside the capturing pawn, not the empty destination
+    assert_eq!(board.capture_value(&Move::new(4, 4, 3, 5, PieceType::Pawn, None)), 100);
+    // A promotion gains the new piece less the pawn
+    assert_eq!(board.capture_value(&Move::new(1, 6, 1, 7, PieceType::Pawn, Some(PieceType::Queen))), 800);
+    assert_eq!(board.capture_value(&Move::new(1, 6, 1, 7, PieceType::Pawn, Some(PieceType::Knight))), 200);
+    // Promoting with a capture counts both
+    assert_eq!(board.capture_value(&Move::new(1, 6, 2, 7, PieceType::Pawn, Some(PieceType::Queen))), 1300);
+    // Quiet moves win nothing
+    assert_eq!(board.capture_value(&Move::new(4, 0, 5, 0, PieceType::King, None)), 0);
+    assert_eq!(board.capture_value(&Move::new(4, 4, 4, 5, PieceType::Pawn, None)), 0);
+}
+
+#[test]
+fn capture_value_2() {
+    // For every capture and promotion the value is the change in material for the side that moves
+    let fens = [
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+    ];
+    for fen in fens {
+        let mut board = Board::from_fen(fen).unwrap();
+        let material = |board: &Board, color: Color| if color.is_white() { board.basic_evaluate() } else { -board.basic_evaluate() };
+        let color = *board.get_player_turn();
+        let before = material(&board, color);
+        for mv in board.generate_legal_moves() {
+            let value = board.capture_value(&mv);
+            let undo = board.make_move(&mv).unwrap();
+            let after = material(&board, color);
+            board.unmake_move(&mv, undo);
+            assert_eq!(value, after - before, "{} in {}", mv.extended_algebraic(), fen);
+        }
+    }
+}
+
+/// Checks legal_moves_iter against filtering the pseudo legal moves, for every position down to the given depth
+fn assert_legal_iter_walk(board: &mut Board, depth: u32) -> usize {
+    let moves: Vec<Move> = board.legal_moves_iter().collect();
+    assert_eq!(moves, board.filter_legal(board.generate_pseudo_legal_moves()), "{}", board.to_fen());
+    if depth == 1 {
+        return moves.len();
+    }
+    let mut nodes = 0;
+    for mv in moves {
+        let undo = board.make_move(&mv).unwrap();
+        nodes += assert_legal_iter_walk(board, depth - 1);
+        board.unmake_move(&mv, undo);
+    }
+    nodes
+}
+
+#[test]
+fn pin_aware_legality_1() {
+    // Positions where a pinned piece, a check or en passant limits the moves, with the moves left in UCI
+    let cases: [(&str, &[&str]); 4] = [
+        // Taking en passant would empty the fifth rank between the king and the rook
+        ("8/8/8/KPp4r/8/8/8/7k w - c6 0 2", &["a5a4", "a5a6", "a5b6", "b5b6"]),
+        // The bishop is pinned on the diagonal and can only slide along it or take the pinner
+        ("7k/8/8/8/3q4/8/1B6/K7 w - - 0 1", &["a1a2", "a1b1", "b2c3", "b2d4"]),
+        // Double check from knight and rook, only the king can move
+        ("4r2k/8/8/8/8/5n2/3B4/R3K3 w - - 0 1", &["e1d1", "e1f1", "e1f2"]),
+        // A single check by a rook is met by blocking, taking or stepping aside, not by stepping along its line
+        ("R3r2k/8/8/8/8/8/8/3BK3 w - - 0 1", &["a8e8", "d1e2", "e1d2", "e1f1", "e1f2"]),
+    ];
+    for (fen, expected) in cases {
+        let board = Board::from_fen(fen).unwrap();
+        let mut moves = board.generate_legal_moves().iter().map(Move::extended_algebraic).collect::<Vec<_>>();
+        moves.sort();
+        assert_eq!(moves, expected, "{}", fen);
+    }
+}
+
+/// Walks the tree below a position checking that the king moves generated as legal are exactly the pseudo legal
+/// king moves that playing them finds legal, returning the leaf count
+fn assert_king_moves_walk(board: &mut Board, depth: u32) -> usize {
+    let legal = board.generate_legal_moves();
+    for mv in board.generate_pseudo_legal_moves().iter().filter(|mv| mv.piece_type == PieceType::King) {
+        assert_eq!(legal.contains(mv), board.is_legal(mv), "{:?} in {}", mv, board.to_fen());
+    }
+    if depth == 1 {
+        return legal.len();
+    }
+    let mut nodes = 0;
+    for mv in legal {
+        let undo = board.make_move(&mv).unwrap();
+        nodes += assert_king_moves_walk(board, depth - 1);
+        board.unmake_move(&mv, undo);
+    }
+    nodes
+}
+
+#[test]
+fn attack_map_1() {
+    // The rook attacks along the eighth rank through the black king, so the king can not step back along it
+    let board = Board::from_fen("R3k3/8/8/8/8/8/8/4K3 b - - 0 1").unwrap();
+    let map = board.attack_map(Color::White);
+    assert!(map.is_attacked(3, 7) && map.is_attacked(5, 7) && map.is_attacked(7, 7) && map.is_attacked(0, 0));
+    assert!(!map.is_attacked(4, 6) && !map.is_attacked(4, 0));
+    assert_eq!(map.bitboard().count_ones(), 7 + 7 + 5);
+    let mut king_moves = board.generate_legal_moves().iter().map(Move::extended_algebraic).collect::<Vec<_>>();
+    king_moves.sort();
+    assert_eq!(king_moves, ["e8d7", "e8e7", "e8f7"]);
+    // King moves into attacked squares are left out exactly as playing them would reject them
+    let cases = [
+        ("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1", 97862),
+        ("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", 2812),
+        ("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1", 9467),
+    ];
+    for (fen, nodes) in cases {
+        let mut board = Board::from_fen(fen).unwrap();
+        assert_eq!(assert_king_moves_walk(&mut board, 3), nodes, "{}", fen);
+    }
+}
+
+#[test]
+fn legal_moves_iter_1() {
+    // The perft suite positions give the same moves in the same order and the same perft counts
+    let cases = [
+        ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", 8902),
+        ("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1", 97862),
+        ("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", 2812),
+        ("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1", 9467),
+        ("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8", 62379),
+    ];
+    for (fen, nodes) in cases {
+        let mut board = Board::from_fen(fen).unwrap();
+        assert_eq!(assert_legal_iter_walk(&mut board, 3), nodes, "{}", fen);
+    }
+}
+
+#[test]
+fn generate_legal_moves_into_1() {
+    // A buffer left over from another position is cleared and refilled with the same moves in the same order
+    let mut moves = Board::starting_position().generate_pseudo_legal_moves();
+    for fen in [
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    ] {
+        let board = Board::from_fen(fen).unwrap();
+        board.generate_pseudo_legal_moves_into(&mut moves);
+        assert_eq!(moves, board.generate_pseudo_legal_moves(), "{}", fen);
+        board.generate_legal_moves_into(&mut moves);
+        assert_eq!(moves, board.generate_legal_moves(), "{}", fen);
+    }
+}
+
+#[test]
+#[ignore = "timing, run with --release -- --ignored --nocapture"]
+fn legal_moves_iter_timing_1() {
+    // A cutoff on the first move, where the iterator generates one piece's moves instead of all of them
+    let board = Board::from_fen("2r2rk1/1b3ppp/p3pn2/1p1q4/3P4/P1NQ1N2/1P3PPP/2R2RK1 w - - 0 22").unwrap();
+    let runs = 20000;
+    let start = std::time::Instant::now();
+    let first = (0..runs).filter(|_| board.legal_moves_iter().next().is_some()).count();
+    let lazy = start.elapsed();
+    let start = std::time::Instant::now();
+    let collected = (0..runs).filter(|_| !board.generate_legal_moves().is_empty()).count();
+    let eager = start.elapsed();
+    assert_eq!(first, collected);
+    println!("legal_moves_iter {:?}, generate_legal_moves {:?} for {} calls", lazy, eager, runs);
+    assert!(lazy < eager);
+}
+
+/// Walks every line to the depth with move_piece and undo_move, checking the kept in check answer against the attackers
+fn assert_in_check_walk(board: &mut Board, depth: u32) {
+    let turn = *board.get_player_turn();
+    assert_eq!(board.king_in_check(), !board.checkers().is_empty(), "{}", board.to_fen());
+    assert_eq!(board.in_check(turn), !board.checkers_of(turn).is_empty(), "{}", board.to_fen());
+    assert!(!board.in_check(turn.opposite()), "{}", board.to_fen());
+    if depth == 0 {
+        return;
+    }
+    for mv in board.generate_legal_moves() {
+        board.move_piece(mv).unwrap();
+        assert_in_check_walk(board, depth - 1);
+        board.undo_move();
+        assert_eq!(board.king_in_check(), !board.checkers().is_empty(), "{} after undo", board.to_fen());
+    }
+}
+
+#[test]
+fn in_check_1() {
+    let fens = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    ];
+    for fen in fens {
+        assert_in_check_walk(&mut Board::from_fen(fen).unwrap(), 2);
+    }
+}
+
+#[test]
+#[ignore = "slow in debug builds, run with --release -- --ignored"]
+fn in_check_2() {
+    let fens = [
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    ];
+    for fen in fens {
+        assert_in_check_walk(&mut Board::from_fen(fen).unwrap(), 3);
+    }
+}
+
+#[test]
+fn in_check_null_move_1() {
+    // The answer kept by move_piece survives a null move and its undo, and the passed position is worked out afresh
+    let mut board = Board::from_fen("4k3/8/8/8/8/8/8/3QK2r b - - 0 1").unwrap();
+    board.move_piece(Move::new(7, 0, 7, 1, PieceType::Rook, None)).unwrap();
+    assert!(!board.king_in_check());
+    let undo = board.make_null_move();
+    assert!(!board.king_in_check());
+    assert!(!board.in_check(Color::White));
+    board.unmake_null_move(undo);
+    assert!(!board.king_in_check());
+    board.move_piece(Move::new(3, 0, 3, 7, PieceType::Queen, None)).unwrap();
+    assert!(board.king_in_check());
+    assert!(board.in_check(Color::Black));
+}
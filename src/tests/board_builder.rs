@@ -0,0 +1,52 @@
+use crate::{board::Board, board_builder::BoardBuilder, castling_rights::CastlingRights, color::Color, errors::fen_error::FenError};
+
+
+#[test]
+fn builder_1() {
+    let board = BoardBuilder::new()
+        .piece("e1", 'K')
+        .piece("h1", 'R')
+        .piece("e8", 'k')
+        .piece((0, 6), 'P')
+        .turn(Color::Black)
+        .castling(CastlingRights { white_king: true, ..CastlingRights::none() })
+        .build()
+        .unwrap();
+    assert_eq!(board, Board::from_fen("4k3/P7/8/8/8/8/8/4K2R b K - 0 1").unwrap());
+}
+
+#[test]
+fn builder_en_passant_1() {
+    let board = BoardBuilder::new()
+        .piece("e1", 'K')
+        .piece("e8", 'k')
+        .piece("d5", 'P')
+        .piece("e5", 'p')
+        .en_passant("e6")
+        .build()
+        .unwrap();
+    assert_eq!(board, Board::from_fen("4k3/8/8/3Pp3/8/8/8/4K3 w - e6 0 1").unwrap());
+}
+
+#[test]
+fn builder_errors_1() {
+    let kings = || BoardBuilder::new().piece("e1", 'K').piece("e8", 'k');
+    assert_eq!(BoardBuilder::new().piece("e1", 'K').build().unwrap_err(), FenError::MissingKing(Color::Black));
+    assert_eq!(kings().piece("d1", 'K').build().unwrap_err(), FenError::TooManyKings(Color::White));
+    assert_eq!(kings().piece("c8", 'P').build().unwrap_err(), FenError::PawnOnBackRank("c8".to_string()));
+    assert_eq!(kings().piece("i9", 'Q').build().unwrap_err(), FenError::InvalidSquare("i9".to_string()));
+    assert_eq!(kings().piece((8, 0), 'Q').build().unwrap_err(), FenError::InvalidSquare("(8, 0)".to_string()));
+    assert_eq!(kings().piece("d4", 'X').build().unwrap_err(), FenError::InvalidPieceChar('X'));
+    assert_eq!(kings().en_passant("e3").build().unwrap_err(), FenError::BadEnPassantSquare("e3".to_string()));
+    let rights = CastlingRights { black_queen: true, ..CastlingRights::none() };
+    assert_eq!(kings().castling(rights).build().unwrap_err(), FenError::InconsistentCastlingRights('q'));
+}
+
+#[test]
+fn back_rank_pawn_fen_1() {
+    // FEN parsing runs the same check
+    let fen = "4k3/8/8/8/8/8/8/P3K3 w - - 0 1";
+    let error = Board::from_fen(fen).unwrap_err();
+    assert_eq!(error, FenError::PawnOnBackRank("a1".to_string()));
+    assert!(error.annotate(fen).ends_with("4k3/8/8/8/8/8/8/P3K3 w - - 0 1\n                ^^^^"));
+}
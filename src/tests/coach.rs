@@ -0,0 +1,58 @@
+use crate::{board::Board, coach::{judge_user_move, CoachConfig, Judgment}, piece_type::PieceType, search::{search_limits::SearchLimits, searcher::analyze}, utils::notation::san_to_move};
+
+
+#[test]
+fn judge_user_move_blunder_1() {
+    // Qd5 puts the queen where the e6 pawn takes it
+    let board = Board::from_fen("4k3/8/4p3/8/8/8/8/3QK3 w - - 0 1").unwrap();
+    let mv = san_to_move(&board, "Qd5").unwrap();
+    let Judgment::Blunder(blunder) = judge_user_move(&board, &mv, &SearchLimits::depth(3), 200).unwrap() else {
+        panic!("Qd5 should be a blunder");
+    };
+    let mut after = board.clone();
+    after.make_move(&mv).unwrap();
+    assert_eq!(blunder.played_san, "Qd5");
+    assert!(blunder.cp_loss > PieceType::Queen.to_centipawns() / 2, "{}", blunder.cp_loss);
+    assert_eq!(blunder.lost_piece, Some(PieceType::Queen));
+    assert_eq!(blunder.refutation.first(), san_to_move(&after, "exd5").as_ref());
+    assert!(blunder.refutation_san.starts_with("exd5"), "{}", blunder.refutation_san);
+    assert!(blunder.warning().starts_with("Qd5 loses a queen to exd5, "), "{}", blunder.warning());
+}
+
+#[test]
+fn judge_user_move_pass_1() {
+    // Developing a knight in the opening is close enough to the best move to pass without a warning
+    let board = Board::starting_position();
+    let mv = san_to_move(&board, "Nc3").unwrap();
+    match judge_user_move(&board, &mv, &SearchLimits::depth(3), 200).unwrap() {
+        Judgment::Pass { cp_loss } => assert!(cp_loss <= 200, "{}", cp_loss),
+        Judgment::Blunder(blunder) => panic!("{}", blunder.warning()),
+    }
+    let best = analyze(&board, SearchLimits::depth(3)).best_move.unwrap();
+    assert_eq!(judge_user_move(&board, &best, &SearchLimits::depth(3), 0).unwrap(), Judgment::Pass { cp_loss: 0 });
+}
+
+#[test]
+fn judge_user_move_depth_1() {
+    // Both moves are scored at depth 3, so the move played is worth what a depth 2 search after it finds
+    let board = Board::from_fen("4k3/8/4p3/8/8/8/8/3QK3 w - - 0 1").unwrap();
+    let mv = san_to_move(&board, "Qd5").unwrap();
+    let Judgment::Blunder(blunder) = judge_user_move(&board, &mv, &SearchLimits::depth(3), 200).unwrap() else {
+        panic!("Qd5 should be a blunder");
+    };
+    let mut after = board.clone();
+    after.make_move(&mv).unwrap();
+    assert_eq!(blunder.played_score, -analyze(&after, SearchLimits::depth(2)).score);
+    let best = analyze(&board, SearchLimits::depth(3));
+    assert_eq!(blunder.cp_loss, best.score - blunder.played_score);
+}
+
+#[test]
+fn coach_config_1() {
+    let config = CoachConfig { threshold_cp: 150, movetime_ms: 50 };
+    assert_eq!(config.limits(), SearchLimits::movetime(50));
+    let board = Board::from_fen("4k3/8/4p3/8/8/8/8/3QK3 w - - 0 1").unwrap();
+    let mv = san_to_move(&board, "Qd5").unwrap();
+    assert!(matches!(config.judge(&board, &mv).unwrap(), Judgment::Blunder(_)));
+    assert!(config.judge(&board, &san_to_move(&Board::starting_position(), "e4").unwrap()).is_err());
+}
@@ -0,0 +1,44 @@
+use crate::{board::Board, color::Color, coords::{fen_rank_to_y, file_char, rank_char, x_to_display_column, y_to_display_row}, piece_type::PieceType, utils::notation::{coords_to_square, square_to_coords}};
+
+
+#[test]
+fn convention_1() {
+    // A white pawn on e2 is stored at x 4, y 1
+    let board = Board::from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1").unwrap();
+    let pawn = board.get_piece(4, 1).unwrap();
+    assert_eq!(pawn.get_type(), PieceType::Pawn);
+    assert_eq!(pawn.get_color(), Color::White);
+    assert_eq!(square_to_coords("e2"), Some((4, 1)));
+    assert_eq!(coords_to_square((4, 1)), "e2");
+}
+
+#[test]
+fn convention_2() {
+    // Drawn from White's side e2 is on the second row from the bottom, from Black's side the second from the top
+    let board = Board::from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1").unwrap();
+    let square_rows = |perspective| board.to_ascii(perspective).lines().filter(|line| line.contains('|')).map(str::to_string).collect::<Vec<_>>();
+    let white = square_rows(Color::White);
+    assert_eq!(white[6], "2 |   |   |   |   | P |   |   |   | 2");
+    assert_eq!(white[y_to_display_row(1, Color::White)], white[6]);
+    let black = square_rows(Color::Black);
+    assert_eq!(black[1], "2 |   |   |   | P |   |   |   |   | 2");
+    assert_eq!(black[y_to_display_row(1, Color::Black)], black[1]);
+}
+
+#[test]
+fn convention_3() {
+    for y in 0..8 {
+        assert_eq!(fen_rank_to_y(fen_rank_to_y(y)), y);
+        for perspective in [Color::White, Color::Black] {
+            assert_eq!(y_to_display_row(y_to_display_row(y, perspective), perspective), y);
+            assert_eq!(x_to_display_column(x_to_display_column(y, perspective), perspective), y);
+        }
+        for x in 0..8 {
+            let square = coords_to_square((x, y));
+            assert_eq!(square, format!("{}{}", file_char(x), rank_char(y)));
+            assert_eq!(square_to_coords(&square), Some((x, y)));
+        }
+    }
+    // The first rank listed in a FEN is rank 8
+    assert_eq!(Board::from_fen("r3k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap().get_piece(0, fen_rank_to_y(0)).unwrap().get_type(), PieceType::Rook);
+}
@@ -0,0 +1,64 @@
+use crate::{board::Board, color::Color, drill::{Drill, DrillFeedback}, errors::drill_error::DrillError, kibitzer::watcher::parse_move};
+
+
+const ITALIAN: &str = "startpos\n1. e4 e5 2. Nf3 Nc6 3. Bc4 {the Italian} Bc5\n";
+
+/// The drill's verdict on a move given in SAN or UCI
+fn step(drill: &mut Drill, text: &str) -> DrillFeedback {
+    let mv = parse_move(drill.board(), text).unwrap();
+    drill.step(&mv)
+}
+
+#[test]
+fn drill_correct_run_1() {
+    // White plays the whole line, in either notation, and each reply comes back
+    let mut drill = Drill::parse(ITALIAN, Color::White).unwrap();
+    let mut board = Board::starting_position();
+    for (user, reply) in [("e4", "e5"), ("g1f3", "Nc6"), ("Bc4", "Bc5")] {
+        board.move_piece(parse_move(&board, user).unwrap()).unwrap();
+        let reply = parse_move(&board, reply).unwrap();
+        assert_eq!(step(&mut drill, user), DrillFeedback::Correct { reply: Some(reply.clone()) });
+        board.move_piece(reply).unwrap();
+    }
+    assert!(drill.is_finished());
+    assert_eq!(drill.deviations(), 0);
+    assert_eq!(drill.board(), &board);
+    assert_eq!(step(&mut drill, "O-O"), DrillFeedback::Finished);
+}
+
+#[test]
+fn drill_deviation_1() {
+    // Playing Black, the drill opens with e4 and wrong answers leave the board alone
+    let mut drill = Drill::parse(ITALIAN, Color::Black).unwrap();
+    let after_e4 = drill.board().clone();
+    assert_eq!(after_e4.to_fen(), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1");
+    let e5 = parse_move(drill.board(), "e5").unwrap();
+    assert_eq!(step(&mut drill, "c5"), DrillFeedback::Deviation { expected: e5.clone() });
+    assert_eq!(step(&mut drill, "e7e6"), DrillFeedback::Deviation { expected: e5.clone() });
+    assert_eq!(drill.board(), &after_e4);
+    assert_eq!(drill.deviations(), 2);
+    assert_eq!(drill.expected(), Some(&e5));
+    assert!(matches!(step(&mut drill, "e7e5"), DrillFeedback::Correct { reply: Some(_) }));
+    assert!(matches!(step(&mut drill, "Nc6"), DrillFeedback::Correct { reply: Some(_) }));
+    // The line ends with Black's move, so there is no reply
+    assert_eq!(step(&mut drill, "Bc5"), DrillFeedback::Correct { reply: None });
+    assert!(drill.is_finished());
+    assert_eq!(drill.deviations(), 2);
+}
+
+#[test]
+fn drill_transposition_1() {
+    // The knights go out and back, so playing e4 at once reaches the position after the fifth move and skips ahead
+    let mut drill = Drill::parse("startpos\n1. Nf3 Nf6 2. Ng1 Ng8 3. e4 e5 4. d4", Color::White).unwrap();
+    let after_e4 = Board::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1").unwrap();
+    assert_eq!(step(&mut drill, "e4"), DrillFeedback::Correct { reply: parse_move(&after_e4, "e5") });
+    assert_eq!(drill.expected(), Some(&parse_move(drill.board(), "d4").unwrap()));
+    assert_eq!(drill.deviations(), 0);
+}
+
+#[test]
+fn drill_errors_1() {
+    assert_eq!(Drill::parse("\n\n", Color::White).err(), Some(DrillError::MissingFen));
+    assert_eq!(Drill::parse("startpos\n1. e4 e5 2. Ke3", Color::White).err(), Some(DrillError::BadMove { ply: 3, text: "Ke3".to_string() }));
+    assert!(matches!(Drill::parse("not a fen\n1. e4", Color::White), Err(DrillError::Fen(_))));
+}
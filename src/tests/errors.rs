@@ -0,0 +1,113 @@
+use crate::{board::Board, chess_move::Move, errors::{fen_error::FenError, move_error::MoveError}, piece_type::PieceType};
+
+
+/// Parses a FEN that must fail and returns the annotated message
+fn annotated(fen: &str) -> String {
+    Board::from_fen(fen).unwrap_err().annotate(fen)
+}
+
+#[test]
+fn annotate_missing_fields_1() {
+    let fen = "4k3/8/8/8/8/8/8/4K3 w";
+    assert_eq!(annotated(fen), format!("FEN needs at least 4 fields, found 2 (castling field, character 22)\n{}\n{}^", fen, " ".repeat(21)));
+}
+
+#[test]
+fn annotate_too_many_ranks_1() {
+    let fen = "4k3/8/8/8/8/8/8/4K3/8 w - - 0 1";
+    assert_eq!(annotated(fen), format!("FEN has more than 8 ranks (piece placement field, character 21)\n{}\n{}^", fen, " ".repeat(20)));
+}
+
+#[test]
+fn annotate_too_few_ranks_1() {
+    let fen = "4k3/8/8/8/8/8/4K3 w - - 0 1";
+    assert_eq!(annotated(fen), format!("FEN has fewer than 8 ranks (piece placement field, character 18)\n{}\n{}^", fen, " ".repeat(17)));
+}
+
+#[test]
+fn annotate_bad_rank_length_1() {
+    // The sixth rank only covers seven squares
+    let fen = "4k3/8/7/8/8/8/8/4K3 w - - 0 1";
+    assert_eq!(annotated(fen), format!("Rank 6 does not describe exactly 8 squares (piece placement field, character 7)\n{}\n      ^", fen));
+    let fen = "4k3/8/8/8/8/8/8/4K4 w - - 0 1";
+    assert_eq!(annotated(fen), format!("Rank 1 does not describe exactly 8 squares (piece placement field, character 17)\n{}\n{}^^^", fen, " ".repeat(16)));
+}
+
+#[test]
+fn annotate_invalid_piece_char_1() {
+    let fen = "4k3/8/8/3x4/8/8/8/4K3 w - - 0 1";
+    assert_eq!(annotated(fen), format!("Invalid piece character 'x' (piece placement field, character 10)\n{}\n{}^", fen, " ".repeat(9)));
+}
+
+#[test]
+fn annotate_bad_side_to_move_1() {
+    let fen = "4k3/8/8/8/8/8/8/4K3 white - - 0 1";
+    assert_eq!(annotated(fen), format!("Invalid side to move 'white' (side to move field, character 21)\n{}\n{}^^^^^", fen, " ".repeat(20)));
+}
+
+#[test]
+fn annotate_bad_castling_field_1() {
+    let fen = "4k3/8/8/8/8/8/8/4K3 w KX - 0 1";
+    assert_eq!(annotated(fen), format!("Invalid castling field 'KX' (castling field, character 23)\n{}\n{}^^", fen, " ".repeat(22)));
+}
+
+#[test]
+fn annotate_bad_en_passant_square_1() {
+    let fen = "4k3/8/8/8/8/8/8/4K3 w - e9 0 1";
+    assert_eq!(annotated(fen), format!("Invalid en passant square 'e9' (en passant field, character 25)\n{}\n{}^^", fen, " ".repeat(24)));
+}
+
+#[test]
+fn annotate_bad_clock_field_1() {
+    let fen = "4k3/8/8/8/8/8/8/4K3 w - - 0 x";
+    assert_eq!(annotated(fen), format!("Invalid halfmove or fullmove field 'x' (fullmove number field, character 29)\n{}\n{}^", fen, " ".repeat(28)));
+}
+
+#[test]
+fn annotate_missing_king_1() {
+    let fen = "4k3/8/8/8/8/8/8/8 w - - 0 1";
+    assert_eq!(Board::from_fen(fen).unwrap_err(), FenError::MissingKing(crate::color::Color::White));
+    assert_eq!(annotated(fen), format!("White has no king (piece placement field, character 1)\n{}\n{}", fen, "^".repeat(17)));
+}
+
+#[test]
+fn annotate_too_many_kings_1() {
+    let fen = "4k3/8/8/8/8/8/8/3KK3 w - - 0 1";
+    assert_eq!(annotated(fen), format!("White has more than one king (piece placement field, character 1)\n{}\n{}", fen, "^".repeat(20)));
+}
+
+#[test]
+fn annotate_non_ascii_1() {
+    // Offsets are counted in characters so multi byte input neither panics nor shifts the caret
+    let fen = "4k3/8/8/8/8/8/8/4K3 ♔ - - 0 1";
+    assert_eq!(annotated(fen), format!("Invalid side to move '♔' (side to move field, character 21)\n{}\n{}^", fen, " ".repeat(20)));
+}
+
+#[test]
+fn explain_pinned_1() {
+    let mut board = Board::from_fen("6k1/8/8/3n4/8/1B6/8/4K3 b - - 0 1").unwrap();
+    let mv = Move::new(3, 4, 5, 3, PieceType::Knight, None);
+    let error = board.move_piece(mv.clone()).unwrap_err();
+    assert_eq!(error.explain(&mv), "your knight on d5 is pinned by the bishop on b3");
+    assert_eq!(error.to_string(), "Move exposes the king to the bishop on b3");
+}
+
+#[test]
+fn explain_king_in_check_1() {
+    let mut board = Board::from_fen("4k3/8/8/8/7b/8/3N4/4K3 w - - 0 1").unwrap();
+    let mv = Move::new(3, 1, 1, 2, PieceType::Knight, None);
+    let error = board.move_piece(mv.clone()).unwrap_err();
+    assert_eq!(error.explain(&mv), "your king is in check from the bishop on h4");
+    let mv = Move::new(4, 0, 5, 1, PieceType::King, None);
+    let error = board.move_piece(mv.clone()).unwrap_err();
+    assert_eq!(error.explain(&mv), "your king is in check from the bishop on h4");
+}
+
+#[test]
+fn explain_attacked_square_1() {
+    let mut board = Board::from_fen("5r1k/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    let mv = Move::new(4, 0, 5, 0, PieceType::King, None);
+    let error = board.move_piece(mv.clone()).unwrap_err();
+    assert_eq!(error.explain(&mv), "your king can not move to f1, it is attacked by the rook on f8");
+    assert_eq!(MoveError::MoveBlocked.explain(&mv), "Move is blocked");
+}
@@ -0,0 +1,228 @@
+use crate::{board::Board, color::Color, evaluation::{MAX_EVAL, MAX_PHASE}, search::searcher::{MATE_SCORE, MAX_DEPTH}};
+
+
+#[test]
+fn evaluate_for_symmetry_1() {
+    let fens = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "4k3/8/8/8/8/8/8/3QK3 w - - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "rnb1kbnr/pppp1ppp/8/4p3/5PPq/8/PPPPP2P/RNBQKBNR b KQkq - 1 3",
+        "4k3/8/8/8/8/8/pppppppp/4K3 b - - 0 1",
+    ];
+    for fen in fens {
+        let board = Board::from_fen(fen).unwrap();
+        assert_eq!(board.evaluate_for(Color::White), -board.evaluate_for(Color::Black), "{}", fen);
+        assert_eq!(board.evaluate_for(Color::White), board.evaluate(), "{}", fen);
+    }
+}
+
+#[test]
+fn evaluate_to_move_1() {
+    let white = Board::from_fen("4k3/8/8/8/8/8/8/3QK3 w - - 0 1").unwrap();
+    let black = Board::from_fen("4k3/8/8/8/8/8/8/3QK3 b - - 0 1").unwrap();
+    assert_eq!(white.basic_evaluate(), 900);
+    assert_eq!(white.evaluate_to_move(), white.evaluate());
+    assert_eq!(black.evaluate_to_move(), -black.evaluate());
+    assert!(white.evaluate() > 800);
+}
+
+#[test]
+fn evaluate_extra_material_1() {
+    let board = Board::from_fen("7k/8/8/8/8/8/QQQQQ3/QQQQQ1K1 w - - 0 1").unwrap();
+    assert_eq!(board.basic_evaluate(), 9000);
+    // forty queens would be worth more than the clamp
+    let board = Board::from_fen("QQQQQQQQ/QQQQQQQQ/QQQQQQQQ/QQQQQQQQ/QQQQQQQQ/8/8/k6K w - - 0 1").unwrap();
+    assert_eq!(board.evaluate_for(Color::White), MAX_EVAL);
+    assert_eq!(board.evaluate_for(Color::Black), -MAX_EVAL);
+    assert!(MAX_EVAL < MATE_SCORE - MAX_DEPTH as i32);
+}
+
+#[test]
+fn incremental_evaluate_1() {
+    // The running balance matches a full count after every move and every undo of random games,
+    // which take in captures, promotions, en passant and castling
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+    let fens = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    ];
+    for fen in fens {
+        for seed in 0..20 {
+            let mut board = Board::from_fen(fen).unwrap();
+            assert_eq!(board.basic_evaluate(), board.evaluate_slow(), "{}", fen);
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut played = 0;
+            for _ in 0..200 {
+                let moves = board.generate_legal_moves();
+                if moves.is_empty() || board.is_draw().is_some() {
+                    break;
+                }
+                board.move_piece(moves[rng.gen_range(0..moves.len())].clone()).unwrap();
+                played += 1;
+                assert_eq!(board.basic_evaluate(), board.evaluate_slow(), "{} after {} moves", fen, played);
+            }
+            while board.undo_move().is_some() {
+                assert_eq!(board.basic_evaluate(), board.evaluate_slow(), "{} undoing", fen);
+            }
+            assert_eq!(board.basic_evaluate(), Board::from_fen(fen).unwrap().basic_evaluate(), "{}", fen);
+        }
+    }
+}
+
+#[test]
+fn incremental_evaluate_2() {
+    // Editing the position and make_move/unmake_move keep the balance as well
+    use crate::{piece::Piece, piece_type::PieceType};
+    let mut board = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    board.set_piece(3, 0, Piece::new(PieceType::Queen, Color::White));
+    assert_eq!(board.basic_evaluate(), 900);
+    board.remove_piece(3, 0);
+    assert_eq!(board.basic_evaluate(), 0);
+    let mut board = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+    for mv in board.generate_legal_moves() {
+        let undo = board.make_move(&mv).unwrap();
+        assert_eq!(board.basic_evaluate(), board.evaluate_slow(), "{}", mv.extended_algebraic());
+        board.unmake_move(&mv, undo);
+        assert_eq!(board.basic_evaluate(), 0);
+    }
+}
+
+#[test]
+fn game_phase_1() {
+    use crate::evaluation::MAX_PHASE;
+    assert_eq!(Board::starting_position().game_phase(), MAX_PHASE);
+    // Each side without its queen and a rook leaves 24 - 12
+    assert_eq!(Board::from_fen("1nb1kbnr/pppppppp/8/8/8/8/PPPPPPPP/1NB1KBNR w Kk - 0 1").unwrap().game_phase(), 12);
+    assert_eq!(Board::from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1").unwrap().game_phase(), 0);
+    // Promoted queens do not take the phase past the start
+    assert_eq!(Board::from_fen("QQQQkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQ - 0 1").unwrap().game_phase(), MAX_PHASE);
+}
+
+#[test]
+fn tapered_evaluate_1() {
+    // King and pawn against king is all endgame, so moving either king changes the score by the endgame king table:
+    // e4 and e5 are worth 40, the corners -50
+    let white_central = Board::from_fen("k7/8/8/8/4K3/8/4P3/8 w - - 0 1").unwrap();
+    let white_cornered = Board::from_fen("k7/8/8/8/8/8/4P3/7K w - - 0 1").unwrap();
+    assert_eq!(white_central.game_phase(), 0);
+    assert_eq!(white_central.evaluate() - white_cornered.evaluate(), 90);
+    let black_central = Board::from_fen("8/8/8/4k3/8/8/4P3/K7 w - - 0 1").unwrap();
+    let black_cornered = Board::from_fen("7k/8/8/8/8/8/4P3/K7 w - - 0 1").unwrap();
+    assert_eq!(black_central.evaluate() - black_cornered.evaluate(), -90);
+}
+
+#[test]
+fn tapered_evaluate_2() {
+    // With every piece on the board the midgame table rules, and it rewards castling over a central king
+    let castled = Board::from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/2B1P3/5N2/PPPP1PPP/RNBQ1RK1 b kq - 0 1").unwrap();
+    let central = Board::from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/2B1P3/5N2/PPPPKPPP/RNBQ3R b kq - 0 1").unwrap();
+    assert_eq!(castled.game_phase(), crate::evaluation::MAX_PHASE);
+    assert!(castled.evaluate() > central.evaluate());
+    assert_eq!(Board::starting_position().evaluate(), 0);
+}
+
+#[test]
+fn king_safety_1() {
+    // The same castled position with the shield intact, and with g2-g4 played and the h-pawn gone
+    let shielded = Board::from_fen("r1bq1rk1/pppp1ppp/2n2n2/2b1p3/2B1P3/3P1N2/PPP2PPP/RNBQ1RK1 w - - 0 1").unwrap();
+    let broken = Board::from_fen("r1bq1rk1/pppp1ppp/2n2n2/2b1p3/2B1P1P1/3P1N2/PPP2P2/RNBQ1RK1 w - - 0 1").unwrap();
+    assert_eq!(shielded.king_safety(Color::White), 0);
+    assert_eq!(shielded.king_safety(Color::Black), 0);
+    // g4 is three ranks ahead of the king, and the h-file is half open
+    assert_eq!(broken.king_safety(Color::White), -40);
+    assert!(shielded.evaluate_for(Color::White) > broken.evaluate_for(Color::White) + 100);
+    // A king with no pawns on its file or either side, none of them with any pawns left
+    let bare = Board::from_fen("q3k3/8/8/8/8/8/PPP5/6K1 w - - 0 1").unwrap();
+    assert_eq!(bare.king_safety(Color::White), -3 * (25 + 20));
+}
+
+#[test]
+fn king_safety_2() {
+    // Without the opponent's queen the term vanishes, however open the king is
+    let broken = Board::from_fen("r1b2rk1/pppp1ppp/2n2n2/2b1p3/2B1P1P1/3P1N2/PPP2P2/RNB2RK1 w - - 0 1").unwrap();
+    assert_eq!(broken.king_safety(Color::White), 0);
+    assert_eq!(broken.king_safety(Color::Black), 0);
+    let bare = Board::from_fen("r3k3/8/8/8/8/8/PPP5/6K1 w - - 0 1").unwrap();
+    assert_eq!(bare.king_safety(Color::White), 0);
+    // Only White's queen left: Black's king is still judged, White's is not
+    let one_queen = Board::from_fen("r1b2rk1/ppppp3/2n2n2/8/8/8/PPP5/RNBQ1RK1 w - - 0 1").unwrap();
+    assert_eq!(one_queen.king_safety(Color::White), 0);
+    assert!(one_queen.king_safety(Color::Black) < 0);
+}
+
+#[test]
+fn piece_counts_1() {
+    use crate::piece_type::PieceType;
+    let board = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+    let [white, black] = board.piece_counts();
+    assert_eq!(white, [8, 2, 2, 2, 1, 1]);
+    assert_eq!(black, white);
+    assert_eq!(white[PieceType::Knight.index()], 2);
+    assert_eq!(Board::empty().piece_counts(), [[0; 6]; 2]);
+}
+
+#[test]
+fn bishop_pair_1() {
+    // Equal material, White's two bishops against Black's bishop and knight on the mirrored squares
+    let pair = Board::from_fen("4k3/8/8/3bn3/3BB3/8/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(pair.basic_evaluate(), 0);
+    assert_eq!(pair.material_adjustment(Color::White), 40);
+    assert!(pair.evaluate() > 0, "{}", pair.evaluate());
+    // Once a bishop is traded the bonus is gone
+    let traded = Board::from_fen("4k3/8/8/4n3/3B4/8/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(traded.material_adjustment(Color::White), 0);
+    assert_eq!(traded.material_adjustment(Color::Black), -5 * 4);
+    assert_eq!(Board::from_fen("4k3/8/8/8/3B4/8/8/4K3 w - - 0 1").unwrap().material_adjustment(Color::White), 0);
+}
+
+#[test]
+fn material_adjustment_1() {
+    // With eight pawns a side the knights gain and the rooks lose, with none it is the other way round
+    assert_eq!(Board::starting_position().material_adjustment(Color::White), 40 + 2 * 4 * 3 - 2 * 6 * 3);
+    let open = Board::from_fen("rn2k3/8/8/8/8/8/8/RN2K3 w - - 0 1").unwrap();
+    assert_eq!(open.material_adjustment(Color::White), -4 * 5 + 6 * 5);
+    assert_eq!(open.material_adjustment(Color::Black), open.material_adjustment(Color::White));
+    // Five pawns is the neutral count
+    let five = Board::from_fen("rn2k3/ppppp3/8/8/8/8/PPPPP3/RN2K3 w - - 0 1").unwrap();
+    assert_eq!(five.material_adjustment(Color::White), 0);
+}
+
+#[test]
+fn mop_up_1() {
+    // Pushing the lone king towards a corner and bringing the winning king over both score, 10 a step
+    let centre = Board::from_fen("8/8/8/8/3k4/8/8/4QK2 w - - 0 1").unwrap();
+    assert_eq!(centre.mop_up(), 10 * (14 - 5));
+    let edge = Board::from_fen("8/8/8/8/8/8/8/3kQK2 w - - 0 1").unwrap();
+    assert_eq!(edge.mop_up(), 10 * 3 + 10 * (14 - 2));
+    let corner = Board::from_fen("8/8/8/8/8/8/2K5/k3Q3 w - - 0 1").unwrap();
+    assert_eq!(corner.mop_up(), 10 * 9 + 10 * (14 - 3));
+    // A corner is worth more than the middle of an edge with the kings as far apart
+    let corner = Board::from_fen("8/8/8/8/8/8/8/k1K1Q3 w - - 0 1").unwrap();
+    assert!(corner.mop_up() > edge.mop_up());
+    let close = Board::from_fen("3k4/8/3K4/8/8/8/8/4Q3 w - - 0 1").unwrap();
+    assert!(close.mop_up() > centre.mop_up());
+    // The same for Black, with the sign flipped
+    assert_eq!(close.mirror().mop_up(), -close.mop_up());
+}
+
+#[test]
+fn mop_up_2() {
+    // Nothing while the loser still has a pawn, or the lead is too small to mate with
+    assert_eq!(Board::from_fen("3k4/3p4/3K4/8/8/8/8/4Q3 w - - 0 1").unwrap().mop_up(), 0);
+    assert_eq!(Board::from_fen("3k4/8/3K4/8/8/8/8/4B3 w - - 0 1").unwrap().mop_up(), 0);
+    assert_eq!(Board::starting_position().mop_up(), 0);
+}
+
+#[test]
+fn mop_up_3() {
+    // With most pieces still on the board the evaluation is all midgame, so where the lone king stands
+    // along the a-file changes mop_up but not evaluate
+    let corner = Board::from_fen("rnbqr3/8/8/8/8/4QQ2/4RRBB/k3NN1K w - - 0 1").unwrap();
+    let edge = Board::from_fen("rnbqr3/8/8/8/k7/4QQ2/4RRBB/4NN1K w - - 0 1").unwrap();
+    assert_eq!(corner.game_phase(), MAX_PHASE);
+    assert!(corner.mop_up() > edge.mop_up());
+    assert_eq!(corner.evaluate(), edge.evaluate());
+}
@@ -0,0 +1,292 @@
+#[cfg(feature = "serde")]
+use crate::game_snapshot::GameSnapshot;
+use crate::{board_render_options::BoardRenderOptions, castling_validation::CastlingValidation, chess_move::Move, color::Color, errors::{fen_error::FenError, move_error::MoveError}, game::Game, piece::Piece, piece_type::PieceType, search::{engine_config::EngineConfig, searcher::MATE_SCORE, transposition_table::ANALYSIS_HASH_MB}, utils::notation::san_to_move};
+
+
+#[test]
+fn load_fen_1() {
+    let mut game = Game::new();
+    game.make_move(Move::new(4, 1, 4, 3, PieceType::Pawn, None)).unwrap();
+    assert_eq!(game.move_history_white.len(), 1);
+    // An invalid FEN leaves the game alone
+    assert!(game.load_fen("4k3/8/8/8/8/8/4K3 w - - 0 1").is_err());
+    assert_eq!(game.move_history_white.len(), 1);
+    game.load_fen("4k3/8/8/8/8/8/4P3/4K3  b - - 0 7").unwrap();
+    assert!(game.move_history_white.is_empty());
+    assert_eq!(game.initial_fen.as_deref(), Some("4k3/8/8/8/8/8/4P3/4K3 b - - 0 7"));
+}
+
+#[test]
+fn to_pgn_1() {
+    let mut game = Game::from_fen("4k3/8/8/8/8/8/4P3/4K3 b - - 0 7").unwrap();
+    game.make_move(Move::new(4, 7, 3, 7, PieceType::King, None)).unwrap();
+    game.make_move(Move::new(4, 1, 4, 3, PieceType::Pawn, None)).unwrap();
+    assert_eq!(game.move_history_black.len(), 1);
+    assert_eq!(game.to_pgn(), "[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 7\"]\n\n7... Kd8 8. e4 *");
+    let mut game = Game::new();
+    game.make_move(Move::new(6, 0, 5, 2, PieceType::Knight, None)).unwrap();
+    assert_eq!(game.to_pgn(), "1. Nf3 *");
+}
+
+#[test]
+fn engine_config_for_1() {
+    // Black starts without the queen, White searches shallower than Black
+    let mut game = Game::from_fen("rnb1kbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+    game.set_engine_config_for(Color::White, EngineConfig::depth(1));
+    game.set_engine_config_for(Color::Black, EngineConfig::depth(3));
+    for ply in 0..4 {
+        let mv = game.engine_move();
+        let search = game.last_search.as_ref().unwrap();
+        assert_eq!(search.best_move.as_ref(), Some(&mv));
+        assert_eq!(search.depth, if ply % 2 == 0 { 1 } else { 3 });
+    }
+    assert_eq!(game.move_history_white.len(), 2);
+    assert_eq!(game.move_history_black.len(), 2);
+    // Hints for the side to move use that side's config
+    assert_eq!(game.hint().depth, 1);
+}
+
+#[test]
+fn odds_position_1() {
+    // Rook odds copied from the start position keeps KQkq, which is rejected unless repairs are asked for
+    let fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/1NBQKBNR w KQkq - 0 1";
+    assert_eq!(Game::from_fen(fen).err(), Some(FenError::InconsistentCastlingRights('Q')));
+    let (game, repairs) = Game::from_fen_with(fen, CastlingValidation::Repair).unwrap();
+    assert_eq!(game.board.castling_rights().to_string(), "Kkq");
+    assert_eq!(repairs.len(), 1);
+    // The repaired FEN is kept for export, so the PGN can be read back strictly
+    assert_eq!(game.initial_fen.as_deref(), Some("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/1NBQKBNR w Kkq - 0 1"));
+    // Without a config engine_move plays a random move and records no search
+    let mut game = game;
+    game.engine_move();
+    assert!(game.last_search.is_none());
+}
+
+/// 1. e4 d5 2. exd5 Qxd5 3. Nc3, a mid-game position with captures by both sides
+fn scandinavian() -> Game {
+    let mut game = Game::new();
+    for san in ["e4", "d5", "exd5", "Qxd5", "Nc3"] {
+        let mv = san_to_move(&game.board, san).unwrap();
+        game.make_move(mv).unwrap();
+    }
+    game
+}
+
+#[test]
+fn snapshot_1() {
+    let game = scandinavian();
+    let snapshot = game.snapshot();
+    assert_eq!(snapshot.fen, game.board.to_fen());
+    assert_eq!(snapshot.side_to_move, Color::Black);
+    assert_eq!(snapshot.legal_moves.len(), game.board.generate_legal_moves().len());
+    assert!(snapshot.legal_moves.iter().any(|mv| mv.uci == "d5a5" && mv.san == "Qa5"));
+    assert!(!snapshot.in_check);
+    assert_eq!(snapshot.result, "*");
+    assert_eq!(snapshot.history_san, vec!["e4", "d5", "exd5", "Qxd5", "Nc3"]);
+    assert_eq!(snapshot.captured, vec!['p', 'P']);
+    assert_eq!(snapshot.repetition_count, game.board.repetition_count());
+    assert_eq!(snapshot.last_move.as_deref(), Some("b1c3"));
+}
+
+#[test]
+fn snapshot_2() {
+    // Fool's mate, and an en passant capture is counted as a captured pawn
+    let mut game = Game::new();
+    for san in ["f3", "e5", "g4", "Qh4#"] {
+        let mv = san_to_move(&game.board, san).unwrap();
+        game.make_move(mv).unwrap();
+    }
+    let snapshot = game.snapshot();
+    assert!(snapshot.in_check);
+    assert!(snapshot.legal_moves.is_empty());
+    assert_eq!(snapshot.result, "0-1");
+    assert_eq!(game.result(), "0-1");
+    assert!(game.to_pgn().ends_with("0-1"));
+    let mut game = Game::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+    game.make_move(Move::new(4, 4, 3, 5, PieceType::Pawn, None)).unwrap();
+    assert_eq!(game.snapshot().captured, vec!['p']);
+}
+
+#[test]
+fn snapshot_3() {
+    // Draws agree with Game::result, by repetition and by stalemate
+    let mut game = Game::new();
+    for san in ["Nf3", "Nf6", "Ng1", "Ng8", "Nf3", "Nf6", "Ng1", "Ng8"] {
+        let mv = san_to_move(&game.board, san).unwrap();
+        game.make_move(mv).unwrap();
+    }
+    let snapshot = game.snapshot();
+    assert_eq!(snapshot.repetition_count, 2);
+    assert_eq!(snapshot.result, "1/2-1/2");
+    assert_eq!(snapshot.result, game.result());
+    let game = Game::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+    assert_eq!(game.snapshot().result, "1/2-1/2");
+    assert_eq!(game.snapshot().result, game.result());
+}
+
+#[test]
+fn snapshot_captured_square_1() {
+    // En passant takes the pawn beside the capturing pawn, an ordinary capture takes on the destination
+    let mut game = Game::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+    let snapshot = game.snapshot();
+    let en_passant = snapshot.legal_moves.iter().find(|mv| mv.uci == "e5d6").unwrap();
+    assert_eq!(en_passant.captured_square, Some((3, 4)));
+    assert!(snapshot.legal_moves.iter().filter(|mv| mv.uci != "e5d6").all(|mv| mv.captured_square.is_none()));
+    let result = game.make_move(Move::new(4, 4, 3, 5, PieceType::Pawn, None)).unwrap();
+    assert_eq!(result.captured_square, Some((3, 4)));
+    // Taking the move back puts the pawn back on d5, not d6
+    game.board.undo_move();
+    assert_eq!(game.board.to_fen(), "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1");
+    let game = scandinavian();
+    let capture = game.snapshot().legal_moves.into_iter().find(|mv| mv.san == "Qxa2").unwrap();
+    assert_eq!(capture.captured_square, Some((0, 1)));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn snapshot_json_1() {
+    let game = scandinavian();
+    let json = serde_json::to_string(&game.snapshot()).unwrap();
+    let snapshot: GameSnapshot = serde_json::from_str(&json).unwrap();
+    assert_eq!(snapshot, game.snapshot());
+    assert_eq!(snapshot.fen, game.board.to_fen());
+    assert_eq!(snapshot.side_to_move, *game.board.get_player_turn());
+    assert_eq!(snapshot.in_check, game.board.king_in_check());
+    assert_eq!(snapshot.result, game.result());
+    assert_eq!(snapshot.history_san.len(), game.move_history_white.len() + game.move_history_black.len());
+    assert_eq!(snapshot.captured, vec!['p', 'P']);
+    assert_eq!(snapshot.repetition_count, game.board.repetition_count());
+    assert_eq!(snapshot.last_move.as_deref(), Some("b1c3"));
+    let legal = game.board.generate_legal_moves();
+    assert_eq!(snapshot.legal_moves.len(), legal.len());
+    for (snapshot_move, mv) in snapshot.legal_moves.iter().zip(&legal) {
+        assert_eq!(snapshot_move.uci, mv.extended_algebraic());
+        assert_eq!(san_to_move(&game.board, &snapshot_move.san).as_ref(), Some(mv));
+    }
+}
+
+#[test]
+fn make_move_result_1() {
+    let mut game = Game::from_fen("4k3/8/8/8/8/8/8/R3K3 w Q - 0 1").unwrap();
+    let result = game.make_move(Move::new(0, 0, 0, 7, PieceType::Rook, None)).unwrap();
+    assert!(result.gives_check);
+    assert_eq!(result.captured, None);
+    assert_eq!(game.move_history_white.len(), 1);
+}
+
+#[test]
+fn edit_mode_1() {
+    // Moves made while editing are not recorded, and the edited position becomes the start of the game
+    let mut game = Game::new();
+    game.make_move(Move::new(4, 1, 4, 3, PieceType::Pawn, None)).unwrap();
+    game.force_move((3, 0), (7, 4));
+    assert!(game.is_editing());
+    game.make_move(Move::new(4, 6, 4, 4, PieceType::Pawn, None)).unwrap();
+    assert_eq!(game.move_history_black.len(), 0);
+    game.pass();
+    // Two white kings is not a position to play from, so the game stays in edit mode
+    game.board.set_piece(0, 3, Piece::new(PieceType::King, Color::White));
+    assert_eq!(game.end_edit(), Err(FenError::TooManyKings(Color::White)));
+    assert!(game.is_editing());
+    game.board.remove_piece(0, 3);
+    game.end_edit().unwrap();
+    assert!(!game.is_editing());
+    assert_eq!(game.initial_fen.as_deref(), Some("rnbqkbnr/pppp1ppp/8/4p2Q/4P3/8/PPPP1PPP/RNB1KBNR b KQkq - 0 2"));
+    assert!(game.move_history_white.is_empty());
+    game.make_move(Move::new(6, 6, 6, 5, PieceType::Pawn, None)).unwrap();
+    assert_eq!(game.move_history_black.len(), 1);
+    assert_eq!(game.to_pgn().lines().find(|line| line.starts_with("[FEN")), Some("[FEN \"rnbqkbnr/pppp1ppp/8/4p2Q/4P3/8/PPPP1PPP/RNB1KBNR b KQkq - 0 2\"]"));
+}
+
+#[test]
+fn render_1() {
+    // The last move is highlighted, the checked king marked and a cached mate hint shown under the board
+    let mut game = Game::from_fen("6k1/5ppp/8/8/8/8/8/R3K2R b KQ - 0 1").unwrap();
+    game.make_move(san_to_move(&game.board, "Kf8").unwrap()).unwrap();
+    game.make_move(san_to_move(&game.board, "Ra8+").unwrap()).unwrap();
+    game.make_move(san_to_move(&game.board, "Ke7").unwrap()).unwrap();
+    let options = BoardRenderOptions { mark_check: true, status: true, ..BoardRenderOptions::default() };
+    let expected = "    a   b   c   d   e   f   g   h
+  +---+---+---+---+---+---+---+---+
+8 | R |   |   |   |   |[ ]|   |   | 8
+  +---+---+---+---+---+---+---+---+
+7 |   |   |   |   |[k]| p | p | p | 7
+  +---+---+---+---+---+---+---+---+
+6 |   |   |   |   |   |   |   |   | 6
+  +---+---+---+---+---+---+---+---+
+5 |   |   |   |   |   |   |   |   | 5
+  +---+---+---+---+---+---+---+---+
+4 |   |   |   |   |   |   |   |   | 4
+  +---+---+---+---+---+---+---+---+
+3 |   |   |   |   |   |   |   |   | 3
+  +---+---+---+---+---+---+---+---+
+2 |   |   |   |   |   |   |   |   | 2
+  +---+---+---+---+---+---+---+---+
+1 |   |   |   |   | K |   |   | R | 1
+  +---+---+---+---+---+---+---+---+
+    a   b   c   d   e   f   g   h
+White to move
+";
+    assert_eq!(game.render(&options), expected);
+
+    game.make_move(san_to_move(&game.board, "Ra7+").unwrap()).unwrap();
+    let checked = game.render(&options);
+    assert!(checked.contains("8 |[ ]|   |   |   |   |   |   |   | 8"), "{}", checked);
+    assert!(checked.contains("7 |[R]|   |   |   |!k!| p | p | p | 7"), "{}", checked);
+    assert!(checked.ends_with("Black to move — in check\n"), "{}", checked);
+
+    // A hint is only shown for the position it was given in
+    game.cache_hint(MATE_SCORE - 3);
+    assert!(game.render(&options).ends_with("Black to move — in check — mate in 2 available\n"));
+    game.make_move(san_to_move(&game.board, "Kd6").unwrap()).unwrap();
+    assert!(game.render(&options).ends_with("White to move\n"));
+}
+
+#[test]
+fn static_eval_1() {
+    // White is a rook up: the accessor and the search agree, and from Black's side the sign flips
+    let mut game = Game::from_fen("4k3/pppp4/8/8/8/8/PPPP4/R3K3 w - - 0 1").unwrap();
+    assert_eq!(game.static_eval(), game.board.evaluate());
+    assert!(game.static_eval() > 400);
+    let result = game.hint();
+    assert_eq!(result.static_eval, game.static_eval());
+    game.make_move(san_to_move(&game.board, "Kd1").unwrap()).unwrap();
+    assert_eq!(game.static_eval(), -game.board.evaluate());
+    let mut game = Game::from_fen("4k3/pppp4/8/8/8/8/PPPP4/R3K3 b - - 0 1").unwrap();
+    game.set_engine_config_for(Color::Black, EngineConfig::depth(1));
+    let result = game.hint();
+    assert_eq!(result.static_eval, -game.board.evaluate());
+    // Nothing can be taken at depth 1, so the search score is the static evaluation after the best quiet move
+    let best = game.board.generate_legal_moves().into_iter().map(|mv| {
+        let mut board = game.board.clone();
+        board.move_piece(mv).unwrap();
+        -board.evaluate_to_move()
+    }).max().unwrap();
+    assert_eq!(result.score, best);
+}
+
+#[test]
+fn analysis_hash_mb_1() {
+    // Searches for both sides fit the smaller of the two budgets, and stay small without configs
+    let mut game = Game::new();
+    assert_eq!(game.analysis_hash_mb(), ANALYSIS_HASH_MB);
+    game.set_engine_config_for(Color::White, EngineConfig { hash_mb: 64, ..Default::default() });
+    assert_eq!(game.analysis_hash_mb(), 64);
+    game.set_engine_config_for(Color::Black, EngineConfig { hash_mb: 64, max_memory_mb: Some(8), ..Default::default() });
+    assert_eq!(game.analysis_hash_mb(), 8);
+}
+
+#[test]
+fn uci_moves_1() {
+    // UCI moves are recorded like any other, in the order they were played
+    let mut game = Game::from_fen("4k3/8/8/8/8/8/4P3/4K3 b - - 0 7").unwrap();
+    assert_eq!(game.apply_uci_moves("e8d8 e2e4"), Ok(2));
+    game.try_move_uci("d8c7").unwrap();
+    assert_eq!(game.moves().iter().map(Move::extended_algebraic).collect::<Vec<_>>(), ["e8d8", "e2e4", "d8c7"]);
+    assert_eq!((game.move_history_white.len(), game.move_history_black.len()), (1, 2));
+    // A move that can not be played stops the list and is not recorded
+    let error = game.apply_uci_moves("e4e5 c7c7").unwrap_err();
+    assert_eq!((error.index, error.source), (1, MoveError::MustMovePiece));
+    assert_eq!(game.moves().len(), 4);
+    assert_eq!(game.try_move_uci("e9"), Err(MoveError::MalformedUci("e9".to_string())));
+}
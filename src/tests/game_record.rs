@@ -0,0 +1,113 @@
+use crate::{color::Color, errors::{decode_error::DecodeError, fen_error::FenError}, game::Game, game_record::GameRecord, utils::notation::san_to_move};
+
+
+/// A game with the SAN moves played from a FEN, or the starting position
+fn scripted(fen: Option<&str>, moves: &str) -> Game {
+    let mut game = match fen {
+        Some(fen) => Game::from_fen(fen).unwrap(),
+        None => Game::new(),
+    };
+    for san in moves.split_whitespace() {
+        let mv = san_to_move(&game.board, san).unwrap_or_else(|| panic!("{} in {}", san, game.board.to_fen()));
+        game.make_move(mv).unwrap();
+    }
+    game
+}
+
+fn assert_round_trip(game: &Game) {
+    let record = GameRecord::encode(game);
+    let decoded = GameRecord::decode(&record).unwrap();
+    assert_eq!(decoded.initial_fen, game.initial_fen);
+    assert_eq!(decoded.move_history_white, game.move_history_white);
+    assert_eq!(decoded.move_history_black, game.move_history_black);
+    assert_eq!(decoded.board.to_fen(), game.board.to_fen());
+    assert_eq!(decoded.to_pgn(), game.to_pgn());
+}
+
+#[test]
+fn round_trip_1() {
+    // Fool's mate, 2 bytes a move plus the start flag, move count and result
+    let game = scripted(None, "f3 e5 g4 Qh4#");
+    let record = GameRecord::encode(&game);
+    assert_eq!(record.len(), 1 + 2 + 4 * 2 + 1);
+    assert_eq!(*record.last().unwrap(), 2);
+    assert_round_trip(&game);
+    assert_round_trip(&Game::new());
+}
+
+#[test]
+fn round_trip_2() {
+    // Castling both ways, en passant and an under-promotion from a set up position with Black to move
+    let game = scripted(
+        Some("r3k2r/1P6/8/8/5p2/8/4P3/R3K2R b KQkq - 3 20"),
+        "O-O e4 fxe3 O-O-O Kg7 bxa8=N e2",
+    );
+    assert_round_trip(&game);
+    let record = GameRecord::encode(&game);
+    assert_eq!(GameRecord::decode(&record).unwrap().initial_fen.as_deref(), Some("r3k2r/1P6/8/8/5p2/8/4P3/R3K2R b KQkq - 3 20"));
+}
+
+#[test]
+fn round_trip_3() {
+    // Stalemate and bare kings keep their drawn result, and an en passant square in the set up position survives
+    let game = scripted(Some("7k/5K2/6Q1/8/8/8/8/8 w - - 0 1"), "Kf8");
+    assert_eq!(game.result(), "1/2-1/2");
+    assert_round_trip(&game);
+    let game = scripted(Some("k7/8/8/8/8/8/8/K7 w - - 0 1"), "");
+    assert_eq!(game.result(), "1/2-1/2");
+    assert_round_trip(&game);
+    assert_round_trip(&scripted(Some("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 9"), "exd6"));
+}
+
+#[test]
+fn corrupt_record_1() {
+    let game = scripted(None, "e4 e5 Nf3 Nc6 Bb5");
+    let record = GameRecord::encode(&game);
+    // The fourth move, Nc6, sent to a square the knight can not reach
+    let mut corrupt = record.clone();
+    corrupt[1 + 2 + 3 * 2 + 1] ^= 0x08;
+    assert_eq!(GameRecord::decode(&corrupt).err(), Some(DecodeError::IllegalMove { ply: 4 }));
+    assert_eq!(GameRecord::decode(&record[..record.len() - 1]).err(), Some(DecodeError::Truncated(record.len() - 1)));
+    let mut wrong_result = record.clone();
+    *wrong_result.last_mut().unwrap() = 1;
+    assert_eq!(GameRecord::decode(&wrong_result).err(), Some(DecodeError::ResultMismatch { recorded: "1-0", actual: "*" }));
+    let mut bad_flag = record.clone();
+    bad_flag[0] = 7;
+    assert_eq!(GameRecord::decode(&bad_flag).err(), Some(DecodeError::BadStartFlag(7)));
+    let mut trailing = record.clone();
+    trailing.push(0);
+    assert_eq!(GameRecord::decode(&trailing).err(), Some(DecodeError::TrailingBytes(1)));
+}
+
+#[test]
+fn corrupt_record_2() {
+    // Set up positions are checked like FENs
+    let record = GameRecord::encode(&Game::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap());
+    // The kings on e1 and e8 share the byte after the occupancy, code 5 for White's king and 11 for Black's
+    assert_eq!(record[1 + 8], 5 | 11 << 4);
+    let mut two_white_kings = record.clone();
+    two_white_kings[1 + 8] = 5 | 5 << 4;
+    assert_eq!(GameRecord::decode(&two_white_kings).err(), Some(DecodeError::BadPosition(FenError::TooManyKings(Color::White))));
+    let mut bad_piece = record.clone();
+    bad_piece[1 + 8] = 0xF5;
+    assert_eq!(GameRecord::decode(&bad_piece).err(), Some(DecodeError::BadPieceCode(15)));
+}
+
+#[test]
+fn frames_1() {
+    // Records framed one after another come back in order, and a cut off frame is reported
+    let games = [scripted(None, "d4 d5 c4"), scripted(Some("8/8/8/4k3/8/8/4P3/4K3 w - - 0 1"), "e4+ Kxe4"), Game::new()];
+    let mut file = Vec::new();
+    for game in &games {
+        file.extend(GameRecord::frame(&GameRecord::encode(game)));
+    }
+    let decoded = GameRecord::frames(&file).map(|record| GameRecord::decode(record.unwrap()).unwrap()).collect::<Vec<_>>();
+    assert_eq!(decoded.len(), 3);
+    for (decoded, game) in decoded.iter().zip(&games) {
+        assert_eq!(decoded.to_pgn(), game.to_pgn());
+    }
+    let cut = &file[..file.len() - 2];
+    let frames = GameRecord::frames(cut).collect::<Vec<_>>();
+    assert_eq!(frames.len(), 3);
+    assert_eq!(frames[2], Err(DecodeError::Truncated(cut.len())));
+}
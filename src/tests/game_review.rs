@@ -0,0 +1,71 @@
+use crate::{color::Color, game::Game, game_review::{move_accuracy, side_accuracy, win_percent}, search::search_limits::SearchLimits, utils::notation::san_to_move};
+
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!((actual - expected).abs() < 0.05, "{} is not {}", actual, expected);
+}
+
+#[test]
+fn win_percent_1() {
+    assert_close(win_percent(0), 50.0);
+    assert_close(win_percent(100), 59.1);
+    assert_close(win_percent(-100), 40.9);
+    assert_close(win_percent(500), 86.3);
+    assert!(win_percent(1000) < 100.0 && win_percent(1000) > 97.0);
+}
+
+#[test]
+fn move_accuracy_1() {
+    // Keeping or improving the winning chances is perfect, losing them falls off quickly
+    assert_eq!(move_accuracy(60.0, 60.0), 100.0);
+    assert_eq!(move_accuracy(60.0, 70.0), 100.0);
+    assert_close(move_accuracy(60.0, 50.0), 63.6);
+    assert_close(move_accuracy(60.0, 30.0), 24.8);
+    assert_eq!(move_accuracy(100.0, 0.0), 0.0);
+}
+
+#[test]
+fn side_accuracy_1() {
+    // Synthetic evaluation sequences, as (before, after) from the mover's side
+    let accuracy = |evals: &[(i32, i32)]| side_accuracy(&evals.iter().map(|&(before, after)| move_accuracy(win_percent(before), win_percent(after))).collect::<Vec<_>>());
+    // Perfect play
+    assert_eq!(accuracy(&[(20, 20), (35, 40), (50, 50)]), Some(100.0));
+    // One blunder from +0.30 to -3.00 among good moves
+    let with_blunder = accuracy(&[(30, 30), (30, -300), (-300, -300)]).unwrap();
+    assert_close(with_blunder, (100.0 + move_accuracy(win_percent(30), win_percent(-300)) + 100.0) / 3.0);
+    assert!(with_blunder < 80.0);
+    // The same loss matters less when the position is already won
+    assert!(accuracy(&[(900, 600)]).unwrap() > accuracy(&[(150, -150)]).unwrap());
+    assert_eq!(side_accuracy(&[]), None);
+}
+
+#[test]
+fn review_1() {
+    // A ten move game reviewed at depth 1
+    let mut game = Game::new();
+    let moves = "e4 e5 Nf3 Nc6 Bc4 Bc5 c3 Nf6 d4 exd4 cxd4 Bb4+ Bd2 Bxd2+ Nbxd2 d5 exd5 Nxd5 Qb3 Na5";
+    for san in moves.split_whitespace() {
+        let mv = san_to_move(&game.board, san).unwrap();
+        game.make_move(mv).unwrap();
+    }
+    let review = game.review(SearchLimits::depth(1));
+    assert_eq!(review.moves.len(), 20);
+    for (i, (reviewed, san)) in review.moves.iter().zip(moves.split_whitespace()).enumerate() {
+        assert_eq!(reviewed.played_san, san);
+        assert_eq!(reviewed.move_number, i as u32 / 2 + 1);
+        assert_eq!(reviewed.color, if i % 2 == 0 { Color::White } else { Color::Black });
+        assert!(reviewed.cp_loss >= 0, "{:?}", reviewed);
+        assert!((0.0..=100.0).contains(&reviewed.accuracy), "{:?}", reviewed);
+        assert!(reviewed.best_san.is_some());
+    }
+    for color in [Color::White, Color::Black] {
+        assert!((0.0..=100.0).contains(&review.accuracy(color).unwrap()));
+        assert!(review.average_cp_loss(color).unwrap() >= 0.0);
+    }
+    let worst = review.worst_moves(3);
+    assert!(worst.len() <= 3);
+    assert!(worst.windows(2).all(|pair| pair[0].cp_loss >= pair[1].cp_loss));
+    let text = review.to_string();
+    assert!(text.starts_with("White: accuracy "), "{}", text);
+    assert!(text.lines().nth(1).unwrap().starts_with("Black: accuracy "), "{}", text);
+}
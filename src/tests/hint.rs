@@ -0,0 +1,52 @@
+use crate::{board::Board, game::Game, hint::{format_score, hint}, search::{search_limits::SearchLimits, searcher::MATE_SCORE}, utils::notation::{line_to_san, uci_to_move}};
+
+
+#[test]
+fn hint_fork_1() {
+    // The knight fork of king and rook comes first and is the engine's choice, the game is left as it was
+    let game = Game::from_fen("r3k3/8/8/1N6/8/8/8/4K3 w - - 0 1").unwrap();
+    let fen = game.board.to_fen();
+    let snapshot = game.snapshot();
+    let lines = hint(&game, SearchLimits::depth(4), 3);
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0].san, "Nc7+");
+    assert!(lines[0].chosen);
+    assert!(lines[0].pv.starts_with("Nc7+ "), "{}", lines[0].pv);
+    assert!(lines[1..].iter().all(|line| !line.chosen));
+    assert!(lines.windows(2).all(|pair| pair[0].score >= pair[1].score));
+    assert!(lines[0].score > lines[1].score);
+    assert_eq!(game.board.to_fen(), fen);
+    assert_eq!(game.snapshot(), snapshot);
+}
+
+#[test]
+fn hint_mate_1() {
+    let game = Game::from_fen("r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4").unwrap();
+    let lines = hint(&game, SearchLimits::depth(3), 3);
+    assert_eq!(lines[0].san, "Qxf7#");
+    assert_eq!(lines[0].pv, "Qxf7#");
+    assert!(lines[0].to_string().starts_with("* Qxf7#       #1  Qxf7#"), "{}", lines[0]);
+}
+
+#[test]
+fn line_to_san_1() {
+    let board = Board::starting_position();
+    let mut after = board.clone();
+    let mut line = Vec::new();
+    for text in ["e2e4", "e7e5", "g1f3", "b8c6", "f1b5"] {
+        let mv = uci_to_move(&after, text).unwrap();
+        after.make_move(&mv).unwrap();
+        line.push(mv);
+    }
+    assert_eq!(line_to_san(&board, &line, 4), "e4 e5 Nf3 Nc6 …");
+    assert_eq!(line_to_san(&board, &line, 5), "e4 e5 Nf3 Nc6 Bb5");
+    assert_eq!(line_to_san(&board, &line[..0], 4), "");
+}
+
+#[test]
+fn format_score_1() {
+    assert_eq!(format_score(125), "+1.25");
+    assert_eq!(format_score(-40), "-0.40");
+    assert_eq!(format_score(MATE_SCORE - 5), "#3");
+    assert_eq!(format_score(-(MATE_SCORE - 4)), "#-2");
+}
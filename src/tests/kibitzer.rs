@@ -0,0 +1,132 @@
+use crate::{board::Board, chess_move::Move, kibitzer::{move_judgment::{classify_move, MoveClass}, watcher::{pgn_moves, Kibitzer, PgnFollower}}, piece_type::PieceType, search::search_limits::SearchLimits, utils::notation::{move_to_san, san_to_move}};
+
+
+#[test]
+fn classify_hanging_queen_1() {
+    // Qd5 walks into exd5
+    let board = Board::from_fen("6k1/8/4p3/8/8/8/8/3Q2K1 w - - 0 1").unwrap();
+    let judgment = classify_move(&board, &Move::new(3, 0, 3, 4, PieceType::Queen, None), &SearchLimits::depth(2)).unwrap();
+    assert_eq!(judgment.class, MoveClass::Blunder);
+    assert!(judgment.cp_loss > 300);
+    assert_ne!(judgment.best_san, "Qd5");
+}
+
+#[test]
+fn classify_engine_choice_1() {
+    // Fool's mate, Qh4# is the engine's choice
+    let board = Board::from_fen("rnbqkbnr/pppp1ppp/8/4p3/6P1/5P2/PPPPP2P/RNBQKBNR b KQkq g3 0 2").unwrap();
+    let judgment = classify_move(&board, &Move::new(3, 7, 7, 3, PieceType::Queen, None), &SearchLimits::depth(2)).unwrap();
+    assert_eq!(judgment.class, MoveClass::Best);
+    assert_eq!(judgment.cp_loss, 0);
+    assert_eq!(judgment.played_san, "Qh4#");
+}
+
+#[test]
+fn move_class_thresholds_1() {
+    assert_eq!(MoveClass::from_cp_loss(0), MoveClass::Best);
+    assert_eq!(MoveClass::from_cp_loss(50), MoveClass::Good);
+    assert_eq!(MoveClass::from_cp_loss(51), MoveClass::Inaccuracy);
+    assert_eq!(MoveClass::from_cp_loss(300), MoveClass::Mistake);
+    assert_eq!(MoveClass::from_cp_loss(301), MoveClass::Blunder);
+}
+
+#[test]
+fn san_round_trip_1() {
+    let fens = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 b kq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    ];
+    for fen in fens {
+        let board = Board::from_fen(fen).unwrap();
+        for mv in board.generate_legal_moves() {
+            let san = move_to_san(&board, &mv);
+            assert_eq!(san_to_move(&board, &san), Some(mv), "{} {}", fen, san);
+        }
+    }
+}
+
+#[test]
+fn san_notation_1() {
+    let board = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+    assert_eq!(move_to_san(&board, &Move::new(4, 0, 6, 0, PieceType::King, None)), "O-O");
+    assert_eq!(move_to_san(&board, &Move::new(4, 0, 2, 0, PieceType::King, None)), "O-O-O");
+    assert_eq!(move_to_san(&board, &Move::new(4, 4, 5, 6, PieceType::Knight, None)), "Nxf7");
+    assert_eq!(move_to_san(&board, &Move::new(3, 4, 4, 5, PieceType::Pawn, None)), "dxe6");
+    assert_eq!(move_to_san(&board, &Move::new(2, 2, 1, 4, PieceType::Knight, None)), "Nb5");
+    // both rooks can reach d1
+    let board = Board::from_fen("4k3/8/8/8/8/8/4K3/R6R w - - 0 1").unwrap();
+    assert_eq!(move_to_san(&board, &Move::new(0, 0, 3, 0, PieceType::Rook, None)), "Rad1");
+    let board = Board::from_fen("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(move_to_san(&board, &Move::new(1, 6, 1, 7, PieceType::Pawn, Some(PieceType::Queen))), "b8=Q+");
+}
+
+#[test]
+fn pgn_moves_1() {
+    let pgn = "[Event \"Test\"]\n[Site \"?\"]\n\n1. e4 {best by test} e5 2. Nf3 (2. f4 exf4) Nc6 $1 3. Bb5 a6 1-0";
+    assert_eq!(pgn_moves(pgn), vec!["e4", "e5", "Nf3", "Nc6", "Bb5", "a6"]);
+}
+
+#[test]
+fn kibitzer_observe_1() {
+    let mut kibitzer = Kibitzer::new(SearchLimits::depth(1));
+    let (_, line) = kibitzer.observe("e4").unwrap();
+    assert!(line.starts_with("1. e4"));
+    let (_, line) = kibitzer.observe("e7e5").unwrap();
+    assert!(line.starts_with("1... e5"));
+    assert!(kibitzer.observe("Ke3").is_err());
+    assert_eq!(kibitzer.moves_seen(), 2);
+}
+
+#[test]
+fn san_check_suffix_1() {
+    // Back rank mate
+    let board = Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+    let mv = Move::new(0, 0, 0, 7, PieceType::Rook, None);
+    assert!(board.is_mate_after(&mv));
+    assert_eq!(move_to_san(&board, &mv), "Ra8#");
+    // The same check with luft is not mate
+    let board = Board::from_fen("6k1/5pp1/7p/8/8/8/8/R5K1 w - - 0 1").unwrap();
+    assert!(board.gives_check(&mv));
+    assert!(!board.is_mate_after(&mv));
+    assert_eq!(move_to_san(&board, &mv), "Ra8+");
+    // A quiet move is neither
+    assert!(!board.gives_check(&Move::new(0, 0, 0, 1, PieceType::Rook, None)));
+    assert_eq!(move_to_san(&board, &Move::new(0, 0, 0, 1, PieceType::Rook, None)), "Ra2");
+}
+
+#[test]
+fn san_check_suffix_2() {
+    // Smothered mate by underpromoting to a knight, promoting to a queen does not even give check
+    let board = Board::from_fen("k7/8/8/8/8/6PP/5pPK/6NB b - - 0 1").unwrap();
+    let knight = Move::new(5, 1, 5, 0, PieceType::Pawn, Some(PieceType::Knight));
+    let queen = Move::new(5, 1, 5, 0, PieceType::Pawn, Some(PieceType::Queen));
+    assert!(board.is_mate_after(&knight));
+    assert_eq!(move_to_san(&board, &knight), "f1=N#");
+    assert!(!board.gives_check(&queen));
+    assert_eq!(move_to_san(&board, &queen), "f1=Q");
+    // An illegal move is never mate
+    assert!(!board.is_mate_after(&Move::new(0, 7, 0, 5, PieceType::King, None)));
+}
+
+#[test]
+fn pgn_moves_2() {
+    // A semicolon comments out the rest of its line, but not inside a brace comment
+    let pgn = "1. e4 ; the king's pawn\ne5 2. Nf3 {a; b} Nc6 ;1-0\n*";
+    assert_eq!(pgn_moves(pgn), vec!["e4", "e5", "Nf3", "Nc6"]);
+}
+
+#[test]
+fn pgn_follower_1() {
+    // A move that can not be played stops the follower until the file changes it, without retrying every read
+    let mut follower = PgnFollower::new(SearchLimits::depth(1));
+    let mut output = Vec::new();
+    follower.update("1. e4 e5 2. Ke3 Nc6", &mut output).unwrap();
+    assert_eq!(follower.unplayable, Some((2, "Ke3".to_string())));
+    follower.update("1. e4 e5 2. Ke3 Nc6 3. d4", &mut output).unwrap();
+    assert_eq!(String::from_utf8(output.clone()).unwrap().lines().count(), 2);
+    follower.update("1. e4 e5 2. Ke2 Nc6", &mut output).unwrap();
+    assert_eq!(follower.unplayable, None);
+    assert_eq!(String::from_utf8(output).unwrap().lines().count(), 4);
+}
@@ -0,0 +1,52 @@
+use crate::board::Board;
+
+
+#[test]
+fn mate_in_two_1() {
+    // Morphy's problem, the quiet 1. Ra6! threatens both b7# and Rxa7#
+    let board = Board::from_fen("kbK5/pp6/1P6/8/8/8/8/R7 w - - 0 1").unwrap();
+    let proof = board.solve_mate_in(2).unwrap();
+    assert_eq!(proof.mate_in, 2);
+    assert_eq!(proof.key_move().extended_algebraic(), "a1a6");
+    assert!(proof.is_sound());
+    assert_eq!(proof.solution.defences.len(), 7);
+    assert_eq!(proof.to_text(&board), "\
+1. Ra6!
+    1... bxa6 2. b7#
+    1... Bh2 2. Rxa7#
+    1... Bg3 2. Rxa7#
+    1... Bf4 2. Rxa7#
+    1... Be5 2. Rxa7#
+    1... Bd6 2. Rxa7#
+    1... Bc7 2. Rxa7#
+");
+    assert!(board.solve_mate_in(1).is_none());
+}
+
+#[test]
+fn mate_in_two_2() {
+    // The king steps aside so the rook can mate on the back rank, with a waiting key
+    let board = Board::from_fen("2k5/8/2K5/8/8/8/8/3R4 w - - 0 1").unwrap();
+    let proof = board.solve_mate_in(2).unwrap();
+    assert_eq!(proof.key_move().extended_algebraic(), "c6b6");
+    assert!(proof.is_sound());
+    assert_eq!(proof.to_text(&board), "1. Kb6!\n    1... Kb8 2. Rd8#\n");
+}
+
+#[test]
+fn mate_in_two_near_miss_1() {
+    // Morphy's problem with the rook one file over has no mate in two
+    let board = Board::from_fen("kbK5/pp6/1P6/8/8/8/8/1R6 w - - 0 1").unwrap();
+    assert!(board.solve_mate_in(2).is_none());
+}
+
+#[test]
+fn mate_cooks_1() {
+    // Many moves mate within two, the quickest is the key and the rest are cooks
+    let board = Board::from_fen("7k/8/6K1/8/8/8/8/1Q6 w - - 0 1").unwrap();
+    let proof = board.solve_mate_in(2).unwrap();
+    assert_eq!(proof.mate_in, 1);
+    assert_eq!(proof.key_move().extended_algebraic(), "b1b8");
+    assert!(proof.solution.defences.is_empty());
+    assert!(!proof.is_sound());
+}
@@ -1,3 +1,25 @@
 
 pub(crate) mod board;
+pub(crate) mod bitboard;
 pub(crate) mod utils;
+pub(crate) mod threads;
+pub(crate) mod search;
+pub(crate) mod selfplay;
+pub(crate) mod evaluation;
+pub(crate) mod kibitzer;
+pub(crate) mod uci;
+pub(crate) mod errors;
+pub(crate) mod game;
+pub(crate) mod coords;
+#[cfg(feature = "async")]
+pub(crate) mod async_search;
+pub(crate) mod mate_solver;
+pub(crate) mod board_builder;
+pub(crate) mod hint;
+pub(crate) mod coach;
+pub(crate) mod piece;
+pub(crate) mod game_review;
+pub(crate) mod game_record;
+pub(crate) mod drill;
+pub(crate) mod perft_suite;
+pub(crate) mod pawn_structure;
@@ -0,0 +1,143 @@
+use crate::{board::Board, color::Color, evaluation::{CONNECTED_PASSER_BONUS, KNIGHT_OUTPOST_BONUS, MAX_PHASE}, pawn_structure::PawnStructure};
+
+#[test]
+fn pawn_structure_healthy_1() {
+    // Connected chains on both wings, nothing doubled, isolated or left behind
+    let board = Board::from_fen("4k3/pp3ppp/2p5/8/8/2P5/PP3PPP/4K3 w - - 0 1").unwrap();
+    for color in [Color::White, Color::Black] {
+        let structure = board.pawn_structure(color);
+        assert_eq!(structure.pawns_per_file, [1, 1, 1, 0, 0, 1, 1, 1]);
+        assert!(structure.doubled.is_empty() && structure.isolated.is_empty() && structure.backward.is_empty(), "{:?}", structure);
+    }
+    assert_eq!(Board::starting_position().pawn_structure(Color::White).doubled, Vec::<usize>::new());
+}
+
+#[test]
+fn pawn_structure_doubled_1() {
+    let board = Board::from_fen("4k3/8/8/8/4P3/4P3/3PP3/4K3 w - - 0 1").unwrap();
+    let structure = board.pawn_structure(Color::White);
+    assert_eq!(structure, PawnStructure { pawns_per_file: [0, 0, 0, 1, 3, 0, 0, 0], doubled: vec![4], isolated: vec![], backward: vec![] });
+    // The doubled side scores worse than the same material spread over more files
+    let spread = Board::from_fen("4k3/8/8/8/8/8/2PPPP2/4K3 w - - 0 1").unwrap();
+    assert!(spread.evaluate() > board.evaluate());
+}
+
+#[test]
+fn pawn_structure_isolated_1() {
+    // The a-pawn and the e-pawn have no neighbours, the g- and h-pawns protect each other
+    let board = Board::from_fen("4k3/p3p1pp/8/8/8/8/8/4K3 b - - 0 1").unwrap();
+    let structure = board.pawn_structure(Color::Black);
+    assert_eq!(structure.isolated, vec![0, 4]);
+    assert!(structure.doubled.is_empty());
+    assert!(board.pawn_structure(Color::White).isolated.is_empty());
+    // An isolated pair on one file is doubled as well
+    let board = Board::from_fen("4k3/8/8/8/8/3P4/3P4/4K3 w - - 0 1").unwrap();
+    let structure = board.pawn_structure(Color::White);
+    assert_eq!((structure.doubled, structure.isolated), (vec![3], vec![3]));
+}
+
+#[test]
+fn pawn_structure_backward_1() {
+    // d3 is behind c4 and e4, and the black pawn on e5 guards d4 in front of it
+    let board = Board::from_fen("4k3/8/8/4p3/2P1P3/3P4/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(board.pawn_structure(Color::White).backward, vec![(3, 2)]);
+    // Without the guard on d4 it can still advance to join them
+    let board = Board::from_fen("4k3/8/8/8/2P1P3/3P4/8/4K3 w - - 0 1").unwrap();
+    assert!(board.pawn_structure(Color::White).backward.is_empty());
+    // The same shape for Black, mirrored
+    let board = Board::from_fen("4k3/8/3p4/2p1p3/4P3/8/8/4K3 b - - 0 1").unwrap();
+    assert_eq!(board.pawn_structure(Color::Black).backward, vec![(3, 5)]);
+}
+
+#[test]
+fn pawn_structure_evaluate_1() {
+    // Colors swapped, the structure and the score mirror
+    let board = Board::from_fen("4k3/p3p1pp/8/8/8/4P3/3PP3/4K3 w - - 0 1").unwrap();
+    assert_eq!(board.evaluate(), -board.mirror().evaluate());
+    assert_eq!(board.pawn_structure(Color::White).doubled, board.mirror().pawn_structure(Color::Black).doubled);
+}
+
+#[test]
+fn passed_pawns_1() {
+    // c5 is stopped by d6's watch and the d-pawns block each other, a4 and h6 are free
+    let board = Board::from_fen("4k3/8/3p3P/2P5/P2P4/8/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(board.passed_pawns(Color::White), vec![(0, 3), (7, 5)]);
+    assert!(board.passed_pawns(Color::Black).is_empty());
+    // From Black's side the ranks ahead run the other way
+    let board = Board::from_fen("4k3/8/8/8/p7/1P6/7p/4K3 b - - 0 1").unwrap();
+    assert_eq!(board.passed_pawns(Color::Black), vec![(7, 1)]);
+    assert!(Board::starting_position().passed_pawns(Color::White).is_empty());
+}
+
+#[test]
+fn passed_pawn_evaluate_1() {
+    // King and pawn on the sixth against a lone king is clearly winning, and more so than on the fourth
+    let sixth = Board::from_fen("8/8/3KP3/8/8/8/8/k7 w - - 0 1").unwrap();
+    let fourth = Board::from_fen("8/8/3K4/8/4P3/8/8/k7 w - - 0 1").unwrap();
+    assert!(sixth.evaluate() > 300, "{}", sixth.evaluate());
+    assert!(sixth.evaluate() > fourth.evaluate() + 100);
+    // Black's passer on its sixth rank mirrors it
+    assert_eq!(sixth.mirror().evaluate(), -sixth.evaluate());
+}
+
+#[test]
+fn passed_pawn_evaluate_2() {
+    // The same passer with a knight in front of it, and with the knight beside it
+    let blockaded = Board::from_fen("4k3/8/4n3/4P3/8/8/8/4K3 w - - 0 1").unwrap();
+    let free = Board::from_fen("4k3/8/3n4/4P3/8/8/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(blockaded.passed_pawns(Color::White), free.passed_pawns(Color::White));
+    assert!(free.evaluate() > blockaded.evaluate());
+    // Two passers on the same rank score CONNECTED_PASSER_BONUS each side by side and nothing extra apart,
+    // compared without the isolated pawn penalties the evaluation adds for the pair apart
+    let connected = Board::from_fen("4k3/8/8/3PP3/8/8/8/4K3 w - - 0 1").unwrap();
+    let apart = Board::from_fen("4k3/8/8/3P3P/8/8/8/4K3 w - - 0 1").unwrap();
+    let (connected_midgame, connected_endgame) = connected.passed_pawn_score(Color::White);
+    let (apart_midgame, apart_endgame) = apart.passed_pawn_score(Color::White);
+    assert_eq!(connected_midgame - apart_midgame, 2 * CONNECTED_PASSER_BONUS.0);
+    assert_eq!(connected_endgame - apart_endgame, 2 * CONNECTED_PASSER_BONUS.1);
+}
+
+#[test]
+fn pawn_can_attack_1() {
+    let board = Board::from_fen("4k3/2p4p/8/3N4/8/8/8/4K3 w - - 0 1").unwrap();
+    assert!(board.pawn_can_attack(3, 4, Color::Black));
+    assert!(board.pawn_can_attack(6, 3, Color::Black));
+    // A pawn on the same file or already past the square can not
+    assert!(!board.pawn_can_attack(2, 4, Color::Black));
+    assert!(!board.pawn_can_attack(3, 6, Color::Black));
+    // White pawns come up the board
+    let board = Board::from_fen("4k3/8/8/3n4/8/8/4P3/4K3 b - - 0 1").unwrap();
+    assert!(board.pawn_can_attack(3, 4, Color::White));
+    assert!(!board.pawn_can_attack(3, 0, Color::White));
+}
+
+#[test]
+fn knight_outposts_1() {
+    // The classic d5 outpost after ...e5 and ...d6, held by e4, with no c- or e-pawn left to kick it
+    let outpost = Board::from_fen("r1bqk2r/pp3ppp/3p1n2/3Np3/4P3/8/PPP2PPP/R2QKB1R w KQkq - 0 1").unwrap();
+    assert_eq!(outpost.knight_outposts(Color::White), vec![(3, 4)]);
+    // The same knight with Black's c-pawn still on c7, ready to play ...c6
+    let kickable = Board::from_fen("r1bqk2r/ppp2ppp/3p1n2/3Np3/4P3/8/PP3PPP/R2QKB1R w KQkq - 0 1").unwrap();
+    assert!(kickable.knight_outposts(Color::White).is_empty());
+    // Without the e4 pawn's protection it is not an outpost either
+    let loose = Board::from_fen("r1bqk2r/pp3ppp/3p1n2/3Np3/8/8/PPP2PPP/R2QKB1R w KQkq - 0 1").unwrap();
+    assert!(loose.knight_outposts(Color::White).is_empty());
+    // Black's knight on e4, held by d5 and out of reach of White's d- and f-pawns
+    let black = Board::from_fen("4k3/8/8/3p4/4n3/8/8/4K3 b - - 0 1").unwrap();
+    assert_eq!(black.knight_outposts(Color::Black), vec![(4, 3)]);
+}
+
+#[test]
+fn knight_outposts_evaluate_1() {
+    // The same pieces with the knight on e5, where nothing can kick it, or on d5, where ...c6 can.
+    // The knight tables score both squares alike, so the evaluations differ by the outpost bonus alone,
+    // blended at a phase of 8 where it comes out exact
+    let outpost = Board::from_fen("q3k2r/2p5/8/4N3/2PP4/8/8/2B1K3 w - - 0 1").unwrap();
+    let kickable = Board::from_fen("q3k2r/2p5/8/3N4/2PP4/8/8/2B1K3 w - - 0 1").unwrap();
+    assert_eq!(outpost.knight_outposts(Color::White), vec![(4, 4)]);
+    assert!(kickable.knight_outposts(Color::White).is_empty());
+    let phase = outpost.game_phase() as i32;
+    assert_eq!(phase, 8);
+    let bonus = (KNIGHT_OUTPOST_BONUS.0 * phase + KNIGHT_OUTPOST_BONUS.1 * (MAX_PHASE as i32 - phase)) / MAX_PHASE as i32;
+    assert_eq!(outpost.evaluate() - kickable.evaluate(), bonus);
+}
@@ -0,0 +1,86 @@
+use crate::{board::Board, utils::performance::{perft, perft_hashed}};
+
+/// The bundled positions with their expected node counts
+const PERFT_SUITE: &str = include_str!("perft_suite.epd");
+/// Counts above this are only checked by the ignored test, so the default run stays fast in debug builds
+const FAST_NODES: u64 = 250_000;
+
+/// A position and the node count expected at each listed depth
+#[derive(Debug, PartialEq)]
+struct PerftCase {
+    fen: String,
+    depths: Vec<(u32, u64)>,
+}
+
+/// Reads "fen ;D1 20 ;D2 400" lines, skipping blank lines and "#" comments
+fn parse_suite(text: &str) -> Vec<PerftCase> {
+    text.lines().enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(index, line)| {
+            let mut fields = line.split(';');
+            let fen = fields.next().unwrap_or_default().trim().to_string();
+            let depths = fields.map(|field| {
+                let parsed = field.trim().strip_prefix('D')
+                    .and_then(|field| field.split_once(' '))
+                    .and_then(|(depth, nodes)| Some((depth.parse().ok()?, nodes.trim().parse().ok()?)));
+                parsed.unwrap_or_else(|| panic!("line {}: malformed depth field {:?}", index + 1, field))
+            }).collect();
+            PerftCase { fen, depths }
+        })
+        .collect()
+}
+
+/// Checks every depth of every position whose count is at most max_nodes
+fn assert_suite(max_nodes: u64) {
+    assert_suite_with(max_nodes, u32::MAX, |depth, board| perft(depth, board.clone()).unwrap() as u64);
+}
+
+/// Checks the counts of a perft variant up to max_depth, skipping those above max_nodes
+fn assert_suite_with(max_nodes: u64, max_depth: u32, count: impl Fn(u32, &Board) -> u64) {
+    let cases = parse_suite(PERFT_SUITE);
+    for case in &cases {
+        let board = Board::from_fen(&case.fen).unwrap_or_else(|e| panic!("{}: {}", case.fen, e));
+        for &(depth, expected) in case.depths.iter().filter(|&&(depth, nodes)| nodes <= max_nodes && depth <= max_depth) {
+            let nodes = count(depth, &board);
+            assert_eq!(nodes, expected, "perft {} of {} counted {} nodes, expected {}", depth, case.fen, nodes, expected);
+        }
+    }
+}
+
+#[test]
+fn parse_suite_1() {
+    let cases = parse_suite("# comment\n\n8/8/8/8/8/8/8/K1k5 w - - 0 1 ;D1 3 ;D2 12\n");
+    assert_eq!(cases, [PerftCase { fen: "8/8/8/8/8/8/8/K1k5 w - - 0 1".to_string(), depths: vec![(1, 3), (2, 12)] }]);
+    let cases = parse_suite(PERFT_SUITE);
+    assert_eq!(cases.len(), 21);
+    assert!(cases.iter().all(|case| !case.depths.is_empty()));
+}
+
+#[test]
+#[should_panic(expected = "line 1: malformed depth field")]
+fn parse_suite_2() {
+    parse_suite("8/8/8/8/8/8/8/K1k5 w - - 0 1 ;D1 three");
+}
+
+#[test]
+fn perft_suite_1() {
+    assert_suite(FAST_NODES);
+}
+
+#[test]
+#[ignore = "slow in debug builds, run with --release -- --ignored"]
+fn perft_suite_2() {
+    assert_suite(u64::MAX);
+}
+
+#[test]
+fn perft_hashed_suite_1() {
+    assert_suite_with(FAST_NODES, 5, |depth, board| perft_hashed(depth, board, 1).unwrap() as u64);
+}
+
+#[test]
+#[ignore = "slow in debug builds, run with --release -- --ignored"]
+fn perft_hashed_suite_2() {
+    // A 1 MB table fills up at depth 5, so entries are replaced and slots shared along the way
+    assert_suite_with(u64::MAX, 5, |depth, board| perft_hashed(depth, board, 1).unwrap() as u64);
+}
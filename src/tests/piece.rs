@@ -0,0 +1,62 @@
+use crate::{color::Color, move_type::MoveType, piece::Piece, piece_type::PieceType};
+
+
+/// Every (from, to) pair on the board as (from_x, from_y, to_x, to_y)
+fn all_pairs() -> impl Iterator<Item = (usize, usize, usize, usize)> {
+    (0..64).flat_map(|from| (0..64).map(move |to| (from % 8, from / 8, to % 8, to / 8)))
+}
+
+/// What movement_type should return, worked out from the distance moved
+fn expected(piece: Piece, from_x: usize, from_y: usize, to_x: usize, to_y: usize) -> MoveType {
+    let dx = to_x as i32 - from_x as i32;
+    let dy = to_y as i32 - from_y as i32;
+    let straight = (dx == 0) != (dy == 0);
+    let diagonal = dx != 0 && dx.abs() == dy.abs();
+    let forward = if piece.is_white() { dy } else { -dy };
+    let home_rank = if piece.is_white() { 0 } else { 7 };
+    match piece.get_type() {
+        PieceType::Rook if straight => MoveType::Rook,
+        PieceType::Bishop if diagonal => MoveType::Bishop,
+        PieceType::Queen if straight || diagonal => MoveType::Queen,
+        PieceType::Knight if dx.abs() * dy.abs() == 2 => MoveType::Knight,
+        PieceType::King if dx.abs() <= 1 && dy.abs() <= 1 && (dx, dy) != (0, 0) => MoveType::KingNormal,
+        PieceType::King if (from_x, from_y) == (4, home_rank) && to_y == home_rank && to_x == 6 => MoveType::KingCastleKingSide,
+        PieceType::King if (from_x, from_y) == (4, home_rank) && to_y == home_rank && to_x == 2 => MoveType::KingCastleQueenSide,
+        PieceType::Pawn if dx == 0 && forward == 1 => MoveType::Pawn1,
+        PieceType::Pawn if dx == 0 && forward == 2 && from_y == if piece.is_white() { 1 } else { 6 } => MoveType::Pawn2,
+        PieceType::Pawn if dx.abs() == 1 && forward == 1 => MoveType::PawnCapture,
+        _ => MoveType::Illegal,
+    }
+}
+
+#[test]
+fn movement_type_1() {
+    // Every piece of both colors against every pair of squares
+    let types = [PieceType::Pawn, PieceType::Rook, PieceType::Knight, PieceType::Bishop, PieceType::Queen, PieceType::King];
+    for color in [Color::White, Color::Black] {
+        for ptype in types {
+            let piece = Piece::new(ptype, color);
+            for (from_x, from_y, to_x, to_y) in all_pairs() {
+                assert_eq!(
+                    piece.movement_type((from_x, from_y), (to_x, to_y)),
+                    expected(piece, from_x, from_y, to_x, to_y),
+                    "{:?} from ({}, {}) to ({}, {})", piece, from_x, from_y, to_x, to_y
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn movement_type_2() {
+    // Knight jumps and staying put are never sliding moves
+    for ptype in [PieceType::Rook, PieceType::Bishop, PieceType::Queen] {
+        let piece = Piece::new(ptype, Color::White);
+        assert_eq!(piece.movement_type((0, 0), (1, 2)), MoveType::Illegal, "{:?} a1b3", ptype);
+        assert_eq!(piece.movement_type((3, 3), (5, 4)), MoveType::Illegal, "{:?} d4f5", ptype);
+        assert_eq!(piece.movement_type((3, 3), (3, 3)), MoveType::Illegal, "{:?} d4d4", ptype);
+    }
+    assert_eq!(Piece::new(PieceType::King, Color::Black).movement_type((4, 7), (4, 7)), MoveType::Illegal);
+    // Squares off the board
+    assert_eq!(Piece::new(PieceType::Queen, Color::White).movement_type((0, 0), (8, 8)), MoveType::Illegal);
+}
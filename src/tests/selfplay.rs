@@ -0,0 +1,93 @@
+use crate::{board::Board, search::search_limits::SearchLimits, selfplay::{match_result::{elo_to_score, score_to_elo, GameOutcome, MatchResult}, match_runner::MatchRunner, sprt::{Sprt, SprtDecision}}, utils::{notation::move_to_san, openings::random_balanced_position}};
+
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!((actual - expected).abs() < 0.01, "expected {} got {}", expected, actual);
+}
+
+#[test]
+fn elo_estimate_1() {
+    let estimate = MatchResult::new(60, 0, 40).elo_estimate().unwrap();
+    assert_close(estimate.elo, 70.44);
+    assert_close(estimate.lower, 2.77);
+    assert_close(estimate.upper, 143.91);
+}
+
+#[test]
+fn elo_estimate_2() {
+    let estimate = MatchResult::new(30, 40, 30).elo_estimate().unwrap();
+    assert_close(estimate.elo, 0.0);
+    assert_close(estimate.lower, -estimate.upper);
+    assert!(MatchResult::new(10, 0, 0).elo_estimate().is_none());
+    assert!(MatchResult::default().elo_estimate().is_none());
+}
+
+#[test]
+fn elo_conversion_1() {
+    assert_close(elo_to_score(0.0), 0.5);
+    assert_close(score_to_elo(elo_to_score(200.0)), 200.0);
+}
+
+#[test]
+fn match_result_add_1() {
+    let mut result = MatchResult::default();
+    result.add(GameOutcome::Win);
+    result.add(GameOutcome::Draw);
+    result.add(GameOutcome::Loss);
+    result.add(GameOutcome::Win);
+    assert_eq!(result, MatchResult::new(2, 1, 1));
+    assert_eq!(result.score(), Some(0.625));
+}
+
+#[test]
+fn sprt_llr_1() {
+    let sprt = Sprt::new(0.0, 10.0, 0.05, 0.05);
+    let (lower, upper) = sprt.bounds();
+    assert_close(lower, -2.944);
+    assert_close(upper, 2.944);
+    assert_close(sprt.llr(&MatchResult::new(600, 0, 400)), 5.563);
+    assert_close(sprt.llr(&MatchResult::new(450, 0, 550)), -3.325);
+    assert_close(sprt.llr(&MatchResult::new(52, 0, 48)), 0.074);
+}
+
+#[test]
+fn sprt_decision_1() {
+    let sprt = Sprt::new(0.0, 10.0, 0.05, 0.05);
+    assert_eq!(sprt.test(&MatchResult::new(600, 0, 400)), SprtDecision::AcceptH1);
+    assert_eq!(sprt.test(&MatchResult::new(450, 0, 550)), SprtDecision::AcceptH0);
+    assert_eq!(sprt.test(&MatchResult::new(52, 0, 48)), SprtDecision::Continue);
+    assert_eq!(sprt.test(&MatchResult::default()), SprtDecision::Continue);
+}
+
+#[test]
+fn match_runner_1() {
+    let runner = MatchRunner::new(SearchLimits::depth(1), SearchLimits::depth(1), 2);
+    let (result, decision) = runner.run();
+    assert_eq!(result.games(), 2);
+    assert_eq!(decision, SprtDecision::Continue);
+}
+
+#[test]
+fn match_runner_2() {
+    // Both games of a pair start with the same random opening, and the PGN holds its moves
+    let path = std::env::temp_dir().join(format!("casey_chess_selfplay_{}.pgn", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let runner = MatchRunner::new(SearchLimits::depth(1), SearchLimits::depth(1), 2).with_random_openings(6).with_pgn(&path);
+    let (result, _) = runner.run();
+    let pgn = std::fs::read_to_string(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(result.games(), 2);
+    let games = pgn.split("[Event \"Self-play\"]").skip(1).collect::<Vec<_>>();
+    assert_eq!(games.len(), 2, "{}", pgn);
+    assert!(games[0].contains("[White \"engine_a\"]") && games[1].contains("[White \"engine_b\"]"), "{}", pgn);
+    let opening = |game: &str| game.split("\n\n").nth(1).unwrap().split_whitespace().take(9).collect::<Vec<_>>().join(" ");
+    let board = random_balanced_position(0, 6, 100);
+    let mut replay = Board::starting_position();
+    let sans = board.moves_played().map(|mv| {
+        let san = move_to_san(&replay, mv);
+        replay.move_piece(mv.clone()).unwrap();
+        san
+    }).collect::<Vec<_>>();
+    assert_eq!(opening(games[0]), opening(games[1]));
+    assert_eq!(opening(games[0]), format!("1. {} {} 2. {} {} 3. {} {}", sans[0], sans[1], sans[2], sans[3], sans[4], sans[5]));
+}
@@ -0,0 +1,61 @@
+use std::sync::Arc;
+
+use crate::{board::Board, chess_move::Move, game::Game, piece::Piece, search::{search_limits::SearchLimits, searcher::{analyze, Searcher}, transposition_table::TranspositionTable}, utils::performance::perft};
+
+fn assert_send<T: Send>() {}
+fn assert_sync<T: Sync>() {}
+
+#[test]
+fn board_is_send_sync() {
+    assert_send::<Board>();
+    assert_sync::<Board>();
+}
+
+#[test]
+fn game_is_send() {
+    assert_send::<Game>();
+}
+
+#[test]
+fn move_and_piece_are_send_sync() {
+    assert_send::<Move>();
+    assert_sync::<Move>();
+    assert_send::<Piece>();
+    assert_sync::<Piece>();
+}
+
+#[test]
+fn search_types_are_send_sync() {
+    assert_send::<TranspositionTable>();
+    assert_sync::<TranspositionTable>();
+    assert_send::<Searcher>();
+}
+
+#[test]
+fn concurrent_perft_1() {
+    let board = Arc::new(Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap());
+    let handles = (0..4).map(|_| {
+        let board = Arc::clone(&board);
+        std::thread::spawn(move || perft(2, (*board).clone()).unwrap())
+    }).collect::<Vec<_>>();
+    let counts = handles.into_iter().map(|h| h.join().unwrap()).collect::<Vec<_>>();
+    assert!(counts.iter().all(|&count| count == 2039));
+}
+
+#[test]
+fn concurrent_analyze_1() {
+    // Each search has its own table, so four at once find the same move and score in the same number of nodes
+    let board = Arc::new(Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap());
+    let handles = (0..4).map(|_| {
+        let board = Arc::clone(&board);
+        std::thread::spawn(move || analyze(&board, SearchLimits::depth(3)))
+    }).collect::<Vec<_>>();
+    let results = handles.into_iter().map(|h| h.join().unwrap()).collect::<Vec<_>>();
+    let alone = analyze(&board, SearchLimits::depth(3));
+    for result in &results {
+        assert_eq!(result.best_move, alone.best_move);
+        assert_eq!(result.score, alone.score);
+        assert_eq!(result.depth, 3);
+        assert_eq!(result.nodes, alone.nodes);
+    }
+}
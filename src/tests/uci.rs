@@ -0,0 +1,454 @@
+use std::{io::{Cursor, Write}, sync::{atomic::AtomicBool, mpsc, Arc, Mutex}, time::{Duration, Instant}};
+
+use crate::{board::Board, errors::uci_option_error::UciOptionError, chess_move::Move, piece_type::PieceType, search::{engine_config::EngineConfig, search_limits::SearchLimits, searcher::{DepthReport, MATE_SCORE}}, uci::{uci_engine::{depth_info, UciEngine}, uci_interface::{parse_setoption, UciHandler}, uci_messages::{EngineMsg, HandlerRx, HandlerTx}, uci_options::UciOptions, uci_pgn_log::{format_emt, utc_timestamp, PgnLog}}};
+
+
+#[test]
+fn bullet_stress_1() {
+    let (handler_tx, engine_rx) = mpsc::channel();
+    let (engine_tx, handler_rx) = mpsc::channel();
+    UciEngine::new(engine_rx, engine_tx, Arc::new(AtomicBool::new(false)), EngineConfig::default()).run_thread();
+    let mut board = Board::starting_position();
+    for _ in 0..50 {
+        let start = Instant::now();
+        handler_tx.send(HandlerTx::StartSearch(SearchLimits::from_go("movetime 10"))).unwrap();
+        let best_move = loop {
+            match handler_rx.recv_timeout(Duration::from_secs(1)).unwrap() {
+                HandlerRx::EngineMsg(EngineMsg::FinalBestMove(mv)) => break mv,
+                HandlerRx::EngineMsg(EngineMsg::Info(_)) => continue,
+                other => panic!("unexpected message {:?}", other),
+            }
+        };
+        // generous bound as debug builds are slow
+        assert!(start.elapsed() < Duration::from_millis(500), "bestmove took {:?}", start.elapsed());
+        let legal = board.generate_legal_moves();
+        if legal.is_empty() {
+            assert_eq!(best_move, "0000");
+            break;
+        }
+        let mv = legal.into_iter().find(|mv| mv.extended_algebraic() == best_move).expect("bestmove must be legal");
+        board.move_piece(mv).unwrap();
+    }
+}
+
+#[test]
+fn debug_info_string_1() {
+    let (handler_tx, engine_rx) = mpsc::channel();
+    let (engine_tx, handler_rx) = mpsc::channel();
+    UciEngine::new(engine_rx, engine_tx, Arc::new(AtomicBool::new(false)), EngineConfig::default()).run_thread();
+    handler_tx.send(HandlerTx::SetDebug(true)).unwrap();
+    handler_tx.send(HandlerTx::StartSearch(SearchLimits::depth(2))).unwrap();
+    // The static evaluation first, one info line per completed depth, then the counters as an info string
+    match handler_rx.recv_timeout(Duration::from_secs(5)).unwrap() {
+        HandlerRx::EngineMsg(EngineMsg::Info(info)) => assert_eq!(info, "string static eval 0"),
+        other => panic!("unexpected message {:?}", other),
+    }
+    for depth in 1..=2 {
+        match handler_rx.recv_timeout(Duration::from_secs(5)).unwrap() {
+            HandlerRx::EngineMsg(EngineMsg::Info(info)) => assert!(info.starts_with(&format!("depth {} score cp ", depth)), "{}", info),
+            other => panic!("unexpected message {:?}", other),
+        }
+    }
+    match handler_rx.recv_timeout(Duration::from_secs(5)).unwrap() {
+        HandlerRx::EngineMsg(EngineMsg::Info(info)) => assert!(info.starts_with("string nodes ")),
+        other => panic!("unexpected message {:?}", other),
+    }
+    assert!(matches!(handler_rx.recv_timeout(Duration::from_secs(5)).unwrap(), HandlerRx::EngineMsg(EngineMsg::FinalBestMove(_))));
+}
+
+#[test]
+fn depth_info_1() {
+    let report = |score| DepthReport {
+        depth: 3,
+        score,
+        best_move: Move::new(4, 1, 4, 3, PieceType::Pawn, None),
+        iteration_nodes: 900,
+        nodes: 1000,
+        elapsed: Duration::from_millis(12),
+    };
+    assert_eq!(depth_info(&report(31)), "depth 3 score cp 31 nodes 1000 time 12 pv e2e4");
+    assert_eq!(depth_info(&report(MATE_SCORE - 3)), "depth 3 score mate 2 nodes 1000 time 12 pv e2e4");
+    assert_eq!(depth_info(&report(-MATE_SCORE + 2)), "depth 3 score mate -1 nodes 1000 time 12 pv e2e4");
+}
+
+#[test]
+fn parse_setoption_1() {
+    assert_eq!(parse_setoption("name HashSave value /tmp/analysis.hash"), Some(("HashSave".to_string(), "/tmp/analysis.hash".to_string())));
+    assert_eq!(parse_setoption("name Clear Hash"), Some(("Clear Hash".to_string(), String::new())));
+    assert_eq!(parse_setoption("name HashLoad value my analysis.hash"), Some(("HashLoad".to_string(), "my analysis.hash".to_string())));
+    assert_eq!(parse_setoption("value 3"), None);
+    assert_eq!(parse_setoption("name  value 3"), None);
+}
+
+#[test]
+fn hash_save_load_messages_1() {
+    let (handler_tx, engine_rx) = mpsc::channel();
+    let (engine_tx, handler_rx) = mpsc::channel();
+    UciEngine::new(engine_rx, engine_tx, Arc::new(AtomicBool::new(false)), EngineConfig::default()).run_thread();
+    let path = std::env::temp_dir().join(format!("casey_chess_uci_{}.hash", std::process::id())).display().to_string();
+    let info = || match handler_rx.recv_timeout(Duration::from_secs(5)).unwrap() {
+        HandlerRx::EngineMsg(EngineMsg::Info(info)) => info,
+        other => panic!("unexpected message {:?}", other),
+    };
+    handler_tx.send(HandlerTx::StartSearch(SearchLimits::depth(2))).unwrap();
+    while !matches!(handler_rx.recv_timeout(Duration::from_secs(5)).unwrap(), HandlerRx::EngineMsg(EngineMsg::FinalBestMove(_))) {}
+    handler_tx.send(HandlerTx::SaveHash(path.clone())).unwrap();
+    let saved = info();
+    assert!(saved.starts_with("string saved ") && saved.ends_with(&path), "{}", saved);
+    handler_tx.send(HandlerTx::LoadHash(path.clone())).unwrap();
+    let loaded = info();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.replace("loaded", "saved").replace("from", "to"), saved);
+    handler_tx.send(HandlerTx::LoadHash(path.clone())).unwrap();
+    assert!(info().starts_with(&format!("string could not load hash from {}: ", path)));
+}
+
+/// Keeps everything written to it so a test can read the protocol stream back
+#[derive(Clone, Default)]
+struct CaptureWriter(Arc<Mutex<Vec<u8>>>);
+
+impl Write for CaptureWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn protocol_stream_1() {
+    // Everything the handler writes is a protocol line, debug output and warnings included
+    let script = "uci\ndebug on\nisready\nucinewgame\nposition startpos moves e2e4 e7e5\nbogus command\ngo depth 3\nquit\n";
+    let output = CaptureWriter::default();
+    UciHandler::with_io("Casey".to_string(), "JKDow".to_string(), Cursor::new(script), output.clone()).run();
+
+    let bytes = output.0.lock().unwrap().clone();
+    let text = String::from_utf8(bytes).expect("protocol output is UTF-8");
+    assert!(text.ends_with('\n'));
+    let lines = text.lines().collect::<Vec<_>>();
+    for line in &lines {
+        assert!(
+            ["id name ", "id author ", "option name ", "uciok", "readyok", "info ", "bestmove "].iter().any(|prefix| line.starts_with(prefix)),
+            "non protocol line {:?} in\n{}", line, text,
+        );
+    }
+    assert_eq!(lines.iter().filter(|line| **line == "uciok").count(), 1);
+    assert_eq!(lines.iter().filter(|line| **line == "readyok").count(), 1);
+    assert!(lines.last().unwrap().starts_with("bestmove "), "{}", text);
+    assert!(lines.iter().any(|line| line.starts_with("info string static eval ")), "{}", text);
+}
+
+#[test]
+fn input_end_quits_1() {
+    // Running out of input is treated as quit, so run returns instead of waiting forever
+    let output = CaptureWriter::default();
+    UciHandler::with_io("Casey".to_string(), "JKDow".to_string(), Cursor::new("isready\n"), output.clone()).run();
+    assert_eq!(String::from_utf8(output.0.lock().unwrap().clone()).unwrap(), "readyok\n");
+}
+
+#[test]
+fn hash_memory_cap_1() {
+    // A Hash above the cap is clamped with an info string, one within it is taken silently
+    let (handler_tx, engine_rx) = mpsc::channel();
+    let (engine_tx, handler_rx) = mpsc::channel();
+    let config = EngineConfig { max_memory_mb: Some(8), ..Default::default() };
+    UciEngine::new(engine_rx, engine_tx, Arc::new(AtomicBool::new(false)), config).run_thread();
+    handler_tx.send(HandlerTx::SetHash(4096)).unwrap();
+    handler_tx.send(HandlerTx::SetHash(4)).unwrap();
+    handler_tx.send(HandlerTx::StartSearch(SearchLimits::depth(1))).unwrap();
+    let mut infos = Vec::new();
+    loop {
+        match handler_rx.recv_timeout(Duration::from_secs(5)).unwrap() {
+            HandlerRx::EngineMsg(EngineMsg::Info(info)) => infos.push(info),
+            HandlerRx::EngineMsg(EngineMsg::FinalBestMove(_)) => break,
+            other => panic!("unexpected message {:?}", other),
+        }
+    }
+    assert_eq!(infos[0], "string Hash 4096 MB is over the memory cap, using 8 MB");
+    assert!(infos[1..].iter().all(|info| info.starts_with("depth ")), "{:?}", infos);
+}
+
+#[test]
+fn hash_option_1() {
+    // The Hash option offers no more than the cap
+    let config = EngineConfig { max_memory_mb: Some(8), ..Default::default() };
+    let output = CaptureWriter::default();
+    UciHandler::with_config("Casey".to_string(), "JKDow".to_string(), Cursor::new("uci\nquit\n"), output.clone(), config).run();
+    let text = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+    assert!(text.contains("option name Hash type spin default 8 min 0 max 8\n"), "{}", text);
+}
+
+#[test]
+fn castling_repair_info_1() {
+    // A GUI's "KQkq" for a rook odds game is repaired and reported rather than rejected
+    let (handler_tx, engine_rx) = mpsc::channel();
+    let (engine_tx, handler_rx) = mpsc::channel();
+    UciEngine::new(engine_rx, engine_tx, Arc::new(AtomicBool::new(false)), EngineConfig::default()).run_thread();
+    handler_tx.send(HandlerTx::NewFen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBN1 w KQkq - 0 1".to_string())).unwrap();
+    let mut infos = Vec::new();
+    loop {
+        match handler_rx.recv_timeout(Duration::from_secs(5)).unwrap() {
+            HandlerRx::EngineMsg(EngineMsg::Info(info)) => infos.push(info),
+            HandlerRx::EngineMsg(EngineMsg::PositionSet) => break,
+            other => panic!("unexpected message {:?}", other),
+        }
+    }
+    assert_eq!(infos, ["string dropped castling right 'K', no White rook on h1"]);
+}
+
+#[test]
+fn uci_options_1() {
+    // Names ignore case, spins are clamped into range and bad values change nothing
+    let mut options = UciOptions::new(&EngineConfig { max_memory_mb: Some(64), ..Default::default() });
+    assert_eq!(options.set("hash", "128"), Ok("64"));
+    assert_eq!(options.set("HASH", "32"), Ok("32"));
+    assert_eq!(options.set("Hash", "lots"), Err(UciOptionError::NotANumber { name: "Hash".to_string(), value: "lots".to_string() }));
+    assert_eq!(options.set("Threads", "4"), Err(UciOptionError::UnknownOption("Threads".to_string())));
+    assert_eq!(options.hash_mb(), 32);
+    assert_eq!(options.get("hashsave").unwrap().current(), "HashSave type string value <empty>");
+    assert_eq!(options.set("StrictProtocol", "TRUE"), Ok("true"));
+    assert_eq!(options.set("StrictProtocol", "yes"), Err(UciOptionError::NotABoolean { name: "StrictProtocol".to_string(), value: "yes".to_string() }));
+    assert!(options.strict_protocol());
+}
+
+#[test]
+fn options_command_1() {
+    // The listing shows the values set, not the defaults, and the engine's table is the size listed
+    let path = std::env::temp_dir().join(format!("casey_chess_options_{}.hash", std::process::id())).display().to_string();
+    let script = format!(
+        "uci\nsetoption name Hash value 8\nsetoption name HashSave value {path}\nsetoption name hashload value {path}\noptions\nquit\n",
+    );
+    let output = CaptureWriter::default();
+    UciHandler::with_io("Casey".to_string(), "JKDow".to_string(), Cursor::new(script), output.clone()).run();
+    let _ = std::fs::remove_file(&path);
+    let text = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+    assert!(text.contains("option name Hash type spin default 16 min 0 max 65536\n"), "{}", text);
+    assert!(text.contains("info string option Hash type spin value 8\n"), "{}", text);
+    assert!(text.contains(&format!("info string option HashSave type string value {}\n", path)), "{}", text);
+    assert!(text.contains(&format!("info string option HashLoad type string value {}\n", path)), "{}", text);
+    assert!(text.contains("info string memory transposition table 8.0 MB"), "{}", text);
+}
+
+#[test]
+fn options_command_2() {
+    // A Hash over the cap is clamped by the registry and the engine uses the clamped size
+    let config = EngineConfig { max_memory_mb: Some(4), ..Default::default() };
+    let output = CaptureWriter::default();
+    let script = "uci\nsetoption name Hash value 4096\noptions\nquit\n";
+    UciHandler::with_config("Casey".to_string(), "JKDow".to_string(), Cursor::new(script), output.clone(), config).run();
+    let text = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+    assert!(text.contains("info string Hash 4096 is out of range, using 4\n"), "{}", text);
+    assert!(text.contains("info string option Hash type spin value 4\n"), "{}", text);
+    assert!(text.contains("info string memory transposition table 4.0 MB"), "{}", text);
+}
+
+#[test]
+fn input_closed_mid_search_1() {
+    // The GUI's pipe closes during an infinite search: the search is stopped, its bestmove sent,
+    // and run returns with the engine and input threads joined
+    let (reader, mut writer) = std::io::pipe().unwrap();
+    let output = CaptureWriter::default();
+    let mut handler = UciHandler::with_io("Casey".to_string(), "JKDow".to_string(), std::io::BufReader::new(reader), output.clone());
+    let (done_tx, done_rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        handler.run();
+        done_tx.send(()).unwrap();
+    });
+    writer.write_all(b"uci\nisready\nposition startpos\ngo infinite\n").unwrap();
+    std::thread::sleep(Duration::from_millis(100));
+    drop(writer);
+    done_rx.recv_timeout(Duration::from_secs(5)).expect("run returns once the input closes");
+    let text = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+    assert!(text.lines().last().unwrap().starts_with("bestmove "), "{}", text);
+}
+
+#[test]
+fn handler_gone_1() {
+    // The engine thread ends quietly instead of panicking when the handler stops listening
+    let (handler_tx, engine_rx) = mpsc::channel();
+    let (engine_tx, handler_rx) = mpsc::channel();
+    let handle = UciEngine::new(engine_rx, engine_tx, Arc::new(AtomicBool::new(false)), EngineConfig::default()).run_thread();
+    drop(handler_rx);
+    handler_tx.send(HandlerTx::StartingPosition(String::new())).unwrap();
+    assert!(handle.join().is_ok());
+}
+
+/// Waits until the protocol stream holds the given number of bestmove lines and returns the last move
+fn wait_for_bestmove(output: &CaptureWriter, count: usize) -> String {
+    let start = Instant::now();
+    loop {
+        let text = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+        let moves = text.lines().filter_map(|line| line.strip_prefix("bestmove ")).map(str::to_string).collect::<Vec<_>>();
+        if moves.len() >= count {
+            return moves[count - 1].clone();
+        }
+        assert!(start.elapsed() < Duration::from_secs(10), "no bestmove {} in\n{}", count, text);
+        std::thread::sleep(Duration::from_millis(5));
+    }
+}
+
+#[test]
+fn save_pgn_1() {
+    // Two games over the protocol, the engine playing Black then White, give two PGN files with every move
+    // and a think time on each of the engine's moves
+    let directory = std::env::temp_dir().join(format!("casey_chess_pgn_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&directory);
+    let (reader, mut writer) = std::io::pipe().unwrap();
+    let output = CaptureWriter::default();
+    let mut handler = UciHandler::with_io("Casey".to_string(), "JKDow".to_string(), std::io::BufReader::new(reader), output.clone());
+    let handle = std::thread::spawn(move || handler.run());
+    writeln!(writer, "uci\nsetoption name SavePGN value {}\nucinewgame\nposition startpos moves e2e4\ngo depth 1", directory.display()).unwrap();
+    let reply = wait_for_bestmove(&output, 1);
+    writeln!(writer, "position startpos moves e2e4 {} d2d4\ngo depth 1", reply).unwrap();
+    wait_for_bestmove(&output, 2);
+    writeln!(writer, "ucinewgame\nposition startpos\ngo depth 1").unwrap();
+    let first = wait_for_bestmove(&output, 3);
+    writeln!(writer, "position startpos moves {} e7e5\ngo depth 1\nquit", first).unwrap();
+    handle.join().unwrap();
+
+    let files = std::fs::read_dir(&directory).unwrap().map(|entry| entry.unwrap().path()).collect::<Vec<_>>();
+    assert_eq!(files.len(), 2, "{:?}", files);
+    let mut plies = Vec::new();
+    for path in &files {
+        assert!(path.file_name().unwrap().to_string_lossy().ends_with("-unknown-vs-unknown.pgn"), "{}", path.display());
+        let pgn = std::fs::read_to_string(path).unwrap();
+        assert!(pgn.starts_with("[Event \"UCI game\"]\n"), "{}", pgn);
+        assert!(pgn.contains("[Result \"*\"]\n"), "{}", pgn);
+        assert!(pgn.trim_end().ends_with(" *"), "{}", pgn);
+        let movetext = pgn.split("\n\n").nth(1).unwrap();
+        assert_eq!(movetext.matches("[%emt ").count(), 2, "{}", pgn);
+        let moves = movetext.split_whitespace()
+            .filter(|token| !token.ends_with('.') && !token.starts_with('{') && !token.ends_with('}') && *token != "*")
+            .count();
+        plies.push(moves);
+    }
+    let _ = std::fs::remove_dir_all(&directory);
+    plies.sort();
+    assert_eq!(plies, [3, 4]);
+}
+
+#[test]
+fn pgn_log_repair_1() {
+    // Black's castling rights have no rooks, which the engine repairs, so the moves are replayed from that position
+    // rather than the start, and a position that can not be set up at all is reported instead of written
+    let fen = "4k3/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+    assert!(Board::from_fen(fen).is_err());
+    let directory = std::env::temp_dir().join(format!("casey_chess_pgn_repair_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&directory);
+    let mut log = PgnLog::new(&directory);
+    let castle = Move::new(4, 0, 6, 0, PieceType::King, None);
+    log.update(Some(fen), &[castle], Some(Duration::from_millis(250))).unwrap();
+    let pgn = std::fs::read_to_string(log.path().unwrap()).unwrap();
+    assert!(pgn.contains(&format!("[FEN \"{}\"]\n", fen)), "{}", pgn);
+    assert!(pgn.contains("\n1. O-O {[%emt 0:00:00.250]} *\n"), "{}", pgn);
+    log.new_game();
+    assert!(log.update(Some("not a fen"), &[], None).is_err());
+    assert!(!log.path().unwrap().exists());
+    let _ = std::fs::remove_dir_all(&directory);
+}
+
+#[test]
+fn pgn_log_timestamps_1() {
+    assert_eq!(format_emt(Duration::from_millis(3_723_045)), "1:02:03.045");
+    let time = std::time::UNIX_EPOCH + Duration::from_secs(951_782_400 + 3_661);
+    assert_eq!(utc_timestamp(time), ("2000.02.29".to_string(), "20000229-010101".to_string()));
+    let time = std::time::UNIX_EPOCH + Duration::from_secs(1_791_936_000);
+    assert_eq!(utc_timestamp(time).0, "2026.10.14");
+}
+
+#[test]
+fn ucinewgame_mid_search_1() {
+    // ucinewgame during an infinite search stops it with a bestmove, and the isready, position and go after it
+    // are only handled once the new game has started
+    let (reader, mut writer) = std::io::pipe().unwrap();
+    let output = CaptureWriter::default();
+    let mut handler = UciHandler::with_io("Casey".to_string(), "JKDow".to_string(), std::io::BufReader::new(reader), output.clone());
+    let handle = std::thread::spawn(move || handler.run());
+    writeln!(writer, "uci\nposition startpos\ngo infinite").unwrap();
+    std::thread::sleep(Duration::from_millis(100));
+    writeln!(writer, "ucinewgame\nisready\nposition startpos moves e2e4\ngo depth 2").unwrap();
+    wait_for_bestmove(&output, 2);
+    writeln!(writer, "quit").unwrap();
+    handle.join().unwrap();
+
+    let text = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+    let lines = text.lines().skip_while(|line| *line != "uciok").collect::<Vec<_>>();
+    let bestmoves = lines.iter().enumerate().filter(|(_, line)| line.starts_with("bestmove ")).map(|(i, _)| i).collect::<Vec<_>>();
+    let readyok = lines.iter().position(|line| *line == "readyok").expect("isready is answered");
+    assert_eq!(bestmoves.len(), 2, "{}", text);
+    assert!(bestmoves[0] < readyok && readyok < bestmoves[1], "{}", text);
+    assert_eq!(lines.iter().filter(|line| **line == "readyok").count(), 1, "{}", text);
+    // The second search is from the position after e2e4, so it answers for Black
+    assert!(["a7", "b7", "c7", "d7", "e7", "f7", "g7", "h7", "b8", "g8"].iter().any(|from| lines[bestmoves[1]][9..].starts_with(from)), "{}", text);
+}
+
+/// Runs a script through a handler and returns the "info string protocol: " warnings it wrote
+fn protocol_warnings(script: &str) -> Vec<String> {
+    let output = CaptureWriter::default();
+    UciHandler::with_io("Casey".to_string(), "JKDow".to_string(), Cursor::new(script.to_string()), output.clone()).run();
+    let text = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+    text.lines().filter_map(|line| line.strip_prefix("info string protocol: ")).map(str::to_string).collect()
+}
+
+#[test]
+fn strict_protocol_1() {
+    // Commands before the handshake and a search are turned away
+    let warnings = protocol_warnings("setoption name StrictProtocol value true\nposition startpos\nuci\ngo depth 1\nquit\n");
+    assert_eq!(warnings, ["position before uci, ignored", "go before position, ignored"]);
+}
+
+#[test]
+fn strict_protocol_2() {
+    // Changing an option or starting another search while one is pending
+    let script = "uci\nsetoption name StrictProtocol value true\nposition startpos\ngo infinite\nsetoption name Hash value 8\ngo depth 1\nstop\nquit\n";
+    let warnings = protocol_warnings(script);
+    assert_eq!(warnings, [
+        "setoption during a search, send stop and wait for bestmove first, ignored",
+        "go during a search, send stop and wait for bestmove first, ignored",
+    ]);
+}
+
+#[test]
+fn strict_protocol_5() {
+    // A new position while a search is pending is turned away, the search keeps the position it had
+    let script = "uci\nsetoption name StrictProtocol value true\nposition startpos\ngo infinite\nposition startpos moves e2e4\nstop\nquit\n";
+    let warnings = protocol_warnings(script);
+    assert_eq!(warnings, ["position during a search, send stop and wait for bestmove first, ignored"]);
+}
+
+#[test]
+fn strict_protocol_3() {
+    // A flood of isready is still answered, lines that are not commands are reported
+    let output = CaptureWriter::default();
+    let script = "uci\nsetoption name StrictProtocol value true\nisready\nisready\nisready\nposition startpos moves e2e5\nfoo bar\nquit\n";
+    UciHandler::with_io("Casey".to_string(), "JKDow".to_string(), Cursor::new(script), output.clone()).run();
+    let text = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+    assert_eq!(text.lines().filter(|line| *line == "readyok").count(), 3, "{}", text);
+    let warnings = text.lines().filter_map(|line| line.strip_prefix("info string protocol: ")).collect::<Vec<_>>();
+    assert_eq!(warnings, [
+        "isready repeated with no command in between",
+        "isready repeated with no command in between",
+        "unknown command 'foo bar', ignored",
+    ]);
+}
+
+#[test]
+fn strict_protocol_4() {
+    // A clean session gives no warnings, and without the option violations stay off the protocol stream
+    let (reader, mut writer) = std::io::pipe().unwrap();
+    let output = CaptureWriter::default();
+    let mut handler = UciHandler::with_io("Casey".to_string(), "JKDow".to_string(), std::io::BufReader::new(reader), output.clone());
+    let handle = std::thread::spawn(move || handler.run());
+    writeln!(writer, "uci\nsetoption name StrictProtocol value true\nisready\nucinewgame\nposition startpos\ngo depth 2").unwrap();
+    let reply = wait_for_bestmove(&output, 1);
+    writeln!(writer, "isready\nposition startpos moves {} e7e5\ngo depth 1", reply).unwrap();
+    wait_for_bestmove(&output, 2);
+    writeln!(writer, "quit").unwrap();
+    handle.join().unwrap();
+    let text = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+    assert!(!text.contains("info string protocol: "), "{}", text);
+    assert!(protocol_warnings("position startpos\nuci\ngo depth 1\nisready\nisready\nbogus\nquit\n").is_empty());
+}
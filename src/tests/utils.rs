@@ -13,3 +13,236 @@ fn square_to_coords_test() {
     let coords = crate::utils::notation::square_to_coords(square);
     assert_eq!(coords, Some((7, 7)));
 }
+
+#[test]
+fn square_to_coords_malformed_1() {
+    // bytes below 'a' and '1' used to underflow
+    for square in ["A1", "e0", "!!", "", "e", "e44", "\u{e9}4"] {
+        assert_eq!(crate::utils::notation::square_to_coords(square), None, "{}", square);
+    }
+}
+
+#[test]
+fn uci_to_move_1() {
+    use crate::{board::Board, piece_type::PieceType, utils::notation::uci_to_move};
+    let board = Board::from_fen("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 b kq - 0 1").unwrap();
+    assert_eq!(uci_to_move(&board, "b2a1q").unwrap().promotion, Some(PieceType::Queen));
+    assert_eq!(uci_to_move(&board, "b2a1N").unwrap().promotion, Some(PieceType::Knight));
+    assert_eq!(uci_to_move(&board, "f6e4").unwrap().piece_type, PieceType::Knight);
+    for mv in board.generate_legal_moves() {
+        assert_eq!(uci_to_move(&board, &mv.extended_algebraic()), Some(mv));
+    }
+    // malformed or illegal
+    for text in ["", "b2", "b2a1x", "b2a1qq", "z9a1", "e8e1", "b2a1", "\u{e9}\u{e9}", "e8g8"] {
+        assert_eq!(uci_to_move(&board, text), None, "{}", text);
+    }
+}
+
+#[test]
+fn perft_timed_1() {
+    use crate::{board::Board, utils::performance::{perft_divide, perft_timed}};
+    let board = Board::starting_position();
+    let result = perft_timed(3, &board).unwrap();
+    assert_eq!((result.depth, result.nodes, result.divide), (3, 8902, None));
+    // Divided counts add up to the total, one entry per legal move
+    let divided = perft_divide(3, &board).unwrap();
+    let entries = divided.divide.clone().unwrap();
+    assert_eq!(divided.nodes, 8902);
+    assert_eq!(entries.len(), 20);
+    assert_eq!(entries.iter().map(|entry| entry.nodes).sum::<u64>(), 8902);
+    assert_eq!(entries.iter().find(|entry| entry.mv == "e2e4").unwrap().nodes, 600);
+    assert!(divided.to_string().starts_with("depth 3 nodes 8902 time "));
+    assert!(divided.to_string().contains("\n  e2e4: 600"));
+}
+
+#[test]
+fn perft_divide_1() {
+    use crate::{board::Board, utils::performance::perft_divide};
+    let entries = perft_divide(2, &Board::starting_position()).unwrap().divide.unwrap();
+    assert_eq!(entries.len(), 20);
+    assert_eq!(entries.iter().map(|entry| entry.nodes).sum::<u64>(), 400);
+    assert!(entries.iter().all(|entry| entry.nodes == 20));
+    // Sorted by UCI notation to diff against another engine's output
+    assert_eq!((entries[0].mv.as_str(), entries[19].mv.as_str()), ("a2a3", "h2h4"));
+    assert!(entries.windows(2).all(|pair| pair[0].mv < pair[1].mv));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn perft_json_1() {
+    // What a CI script reads from perft 3 --divide --format json
+    use crate::{board::Board, utils::performance::{perft_divide, perft_json, PerftResult}};
+    let board = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+    let results = (1..=3).map(|depth| perft_divide(depth, &board).unwrap()).collect::<Vec<_>>();
+    let json = perft_json(&results);
+    let parsed: Vec<PerftResult> = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, results);
+    assert_eq!(parsed.iter().map(|result| result.nodes).collect::<Vec<_>>(), [48, 2039, 97862]);
+    assert_eq!(parsed[2].divide.as_ref().unwrap().len(), 48);
+}
+
+/// Ten lines for evaluate_fens, the fourth and eighth are not valid FENs
+const BATCH_INPUT: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1
+r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1
+8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1
+not a fen
+r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1
+rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8,0.5
+4k3/8/4p3/8/8/8/8/3QK3 w - - 0 1
+rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1
+r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4
+8/8/4k3/8/8/3K4/8/8 w - - 0 1
+";
+
+#[test]
+fn evaluate_fens_1() {
+    use crate::{board::Board, search::search_limits::SearchLimits, utils::batch::evaluate_fens};
+    let fens = BATCH_INPUT.lines().map(|line| line.split(',').next().unwrap()).collect::<Vec<_>>();
+    let valid = [0, 1, 2, 4, 5, 6, 8, 9].map(|index| fens[index]);
+
+    let mut output = Vec::new();
+    let report = evaluate_fens(BATCH_INPUT.as_bytes(), &mut output, None, 4).unwrap();
+    assert_eq!((report.scored, report.skipped), (8, 2));
+    let output = String::from_utf8(output).unwrap();
+    let lines = output.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 8);
+    for (line, fen) in lines.iter().zip(valid) {
+        let score = Board::from_fen(fen).unwrap().evaluate_to_move();
+        assert_eq!(*line, format!("{},{}", fen, score));
+    }
+
+    // A fixed depth gives the same scores in the same order whatever the thread count
+    let searched = |threads| {
+        let mut output = Vec::new();
+        let report = evaluate_fens(BATCH_INPUT.as_bytes(), &mut output, Some(SearchLimits::depth(3)), threads).unwrap();
+        assert_eq!((report.scored, report.skipped), (8, 2));
+        String::from_utf8(output).unwrap()
+    };
+    let single = searched(1);
+    assert_eq!(single.lines().map(|line| line.rsplit_once(',').unwrap().0).collect::<Vec<_>>(), valid);
+    assert_eq!(searched(4), single);
+    assert_eq!(searched(4), single);
+}
+
+#[test]
+fn perft_parallel_1() {
+    use crate::{board::Board, utils::performance::{perft, perft_parallel}};
+    let board = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+    for threads in [1, 3, 8] {
+        let result = perft_parallel(3, &board, threads).unwrap();
+        assert_eq!(result.nodes, perft(3, board.clone()).unwrap() as u64);
+        let thread_nodes = result.thread_nodes.clone().unwrap();
+        assert_eq!(thread_nodes.len(), threads);
+        assert_eq!(thread_nodes.iter().sum::<u64>(), 97862);
+        assert!(result.to_string().contains(" threads "), "{}", result);
+    }
+    assert_eq!(perft_parallel(0, &board, 4).unwrap().nodes, 1);
+}
+
+#[test]
+#[ignore = "slow in debug builds, run with --release -- --ignored"]
+fn perft_parallel_2() {
+    use crate::{board::Board, utils::performance::perft_parallel};
+    assert_eq!(perft_parallel(5, &Board::starting_position(), 4).unwrap().nodes, 4865609);
+    let kiwipete = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+    assert_eq!(perft_parallel(5, &kiwipete, 4).unwrap().nodes, 193690690);
+}
+
+#[test]
+fn random_balanced_position_1() {
+    use crate::utils::openings::random_balanced_position;
+    // The same seed gives the same line, different seeds different ones
+    let board = random_balanced_position(42, 8, 100);
+    assert_eq!(board.to_fen(), random_balanced_position(42, 8, 100).to_fen());
+    assert_eq!(board.moves_played().count(), 8);
+    let fens = (0..50).map(|seed| random_balanced_position(seed, 8, 100).to_fen()).collect::<std::collections::HashSet<_>>();
+    assert!(fens.len() > 40, "{} distinct openings", fens.len());
+}
+
+#[test]
+fn random_balanced_position_2() {
+    use crate::utils::openings::random_balanced_position;
+    // Every opening is within the bound, out of check and has moves to play
+    for (plies, bound) in [(8, 100), (16, 0)] {
+        for seed in 0..50 {
+            let board = random_balanced_position(seed, plies, bound);
+            assert!(board.basic_evaluate().abs() <= bound, "{}", board.to_fen());
+            assert!(!board.king_in_check(), "{}", board.to_fen());
+            assert!(!board.generate_legal_moves().is_empty(), "{}", board.to_fen());
+        }
+    }
+}
+
+#[test]
+fn perft_stats_1() {
+    use crate::{board::Board, utils::performance::{perft_stats, PerftStats}};
+    // The published breakdowns of the start position and Kiwipete
+    let stats = |nodes, captures, en_passant, castles, promotions, checks, checkmates| {
+        PerftStats { nodes, captures, en_passant, castles, promotions, checks, checkmates }
+    };
+    let board = Board::starting_position();
+    assert_eq!(perft_stats(0, &board).unwrap(), stats(1, 0, 0, 0, 0, 0, 0));
+    assert_eq!(perft_stats(1, &board).unwrap(), stats(20, 0, 0, 0, 0, 0, 0));
+    assert_eq!(perft_stats(2, &board).unwrap(), stats(400, 0, 0, 0, 0, 0, 0));
+    assert_eq!(perft_stats(3, &board).unwrap(), stats(8902, 34, 0, 0, 0, 12, 0));
+    assert_eq!(perft_stats(4, &board).unwrap(), stats(197281, 1576, 0, 0, 0, 469, 8));
+    let kiwipete = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+    assert_eq!(perft_stats(1, &kiwipete).unwrap(), stats(48, 8, 0, 2, 0, 0, 0));
+    assert_eq!(perft_stats(2, &kiwipete).unwrap(), stats(2039, 351, 1, 91, 0, 3, 0));
+    assert_eq!(perft_stats(3, &kiwipete).unwrap(), stats(97862, 17102, 45, 3162, 0, 993, 1));
+    assert_eq!(perft_stats(3, &kiwipete).unwrap().to_string(), "nodes 97862 captures 17102 ep 45 castles 3162 promotions 0 checks 993 checkmates 1");
+}
+
+#[test]
+#[ignore = "slow in debug builds, run with --release -- --ignored"]
+fn perft_stats_2() {
+    use crate::{board::Board, utils::performance::{perft_stats, PerftStats}};
+    let kiwipete = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+    let expected = PerftStats { nodes: 4085603, captures: 757163, en_passant: 1929, castles: 128013, promotions: 15172, checks: 25523, checkmates: 43 };
+    assert_eq!(perft_stats(4, &kiwipete).unwrap(), expected);
+    let expected = PerftStats { nodes: 4865609, captures: 82719, en_passant: 258, castles: 0, promotions: 0, checks: 27351, checkmates: 347 };
+    assert_eq!(perft_stats(5, &Board::starting_position()).unwrap(), expected);
+}
+
+#[test]
+fn perft_with_1() {
+    use crate::{board::Board, errors::{move_error::MoveError, perft_error::PerftError}, utils::performance::{perft_with, PerftMode, PerftStats}};
+    // Every mode counts the same nodes, only Stats counts the kinds of moves
+    let board = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+    let nodes_only = PerftStats { nodes: 2039, ..Default::default() };
+    assert_eq!(perft_with(2, &board, PerftMode::Bulk).unwrap(), nodes_only);
+    assert_eq!(perft_with(2, &board, PerftMode::MakeEveryMove).unwrap(), nodes_only);
+    let stats = perft_with(2, &board, PerftMode::Stats).unwrap();
+    assert_eq!((stats.nodes, stats.captures, stats.en_passant, stats.castles), (2039, 351, 1, 91));
+    let error = PerftError { mv: "e1g1".to_string(), fen: "4k3/8/8/8/8/8/8/4K3 w - - 0 1".to_string(), source: MoveError::IllegalMove };
+    assert_eq!(error.to_string(), "Generated move e1g1 could not be played in 4k3/8/8/8/8/8/8/4K3 w - - 0 1: Illegal move");
+}
+
+#[test]
+fn perft_hashed_1() {
+    use crate::{board::Board, utils::performance::{perft, perft_hashed}};
+    // No table counts the same as plain perft, and the table is rebuilt for every call
+    let kiwipete = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+    for depth in 0..=3 {
+        let expected = perft(depth, kiwipete.clone()).unwrap();
+        assert_eq!(perft_hashed(depth, &kiwipete, 0).unwrap(), expected);
+        assert_eq!(perft_hashed(depth, &kiwipete, 1).unwrap(), expected);
+        assert_eq!(perft_hashed(depth, &kiwipete, 1).unwrap(), expected);
+    }
+}
+
+#[test]
+#[ignore = "timing, run with --release -- --ignored --nocapture"]
+fn perft_hashed_timing_1() {
+    use crate::{board::Board, utils::performance::{perft, perft_hashed}};
+    let board = Board::starting_position();
+    let start = std::time::Instant::now();
+    let plain = perft(6, board.clone()).unwrap();
+    let plain_time = start.elapsed();
+    let start = std::time::Instant::now();
+    let hashed = perft_hashed(6, &board, 64).unwrap();
+    let hashed_time = start.elapsed();
+    assert_eq!(plain, hashed);
+    println!("perft 6 plain {:?}, hashed {:?}", plain_time, hashed_time);
+    assert!(hashed_time < plain_time);
+}
@@ -4,3 +4,5 @@ pub mod uci_engine;
 pub mod uci_commands;
 pub mod uci_messages;
 pub mod uci_input;
+pub mod uci_options;
+pub mod uci_pgn_log;
@@ -12,6 +12,10 @@ pub enum UciGuiToEngine {
     Stop,
     PonderHit,
     Quit,
+    /// Not part of UCI, lists the options and their current values
+    Options,
+    /// A line that is not a command, kept to be reported under StrictProtocol
+    Unknown(String),
 }
 
 impl UciGuiToEngine {
@@ -28,7 +32,27 @@ impl UciGuiToEngine {
             Some("stop") => Some(UciGuiToEngine::Stop),
             Some("ponderhit") => Some(UciGuiToEngine::PonderHit),
             Some("quit") => Some(UciGuiToEngine::Quit),
-            _ => None,
+            Some("options") => Some(UciGuiToEngine::Options),
+            Some(_) => Some(UciGuiToEngine::Unknown(input.trim().to_string())),
+            None => None,
+        }
+    }
+
+    /// The command's first word, as PROTOCOL_RULES names it, e.g. "go"
+    pub fn name(&self) -> &'static str {
+        match self {
+            UciGuiToEngine::Uci => "uci",
+            UciGuiToEngine::Debug(_) => "debug",
+            UciGuiToEngine::IsReady => "isready",
+            UciGuiToEngine::SetOption(_) => "setoption",
+            UciGuiToEngine::UciNewGame => "ucinewgame",
+            UciGuiToEngine::Position(_) => "position",
+            UciGuiToEngine::Go(_) => "go",
+            UciGuiToEngine::Stop => "stop",
+            UciGuiToEngine::PonderHit => "ponderhit",
+            UciGuiToEngine::Quit => "quit",
+            UciGuiToEngine::Options => "options",
+            UciGuiToEngine::Unknown(_) => "unknown",
         }
     }
 }
@@ -1,6 +1,8 @@
-use crate::{chess_move::Move, game::Game, piece_type::PieceType};
+use std::{sync::{atomic::AtomicBool, Arc}, time::Duration};
 
-use super::uci_messages::{EngineMsg, HandlerRx, HandlerTx};
+use crate::{castling_validation::CastlingValidation, game::Game, search::{engine_config::EngineConfig, memory_report::MemoryReport, search_limits::SearchLimits, searcher::{DepthReport, Searcher, MATE_SCORE, MAX_DEPTH}, transposition_table::TranspositionTable}};
+
+use super::{uci_messages::{EngineMsg, HandlerRx, HandlerTx}, uci_pgn_log::PgnLog};
 
 #[derive(Debug, PartialEq)]
 enum UciEngineState {
@@ -13,15 +15,31 @@ pub struct UciEngine {
     rx: std::sync::mpsc::Receiver<HandlerTx>,
     tx: std::sync::mpsc::Sender<HandlerRx>,
     game: Game,
+    stop: Arc<AtomicBool>,
+    debug: bool,
+    // Kept between searches so later moves and resumed analysis benefit from earlier ones
+    table: TranspositionTable,
+    // Holds the hash size and the memory cap the table must fit in
+    config: EngineConfig,
+    // Set when a message could not be sent because the handler has gone away
+    disconnected: bool,
+    // Writes the games played to PGN files while the SavePGN option is set
+    pgn_log: Option<PgnLog>,
 }
 
 impl UciEngine {
-    pub fn new(rx: std::sync::mpsc::Receiver<HandlerTx>, tx: std::sync::mpsc::Sender<HandlerRx>) -> UciEngine {
+    pub fn new(rx: std::sync::mpsc::Receiver<HandlerTx>, tx: std::sync::mpsc::Sender<HandlerRx>, stop: Arc<AtomicBool>, config: EngineConfig) -> UciEngine {
         UciEngine {
             state: UciEngineState::Idle,
             rx,
             tx,
             game: Game::new(),
+            stop,
+            debug: false,
+            table: TranspositionTable::new(config.hash_budget_mb()),
+            config,
+            disconnected: false,
+            pgn_log: None,
         }
     }
 
@@ -30,60 +48,103 @@ impl UciEngine {
     }
 
     fn main_loop(&mut self) {
-        loop {
-            let message = self.rx.recv().unwrap();
+        // Exit once the handler asks to or has gone away
+        while let Ok(message) = self.rx.recv() {
             match message {
+                HandlerTx::Quit => return,
+                HandlerTx::NewGame => {
+                    if let Some(log) = self.pgn_log.as_mut() {
+                        log.new_game();
+                    }
+                    self.send(EngineMsg::NewGameStarted);
+                }
+                HandlerTx::SavePgn(directory) => self.pgn_log = (!directory.is_empty()).then(|| PgnLog::new(directory)),
                 HandlerTx::NewFen(fen) => self.handle_new_fen(fen),
                 HandlerTx::StartingPosition(moves) => self.handle_starting_position(moves),
-                HandlerTx::StartSearch => self.handle_start_search(),
+                HandlerTx::StartSearch(limits) => self.handle_start_search(limits),
                 HandlerTx::StopSearch => self.handle_stop_search(),
                 HandlerTx::MakeMove(mv) => self.handle_make_move(mv),
-                HandlerTx::StartSearchTime(time) => self.handle_start_search_time(time),
+                HandlerTx::SetDebug(debug) => self.debug = debug,
+                HandlerTx::SaveHash(path) => self.handle_save_hash(path),
+                HandlerTx::LoadHash(path) => self.handle_load_hash(path),
+                HandlerTx::SetHash(megabytes) => self.handle_set_hash(megabytes),
+                HandlerTx::ReportMemory => {
+                    let report = MemoryReport { transposition_table: self.table.allocated_bytes() };
+                    self.send(EngineMsg::Memory(report));
+                }
+            }
+            if self.disconnected {
+                return;
             }
         }
     }
 
+    /// Send a message to the handler, noting when it has gone away instead of panicking
+    fn send(&mut self, message: EngineMsg) {
+        if self.tx.send(HandlerRx::EngineMsg(message)).is_err() {
+            log::error!("UCI handler disconnected, stopping the engine thread");
+            self.disconnected = true;
+        }
+    }
+
     fn handle_new_fen(&mut self, fen: String) {
-        self.game = Game::from_fen(&fen);
-        self.tx.send(HandlerRx::EngineMsg(EngineMsg::PositionSet)).unwrap();
+        // GUIs often send "KQkq" for odds and setup positions, so impossible rights are dropped rather than rejected
+        match Game::from_fen_with(&fen, CastlingValidation::Repair) {
+            Ok((game, repairs)) => {
+                self.game = game;
+                for repair in repairs {
+                    let info = format!("string {}", repair);
+                    self.send(EngineMsg::Info(info));
+                }
+            }
+            Err(e) => log::error!("Ignoring invalid FEN '{}': {}", fen, e),
+        }
+        self.log_game(None);
+        self.send(EngineMsg::PositionSet);
     }
 
     fn handle_starting_position(&mut self, moves: String) {
         log::debug!("Setting starting position with moves: {}", moves);
         self.game = Game::new();
-        let mut moves = moves.split_whitespace().collect::<Vec<&str>>();
-        if moves.len() == 0 {
-            self.tx.send(HandlerRx::EngineMsg(EngineMsg::PositionSet)).unwrap();
-            return
-        }
-        if moves.remove(0) != "moves" {
-            self.tx.send(HandlerRx::EngineMsg(EngineMsg::PositionSet)).unwrap();
-            return 
-        }
-        for mv in moves {
-            let from_x = mv.chars().nth(0).unwrap() as u8 - 97;
-            let from_y = mv.chars().nth(1).unwrap() as u8 - 49;
-            let to_x = mv.chars().nth(2).unwrap() as u8 - 97;
-            let to_y = mv.chars().nth(3).unwrap() as u8 - 49;
-            let promotion = if mv.len() == 5 {
-                let piece = mv.chars().nth(4).unwrap();
-                let piece = PieceType::try_from(piece).unwrap();
-                Some(piece)
-            } else {
-                None
-            };
-            let piece = self.game.board.get_piece(from_x as usize, from_y as usize).unwrap();
-            let mv: Move = Move::new(from_x as usize, from_y as usize, to_x as usize, to_y as usize, piece.get_type().clone(), promotion);
-            self.game.make_move(mv).unwrap();
+        if let Some(moves) = moves.trim().strip_prefix("moves") {
+            if let Err(e) = self.game.apply_uci_moves(moves) {
+                log::error!("Ignoring the moves from {}: {}", e.index + 1, e);
+            }
         }
-        self.tx.send(HandlerRx::EngineMsg(EngineMsg::PositionSet)).unwrap();
+        self.log_game(None);
+        self.send(EngineMsg::PositionSet);
     }
 
-    fn handle_start_search(&mut self) {
+    /// Search the current position until a limit is hit or the handler raises the stop flag.
+    /// The best move is played on the engine's board since the handler only forwards the opponent's replies.
+    fn handle_start_search(&mut self, limits: SearchLimits) {
+        log::trace!("Received start search command with limits {:?}", limits);
         self.state = UciEngineState::Running;
-        let mv = self.game.engine_move();
-        self.tx.send(HandlerRx::EngineMsg(EngineMsg::CurrentBestMove(mv.extended_algebraic()))).unwrap();
-        self.state = UciEngineState::Idle; 
+        if self.debug {
+            self.send(EngineMsg::Info(format!("string static eval {}", self.game.static_eval())));
+        }
+        let tx = self.tx.clone();
+        let mut searcher = Searcher::new_with_table(limits, Arc::clone(&self.stop), std::mem::take(&mut self.table))
+            .with_observer(move |report| {
+                let _ = tx.send(HandlerRx::EngineMsg(EngineMsg::Info(depth_info(report))));
+            });
+        let result = searcher.search(&self.game.board);
+        log::debug!("Search finished at depth {} with score {} after {} nodes", result.depth, result.score, result.nodes);
+        if self.debug {
+            self.send(EngineMsg::Info(format!("string {}", result.stats)));
+        }
+        self.table = searcher.take_transposition_table();
+        let best_move = match result.best_move {
+            Some(mv) => {
+                let notation = mv.extended_algebraic();
+                self.game.make_move(mv).expect("search returns legal moves");
+                self.log_game(Some(result.elapsed));
+                notation
+            }
+            None => "0000".to_string(), // no legal moves
+        };
+        self.send(EngineMsg::FinalBestMove(best_move));
+        self.state = UciEngineState::Idle;
     }
 
     fn handle_stop_search(&mut self) {
@@ -93,35 +154,72 @@ impl UciEngine {
         self.state = UciEngineState::Idle;
     }
 
-    fn handle_start_search_time(&mut self, time: u64) {
-        log::trace!("Received start search time: {} command", time);
-        self.state = UciEngineState::Running;
-        let mv = self.game.engine_move();
-        // delay for time ms 
-        std::thread::sleep(std::time::Duration::from_millis(time));
-        self.tx.send(HandlerRx::EngineMsg(EngineMsg::FinalBestMove(mv.extended_algebraic()))).unwrap();
-        self.state = UciEngineState::Idle;
+    /// Write the transposition table to a file, reporting the outcome as an info string
+    fn handle_save_hash(&mut self, path: String) {
+        let info = match self.table.save(&path) {
+            Ok(entries) => format!("string saved {} hash entries to {}", entries, path),
+            Err(e) => format!("string could not save hash to {}: {}", path, e),
+        };
+        self.send(EngineMsg::Info(info));
+    }
+
+    /// Add the entries of a saved hash file to the transposition table, reporting the outcome as an info string
+    fn handle_load_hash(&mut self, path: String) {
+        let info = match self.table.load(&path) {
+            Ok(entries) => format!("string loaded {} hash entries from {}", entries, path),
+            Err(e) => format!("string could not load hash from {}: {}", path, e),
+        };
+        self.send(EngineMsg::Info(info));
+    }
+
+    /// Resize the transposition table, cutting the size down to the memory cap with an info string if it is over
+    fn handle_set_hash(&mut self, megabytes: usize) {
+        let allowed = self.config.clamp_hash_mb(megabytes);
+        if allowed < megabytes {
+            let info = format!("string Hash {} MB is over the memory cap, using {} MB", megabytes, allowed);
+            self.send(EngineMsg::Info(info));
+        }
+        self.config.hash_mb = allowed;
+        self.table.resize(allowed);
     }
 
     fn handle_make_move(&mut self, mv: String) {
-        let mv = mv.trim();
-        log::trace!("Engine translating move: {}", mv);
-        let from_x = mv.chars().nth(0).unwrap() as u8 - 97;
-        let from_y = mv.chars().nth(1).unwrap() as u8 - 49;
-        let to_x = mv.chars().nth(2).unwrap() as u8 - 97;
-        let to_y = mv.chars().nth(3).unwrap() as u8 - 49;
-        log::debug!("Engine translated move to: ({}{}) ({}{})", from_x, from_y, to_x, to_y);
-        let promotion = if mv.len() == 5 {
-            let piece = mv.chars().nth(4).unwrap().to_ascii_uppercase();
-            let piece = PieceType::try_from(piece).unwrap();
-            Some(piece)
-        } else {
-            None
+        log::trace!("Engine making move: {}", mv);
+        if let Err(e) = self.game.try_move_uci(&mv) {
+            log::error!("Ignoring move {}: {}", mv.trim(), e);
+        }
+        self.log_game(None);
+        self.send(EngineMsg::PositionSet);
+    }
+
+    /// Bring the PGN file of the game up to date when SavePGN is set, reporting a file that can not be written
+    fn log_game(&mut self, think: Option<Duration>) {
+        let Some(log) = self.pgn_log.as_mut() else {
+            return;
         };
-        let piece = self.game.board.get_piece(from_x as usize, from_y as usize).unwrap();
-        let mv: Move = Move::new(from_x as usize, from_y as usize, to_x as usize, to_y as usize, piece.get_type().clone(), promotion);
-        log::debug!("Engine is making move: {}", mv.extended_algebraic());
-        self.game.make_move(mv).unwrap();
-        self.tx.send(HandlerRx::EngineMsg(EngineMsg::PositionSet)).unwrap();
+        if let Err(e) = log.update(self.game.initial_fen.as_deref(), &self.game.moves(), think) {
+            let path = log.path().map(|path| path.display().to_string()).unwrap_or_default();
+            self.send(EngineMsg::Info(format!("string could not save PGN to {}: {}", path, e)));
+        }
     }
 }
+
+/// The UCI info line for a completed iteration, e.g. "depth 5 score cp 31 nodes 81234 time 412 pv e2e4"
+pub(crate) fn depth_info(report: &DepthReport) -> String {
+    let plies_to_mate = MATE_SCORE - report.score.abs();
+    let score = if plies_to_mate <= MAX_DEPTH as i32 {
+        // Mate distances are given in moves, negative when the engine is being mated
+        let moves = (plies_to_mate + 1) / 2;
+        format!("mate {}", if report.score > 0 { moves } else { -moves })
+    } else {
+        format!("cp {}", report.score)
+    };
+    format!(
+        "depth {} score {} nodes {} time {} pv {}",
+        report.depth,
+        score,
+        report.nodes,
+        report.elapsed.as_millis(),
+        report.best_move.extended_algebraic(),
+    )
+}
@@ -1,14 +1,19 @@
+use std::io::BufRead;
+
 use super::{uci_commands::UciGuiToEngine, uci_messages::HandlerRx};
 
 
+/// Reads GUI commands line by line and forwards them to the handler, skipping blank lines.
 pub struct UciInput {
     tx: std::sync::mpsc::Sender<HandlerRx>,
+    reader: Box<dyn BufRead + Send>,
 }
 
 impl UciInput {
-    pub fn new(tx: std::sync::mpsc::Sender<HandlerRx>) -> UciInput {
+    pub fn new(tx: std::sync::mpsc::Sender<HandlerRx>, reader: Box<dyn BufRead + Send>) -> UciInput {
         UciInput {
             tx,
+            reader,
         }
     }
 
@@ -16,15 +21,31 @@ impl UciInput {
         std::thread::spawn(move || self.main_loop())
     }
 
+    /// Forwards commands until "quit" or the input closes, which is treated as "quit".
+    /// Stops without a word if the handler has gone away.
     fn main_loop(&mut self) {
         loop {
             let mut input = String::new();
-            std::io::stdin().read_line(&mut input).unwrap();
-            let input = input.trim();
-            let command = UciGuiToEngine::from_string(input);
+            match self.reader.read_line(&mut input) {
+                Ok(0) => break,
+                Ok(_) => {}
+                Err(e) => {
+                    log::error!("Could not read UCI input: {}", e);
+                    break;
+                }
+            }
+            let command = UciGuiToEngine::from_string(input.trim());
             if let Some(command) = command {
-                self.tx.send(HandlerRx::GuiMsg(command)).unwrap();
+                let quit = command == UciGuiToEngine::Quit;
+                if self.tx.send(HandlerRx::GuiMsg(command)).is_err() {
+                    log::error!("UCI handler disconnected, stopping the input thread");
+                    return;
+                }
+                if quit {
+                    return;
+                }
             }
         }
+        let _ = self.tx.send(HandlerRx::GuiMsg(UciGuiToEngine::Quit));
     }
 }
@@ -1,4 +1,11 @@
-use super::{uci_commands::{UciEngineToGui, UciGuiToEngine}, uci_engine::UciEngine, uci_input::UciInput, uci_messages::{EngineMsg, HandlerRx, HandlerTx}};
+use std::{collections::VecDeque, io::{BufRead, BufReader, Write}, sync::{atomic::{AtomicBool, Ordering}, mpsc::RecvTimeoutError, Arc}, thread::JoinHandle, time::Duration};
+
+use crate::search::{engine_config::EngineConfig, search_limits::SearchLimits};
+
+use super::{uci_commands::{UciEngineToGui, UciGuiToEngine}, uci_engine::UciEngine, uci_input::UciInput, uci_messages::{EngineMsg, HandlerRx, HandlerTx}, uci_options::UciOptions};
+
+/// How long the handler waits for a message before checking the engine thread is still alive
+const ENGINE_CHECK_INTERVAL: Duration = Duration::from_millis(100);
 
 #[derive(Debug, PartialEq)]
 enum UciHandlerState {
@@ -10,24 +17,131 @@ enum UciHandlerState {
     SettingPositionGo(String),
 }
 
+/// A command the GUI should not send in the handler's current state.
+struct ProtocolRule {
+    /// The command's first word, see UciGuiToEngine::name
+    command: &'static str,
+    /// True when the command breaks the protocol
+    applies: fn(&UciHandler) -> bool,
+    warning: &'static str,
+    /// Whether the command is turned away, otherwise it is handled as usual after the warning
+    ignored: bool,
+}
+
+/// The commands the handler turns away, or tolerates but would warn about, and in which states.
+/// Checked before every command, and a command an ignored rule applies to never reaches its handler,
+/// so the handlers only deal with the states left over and StrictProtocol warns about exactly what the handler does.
+static PROTOCOL_RULES: [ProtocolRule; 7] = [
+    ProtocolRule {
+        command: "position",
+        applies: |handler| handler.state == UciHandlerState::New,
+        warning: "position before uci",
+        ignored: true,
+    },
+    ProtocolRule {
+        command: "position",
+        applies: |handler| handler.search_pending(),
+        warning: "position during a search, send stop and wait for bestmove first",
+        ignored: true,
+    },
+    ProtocolRule {
+        command: "position",
+        applies: |handler| handler.state == UciHandlerState::SettingPosition,
+        warning: "position before the previous position was set",
+        ignored: true,
+    },
+    ProtocolRule {
+        command: "go",
+        applies: |handler| matches!(handler.state, UciHandlerState::New | UciHandlerState::Ready),
+        warning: "go before position",
+        ignored: true,
+    },
+    ProtocolRule {
+        command: "go",
+        applies: |handler| handler.search_pending(),
+        warning: "go during a search, send stop and wait for bestmove first",
+        ignored: true,
+    },
+    ProtocolRule {
+        command: "setoption",
+        applies: |handler| handler.search_pending(),
+        warning: "setoption during a search, send stop and wait for bestmove first",
+        ignored: true,
+    },
+    ProtocolRule {
+        command: "isready",
+        applies: |handler| handler.last_command == Some("isready"),
+        warning: "isready repeated with no command in between",
+        ignored: false,
+    },
+];
+
+/// How far a "ucinewgame" that arrived during a search has got
+#[derive(Debug, PartialEq)]
+enum PendingNewGame {
+    Stopping, // Waiting for the search's bestmove
+    Resetting, // Waiting for the engine to start the new game
+}
+
 pub struct UciHandler {
     pub name: String,
     pub author: String,
     state: UciHandlerState,
     tx: std::sync::mpsc::Sender<HandlerTx>,
     rx: std::sync::mpsc::Receiver<HandlerRx>,
-    _engine_handle: std::thread::JoinHandle<()>,
-    _input_handle: std::thread::JoinHandle<()>,
-    current_best_move: Option<String>,
+    // Taken and joined when run returns
+    engine_handle: Option<JoinHandle<()>>,
+    input_handle: Option<JoinHandle<()>>,
+    stop: Arc<AtomicBool>,
+    // The protocol stream, nothing else may be written to it
+    output: Box<dyn Write + Send>,
+    quitting: bool,
+    // The options and their current values, the only place the engine's settings are read from
+    options: UciOptions,
+    // Memory reports asked of the engine and not yet received, answered before quitting
+    pending_reports: usize,
+    // Set by quit, after which the input thread stops reading
+    input_closed: bool,
+    // A new game asked for mid-search, the commands after it wait in deferred until it has started
+    new_game: Option<PendingNewGame>,
+    deferred: VecDeque<UciGuiToEngine>,
+    // The name of the last GUI command handled, to spot repeated isready
+    last_command: Option<&'static str>,
 }
 
 impl UciHandler {
+    /// A handler speaking UCI over stdin and stdout
     pub fn new(name: String, author: String) -> UciHandler {
+        UciHandler::with_io(name, author, BufReader::new(std::io::stdin()), std::io::stdout())
+    }
+
+    /// A handler reading GUI commands from input and writing its replies to output.
+    /// # Description
+    /// Only protocol lines are written to output, logging goes through the log crate.
+    /// # Example
+    /// ``` Rust
+    /// let script = std::io::Cursor::new("uci\nisready\nquit\n");
+    /// UciHandler::with_io("Casey".to_string(), "JKDow".to_string(), script, std::io::sink()).run();
+    /// ```
+    pub fn with_io(name: String, author: String, input: impl BufRead + Send + 'static, output: impl Write + Send + 'static) -> UciHandler {
+        UciHandler::with_config(name, author, input, output, EngineConfig::default())
+    }
+
+    /// A handler whose engine uses the hash size and memory cap of config.
+    /// # Description
+    /// "setoption name Hash" can resize the transposition table later but never beyond config.max_memory_mb.
+    /// # Example
+    /// ``` Rust
+    /// let config = EngineConfig { max_memory_mb: Some(64), ..Default::default() };
+    /// UciHandler::with_config("Casey".to_string(), "JKDow".to_string(), BufReader::new(std::io::stdin()), std::io::stdout(), config).run();
+    /// ```
+    pub fn with_config(name: String, author: String, input: impl BufRead + Send + 'static, output: impl Write + Send + 'static, config: EngineConfig) -> UciHandler {
         let (handler_tx, engine_rx) = std::sync::mpsc::channel();
         let (engine_tx, handler_rx) = std::sync::mpsc::channel();
-        let engine = UciEngine::new(engine_rx, engine_tx.clone());
+        let stop = Arc::new(AtomicBool::new(false));
+        let engine = UciEngine::new(engine_rx, engine_tx.clone(), Arc::clone(&stop), config.clone());
         let engine_handle = engine.run_thread();
-        let input_handler = UciInput::new(engine_tx);
+        let input_handler = UciInput::new(engine_tx, Box::new(input));
         let input_handle = input_handler.run_thread();
         UciHandler {
             name,
@@ -35,15 +149,39 @@ impl UciHandler {
             state: UciHandlerState::New,
             tx: handler_tx,
             rx: handler_rx,
-            _engine_handle: engine_handle,
-            _input_handle: input_handle,
-            current_best_move: None,
+            engine_handle: Some(engine_handle),
+            input_handle: Some(input_handle),
+            stop,
+            output: Box::new(output),
+            quitting: false,
+            options: UciOptions::new(&config),
+            pending_reports: 0,
+            input_closed: false,
+            new_game: None,
+            deferred: VecDeque::new(),
+            last_command: None,
         }
     }
 
+    /// Handle commands until "quit" or the end of the input.
+    /// A search still running or waiting on its position at that point is stopped and its bestmove sent before returning,
+    /// as is the engine's part of an options listing and a new game asked for during the search.
+    /// # Description
+    /// If the engine thread dies or stops listening the handler quits as well, without the answers it was waiting for.
+    /// The engine and input threads are joined before returning, except an input thread still blocked reading
+    /// after the engine died, which is left to end with the process.
     pub fn run(&mut self) {
-        loop {
-            let message = self.rx.recv().unwrap();
+        while !self.quitting || self.search_pending() || self.pending_reports > 0 || self.new_game.is_some() {
+            let message = match self.rx.recv_timeout(ENGINE_CHECK_INTERVAL) {
+                Ok(message) => message,
+                Err(RecvTimeoutError::Timeout) => {
+                    if self.engine_handle.as_ref().is_some_and(|handle| handle.is_finished()) {
+                        self.engine_lost();
+                    }
+                    continue;
+                }
+                Err(RecvTimeoutError::Disconnected) => break,
+            };
             log::trace!("Received message: {:?}", message);
             log::trace!("Current state: {:?}", self.state);
             match message {
@@ -51,53 +189,125 @@ impl UciHandler {
                 HandlerRx::GuiMsg(input) => self.handle_input(input),
             }
         }
+        self.join_threads();
+    }
+
+    /// Tell the engine thread to exit and wait for it, and for the input thread once it has stopped reading
+    fn join_threads(&mut self) {
+        let _ = self.tx.send(HandlerTx::Quit);
+        if let Some(handle) = self.engine_handle.take() {
+            if handle.join().is_err() {
+                log::error!("UCI engine thread panicked");
+            }
+        }
+        // The input thread ends after forwarding quit, which it also does at the end of the input
+        if let Some(handle) = self.input_handle.take_if(|handle| handle.is_finished() || self.input_closed) {
+            if handle.join().is_err() {
+                log::error!("UCI input thread panicked");
+            }
+        }
+    }
+
+    /// Send a message to the engine thread, quitting if it has gone away
+    fn send_engine(&mut self, message: HandlerTx) {
+        if self.tx.send(message).is_err() {
+            self.engine_lost();
+        }
+    }
+
+    /// The engine thread has died, so nothing it was asked for will be answered
+    fn engine_lost(&mut self) {
+        log::error!("UCI engine thread stopped, quitting");
+        self.quitting = true;
+        self.pending_reports = 0;
+        self.new_game = None;
+        self.state = UciHandlerState::Idle;
     }
     
+    /// True while a go command has not been answered with a bestmove
+    fn search_pending(&self) -> bool {
+        matches!(self.state, UciHandlerState::Thinking | UciHandlerState::SettingPositionGo(_))
+    }
+
     fn handle_engine_message(&mut self, message: EngineMsg) {
         log::debug!("Received engine message: {:?}", message);
         match message {
             EngineMsg::PositionSet => {
                 if let UciHandlerState::SettingPositionGo(options) = &self.state {
-                    let options = options.split_whitespace().collect::<Vec<&str>>();
-                    if options.contains(&"infinite") {
-                        self.tx.send(HandlerTx::StartSearch).unwrap();
-                        self.state = UciHandlerState::Thinking;
-                        return;
-                    }
-                    if options.contains(&"movetime") {
-                        log::debug!("Got movetime option");
-                        let idx = options.iter().position(|&x| x == "movetime").unwrap();
-                        let time = options[idx + 1].parse::<u64>().unwrap();
-                        self.tx.send(HandlerTx::StartSearchTime(time)).unwrap();
-                        self.state = UciHandlerState::Thinking;
-                        return;
-                    }
-                    self.tx.send(HandlerTx::StartSearch).unwrap();
-                    self.state = UciHandlerState::Thinking;
+                    let options = options.clone();
+                    self.start_search(&options);
                 } else {
                     self.state = UciHandlerState::Idle;
                 }
             },
-            EngineMsg::CurrentBestMove(mv) => {
-                self.current_best_move = Some(mv);
+            EngineMsg::Info(info) => self.send_command(UciEngineToGui::info(&info)),
+            EngineMsg::Memory(report) => {
+                self.pending_reports -= 1;
+                self.send_command(UciEngineToGui::info(&format!("string memory {}", report)));
             },
             EngineMsg::FinalBestMove(mv) => {
                 self.send_command(UciEngineToGui::best_move(&mv));
                 self.state = UciHandlerState::Idle;
+                if self.new_game == Some(PendingNewGame::Stopping) {
+                    self.new_game = Some(PendingNewGame::Resetting);
+                    self.start_new_game();
+                }
+            },
+            EngineMsg::NewGameStarted => {
+                if self.new_game == Some(PendingNewGame::Resetting) {
+                    self.new_game = None;
+                    self.replay_deferred();
+                }
             },
         }
     }
 
     fn handle_input(&mut self, input: UciGuiToEngine) {
         log::debug!("Received input: {:?}", input);
+        // Quit is never held back, the search is already stopping and the new game still starts before run returns
+        if self.new_game.is_some() && input != UciGuiToEngine::Quit {
+            log::debug!("Deferring {:?} until the new game has started", input);
+            self.deferred.push_back(input);
+            return;
+        }
+        let turned_away = self.check_protocol(&input);
+        self.last_command = Some(input.name());
+        if turned_away {
+            return;
+        }
         match input {
             UciGuiToEngine::Uci => self.command_uci(),
+            UciGuiToEngine::Debug(mode) => self.command_debug(&mode),
             UciGuiToEngine::IsReady => self.command_isready(),
+            UciGuiToEngine::UciNewGame => self.command_ucinewgame(),
+            UciGuiToEngine::SetOption(option) => self.command_setoption(&option),
             UciGuiToEngine::Position(pos) => self.command_position(&pos),
             UciGuiToEngine::Go(options) => self.command_go(&options),
             UciGuiToEngine::Stop => self.command_stop(),
             UciGuiToEngine::Quit => self.command_quit(),
-            _ => {},
+            UciGuiToEngine::Options => self.command_options(),
+            UciGuiToEngine::Unknown(line) => self.protocol_violation(&format!("unknown command '{}', ignored", line)),
+            UciGuiToEngine::PonderHit => {},
+        }
+    }
+
+    /// Report every PROTOCOL_RULES entry the command breaks, returning true if one of them turns it away
+    fn check_protocol(&mut self, input: &UciGuiToEngine) -> bool {
+        let mut turned_away = false;
+        let broken = PROTOCOL_RULES.iter().filter(|rule| rule.command == input.name() && (rule.applies)(self)).collect::<Vec<_>>();
+        for rule in broken {
+            let action = if rule.ignored { ", ignored" } else { "" };
+            self.protocol_violation(&format!("{}{}", rule.warning, action));
+            turned_away |= rule.ignored;
+        }
+        turned_away
+    }
+
+    /// Logged, and with StrictProtocol also sent to the GUI as e.g. "info string protocol: go before position, ignored"
+    fn protocol_violation(&mut self, warning: &str) {
+        log::warn!("Protocol violation: {}", warning);
+        if self.options.strict_protocol() {
+            self.send_command(UciEngineToGui::info(&format!("string protocol: {}", warning)));
         }
     }
 
@@ -108,84 +318,190 @@ impl UciHandler {
         }
         self.send_command(UciEngineToGui::id_name(&self.name));
         self.send_command(UciEngineToGui::id_author(&self.author));
+        let declarations = self.options.iter().map(|option| option.declaration()).collect::<Vec<_>>();
+        for declaration in declarations {
+            self.send_command(UciEngineToGui::option(&declaration));
+        }
         self.send_command(UciEngineToGui::uci_ok());
         self.state = UciHandlerState::Ready;
     }
 
-    fn command_isready(&self) {
-        self.send_command(UciEngineToGui::ready_ok());
+    /// "debug on" makes the engine report the static evaluation as an info string before each search
+    /// and its search counters after it
+    fn command_debug(&mut self, mode: &str) {
+        match mode {
+            "on" => self.send_engine(HandlerTx::SetDebug(true)),
+            "off" => self.send_engine(HandlerTx::SetDebug(false)),
+            _ => log::warn!("Ignoring malformed debug command: {}", mode),
+        }
     }
 
-    fn command_position(&mut self, pos: &str) {
-        match self.state {
-            UciHandlerState::New => {}
-            UciHandlerState::Ready => {
-                let parts: Vec<&str> = pos.split_whitespace().collect(); 
-                if parts[0] == "startpos" {
-                    self.tx.send(HandlerTx::StartingPosition(parts[1..].join(" "))).unwrap();
-                } else if parts[0] == "fen" {
-                    self.tx.send(HandlerTx::NewFen(parts[1..].join(" "))).unwrap();
-                }
-                self.state = UciHandlerState::SettingPosition;
-            }
-            UciHandlerState::Idle => {
-                let parts: Vec<&str> = pos.trim().split_whitespace().collect(); 
-                let mv = parts.last().unwrap().to_string();
-                log::debug!("Got move {} from parts {:?}", mv, parts);
-                self.tx.send(HandlerTx::MakeMove(mv)).unwrap();
-                self.state = UciHandlerState::SettingPosition;
+    /// "setoption name Hash value <MB>" resizes the transposition table, clearing it.
+    /// "setoption name HashSave value <path>" saves the transposition table and "HashLoad" loads one.
+    /// "setoption name SavePGN value <directory>" writes each game played to a PGN file there, an empty value stops.
+    /// The value is stored in the option registry first and the engine is sent what was stored,
+    /// so a Hash outside the advertised range is clamped and reported with an info string.
+    /// "setoption name StrictProtocol value true" reports commands that break the protocol, see PROTOCOL_RULES.
+    /// All are refused while a search is running.
+    fn command_setoption(&mut self, option: &str) {
+        let Some((name, value)) = parse_setoption(option) else {
+            log::warn!("Ignoring malformed setoption command: {}", option);
+            return;
+        };
+        let stored = match self.options.set(&name, &value) {
+            Ok(stored) => stored.to_string(),
+            Err(e) => {
+                log::warn!("Ignoring setoption: {}", e);
+                return;
             }
-            UciHandlerState::Thinking => {}
-            UciHandlerState::SettingPosition => {}
-            UciHandlerState::SettingPositionGo(_) => {}
+        };
+        let name = self.options.get(&name).expect("the option was just set").name;
+        if stored != value {
+            self.send_command(UciEngineToGui::info(&format!("string {} {} is out of range, using {}", name, value, stored)));
+        }
+        match name {
+            "Hash" => self.send_engine(HandlerTx::SetHash(self.options.hash_mb())),
+            "HashSave" => self.send_engine(HandlerTx::SaveHash(stored)),
+            "HashLoad" => self.send_engine(HandlerTx::LoadHash(stored)),
+            "SavePGN" => self.send_engine(HandlerTx::SavePgn(stored)),
+            "StrictProtocol" => {}
+            _ => unreachable!("every registered option is handled"),
         }
     }
 
-    fn command_go(&mut self, options: &str) {
-        if self.state == UciHandlerState::SettingPosition {
-            self.state = UciHandlerState::SettingPositionGo(options.to_string());
-            return;
+    /// "options" lists every option with the value in use, e.g. "info string option Hash type spin value 64",
+    /// followed by the memory the engine's tables really hold, to check the two agree.
+    /// Not part of UCI, it is for checking the configuration from a console or a GUI's debug window.
+    fn command_options(&mut self) {
+        let lines = self.options.iter().map(|option| format!("string option {}", option.current())).collect::<Vec<_>>();
+        for line in lines {
+            self.send_command(UciEngineToGui::info(&line));
         }
-        if self.state != UciHandlerState::Idle {
+        self.pending_reports += 1;
+        self.send_engine(HandlerTx::ReportMemory);
+    }
+
+    fn command_isready(&mut self) {
+        self.send_command(UciEngineToGui::ready_ok());
+    }
+
+    /// The next position is taken in full rather than as one more move, and the engine starts a new game.
+    /// During a search the search is stopped first and answered with its bestmove, and the commands that follow,
+    /// isready included, are only handled once the engine has started the new game.
+    fn command_ucinewgame(&mut self) {
+        if self.search_pending() {
+            self.new_game = Some(PendingNewGame::Stopping);
+            self.stop.store(true, Ordering::Relaxed);
+            if self.state == UciHandlerState::Thinking {
+                self.send_engine(HandlerTx::StopSearch);
+            }
             return;
         }
-        let options = options.split_whitespace().collect::<Vec<&str>>();
-        if options.contains(&"infinite") {
-            self.tx.send(HandlerTx::StartSearch).unwrap();
-            self.state = UciHandlerState::Thinking;
+        self.start_new_game();
+    }
+
+    fn start_new_game(&mut self) {
+        if self.state == UciHandlerState::Idle {
+            self.state = UciHandlerState::Ready;
+        }
+        self.send_engine(HandlerTx::NewGame);
+    }
+
+    /// Handle the commands held back by a new game in order, until one of them starts another search and new game
+    fn replay_deferred(&mut self) {
+        while self.new_game.is_none() {
+            let Some(input) = self.deferred.pop_front() else {
+                return;
+            };
+            self.handle_input(input);
+        }
+    }
+
+    /// The first position is sent to the engine in full, later ones only add their last move to the engine's game.
+    /// A position without moves, such as a fresh startpos, is always taken in full as it starts a new game.
+    /// PROTOCOL_RULES turns position away in every state but Ready and Idle.
+    fn command_position(&mut self, pos: &str) {
+        if self.state != UciHandlerState::Idle || !pos.split_whitespace().any(|part| part == "moves") {
+            self.send_full_position(pos);
             return;
         }
-        if options.contains(&"movetime") {
-            log::debug!("Got movetime option");
-            let idx = options.iter().position(|&x| x == "movetime").unwrap();
-            let time = options[idx + 1].parse::<u64>().unwrap();
-            self.state = UciHandlerState::Thinking;
-            self.tx.send(HandlerTx::StartSearchTime(time)).unwrap();
+        let parts: Vec<&str> = pos.split_whitespace().collect(); 
+        let Some(mv) = parts.last().map(|mv| mv.to_string()) else {
+            log::warn!("Ignoring empty position command");
             return;
+        };
+        log::debug!("Got move {} from parts {:?}", mv, parts);
+        self.send_engine(HandlerTx::MakeMove(mv));
+        self.state = UciHandlerState::SettingPosition;
+    }
+
+    fn send_full_position(&mut self, pos: &str) {
+        let parts: Vec<&str> = pos.split_whitespace().collect(); 
+        match parts.first() {
+            Some(&"startpos") => self.send_engine(HandlerTx::StartingPosition(parts[1..].join(" "))),
+            Some(&"fen") => self.send_engine(HandlerTx::NewFen(parts[1..].join(" "))),
+            _ => {
+                log::warn!("Ignoring malformed position command: {}", pos);
+                return;
+            }
+        }
+        self.state = UciHandlerState::SettingPosition;
+    }
+
+    /// PROTOCOL_RULES turns go away in every state but Idle and SettingPosition,
+    /// where it waits for the position to be set.
+    fn command_go(&mut self, options: &str) {
+        if self.state == UciHandlerState::SettingPosition {
+            self.state = UciHandlerState::SettingPositionGo(options.to_string());
+        } else {
+            self.start_search(options);
         }
-        self.tx.send(HandlerTx::StartSearch).unwrap();
+    }
+
+    /// Parse the go parameters and hand the search to the engine thread
+    fn start_search(&mut self, options: &str) {
+        let limits = SearchLimits::from_go(options);
+        log::debug!("Starting search with limits {:?}", limits);
+        // A go that was waiting on its position when quit or ucinewgame arrived still answers, but straight away
+        self.stop.store(self.quitting || self.new_game.is_some(), Ordering::Relaxed);
+        self.send_engine(HandlerTx::StartSearch(limits));
         self.state = UciHandlerState::Thinking;
     }
 
+    /// Raise the stop flag, the bestmove is sent once the engine reports its final move
     fn command_stop(&mut self) {
         if self.state != UciHandlerState::Thinking {
             return;
         }
-        let mv = match self.current_best_move.take() {
-            Some(mv) => mv,
-            None => "0000".to_string(), // this is an invalid move
-        };
-        self.send_command(UciEngineToGui::best_move(&mv));
-        self.tx.send(HandlerTx::StopSearch).unwrap();
-        self.state = UciHandlerState::Idle;
+        self.stop.store(true, Ordering::Relaxed);
+        self.send_engine(HandlerTx::StopSearch);
     }
 
-    fn command_quit(&self) {
-        std::process::exit(0);
+    fn command_quit(&mut self) {
+        self.quitting = true;
+        self.input_closed = true;
+        self.stop.store(true, Ordering::Relaxed);
     }
 
-    fn send_command(&self, command: UciEngineToGui) {
+    fn send_command(&mut self, command: UciEngineToGui) {
         log::debug!("Sending command: {}", command);
-        println!("{}", command);
+        if let Err(e) = writeln!(self.output, "{}", command).and_then(|_| self.output.flush()) {
+            log::error!("Could not write UCI output: {}", e);
+        }
+    }
+}
+
+/// Splits the arguments of a setoption command, "name <id> value <x>", into the id and the value.
+/// Either may contain spaces, a missing value gives an empty string.
+pub(crate) fn parse_setoption(option: &str) -> Option<(String, String)> {
+    let rest = option.trim().strip_prefix("name ")?;
+    let (name, value) = match rest.split_once(" value ") {
+        Some((name, value)) => (name, value),
+        None => (rest, ""),
+    };
+    let name = name.trim();
+    if name.is_empty() {
+        return None;
     }
+    Some((name.to_string(), value.trim().to_string()))
 }
@@ -1,3 +1,5 @@
+use crate::search::{memory_report::MemoryReport, search_limits::SearchLimits};
+
 use super::uci_commands::UciGuiToEngine;
 
 
@@ -5,10 +7,22 @@ use super::uci_commands::UciGuiToEngine;
 pub enum HandlerTx {
     NewFen(String),
     StartingPosition(String),
-    StartSearch,
+    StartSearch(SearchLimits),
     StopSearch,
     MakeMove(String),
-    StartSearchTime(u64),
+    SetDebug(bool),
+    SaveHash(String),
+    LoadHash(String),
+    /// Resize the transposition table to this many megabytes, within the memory cap
+    SetHash(usize),
+    /// Reply with the memory the search tables hold
+    ReportMemory,
+    /// Exit the engine thread
+    Quit,
+    /// The GUI sent "ucinewgame", the next position starts a new game
+    NewGame,
+    /// Write the games played as PGN files into this directory, an empty path stops
+    SavePgn(String),
 }
 
 #[derive(Debug, PartialEq)]
@@ -20,6 +34,9 @@ pub enum HandlerRx {
 #[derive(Debug, PartialEq)]
 pub enum EngineMsg {
     PositionSet,
-    CurrentBestMove(String),
     FinalBestMove(String),
+    Info(String),
+    Memory(MemoryReport),
+    /// The engine has handled "ucinewgame"
+    NewGameStarted,
 }
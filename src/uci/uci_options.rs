@@ -0,0 +1,137 @@
+use crate::{errors::uci_option_error::UciOptionError, search::engine_config::EngineConfig};
+
+/// The largest Hash offered when there is no memory cap
+pub const MAX_HASH_MB: usize = 65536;
+
+/// The values a UCI option takes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UciOptionType {
+    /// A whole number, values outside min..=max are clamped into range
+    Spin { min: usize, max: usize },
+    /// Any text, e.g. a file path
+    String,
+    /// On or off, "true" or "false"
+    Check,
+}
+
+/// One option the engine offers, with the value it is using now.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UciOption {
+    pub name: &'static str,
+    pub option_type: UciOptionType,
+    pub default: String,
+    pub value: String,
+}
+
+impl UciOption {
+    fn spin(name: &'static str, default: usize, min: usize, max: usize) -> UciOption {
+        UciOption { name, option_type: UciOptionType::Spin { min, max }, default: default.to_string(), value: default.to_string() }
+    }
+
+    fn string(name: &'static str) -> UciOption {
+        UciOption { name, option_type: UciOptionType::String, default: String::new(), value: String::new() }
+    }
+
+    fn check(name: &'static str, default: bool) -> UciOption {
+        UciOption { name, option_type: UciOptionType::Check, default: default.to_string(), value: default.to_string() }
+    }
+
+    /// The option as sent in reply to "uci", e.g. "name Hash type spin default 16 min 0 max 65536".
+    /// The spec asks for the default here, an empty string default is written "<empty>".
+    pub fn declaration(&self) -> String {
+        match self.option_type {
+            UciOptionType::Spin { min, max } => format!("name {} type spin default {} min {} max {}", self.name, self.default, min, max),
+            UciOptionType::String => format!("name {} type string default {}", self.name, empty_as_placeholder(&self.default)),
+            UciOptionType::Check => format!("name {} type check default {}", self.name, self.default),
+        }
+    }
+
+    /// The option and the value in use, as listed by the "options" command, e.g. "Hash type spin value 64"
+    pub fn current(&self) -> String {
+        let option_type = match self.option_type {
+            UciOptionType::Spin { .. } => "spin",
+            UciOptionType::String => "string",
+            UciOptionType::Check => "check",
+        };
+        format!("{} type {} value {}", self.name, option_type, empty_as_placeholder(&self.value))
+    }
+}
+
+fn empty_as_placeholder(value: &str) -> &str {
+    if value.is_empty() { "<empty>" } else { value }
+}
+
+/// Every option the UCI engine offers and its current value.
+/// # Description
+/// The handler advertises these in reply to "uci", updates them on "setoption" and lists them on "options",
+/// and the values it passes to the engine thread are read back from here,
+/// so what a GUI is shown is always what the engine uses.
+/// Names are matched without regard to case, as the UCI spec asks.
+/// # Example
+/// ``` Rust
+/// let mut options = UciOptions::new(&EngineConfig { max_memory_mb: Some(64), ..Default::default() });
+/// assert_eq!(options.set("hash", "128").unwrap(), "64");
+/// assert_eq!(options.hash_mb(), 64);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UciOptions {
+    options: Vec<UciOption>,
+}
+
+impl UciOptions {
+    /// The options with the defaults of config, Hash is offered up to its memory cap
+    pub fn new(config: &EngineConfig) -> UciOptions {
+        let max_hash = config.max_memory_mb.unwrap_or(MAX_HASH_MB);
+        UciOptions {
+            options: vec![
+                UciOption::spin("Hash", config.hash_budget_mb(), 0, max_hash),
+                UciOption::string("HashSave"),
+                UciOption::string("HashLoad"),
+                UciOption::string("SavePGN"),
+                UciOption::check("StrictProtocol", false),
+            ],
+        }
+    }
+
+    /// The options in the order they are advertised
+    pub fn iter(&self) -> impl Iterator<Item = &UciOption> {
+        self.options.iter()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&UciOption> {
+        self.options.iter().find(|option| option.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Change an option's value.
+    /// # Inputs/Outputs
+    /// - Input: name - The option name in any case
+    /// - Input: value - The value as given to setoption
+    /// - Output: The value stored, which for a spin may have been clamped into range, or why nothing changed
+    pub fn set(&mut self, name: &str, value: &str) -> Result<&str, UciOptionError> {
+        let option = self.options.iter_mut()
+            .find(|option| option.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| UciOptionError::UnknownOption(name.to_string()))?;
+        option.value = match option.option_type {
+            UciOptionType::Spin { min, max } => {
+                let number = value.parse::<usize>().map_err(|_| UciOptionError::NotANumber { name: option.name.to_string(), value: value.to_string() })?;
+                number.clamp(min, max).to_string()
+            }
+            UciOptionType::String => value.to_string(),
+            UciOptionType::Check => match value.to_ascii_lowercase().as_str() {
+                "true" | "false" => value.to_ascii_lowercase(),
+                _ => return Err(UciOptionError::NotABoolean { name: option.name.to_string(), value: value.to_string() }),
+            },
+        };
+        Ok(&option.value)
+    }
+
+    /// The transposition table size in megabytes
+    pub fn hash_mb(&self) -> usize {
+        self.get("Hash").and_then(|option| option.value.parse().ok()).expect("spin options always hold a number")
+    }
+
+    /// Whether the handler reports the GUI's protocol violations as info strings
+    pub fn strict_protocol(&self) -> bool {
+        self.get("StrictProtocol").is_some_and(|option| option.value == "true")
+    }
+}
@@ -0,0 +1,156 @@
+use std::{io, path::PathBuf, time::{Duration, SystemTime, UNIX_EPOCH}};
+
+use crate::{castling_validation::CastlingValidation, chess_move::Move, color::Color, errors::fen_error::FenError, game::Game, utils::notation::move_to_san};
+
+
+/// A move of a logged game, with the time the engine spent on it when it was the engine's own move
+#[derive(Debug, Clone)]
+struct LoggedMove {
+    mv: Move,
+    think: Option<Duration>,
+}
+
+/// The game being written to one file
+#[derive(Debug)]
+struct LoggedGame {
+    path: PathBuf,
+    date: String,
+    round: usize,
+    initial_fen: Option<String>,
+    moves: Vec<LoggedMove>,
+}
+
+/// Writes the games the engine plays over UCI as PGN files, one per game, for the "SavePGN" option.
+/// # Description
+/// A game starts with the first position after "ucinewgame", or with a position that does not continue the game
+/// being logged, such as a fresh startpos with no moves. The file is rewritten after every change, so it is complete
+/// whenever the GUI stops. The engine's own moves carry their think time as a %emt comment and the result is
+/// written once the position is mate or a draw, "*" until then.
+/// Files are named by when the game started and its number in the session, e.g.
+/// "20261015-142301-1-unknown-vs-unknown.pgn", as UCI never tells the engine who it is playing.
+#[derive(Debug)]
+pub struct PgnLog {
+    directory: PathBuf,
+    games: usize,
+    current: Option<LoggedGame>,
+}
+
+impl PgnLog {
+    /// A log writing into directory, which is created when the first game is written
+    pub fn new(directory: impl Into<PathBuf>) -> PgnLog {
+        PgnLog {
+            directory: directory.into(),
+            games: 0,
+            current: None,
+        }
+    }
+
+    /// Ends the game being logged, the next position starts a new file
+    pub fn new_game(&mut self) {
+        self.current = None;
+    }
+
+    /// The file of the game being logged, None before the first position
+    pub fn path(&self) -> Option<&PathBuf> {
+        self.current.as_ref().map(|game| &game.path)
+    }
+
+    /// Records the position the engine is playing from and rewrites the game's file.
+    /// # Inputs/Outputs
+    /// - Input: initial_fen - The position the game was set up from, None for the standard start
+    /// - Input: moves - Every move played since then, oldest first
+    /// - Input: think - The engine's time on the last move, when that move was the engine's own
+    /// - Output: Why the file could not be written, including an initial position that can not be set up
+    pub fn update(&mut self, initial_fen: Option<&str>, moves: &[Move], think: Option<Duration>) -> io::Result<()> {
+        let continues = self.current.as_ref().is_some_and(|game| {
+            game.initial_fen.as_deref() == initial_fen
+                && game.moves.len() <= moves.len()
+                && game.moves.iter().zip(moves).all(|(logged, mv)| logged.mv == *mv)
+        });
+        if !continues {
+            self.games += 1;
+            let (date, stamp) = utc_timestamp(SystemTime::now());
+            self.current = Some(LoggedGame {
+                path: self.directory.join(format!("{}-{}-unknown-vs-unknown.pgn", stamp, self.games)),
+                date,
+                round: self.games,
+                initial_fen: initial_fen.map(str::to_string),
+                moves: Vec::new(),
+            });
+        }
+        let game = self.current.as_mut().expect("a game was just started if there was none");
+        let known = game.moves.len();
+        game.moves.extend(moves[known..].iter().map(|mv| LoggedMove { mv: mv.clone(), think: None }));
+        if let Some(last) = game.moves.last_mut().filter(|_| moves.len() > known) {
+            last.think = think;
+        }
+        std::fs::create_dir_all(&self.directory)?;
+        let pgn = game.to_pgn().map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        std::fs::write(&game.path, pgn)
+    }
+}
+
+impl LoggedGame {
+    /// The tags and the moves with their think times, replayed from the initial position.
+    /// The position is set up leniently like the engine's own, so a FEN the engine accepted is never swapped for the start.
+    fn to_pgn(&self) -> Result<String, FenError> {
+        let mut game = match &self.initial_fen {
+            Some(fen) => Game::from_fen_with(fen, CastlingValidation::Repair)?.0,
+            None => Game::new(),
+        };
+        let mut movetext = String::new();
+        for (ply, logged) in self.moves.iter().enumerate() {
+            let board = &game.board;
+            match board.get_player_turn() {
+                Color::White => movetext.push_str(&format!("{}. ", board.fullmove_number())),
+                Color::Black if ply == 0 => movetext.push_str(&format!("{}... ", board.fullmove_number())),
+                Color::Black => {}
+            }
+            movetext.push_str(&move_to_san(board, &logged.mv));
+            movetext.push(' ');
+            if let Some(think) = logged.think {
+                movetext.push_str(&format!("{{[%emt {}]}} ", format_emt(think)));
+            }
+            if game.make_move(logged.mv.clone()).is_err() {
+                break;
+            }
+        }
+        let result = game.result();
+        let mut pgn = format!(
+            "[Event \"UCI game\"]\n[Site \"?\"]\n[Date \"{}\"]\n[Round \"{}\"]\n[White \"?\"]\n[Black \"?\"]\n[Result \"{}\"]\n",
+            self.date, self.round, result,
+        );
+        if let Some(fen) = &self.initial_fen {
+            pgn.push_str(&format!("[SetUp \"1\"]\n[FEN \"{}\"]\n", fen));
+        }
+        pgn.push_str(&format!("\n{}{}\n", movetext, result));
+        Ok(pgn)
+    }
+}
+
+/// A think time as %emt expects it, hours, minutes and seconds to the millisecond, e.g. "0:00:01.250"
+pub(crate) fn format_emt(think: Duration) -> String {
+    let millis = think.as_millis();
+    format!("{}:{:02}:{:02}.{:03}", millis / 3_600_000, millis / 60_000 % 60, millis / 1000 % 60, millis % 1000)
+}
+
+/// The UTC date as a PGN Date tag, e.g. "2026.10.15", and a timestamp for file names, e.g. "20261015-142301"
+pub(crate) fn utc_timestamp(time: SystemTime) -> (String, String) {
+    let seconds = time.duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs());
+    let (days, time_of_day) = (seconds / 86_400, seconds % 86_400);
+    // Civil date from days since 1970-01-01, counting years from March so the leap day comes last
+    let shifted = days as i64 + 719_468;
+    let era = shifted.div_euclid(146_097);
+    let day_of_era = shifted.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    let (hours, minutes, secs) = (time_of_day / 3600, time_of_day / 60 % 60, time_of_day % 60);
+    (
+        format!("{}.{:02}.{:02}", year, month, day),
+        format!("{}{:02}{:02}-{:02}{:02}{:02}", year, month, day, hours, minutes, secs),
+    )
+}
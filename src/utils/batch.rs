@@ -0,0 +1,103 @@
+use std::{fmt, io::{self, BufRead, Write}, time::{Duration, Instant}};
+
+use crate::{board::Board, search::{search_limits::SearchLimits, searcher::analyze_with_table, transposition_table::TranspositionTable}};
+
+/// Lines read and scored together, each thread takes an equal share
+const BATCH_CHUNK_LINES: usize = 1024;
+/// Size of each thread's transposition table, cleared before every position
+const BATCH_HASH_MB: usize = 1;
+
+/// What evaluate_fens did with its input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchReport {
+    /// Positions written out with a score
+    pub scored: usize,
+    /// Lines that did not hold a valid FEN, left out of the output
+    pub skipped: usize,
+    pub elapsed: Duration,
+}
+
+/// e.g. "scored 9998 positions, skipped 2 malformed FENs in 1520 ms"
+impl fmt::Display for BatchReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "scored {} positions, skipped {} malformed FENs in {} ms", self.scored, self.skipped, self.elapsed.as_millis())
+    }
+}
+
+/// Score a stream of positions, one FEN per line, for building datasets.
+/// # Description
+/// Each line is scored with the static evaluation when limits is None, otherwise with a search bounded by limits,
+/// and written out as "fen,score". Scores are centipawns from the side to move's perspective.
+/// Anything after the first comma of a line is ignored, so the first column of a CSV can be read as is.
+/// Blank lines are passed over and lines without a valid FEN are skipped and counted.
+/// Lines are read in chunks that are split between the threads, and written in the order they were read.
+/// Each search starts from an empty transposition table, so a depth limited score does not depend on the thread
+/// count or on the positions before it.
+/// # Inputs/Outputs
+/// - Input: reader - The FENs, one per line
+/// - Input: writer - Where the "fen,score" lines go
+/// - Input: limits - The limits of each search, None to only evaluate statically
+/// - Input: threads - How many positions are scored at once, at least one
+/// - Output: The counts of scored and skipped lines, or the error reading or writing them
+/// # Example
+/// ``` Rust
+/// let input = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1\n";
+/// let mut output = Vec::new();
+/// let report = evaluate_fens(input.as_bytes(), &mut output, Some(SearchLimits::depth(4)), 4).unwrap();
+/// println!("{}", report);
+/// ```
+pub fn evaluate_fens(reader: impl BufRead, mut writer: impl Write, limits: Option<SearchLimits>, threads: usize) -> io::Result<BatchReport> {
+    let start = Instant::now();
+    let threads = threads.max(1);
+    let mut tables = (0..threads).map(|_| TranspositionTable::new(BATCH_HASH_MB)).collect::<Vec<_>>();
+    let mut report = BatchReport { scored: 0, skipped: 0, elapsed: Duration::ZERO };
+    let mut lines = reader.lines();
+    let mut line_number = 0;
+    loop {
+        let mut chunk = Vec::with_capacity(BATCH_CHUNK_LINES);
+        for line in lines.by_ref().take(BATCH_CHUNK_LINES) {
+            line_number += 1;
+            let line = line?;
+            let fen = line.split(',').next().unwrap_or_default().trim();
+            if !fen.is_empty() {
+                chunk.push((line_number, fen.to_string()));
+            }
+        }
+        if chunk.is_empty() {
+            break;
+        }
+        let share = chunk.len().div_ceil(threads);
+        let scores = std::thread::scope(|scope| {
+            let workers = chunk.chunks(share).zip(tables.iter_mut()).map(|(positions, table)| {
+                let limits = limits.as_ref();
+                scope.spawn(move || positions.iter().map(|(_, fen)| score_fen(fen, limits, table)).collect::<Vec<_>>())
+            }).collect::<Vec<_>>();
+            workers.into_iter().flat_map(|worker| worker.join().expect("scoring a position does not panic")).collect::<Vec<_>>()
+        });
+        for ((line_number, fen), score) in chunk.iter().zip(scores) {
+            match score {
+                Some(score) => {
+                    writeln!(writer, "{},{}", fen, score)?;
+                    report.scored += 1;
+                }
+                None => {
+                    log::warn!("Skipping line {}, not a valid FEN: {}", line_number, fen);
+                    report.skipped += 1;
+                }
+            }
+        }
+    }
+    writer.flush()?;
+    report.elapsed = start.elapsed();
+    Ok(report)
+}
+
+/// The score of one position, None if the FEN does not parse
+fn score_fen(fen: &str, limits: Option<&SearchLimits>, table: &mut TranspositionTable) -> Option<i32> {
+    let board = Board::from_fen(fen).ok()?;
+    let Some(limits) = limits else {
+        return Some(board.evaluate_to_move());
+    };
+    table.clear();
+    Some(analyze_with_table(&board, limits.clone(), table).score)
+}
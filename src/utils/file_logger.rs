@@ -0,0 +1,47 @@
+use std::{fs::File, io::Write, path::Path, sync::Mutex};
+
+use log::{LevelFilter, Log, Metadata, Record, SetLoggerError};
+
+/// A logger that appends every record to a file, for running under a GUI where stderr is hard to see.
+/// # Description
+/// Lines are written as "LEVEL [target] message" without colors.
+pub struct FileLogger {
+    file: Mutex<File>,
+    level: LevelFilter,
+}
+
+impl FileLogger {
+    /// Opens the file for appending, creating it if needed
+    pub fn new(path: &Path, level: LevelFilter) -> std::io::Result<FileLogger> {
+        let file = File::options().create(true).append(true).open(path)?;
+        Ok(FileLogger { file: Mutex::new(file), level })
+    }
+
+    /// Makes this the logger used by the log macros, which can only be done once per process
+    pub fn init(self) -> Result<(), SetLoggerError> {
+        log::set_max_level(self.level);
+        log::set_boxed_logger(Box::new(self))
+    }
+}
+
+impl Log for FileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        if let Ok(mut file) = self.file.lock() {
+            // Nowhere left to report a failed write to
+            let _ = writeln!(file, "{:<5} [{}] {}", record.level(), record.target(), record.args());
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut file) = self.file.lock() {
+            let _ = file.flush();
+        }
+    }
+}
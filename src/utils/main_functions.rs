@@ -1,48 +1,225 @@
 
-use crate::{board::Board, piece_type::PieceType, utils::performance::perft};
-use rand::Rng;
+use std::io::IsTerminal;
 
+use crate::{board::Board, board_render_options::BoardRenderOptions, chess_move::Move, coach::{CoachConfig, Judgment}, color::Color, drill::{Drill, DrillFeedback}, game::Game, kibitzer::watcher::parse_move, hint::hint, piece_type::PieceType, search::search_limits::SearchLimits, utils::{notation::{move_to_san, san_to_move}, performance::{perft, perft_divide, perft_parallel}}};
+
+/// Candidate moves listed by the hint command
+const HINT_LINES: usize = 3;
+/// Milliseconds spent on each position by the review command
+const REVIEW_MOVETIME_MS: u64 = 200;
+
+/// Play against random moves from the console.
+/// # Description
+/// The player has White and enters moves in SAN.
+/// "load <fen>" (or "setboard <fen>") starts a new game from a position, printing what is wrong with the FEN if it does not parse.
+/// "hint" lists the best three moves with their scores and expected lines using White's engine config.
+/// "review" prints each side's accuracy and the worst moves so far, and is offered again once the game is over.
+/// "drill <file> [white|black]" practises the line in a drill file, see run_drill.
+/// "coach [threshold cp] [movetime ms]" checks each move before it is played and asks before playing a blunder,
+/// "coach off" turns that off again.
+/// The board is drawn after every move with the last move highlighted and a status line beneath it.
 pub fn console_game_loop() {
-    let mut board = Board::starting_position();
-    board.print(crate::color::Color::White);
+    let mut game = Game::new();
+    let mut coach: Option<CoachConfig> = None;
+    let options = console_render_options();
+    log::info!("\n{}", game.render(&options));
 
     loop {
-        let moves = board.generate_legal_moves();
-        if moves.len() == 0 {
-            log::info!("Game over!\nWhite Wins!");
+        let draw = game.board.is_draw();
+        if draw.is_some() || !game.board.has_legal_moves() {
+            match draw {
+                Some(reason) => log::info!("Game over!\nDraw by {}", reason),
+                None => log::info!("Game over!\n{} Wins!", game.board.get_player_turn().opposite()),
+            }
+            log::info!("Enter review for a summary of the game, anything else to quit: ");
+            let mut input = String::new();
+            if std::io::stdin().read_line(&mut input).is_ok() && input.trim() == "review" {
+                print_review(&game);
+            }
             break;
         }
+        if *game.board.get_player_turn() == Color::Black {
+            let random_move = game.engine_move();
+            log::info!("Black made move: {}", random_move);
+            log::info!("Evaluation for White: {}", game.board.evaluate_for(Color::White));
+            log::info!("\n{}", game.render(&options));
+            continue;
+        }
+        log::info!("Enter move: ");
         let mut input = String::new();
-        loop {
-            println!("Enter move: ");
-            std::io::stdin().read_line(&mut input).unwrap();
-            if let Err(e) = board.algebraic_move(input.trim()) {
-                log::warn!("Invalid move: {}", e);
-                input.clear();
-                board.print(crate::color::Color::White);
-            } else { break }
-        }
-        log::info!("White made move: {}", input);
-        let moves = board.generate_legal_moves();
-        if moves.len() == 0 {
-            log::info!("Game over!\nWhite Wins!");
+        if std::io::stdin().read_line(&mut input).unwrap_or(0) == 0 {
             break;
         }
-        let mut rng = rand::thread_rng(); 
-        let random_move = &moves[rng.gen_range(0..moves.len())];
-        board.move_piece(random_move.clone()).unwrap();
-        log::info!("Black made move: {}", random_move);
-        board.print(crate::color::Color::White);
+        let input = input.trim();
+        if let Some(fen) = input.strip_prefix("load ").or_else(|| input.strip_prefix("setboard ")) {
+            load_position(&mut game, fen.trim());
+            continue;
+        }
+        if input == "review" {
+            print_review(&game);
+            continue;
+        }
+        if let Some(args) = input.strip_prefix("drill ") {
+            run_drill(args);
+            log::info!("\n{}", game.render(&options));
+            continue;
+        }
+        if input == "hint" {
+            let limits = game.engine_config_for(Color::White).map(|config| config.limits.clone()).unwrap_or_default();
+            let lines = hint(&game, limits, HINT_LINES);
+            log::info!("Candidates, * is the engine's choice:");
+            for line in &lines {
+                log::info!("{}", line);
+            }
+            if let Some(line) = lines.iter().find(|line| line.chosen) {
+                log::info!("Hint: {}", line.san);
+                game.cache_hint(line.score);
+                log::info!("\n{}", game.render(&options));
+            }
+            continue;
+        }
+        if input == "coach" || input.starts_with("coach ") {
+            coach = parse_coach(&input["coach".len()..]);
+            match coach {
+                Some(config) => log::info!("Coach on, warning about moves losing over {} cp, {} ms a search", config.threshold_cp, config.movetime_ms),
+                None => log::info!("Coach off"),
+            }
+            continue;
+        }
+        if let (Some(config), Some(mv)) = (coach, san_to_move(&game.board, input)) {
+            if !coach_allows(&config, &game.board, &mv) {
+                continue;
+            }
+        }
+        match san_to_move(&game.board, input).map(|mv| game.make_move(mv)) {
+            Some(Ok(_)) => {
+                log::info!("White made move: {}", input);
+                log::info!("\n{}", game.render(&options));
+            }
+            Some(Err(e)) => log::warn!("Invalid move: {}", e),
+            None => match rejected_move_reason(&game.board, input) {
+                Some(reason) => log::warn!("Invalid move: {}", reason),
+                None => log::warn!("Invalid move: {}", input),
+            },
+        }
     }
 }
 
+/// The coach's settings from the arguments of the coach command, None for "off".
+/// Missing or invalid numbers keep their defaults.
+fn parse_coach(args: &str) -> Option<CoachConfig> {
+    let mut args = args.split_whitespace();
+    let mut config = CoachConfig::default();
+    match args.next() {
+        Some("off") => return None,
+        Some(threshold) => config.threshold_cp = threshold.parse().unwrap_or(config.threshold_cp),
+        None => {}
+    }
+    if let Some(movetime) = args.next() {
+        config.movetime_ms = movetime.parse().unwrap_or(config.movetime_ms);
+    }
+    Some(config)
+}
+
+/// Whether to play a move the coach has checked, asking the player first when it is a blunder
+fn coach_allows(config: &CoachConfig, board: &Board, mv: &Move) -> bool {
+    let Ok(Judgment::Blunder(blunder)) = config.judge(board, mv) else {
+        return true;
+    };
+    log::warn!("{} — play anyway? (y/n)", blunder.warning());
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer).is_ok() && answer.trim().eq_ignore_ascii_case("y")
+}
+
+/// How the console draws the board, in inverse video when the output is a terminal and with markers otherwise
+fn console_render_options() -> BoardRenderOptions {
+    BoardRenderOptions { mark_check: true, status: true, ansi: std::io::stdout().is_terminal(), ..BoardRenderOptions::default() }
+}
+
+/// Practise a drill file from the console, playing White unless "black" follows the path.
+/// # Description
+/// The file holds a FEN, or "startpos", on its first line and the moves of the line after it, see Drill::parse.
+/// Moves are entered in SAN or UCI, "show" gives the expected move and "quit" leaves the drill.
+fn run_drill(args: &str) {
+    let mut args = args.split_whitespace();
+    let path = args.next().unwrap_or_default();
+    let user = if args.next() == Some("black") { Color::Black } else { Color::White };
+    let drill = std::fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|text| Drill::parse(&text, user).map_err(|e| e.to_string()));
+    let mut drill = match drill {
+        Ok(drill) => drill,
+        Err(e) => {
+            log::warn!("Could not load drill {}: {}", path, e);
+            return;
+        }
+    };
+    while !drill.is_finished() {
+        log::info!("\n{}", drill.board());
+        log::info!("Your move as {}: ", drill.user());
+        let mut input = String::new();
+        if std::io::stdin().read_line(&mut input).unwrap_or(0) == 0 || input.trim() == "quit" {
+            return;
+        }
+        let input = input.trim();
+        if input == "show" {
+            if let Some(expected) = drill.expected() {
+                log::info!("The line continues {}", move_to_san(drill.board(), expected));
+            }
+            continue;
+        }
+        let Some(mv) = parse_move(drill.board(), input) else {
+            log::warn!("Invalid move: {}", input);
+            continue;
+        };
+        let before = drill.board().clone();
+        match drill.step(&mv) {
+            DrillFeedback::Correct { reply: Some(reply) } => {
+                let mut after = before;
+                after.move_piece(mv).unwrap();
+                log::info!("Correct, the reply is {}", move_to_san(&after, &reply));
+            }
+            DrillFeedback::Correct { reply: None } => log::info!("Correct"),
+            DrillFeedback::Deviation { .. } => log::info!("That leaves the line, try again or enter show for the expected move"),
+            DrillFeedback::Finished => {}
+        }
+    }
+    log::info!("Drill complete with {} deviations", drill.deviations());
+}
+
+fn print_review(game: &Game) {
+    log::info!("Reviewing the game, {} ms a position", REVIEW_MOVETIME_MS);
+    log::info!("\n{}", game.review(SearchLimits::movetime(REVIEW_MOVETIME_MS)));
+}
+
+/// Explains why a move in SAN that is not legal was rejected, such as the piece being pinned.
+/// None if the text does not describe a move any piece could make, even ignoring checks.
+fn rejected_move_reason(board: &Board, san: &str) -> Option<String> {
+    let strip = |text: &str| text.chars().filter(|c| !"+#!?=".contains(*c)).collect::<String>();
+    let target = strip(san.trim());
+    let mv = board.generate_pseudo_legal_moves().into_iter().find(|mv| strip(&move_to_san(board, mv)) == target)?;
+    board.clone().move_piece(mv.clone()).err().map(|e| e.explain(&mv))
+}
+
+/// Replaces the game with one set up from a FEN, explaining any problem with the FEN
+fn load_position(game: &mut Game, fen: &str) {
+    match game.load_fen(fen) {
+        Ok(()) => {
+            log::info!("\n{}", game.board);
+            log::info!("{} to move", game.board.get_player_turn());
+        }
+        Err(e) => log::warn!("{}", e.annotate(fen)),
+    }
+}
+
+/// Perft from the start position to each depth up to depth, on every core
 pub fn depth_calc(depth: u32) {
+    let threads = std::thread::available_parallelism().map_or(1, |threads| threads.get());
     for i in 0..=depth {
-        let board = Board::starting_position();
-        let start = std::time::Instant::now();
-        let count = perft(i, board);
-        let duration = start.elapsed();
-        log::info!("Depth: {}, {} moves generated in {}ms", i, count, duration.as_millis());
+        match perft_parallel(i, &Board::starting_position(), threads) {
+            Ok(result) => log::info!("{}", result),
+            Err(e) => log::error!("{}", e),
+        }
     }
 }
 
@@ -50,27 +227,19 @@ pub fn perft_1() {
    for i in 0..=5 {
         let board = Board::from_fen("r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10 ").unwrap();
         let start = std::time::Instant::now();
-        let count = perft(i, board);
-        let duration = start.elapsed();
-        log::info!("Depth: {}, {} moves generated in {}ms", i, count, duration.as_millis());
+        match perft(i, board) {
+            Ok(count) => log::info!("Depth: {}, {} moves generated in {}ms", i, count, start.elapsed().as_millis()),
+            Err(e) => log::error!("{}", e),
+        }
    }
 }
 
 pub fn perft_2() {
     let mut board = Board::from_fen("r3k2r/p1ppqpb1/bn2pnN1/3P4/1p2P3/2N2Q2/PPPBBPpP/R3K2R w KQkq - 0 2").unwrap();
-    let mv = crate::chess_move::Move::new(6, 5, 7, 7, PieceType::Knight, None);
+    let mv = Move::new(6, 5, 7, 7, PieceType::Knight, None);
     board.move_piece(mv).unwrap();
-    let start = std::time::Instant::now();
-    let moves = board.generate_legal_moves();
-    let mut count = 0;
-    log::info!("{} moves generated", moves.len());
-    for (i, mv) in moves.iter().enumerate() {
-        let mut new_board = board.clone();
-        new_board.move_piece(mv.clone()).unwrap();
-        let n = perft(0, new_board);
-        count += n;
-        log::info!("{}| Move: {}, {} moves generated", i, mv, n);
+    match perft_divide(1, &board) {
+        Ok(result) => log::info!("{}", result),
+        Err(e) => log::error!("{}", e),
     }
-    let duration = start.elapsed();
-    log::info!("{} moves generated in {}ms", count, duration.as_millis());
 }
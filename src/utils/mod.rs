@@ -3,3 +3,6 @@
 pub mod notation;
 pub mod performance;
 pub mod main_functions;
+pub mod file_logger;
+pub mod batch;
+pub mod openings;
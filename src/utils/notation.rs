@@ -1,3 +1,4 @@
+use crate::{board::Board, chess_move::Move, coords::{file_char, rank_char}, piece_type::PieceType};
 
 /// Converts a square in algrbratic chess notation to a pair of coordinates
 /// # Description
@@ -16,10 +17,170 @@
 pub fn square_to_coords(square: &str) -> Option<(usize, usize)> {
     let square = square.bytes().collect::<Vec<u8>>();
     if square.len() != 2 { return None }
-    let letter = square[0] - 97; 
+    let letter = square[0].checked_sub(b'a')?;
     if letter > 7 { return None }
-    let number = square[1] - 49; 
+    let number = square[1].checked_sub(b'1')?;
     if number > 7 { return None }
-    return Some((letter as usize, number as usize))
+    Some((letter as usize, number as usize))
 
 }
+
+/// Converts a pair of coordinates to a square in algebraic chess notation
+/// # Description
+/// The inverse of square_to_coords, the coordinates must be on the board.
+/// # Example
+/// ```Rust
+/// assert_eq!(coords_to_square((4, 3)), "e4");
+/// ```
+pub fn coords_to_square(coords: (usize, usize)) -> String {
+    format!("{}{}", file_char(coords.0), rank_char(coords.1))
+}
+
+/// Converts a legal move to Standard Algebraic Notation
+/// # Description
+/// Produces the SAN used in PGN files such as "Nf3", "exd5", "O-O" or "e8=Q+".
+/// Moves are disambiguated by file, then rank, then both when another piece of the same type can reach the same square.
+/// The move must be legal in the given position.
+/// # Inputs/Outputs
+/// - Input: board: &Board - The position before the move
+/// - Input: mv: &Move - The move to describe
+/// - Output: String - The move in SAN
+/// # Example
+/// ```Rust
+/// let board = Board::starting_position();
+/// let san = move_to_san(&board, &Move::new(6, 0, 5, 2, PieceType::Knight, None));
+/// assert_eq!(san, "Nf3");
+/// ```
+pub fn move_to_san(board: &Board, mv: &Move) -> String {
+    let mut san = String::new();
+    if mv.piece_type == PieceType::King && mv.from_x.abs_diff(mv.to_x) == 2 {
+        san.push_str(if mv.to_x == 6 { "O-O" } else { "O-O-O" });
+    } else {
+        let capture = board.get_piece(mv.to_x, mv.to_y).is_some() || (mv.piece_type == PieceType::Pawn && mv.from_x != mv.to_x);
+        if mv.piece_type == PieceType::Pawn {
+            if capture {
+                san.push(file_char(mv.from_x));
+            }
+        } else {
+            san.push_str(&mv.piece_type.to_string());
+            let rivals = board.generate_legal_moves().into_iter()
+                .filter(|other| other.piece_type == mv.piece_type && other.to_x == mv.to_x && other.to_y == mv.to_y
+                    && (other.from_x, other.from_y) != (mv.from_x, mv.from_y))
+                .collect::<Vec<_>>();
+            if !rivals.is_empty() {
+                if rivals.iter().all(|other| other.from_x != mv.from_x) {
+                    san.push(file_char(mv.from_x));
+                } else if rivals.iter().all(|other| other.from_y != mv.from_y) {
+                    san.push(rank_char(mv.from_y));
+                } else {
+                    san.push(file_char(mv.from_x));
+                    san.push(rank_char(mv.from_y));
+                }
+            }
+        }
+        if capture {
+            san.push('x');
+        }
+        san.push(file_char(mv.to_x));
+        san.push(rank_char(mv.to_y));
+        if let Some(promotion) = mv.promotion {
+            san.push('=');
+            san.push_str(&promotion.to_string());
+        }
+    }
+    // Only checks can be mate, so the search for a reply is skipped for the rest
+    if board.gives_check(mv) {
+        san.push(if board.is_mate_after(mv) { '#' } else { '+' });
+    }
+    san
+}
+
+/// Writes a line of moves in SAN, cut short for display
+/// # Description
+/// Gives the first max_moves moves separated by spaces, followed by "…" if the line is longer.
+/// Each move must be legal after the ones before it.
+/// # Inputs/Outputs
+/// - Input: board: &Board - The position before the first move
+/// - Input: line: &[Move] - The moves, in order
+/// - Input: max_moves: usize - The most moves to write
+/// - Output: String - e.g. "Nxe5 d6 Nf3 Qe7 …"
+/// # Example
+/// ```Rust
+/// let board = Board::starting_position();
+/// let mut after = board.clone();
+/// let mut line = Vec::new();
+/// for text in ["e2e4", "e7e5", "g1f3", "b8c6", "f1b5"] {
+///     let mv = uci_to_move(&after, text).unwrap();
+///     after.make_move(&mv).unwrap();
+///     line.push(mv);
+/// }
+/// assert_eq!(line_to_san(&board, &line, 4), "e4 e5 Nf3 Nc6 …");
+/// ```
+pub fn line_to_san(board: &Board, line: &[Move], max_moves: usize) -> String {
+    let mut board = board.clone();
+    let mut sans = Vec::new();
+    for mv in line.iter().take(max_moves) {
+        sans.push(move_to_san(&board, mv));
+        if board.make_move(mv).is_err() {
+            break;
+        }
+    }
+    if line.len() > max_moves {
+        sans.push("…".to_string());
+    }
+    sans.join(" ")
+}
+
+/// Finds the legal move a SAN string describes
+/// # Description
+/// Check, mate and annotation marks ("+", "#", "!", "?") are ignored, "0-0" is accepted for castling
+/// and the "=" before a promotion piece is optional.
+/// Returns None if no legal move matches.
+/// # Inputs/Outputs
+/// - Input: board: &Board - The position the move is played in
+/// - Input: san: &str - The move in SAN
+/// - Output: Option<Move> - The matching legal move
+/// # Example
+/// ```Rust
+/// let board = Board::starting_position();
+/// let mv = san_to_move(&board, "e4").unwrap();
+/// assert_eq!(mv.extended_algebraic(), "e2e4");
+/// ```
+pub fn san_to_move(board: &Board, san: &str) -> Option<Move> {
+    let strip = |text: &str| text.replace('0', "O").chars().filter(|c| !"+#!?=".contains(*c)).collect::<String>();
+    let target = strip(san.trim());
+    if target.is_empty() {
+        return None;
+    }
+    board.generate_legal_moves().into_iter().find(|mv| strip(&move_to_san(board, mv)) == target)
+}
+
+/// Finds the legal move a UCI move string describes
+/// # Description
+/// UCI moves give the from and to squares followed by an optional promotion piece, e.g. "e2e4" or "e7e8q".
+/// The promotion letter may be in either case. Returns None for malformed text or a move that is not legal.
+/// # Inputs/Outputs
+/// - Input: board: &Board - The position the move is played in
+/// - Input: text: &str - The move in UCI notation
+/// - Output: Option<Move> - The matching legal move
+/// # Example
+/// ```Rust
+/// let board = Board::starting_position();
+/// let mv = uci_to_move(&board, "g1f3").unwrap();
+/// assert_eq!(mv.piece_type, PieceType::Knight);
+/// ```
+pub fn uci_to_move(board: &Board, text: &str) -> Option<Move> {
+    let text = text.trim();
+    if !(4..=5).contains(&text.len()) || !text.is_ascii() {
+        return None;
+    }
+    let (from_x, from_y) = square_to_coords(&text[0..2])?;
+    let (to_x, to_y) = square_to_coords(&text[2..4])?;
+    let promotion = match text.chars().nth(4) {
+        Some(c) => Some(PieceType::try_from(c.to_ascii_uppercase()).ok()?),
+        None => None,
+    };
+    board.generate_legal_moves().into_iter().find(|mv| {
+        (mv.from_x, mv.from_y, mv.to_x, mv.to_y) == (from_x, from_y, to_x, to_y) && mv.promotion == promotion
+    })
+}
@@ -0,0 +1,55 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::board::Board;
+
+/// Lines tried before giving up on finding a balanced one
+const MAX_OPENING_ATTEMPTS: usize = 10_000;
+
+/// A random but playable opening for self-play, so games from it are not all alike.
+/// # Description
+/// Plays plies random legal moves from the start position. A line is thrown away and another one tried when
+/// the game ends before the last ply, or the final position has the side to move in check or a static evaluation
+/// more than max_imbalance_cp from equal. The same seed always gives the same line, the moves are kept in the
+/// board's history so they can be replayed into a game record.
+/// If no line passes after MAX_OPENING_ATTEMPTS tries, which only happens with an impossible bound,
+/// the start position is returned.
+/// # Inputs/Outputs
+/// - Input: seed - Picks the line, the same seed gives the same position
+/// - Input: plies - How many random moves to play
+/// - Input: max_imbalance_cp - The most centipawns the final position may favour either side
+/// - Output: The position after the opening, with its moves played on the board
+/// # Example
+/// ``` Rust
+/// let board = random_balanced_position(7, 8, 100);
+/// assert_eq!(board.moves_played().count(), 8);
+/// println!("{}", board.to_fen());
+/// ```
+pub fn random_balanced_position(seed: u64, plies: u32, max_imbalance_cp: i32) -> Board {
+    let mut rng = StdRng::seed_from_u64(seed);
+    for _ in 0..MAX_OPENING_ATTEMPTS {
+        if let Some(board) = random_line(&mut rng, plies).filter(|board| balanced(board, max_imbalance_cp)) {
+            return board;
+        }
+    }
+    log::warn!("No opening of {} plies within {} cp found for seed {}, using the start position", plies, max_imbalance_cp, seed);
+    Board::starting_position()
+}
+
+/// The position after plies random moves, None if the game ends first
+fn random_line(rng: &mut StdRng, plies: u32) -> Option<Board> {
+    let mut board = Board::starting_position();
+    for _ in 0..plies {
+        let moves = board.generate_legal_moves();
+        if moves.is_empty() {
+            return None;
+        }
+        let mv = moves[rng.gen_range(0..moves.len())].clone();
+        board.move_piece(mv).ok()?;
+    }
+    Some(board)
+}
+
+/// A position a game can be played from, close enough to equal
+fn balanced(board: &Board, max_imbalance_cp: i32) -> bool {
+    !board.king_in_check() && board.has_legal_moves() && board.is_draw().is_none() && board.basic_evaluate().abs() <= max_imbalance_cp
+}
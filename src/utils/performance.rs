@@ -1,21 +1,344 @@
-use crate::board::Board;
+use std::{fmt, sync::atomic::{AtomicUsize, Ordering}, time::{Duration, Instant}};
 
+use crate::{board::Board, chess_move::Move, errors::perft_error::PerftError};
 
-pub fn perft(depth: u32, board: Board) -> usize {
+
+/// The outcome of a timed perft run, see perft_timed.
+/// With the serde feature it serializes for tools that track move generation speed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PerftResult {
+    pub depth: u32,
+    /// Leaf positions counted
+    pub nodes: u64,
+    pub elapsed: Duration,
+    /// Nodes per second, 0 if the run took no measurable time
+    pub nps: u64,
+    /// Nodes below each root move sorted by the move's UCI notation, only filled by perft_divide
+    pub divide: Option<Vec<PerftDivide>>,
+    /// Nodes counted by each worker thread, only filled by perft_parallel
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub thread_nodes: Option<Vec<u64>>,
+}
+
+/// The nodes below one root move
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PerftDivide {
+    /// The move in UCI notation, e.g. "e2e4"
+    pub mv: String,
+    pub nodes: u64,
+}
+
+/// e.g. "depth 3 nodes 8902 time 4 ms nps 2225500", then "threads 4410 4492" when run in parallel,
+/// followed by a line per root move when divided
+impl fmt::Display for PerftResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "depth {} nodes {} time {} ms nps {}", self.depth, self.nodes, self.elapsed.as_millis(), self.nps)?;
+        if let Some(thread_nodes) = &self.thread_nodes {
+            write!(f, " threads")?;
+            for nodes in thread_nodes {
+                write!(f, " {}", nodes)?;
+            }
+        }
+        for entry in self.divide.iter().flatten() {
+            write!(f, "\n  {}: {}", entry.mv, entry.nodes)?;
+        }
+        Ok(())
+    }
+}
+
+/// Count the leaf positions to a depth and time the count.
+/// # Example
+/// ``` Rust
+/// let result = perft_timed(3, &Board::starting_position()).unwrap();
+/// assert_eq!(result.nodes, 8902);
+/// println!("{}", result);
+/// ```
+pub fn perft_timed(depth: u32, board: &Board) -> Result<PerftResult, PerftError> {
+    let start = Instant::now();
+    let nodes = perft_with(depth, board, PerftMode::Bulk)?.nodes;
+    Ok(timed_result(depth, nodes, start.elapsed(), None))
+}
+
+/// Count the leaf positions to a depth, split by root move, for finding which move a generator gets wrong.
+/// The totals and timing are the same as perft_timed.
+/// Entries are sorted by UCI notation, so the output diffs line by line against another engine's divide,
+/// e.g. Stockfish's "go perft N" sorted the same way.
+pub fn perft_divide(depth: u32, board: &Board) -> Result<PerftResult, PerftError> {
+    let start = Instant::now();
+    let mut board = board.clone();
+    let mut divide = Vec::new();
+    let mut buffers = move_buffers(depth);
+    if depth > 0 {
+        for mv in board.generate_legal_moves() {
+            let undo = board.make_move(&mv).map_err(|source| perft_error(&board, &mv, source))?;
+            let mut stats = PerftStats::default();
+            perft_recursive(depth - 1, &mut board, &mut buffers, PerftMode::Bulk, &mut stats)?;
+            board.unmake_move(&mv, undo);
+            divide.push(PerftDivide { mv: mv.extended_algebraic(), nodes: stats.nodes });
+        }
+    }
+    divide.sort_by(|a, b| a.mv.cmp(&b.mv));
+    let nodes = if depth == 0 { 1 } else { divide.iter().map(|entry| entry.nodes).sum() };
+    Ok(timed_result(depth, nodes, start.elapsed(), Some(divide)))
+}
+
+/// The leaf positions of a perft broken down by the move that reached them, as in the tables of published perft results.
+/// Captures include en passant and promotions that capture, checkmates are also counted as checks.
+/// Only nodes is counted unless the perft ran in PerftMode::Stats.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PerftStats {
+    pub nodes: u64,
+    pub captures: u64,
+    pub en_passant: u64,
+    pub castles: u64,
+    pub promotions: u64,
+    pub checks: u64,
+    pub checkmates: u64,
+}
+
+/// e.g. "nodes 8902 captures 34 ep 0 castles 0 promotions 0 checks 12 checkmates 0"
+impl fmt::Display for PerftStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f, "nodes {} captures {} ep {} castles {} promotions {} checks {} checkmates {}",
+            self.nodes, self.captures, self.en_passant, self.castles, self.promotions, self.checks, self.checkmates,
+        )
+    }
+}
+
+/// How perft_with reaches the leaves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PerftMode {
+    /// Counts the moves of the last ply without making them, which is what other engines report as perft
+    Bulk,
+    /// Makes every move down to depth 0, to check the bulk count against make_move
+    MakeEveryMove,
+    /// Plays the last ply with move_piece and counts each kind of move from the MoveResult it returns, much slower
+    Stats,
+}
+
+/// Count the leaf positions to a depth, the one entry point behind the other perft functions.
+/// # Description
+/// Works on a copy of the board, making and unmaking moves. At depth 0 the position itself is the one leaf.
+/// A generated move that the board refuses to play is returned as an error naming the move and position,
+/// rather than ending the process, as it means move generation has a bug.
+/// # Inputs/Outputs
+/// - Input: depth - Plies to count to
+/// - Input: board - The position to count from
+/// - Input: mode - How the last ply is counted, see PerftMode
+/// - Output: The counts, with only nodes filled in unless mode is Stats, or the move that could not be played
+/// # Example
+/// ``` Rust
+/// let stats = perft_with(3, &Board::starting_position(), PerftMode::Stats).unwrap();
+/// assert_eq!((stats.nodes, stats.captures, stats.checks), (8902, 34, 12));
+/// ```
+pub fn perft_with(depth: u32, board: &Board, mode: PerftMode) -> Result<PerftStats, PerftError> {
+    let mut stats = PerftStats::default();
+    perft_recursive(depth, &mut board.clone(), &mut move_buffers(depth), mode, &mut stats)?;
+    Ok(stats)
+}
+
+/// Count the leaf positions to a depth with bulk counting, see perft_with
+pub fn perft(depth: u32, board: Board) -> Result<usize, PerftError> {
+    Ok(perft_with(depth, &board, PerftMode::Bulk)?.nodes as usize)
+}
+
+/// Count the leaf positions to a depth making every move, see PerftMode::MakeEveryMove
+pub fn perft_slow(depth: u32, board: Board) -> Result<usize, PerftError> {
+    Ok(perft_with(depth, &board, PerftMode::MakeEveryMove)?.nodes as usize)
+}
+
+/// Count the leaf positions to a depth by the kind of move that reached them, see PerftMode::Stats
+pub fn perft_stats(depth: u32, board: &Board) -> Result<PerftStats, PerftError> {
+    perft_with(depth, board, PerftMode::Stats)
+}
+
+/// Count the leaf positions to a depth with the root moves shared between threads.
+/// # Description
+/// Each thread works on its own copy of the board and takes the next root move not yet counted until none are left,
+/// so the totals are the same as perft_timed whatever the thread count.
+/// # Inputs/Outputs
+/// - Input: depth - Plies to count to
+/// - Input: board - The position to count from
+/// - Input: threads - How many threads count at once, at least one
+/// - Output: The total and timing, with the nodes each thread counted in thread_nodes, or the first move that could not be played
+/// # Example
+/// ``` Rust
+/// let result = perft_parallel(5, &Board::starting_position(), 4).unwrap();
+/// assert_eq!(result.nodes, 4865609);
+/// println!("{}", result);
+/// ```
+pub fn perft_parallel(depth: u32, board: &Board, threads: usize) -> Result<PerftResult, PerftError> {
+    let start = Instant::now();
+    if depth == 0 {
+        return Ok(timed_result(depth, 1, start.elapsed(), None));
+    }
+    let root_moves = board.generate_legal_moves();
+    let next = AtomicUsize::new(0);
+    let thread_nodes = std::thread::scope(|scope| {
+        let workers = (0..threads.max(1)).map(|_| {
+            let (root_moves, next) = (&root_moves, &next);
+            let mut board = board.clone();
+            scope.spawn(move || {
+                let mut buffers = move_buffers(depth);
+                let mut stats = PerftStats::default();
+                while let Some(mv) = root_moves.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    let undo = board.make_move(mv).map_err(|source| perft_error(&board, mv, source))?;
+                    perft_recursive(depth - 1, &mut board, &mut buffers, PerftMode::Bulk, &mut stats)?;
+                    board.unmake_move(mv, undo);
+                }
+                Ok(stats.nodes)
+            })
+        }).collect::<Vec<_>>();
+        workers.into_iter().map(|worker| worker.join().expect("perft does not panic")).collect::<Result<Vec<_>, PerftError>>()
+    })?;
+    let mut result = timed_result(depth, thread_nodes.iter().sum(), start.elapsed(), None);
+    result.thread_nodes = Some(thread_nodes);
+    Ok(result)
+}
+
+/// Count the leaf positions to a depth, remembering the count below each position in a hash table.
+/// # Description
+/// Deep perft reaches the same position by many move orders, so the count below a position at a depth is
+/// stored under its Zobrist key and reused whenever the position comes up again. Counts are exact, so nothing
+/// is ever invalidated during a run, and a new entry always replaces the old one in its slot.
+/// Each entry keeps the full key alongside the depth, and only an entry matching both is used, so positions
+/// sharing a slot are never confused. The last ply is bulk counted and not stored.
+/// # Inputs/Outputs
+/// - Input: depth - Plies to count to
+/// - Input: board - The position to count from
+/// - Input: table_mb - Megabytes for the table, rounded down to a power of two entries, 0 counts without one
+/// - Output: The number of leaf positions, or the first move that could not be played
+/// # Example
+/// ``` Rust
+/// assert_eq!(perft_hashed(6, &Board::starting_position(), 64).unwrap(), 119060324);
+/// ```
+pub fn perft_hashed(depth: u32, board: &Board, table_mb: usize) -> Result<usize, PerftError> {
+    let mut table = PerftTable::new(table_mb);
+    let nodes = perft_hashed_recursive(depth, &mut board.clone(), &mut move_buffers(depth), &mut table)?;
+    Ok(nodes as usize)
+}
+
+/// A stored count, empty while depth is 0 as depth 0 is never stored
+#[derive(Debug, Clone, Copy, Default)]
+struct PerftEntry {
+    key: u64,
+    depth: u32,
+    nodes: u64,
+}
+
+/// Fixed size always-replace table of node counts for perft_hashed
+struct PerftTable {
+    entries: Vec<PerftEntry>,
+}
+
+impl PerftTable {
+    fn new(megabytes: usize) -> PerftTable {
+        let slots = megabytes * 1024 * 1024 / std::mem::size_of::<PerftEntry>();
+        let capacity = if slots == 0 { 0 } else { 1 << slots.ilog2() };
+        PerftTable { entries: vec![PerftEntry::default(); capacity] }
+    }
+
+    fn probe(&self, key: u64, depth: u32) -> Option<u64> {
+        let entry = self.entries.get(self.index(key)?)?;
+        (entry.key == key && entry.depth == depth).then_some(entry.nodes)
+    }
+
+    fn store(&mut self, entry: PerftEntry) {
+        if let Some(index) = self.index(entry.key) {
+            self.entries[index] = entry;
+        }
+    }
+
+    fn index(&self, key: u64) -> Option<usize> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        Some(key as usize & (self.entries.len() - 1))
+    }
+}
+
+fn perft_hashed_recursive(depth: u32, board: &mut Board, buffers: &mut [Vec<Move>], table: &mut PerftTable) -> Result<u64, PerftError> {
     if depth == 0 {
-        return 1;
-    }
-    let legal_moves = board.generate_legal_moves();
-    let mut num_moves = 0;
-    for mv in &legal_moves {
-        let mut new_board = board.clone();
-        if let Err(e) = new_board.move_piece(mv.clone()) {
-            log::error!("Generated legal move flagged as illegal by move_piece: {}", e);
-            new_board.print(crate::color::Color::White);
-            log::trace!("Mv: {:?}", mv);
-            std::process::exit(1);
+        return Ok(1);
+    }
+    let (legal_moves, deeper) = buffers.split_first_mut().expect("a buffer per remaining ply");
+    if depth == 1 {
+        board.generate_legal_moves_into(legal_moves);
+        return Ok(legal_moves.len() as u64);
+    }
+    let key = board.zobrist_key();
+    if let Some(nodes) = table.probe(key, depth) {
+        return Ok(nodes);
+    }
+    board.generate_legal_moves_into(legal_moves);
+    let mut nodes = 0;
+    for mv in legal_moves.iter() {
+        let undo = board.make_move(mv).map_err(|source| perft_error(board, mv, source))?;
+        nodes += perft_hashed_recursive(depth - 1, board, deeper, table)?;
+        board.unmake_move(mv, undo);
+    }
+    table.store(PerftEntry { key, depth, nodes });
+    Ok(nodes)
+}
+
+fn timed_result(depth: u32, nodes: u64, elapsed: Duration, divide: Option<Vec<PerftDivide>>) -> PerftResult {
+    let seconds = elapsed.as_secs_f64();
+    let nps = if seconds > 0.0 { (nodes as f64 / seconds) as u64 } else { 0 };
+    PerftResult { depth, nodes, elapsed, nps, divide, thread_nodes: None }
+}
+
+/// The results as a JSON array, for CI scripts
+#[cfg(feature = "serde")]
+pub fn perft_json(results: &[PerftResult]) -> String {
+    serde_json::to_string_pretty(results).expect("perft results always serialize")
+}
+
+/// One move buffer per ply
+fn move_buffers(depth: u32) -> Vec<Vec<Move>> {
+    vec![Vec::new(); depth as usize]
+}
+
+fn perft_error(board: &Board, mv: &Move, source: crate::errors::move_error::MoveError) -> PerftError {
+    PerftError { mv: mv.extended_algebraic(), fen: board.to_fen(), source }
+}
+
+/// Adds the leaves below the position to stats by making and unmaking moves on a single board.
+/// buffers holds a move list for this ply followed by one for each ply below it, which is reused by every node at that ply.
+fn perft_recursive(depth: u32, board: &mut Board, buffers: &mut [Vec<Move>], mode: PerftMode, stats: &mut PerftStats) -> Result<(), PerftError> {
+    if depth == 0 {
+        stats.nodes += 1;
+        return Ok(());
+    }
+    let (legal_moves, deeper) = buffers.split_first_mut().expect("a buffer per remaining ply");
+    board.generate_legal_moves_into(legal_moves);
+    match mode {
+        PerftMode::Bulk if depth == 1 => {
+            stats.nodes += legal_moves.len() as u64;
+            return Ok(());
         }
-        num_moves += perft(depth - 1, new_board);
+        PerftMode::Stats if depth == 1 => {
+            for mv in legal_moves.iter() {
+                let result = board.move_piece(mv.clone()).map_err(|source| perft_error(board, mv, source))?;
+                stats.nodes += 1;
+                stats.captures += u64::from(result.captured.is_some());
+                stats.en_passant += u64::from(result.is_en_passant);
+                stats.castles += u64::from(result.is_castle.is_some());
+                stats.promotions += u64::from(result.promotion.is_some());
+                stats.checks += u64::from(result.gives_check);
+                stats.checkmates += u64::from(result.gives_check && !board.has_legal_moves());
+                board.undo_move();
+            }
+            return Ok(());
+        }
+        _ => {}
+    }
+    for mv in legal_moves.iter() {
+        let undo = board.make_move(mv).map_err(|source| perft_error(board, mv, source))?;
+        perft_recursive(depth - 1, board, deeper, mode, stats)?;
+        board.unmake_move(mv, undo);
     }
-    return num_moves;
+    Ok(())
 }
@@ -0,0 +1,58 @@
+//! Random numbers combined into a 64 bit key identifying a position.
+//! # Description
+//! A position's key is the xor of the numbers for each piece on its square, the side to move,
+//! each castling right and the file of a capturable en passant square.
+//! The numbers are generated at compile time from a fixed seed so keys are the same between runs,
+//! which lets transposition tables be saved and loaded again.
+
+/// One number per color, piece type and square, indexed [color][piece type][square index]
+pub const PIECE_KEYS: [[[u64; 64]; 6]; 2] = piece_keys();
+/// Xored in when Black is to move
+pub const BLACK_TO_MOVE_KEY: u64 = splitmix64(SEED ^ 0xB1AC);
+/// Castling rights in the order white king side, white queen side, black king side, black queen side
+pub const CASTLING_KEYS: [u64; 4] = [
+    splitmix64(SEED ^ 0xCA51),
+    splitmix64(SEED ^ 0xCA52),
+    splitmix64(SEED ^ 0xCA53),
+    splitmix64(SEED ^ 0xCA54),
+];
+/// One number per file of the en passant square
+pub const EN_PASSANT_KEYS: [u64; 8] = en_passant_keys();
+
+const SEED: u64 = 0x2545_F491_4F6C_DD1D;
+
+/// The splitmix64 mixing function, good enough to spread sequential inputs over all 64 bits
+const fn splitmix64(state: u64) -> u64 {
+    let mut z = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+const fn piece_keys() -> [[[u64; 64]; 6]; 2] {
+    let mut keys = [[[0; 64]; 6]; 2];
+    let mut color = 0;
+    while color < 2 {
+        let mut piece_type = 0;
+        while piece_type < 6 {
+            let mut square = 0;
+            while square < 64 {
+                keys[color][piece_type][square] = splitmix64(SEED.wrapping_add(((color * 6 + piece_type) * 64 + square) as u64));
+                square += 1;
+            }
+            piece_type += 1;
+        }
+        color += 1;
+    }
+    keys
+}
+
+const fn en_passant_keys() -> [u64; 8] {
+    let mut keys = [0; 8];
+    let mut file = 0;
+    while file < 8 {
+        keys[file] = splitmix64(SEED ^ (0xE9A5 + file as u64));
+        file += 1;
+    }
+    keys
+}